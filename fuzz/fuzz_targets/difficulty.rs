@@ -0,0 +1,27 @@
+//! Fuzz the difficulty check against a bit-by-bit model
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use uhash_core::meets_difficulty;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 36 {
+        return;
+    }
+    let hash: [u8; 32] = data[..32].try_into().unwrap();
+    let difficulty = u32::from_le_bytes(data[32..36].try_into().unwrap());
+
+    // Count leading zero bits one at a time
+    let mut zero_bits = 0u32;
+    'outer: for byte in hash {
+        for bit in (0..8).rev() {
+            if byte >> bit & 1 == 1 {
+                break 'outer;
+            }
+            zero_bits += 1;
+        }
+    }
+
+    assert_eq!(meets_difficulty(&hash, difficulty), zero_bits >= difficulty);
+});
@@ -0,0 +1,20 @@
+//! Fuzz arbitrary-length inputs through the full hash
+//!
+//! The round loop indexes the scratchpad with data-dependent addresses,
+//! so any input must hash without panicking, a reused hasher must agree
+//! with a fresh one (no state leaks between calls), and the native
+//! primitives (AES-NI, NEON, Zkn) must agree with the portable ones.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use uhash_core::{UniversalHash, hash, portable_hash};
+
+fuzz_target!(|data: &[u8]| {
+    let fresh = hash(data);
+    assert_eq!(fresh, portable_hash(data));
+
+    let mut hasher = UniversalHash::new();
+    let _ = hasher.hash(b"previous input leaves scratchpads dirty");
+    assert_eq!(hasher.hash(data), fresh);
+});
@@ -0,0 +1,19 @@
+//! Fuzz the mining header parser
+//!
+//! Any 68-byte input must decode and re-encode to the same bytes; any
+//! other length must be rejected.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use uhash_core::{HEADER_SIZE, MiningHeader};
+
+fuzz_target!(|data: &[u8]| {
+    match MiningHeader::from_slice(data) {
+        Some(header) => {
+            assert_eq!(data.len(), HEADER_SIZE);
+            assert_eq!(&header.to_bytes()[..], data);
+        }
+        None => assert_ne!(data.len(), HEADER_SIZE),
+    }
+});
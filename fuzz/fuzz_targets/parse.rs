@@ -0,0 +1,33 @@
+//! Fuzz the difficulty and target parsers
+//!
+//! Any string must parse or be rejected without panicking, and whatever
+//! parses must survive a `Display` round trip: targets exactly, and
+//! difficulties to the three decimals `Display` keeps.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use uhash_core::{Difficulty, Target};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(s) = core::str::from_utf8(data) else {
+        return;
+    };
+
+    if let Ok(target) = Target::from_hex(s) {
+        assert_eq!(target.to_string().parse::<Target>(), Ok(target));
+    }
+
+    if let Ok(difficulty) = s.parse::<Difficulty>() {
+        assert!(difficulty.value().is_finite() && difficulty.value() >= 1.0);
+        let reparsed: Difficulty = difficulty.to_string().parse().unwrap();
+        // Display rounds to half a thousandth of a scaled value of at least 1
+        assert!(
+            (reparsed.value() / difficulty.value() - 1.0).abs() <= 5e-4 + 1e-12,
+            "{} -> {} -> {}",
+            s,
+            difficulty,
+            reparsed.value()
+        );
+    }
+});
@@ -0,0 +1,137 @@
+//! Incremental absorption of arbitrarily long inputs
+//!
+//! The header part of an input (everything but the last [`NONCE_SIZE`]
+//! bytes) only ever feeds BLAKE3, so it can be absorbed as it arrives.
+//! [`Absorber`] does that, holding back the most recent [`NONCE_SIZE`]
+//! bytes because any of them may turn out to be the nonce. Multi-megabyte
+//! payloads are then hashed in constant memory with one pass over the data:
+//!
+//! ```rust
+//! # #[cfg(feature = "alloc")] {
+//! use uhash_core::{Absorber, UniversalHash, hash};
+//!
+//! let payload = vec![7u8; 1 << 20];
+//! let mut absorber = Absorber::new();
+//! for chunk in payload.chunks(4096) {
+//!     absorber.update(chunk);
+//! }
+//! assert_eq!(UniversalHash::new().hash_absorbed(&absorber), hash(&payload));
+//! # }
+//! ```
+
+use blake3::Hasher as Blake3;
+
+use crate::endian::load_u64_le;
+use crate::params::NONCE_SIZE;
+
+/// Input absorbed so far, ready to be hashed at any point
+///
+/// Produces the same hash as the concatenation of every
+/// [`update`](Self::update), for any split of the input.
+#[derive(Debug, Clone, Default)]
+pub struct Absorber {
+    /// BLAKE3 state over the input except the held-back tail
+    midstate: Blake3,
+    /// The last `tail_len` bytes absorbed
+    tail: [u8; NONCE_SIZE],
+    tail_len: usize,
+    /// Total bytes absorbed
+    len: u64,
+}
+
+impl Absorber {
+    /// An absorber over the empty input
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `data` to the input
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        self.len = self.len.wrapping_add(data.len() as u64);
+        match data.len().checked_sub(NONCE_SIZE) {
+            // `data` alone refills the tail; everything older is header
+            Some(header_len) => {
+                self.midstate.update(&self.tail[..self.tail_len]);
+                self.midstate.update(&data[..header_len]);
+                self.tail.copy_from_slice(&data[header_len..]);
+                self.tail_len = NONCE_SIZE;
+            }
+            // Shift out just enough of the tail to make room for `data`
+            None => {
+                let spill = (self.tail_len + data.len()).saturating_sub(NONCE_SIZE);
+                self.midstate.update(&self.tail[..spill]);
+                self.tail.copy_within(spill..self.tail_len, 0);
+                self.tail_len -= spill;
+                self.tail[self.tail_len..self.tail_len + data.len()].copy_from_slice(data);
+                self.tail_len += data.len();
+            }
+        }
+        self
+    }
+
+    /// Total number of bytes absorbed
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether nothing has been absorbed yet
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Header midstate and effective nonce of the input absorbed so far
+    ///
+    /// Matches `header_midstate` and `extract_nonce` on the whole input:
+    /// an input shorter than [`NONCE_SIZE`] sits entirely in the tail, so
+    /// the midstate is still empty and the nonce is hashed from the tail.
+    pub(crate) fn split(&self) -> (&Blake3, u64) {
+        let nonce = if self.tail_len == NONCE_SIZE {
+            load_u64_le(&self.tail, 0)
+        } else {
+            load_u64_le(blake3::hash(&self.tail[..self.tail_len]).as_bytes(), 0)
+        };
+        (&self.midstate, nonce)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for Absorber {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uhash::{extract_nonce, header_midstate};
+
+    #[test]
+    fn test_split_matches_whole_input() {
+        let data: [u8; 24] = core::array::from_fn(|i| i as u8 ^ 0x5A);
+        for len in 0..data.len() {
+            let input = &data[..len];
+            let expected_nonce = extract_nonce(input);
+            let expected_midstate = header_midstate(input).finalize();
+            // Every pair of cut points, including empty pieces
+            for a in 0..=len {
+                for b in a..=len {
+                    let mut absorber = Absorber::new();
+                    absorber
+                        .update(&input[..a])
+                        .update(&input[a..b])
+                        .update(&input[b..]);
+                    let (midstate, nonce) = absorber.split();
+                    assert_eq!(nonce, expected_nonce, "len {len} cuts {a} {b}");
+                    assert_eq!(midstate.finalize(), expected_midstate);
+                    assert_eq!(absorber.len(), len as u64);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! CPU core selection and thread pinning for miner workers
+//!
+//! Each hash streams through a 2MB scratchpad, so throughput is bound by
+//! per-core cache. Two SMT siblings sharing a physical core compete for
+//! the same L1/L2 and usually lower aggregate hashrate; pinning one worker
+//! per physical core avoids that. Pinning is implemented on Linux; on other
+//! platforms workers run unpinned.
+
+use std::thread;
+
+/// Which CPUs miner workers are pinned to
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum CoreSelection {
+    /// Leave scheduling to the OS
+    #[default]
+    Unpinned,
+    /// Pin workers round-robin over every logical CPU the process may use
+    AllLogical,
+    /// Pin workers round-robin over one logical CPU per physical core,
+    /// skipping SMT siblings
+    PhysicalOnly,
+    /// Pin worker `i` to `cpus[i % cpus.len()]`
+    Cpus(Vec<usize>),
+}
+
+impl CoreSelection {
+    /// Logical CPU ids workers will be pinned to, in assignment order
+    ///
+    /// Empty for [`CoreSelection::Unpinned`].
+    pub fn cpus(&self) -> Vec<usize> {
+        match self {
+            CoreSelection::Unpinned => Vec::new(),
+            CoreSelection::AllLogical => logical_cpus(),
+            CoreSelection::PhysicalOnly => physical_cpus(),
+            CoreSelection::Cpus(cpus) => cpus.clone(),
+        }
+    }
+}
+
+/// Logical CPUs this process is allowed to run on
+pub fn logical_cpus() -> Vec<usize> {
+    #[cfg(target_os = "linux")]
+    if let Some(cpus) = linux::allowed_cpus() {
+        return cpus;
+    }
+    (0..thread::available_parallelism().map_or(1, |n| n.get())).collect()
+}
+
+/// One logical CPU per physical core (the lowest-numbered SMT sibling)
+///
+/// Falls back to [`logical_cpus`] where the topology is unknown.
+pub fn physical_cpus() -> Vec<usize> {
+    let logical = logical_cpus();
+    #[cfg(target_os = "linux")]
+    {
+        logical
+            .iter()
+            .copied()
+            .filter(|&cpu| match linux::thread_siblings(cpu) {
+                Some(siblings) => siblings.iter().min().is_none_or(|&first| first == cpu),
+                None => true,
+            })
+            .collect()
+    }
+    #[cfg(not(target_os = "linux"))]
+    logical
+}
+
+/// Pin the calling thread to logical CPU `cpu`
+///
+/// Returns `false` if pinning failed or is unsupported on this platform.
+pub fn pin_current_thread(cpu: usize) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        linux::pin(cpu)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = cpu;
+        false
+    }
+}
+
+/// Parse a sysfs CPU list such as `"0-3,8,10-11"`
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((lo, hi)) => cpus.extend(lo.parse::<usize>().ok()?..=hi.parse().ok()?),
+            None => cpus.push(part.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::parse_cpu_list;
+
+    pub(super) fn allowed_cpus() -> Option<Vec<usize>> {
+        // SAFETY: cpu_set_t is plain data; sched_getaffinity fills it in
+        unsafe {
+            let mut set: libc::cpu_set_t = core::mem::zeroed();
+            if libc::sched_getaffinity(0, core::mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+                return None;
+            }
+            let cpus: Vec<usize> = (0..libc::CPU_SETSIZE as usize)
+                .filter(|&cpu| libc::CPU_ISSET(cpu, &set))
+                .collect();
+            (!cpus.is_empty()).then_some(cpus)
+        }
+    }
+
+    pub(super) fn thread_siblings(cpu: usize) -> Option<Vec<usize>> {
+        let path = format!(
+            "/sys/devices/system/cpu/cpu{}/topology/thread_siblings_list",
+            cpu
+        );
+        parse_cpu_list(&std::fs::read_to_string(path).ok()?)
+    }
+
+    pub(super) fn pin(cpu: usize) -> bool {
+        if cpu >= libc::CPU_SETSIZE as usize {
+            return false;
+        }
+        // SAFETY: cpu is in range for cpu_set_t; pid 0 means the calling thread
+        unsafe {
+            let mut set: libc::cpu_set_t = core::mem::zeroed();
+            libc::CPU_SET(cpu, &mut set);
+            libc::sched_setaffinity(0, core::mem::size_of::<libc::cpu_set_t>(), &set) == 0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(
+            parse_cpu_list("0-3,8,10-11\n"),
+            Some(vec![0, 1, 2, 3, 8, 10, 11])
+        );
+        assert_eq!(parse_cpu_list("5"), Some(vec![5]));
+        assert_eq!(parse_cpu_list("x"), None);
+    }
+
+    #[test]
+    fn test_core_selection() {
+        let logical = logical_cpus();
+        let physical = physical_cpus();
+        assert!(!logical.is_empty());
+        assert!(!physical.is_empty());
+        assert!(physical.iter().all(|cpu| logical.contains(cpu)));
+
+        assert!(CoreSelection::Unpinned.cpus().is_empty());
+        assert_eq!(CoreSelection::Cpus(vec![3, 1]).cpus(), vec![3, 1]);
+    }
+}
@@ -0,0 +1,185 @@
+//! Batch proof verification
+//!
+//! Initial block download checks thousands of proofs against the same
+//! target. [`verify_batch`] spreads them over the rayon pool (with the
+//! `parallel` feature), each worker hashing in a scratchpad drawn from an
+//! [`UhashContext`] pool, and stops at the first invalid proof.
+//! [`audit_batch`] checks every proof and reports all failures.
+
+use crate::context::UhashContext;
+use crate::pow::PowError;
+use crate::proof::PowProof;
+use crate::target::Target;
+use crate::uhash::UniversalHash;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Outcome of verifying a batch of proofs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchResult {
+    /// Number of proofs in the batch
+    pub total: usize,
+    /// Invalid proofs by index, in ascending order
+    ///
+    /// After [`verify_batch`] this holds at most the lowest failing index;
+    /// after [`audit_batch`] every failure.
+    pub failures: Vec<(usize, PowError)>,
+}
+
+impl BatchResult {
+    /// Whether no proof failed
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Lowest-index failure, if any
+    pub fn first_failure(&self) -> Option<(usize, PowError)> {
+        self.failures.first().copied()
+    }
+}
+
+impl UhashContext {
+    /// Verify `proofs` against `target`, stopping at the first failure
+    ///
+    /// The reported failure is always the lowest failing index, whatever
+    /// order workers finish in, so results are reproducible.
+    pub fn verify_batch(&self, proofs: &[PowProof], target: &Target) -> BatchResult {
+        let check = |(index, proof)| self.check_proof(index, proof, target);
+        #[cfg(feature = "parallel")]
+        let first = proofs
+            .par_iter()
+            .enumerate()
+            .filter_map(check)
+            .find_first(|_| true);
+        #[cfg(not(feature = "parallel"))]
+        let first = proofs.iter().enumerate().find_map(check);
+
+        BatchResult {
+            total: proofs.len(),
+            failures: first.into_iter().collect(),
+        }
+    }
+
+    /// Verify every proof in `proofs` against `target`, collecting all failures
+    pub fn audit_batch(&self, proofs: &[PowProof], target: &Target) -> BatchResult {
+        let check = |(index, proof)| self.check_proof(index, proof, target);
+        #[cfg(feature = "parallel")]
+        let failures = proofs.par_iter().enumerate().filter_map(check).collect();
+        #[cfg(not(feature = "parallel"))]
+        let failures = proofs.iter().enumerate().filter_map(check).collect();
+
+        BatchResult {
+            total: proofs.len(),
+            failures,
+        }
+    }
+
+    /// Verify one proof, returning its failure
+    fn check_proof(
+        &self,
+        index: usize,
+        proof: &PowProof,
+        target: &Target,
+    ) -> Option<(usize, PowError)> {
+        self.with_hasher(|hasher| proof.verify_target(hasher, target))
+            .err()
+            .map(|err| (index, err))
+    }
+}
+
+/// Context for the one-shot batch functions: the batch itself is spread
+/// over the workers, so each hasher runs its chains sequentially
+fn batch_context() -> UhashContext {
+    UhashContext::from_hasher(UniversalHash::new_sequential())
+}
+
+/// Verify `proofs` against `target`, stopping at the first failure
+///
+/// Allocates one scratchpad set per worker thread; keep an
+/// [`UhashContext`] and call [`UhashContext::verify_batch`] to reuse them
+/// across batches.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{MiningHeader, PowProof, Target, UniversalHash, verify_batch};
+///
+/// let mut hasher = UniversalHash::new();
+/// let mut proofs: Vec<_> = (0..4)
+///     .map(|nonce| PowProof::compute(&mut hasher, &MiningHeader::default().with_nonce(nonce)))
+///     .collect();
+/// assert!(verify_batch(&proofs, &Target::MAX).is_valid());
+///
+/// proofs[2].nonce += 1;
+/// let result = verify_batch(&proofs, &Target::MAX);
+/// assert_eq!(result.first_failure().map(|(index, _)| index), Some(2));
+/// ```
+pub fn verify_batch(proofs: &[PowProof], target: &Target) -> BatchResult {
+    batch_context().verify_batch(proofs, target)
+}
+
+/// Verify every proof in `proofs` against `target`, collecting all failures
+pub fn audit_batch(proofs: &[PowProof], target: &Target) -> BatchResult {
+    batch_context().audit_batch(proofs, target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::MiningHeader;
+
+    fn proofs(count: u64) -> Vec<PowProof> {
+        let mut hasher = UniversalHash::new();
+        (0..count)
+            .map(|nonce| PowProof::compute(&mut hasher, &MiningHeader::default().with_nonce(nonce)))
+            .collect()
+    }
+
+    #[test]
+    fn test_batch_reports_lowest_failure() {
+        let mut batch = proofs(6);
+        let context = UhashContext::from_hasher(UniversalHash::new_sequential());
+        assert_eq!(
+            context.verify_batch(&batch, &Target::MAX),
+            BatchResult {
+                total: 6,
+                failures: Vec::new()
+            }
+        );
+        assert!(context.verify_batch(&[], &Target::MAX).is_valid());
+
+        batch[4].hash.0[0] ^= 1;
+        batch[1].nonce += 1;
+        let strict = context.verify_batch(&batch, &Target::MAX);
+        assert_eq!(strict.failures.len(), 1);
+        assert!(matches!(
+            strict.first_failure(),
+            Some((1, PowError::HashMismatch { .. }))
+        ));
+
+        let audit = context.audit_batch(&batch, &Target::MAX);
+        let indices: Vec<_> = audit.failures.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indices, [1, 4]);
+        assert_eq!(audit.total, 6);
+    }
+
+    #[test]
+    fn test_batch_checks_target_before_hashing() {
+        let mut batch = proofs(3);
+        let target = Target::from_difficulty_bits(8);
+        // Claims above the target fail without a hash mismatch being reported
+        for proof in &mut batch {
+            proof.hash.0[0] = 0xFF;
+        }
+        let audit = audit_batch(&batch, &target);
+        assert_eq!(audit.failures.len(), 3);
+        assert!(
+            audit
+                .failures
+                .iter()
+                .all(|(_, err)| *err == PowError::AboveTarget { target })
+        );
+        assert!(!verify_batch(&batch, &target).is_valid());
+    }
+}
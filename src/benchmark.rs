@@ -0,0 +1,342 @@
+//! Built-in benchmark for estimating mining performance
+//!
+//! Lets miner frontends show an expected hashrate without shipping their
+//! own measurement loop. [`benchmark_memory`] isolates the scratchpad
+//! access pattern, so operators can see whether more threads per memory
+//! channel would still help.
+
+use core::hint::black_box;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "perf")]
+use crate::PerfCounters;
+use crate::UniversalHash;
+use crate::params::*;
+use crate::primitives::{aes_compress, aes_expand_block, blake3_compress, sha256_compress};
+
+/// Iterations used when timing individual primitives
+const PRIMITIVE_ITERATIONS: u32 = 10_000;
+
+/// Hashes run under hardware counters
+#[cfg(feature = "perf")]
+const PERF_HASHES: u64 = 16;
+
+/// Bytes of scratchpad traffic per hash
+///
+/// Initialization writes every scratchpad once; each round reads one
+/// 64-byte block and writes 32 bytes back.
+pub const BYTES_PER_HASH: usize = TOTAL_MEMORY + ROUNDS * CHAINS * (BLOCK_SIZE + 32);
+
+/// Average time of a single primitive invocation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrimitiveTimings {
+    /// AES_Compress (4 AESENC rounds per state half)
+    pub aes_compress: Duration,
+    /// SHA256_Compress (one compression function call)
+    pub sha256_compress: Duration,
+    /// BLAKE3_Compress (7 rounds)
+    pub blake3_compress: Duration,
+    /// AES expansion used for scratchpad initialization
+    pub aes_expand: Duration,
+}
+
+impl PrimitiveTimings {
+    /// Measure all primitives on the current machine
+    pub fn measure() -> Self {
+        let state = [0u8; 32];
+        let block = [1u8; BLOCK_SIZE];
+        let key16 = [0u8; 16];
+        let state16 = [1u8; 16];
+
+        Self {
+            aes_compress: time_per_call(|| {
+                black_box(aes_compress(black_box(&state), black_box(&block)));
+            }),
+            sha256_compress: time_per_call(|| {
+                black_box(sha256_compress(black_box(&state), black_box(&block)));
+            }),
+            blake3_compress: time_per_call(|| {
+                black_box(blake3_compress(black_box(&state), black_box(&block)));
+            }),
+            aes_expand: time_per_call(|| {
+                black_box(aes_expand_block(black_box(&state16), black_box(&key16)));
+            }),
+        }
+    }
+
+    /// Average of the three round primitives
+    pub fn compress_average(&self) -> Duration {
+        (self.aes_compress + self.sha256_compress + self.blake3_compress) / 3
+    }
+
+    /// Estimated scratchpad initialization time per hash
+    ///
+    /// Each block needs 1 new AES expansion; its second half is the next
+    /// block's first.
+    pub fn scratchpad_init_estimate(&self) -> Duration {
+        self.aes_expand * (BLOCKS_PER_SCRATCHPAD * CHAINS) as u32
+    }
+
+    /// Estimated round execution time per hash (all chains, sequential)
+    pub fn rounds_estimate(&self) -> Duration {
+        self.compress_average() * (ROUNDS * CHAINS) as u32
+    }
+}
+
+/// Result of a [`benchmark`] run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchmarkReport {
+    /// Number of hashes computed
+    pub hashes: u64,
+    /// Wall-clock time spent hashing
+    pub elapsed: Duration,
+    /// Hashes per second
+    pub hashrate: f64,
+    /// Per-primitive timing breakdown
+    pub primitives: PrimitiveTimings,
+    /// Estimated scratchpad bandwidth in bytes per second
+    pub memory_bandwidth: f64,
+    /// Hardware counters per hash, from a separate run on a sequential
+    /// hasher (counters only see the calling thread)
+    #[cfg(feature = "perf")]
+    pub perf: PerfCounters,
+}
+
+impl BenchmarkReport {
+    /// Average wall-clock time of one hash
+    pub fn time_per_hash(&self) -> Duration {
+        if self.hashes == 0 {
+            return Duration::ZERO;
+        }
+        self.elapsed / self.hashes as u32
+    }
+}
+
+/// Hash for (at least) `duration` and report the achieved performance
+///
+/// Always computes at least one hash. Primitive timings are measured
+/// separately and are not included in `elapsed`. With the `perf` feature
+/// hardware counters (Linux) are read over a few extra hashes on a
+/// [`new_sequential`](UniversalHash::new_sequential) hasher: they count
+/// only the calling thread, so a parallel hasher's rounds on the thread
+/// pool would be missed.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use uhash_core::benchmark;
+///
+/// let report = benchmark(Duration::from_secs(5));
+/// println!("{:.1} H/s", report.hashrate);
+/// ```
+pub fn benchmark(duration: Duration) -> BenchmarkReport {
+    let mut hasher = UniversalHash::new();
+    let mut input = [0u8; 68];
+
+    hasher.warm_up();
+
+    let mut hashes = 0u64;
+    let start = Instant::now();
+    loop {
+        input[60..].copy_from_slice(&hashes.to_le_bytes());
+        black_box(hasher.hash(black_box(&input)));
+        hashes += 1;
+        if start.elapsed() >= duration {
+            break;
+        }
+    }
+    let elapsed = start.elapsed();
+
+    #[cfg(feature = "perf")]
+    let perf = {
+        let mut hasher = UniversalHash::new_sequential();
+        hasher.warm_up();
+        PerfCounters::measure(|| {
+            for nonce in 0..PERF_HASHES {
+                input[60..].copy_from_slice(&nonce.to_le_bytes());
+                black_box(hasher.hash(black_box(&input)));
+            }
+            PERF_HASHES
+        })
+    };
+
+    let secs = elapsed.as_secs_f64();
+    let hashrate = if secs > 0.0 {
+        hashes as f64 / secs
+    } else {
+        0.0
+    };
+
+    BenchmarkReport {
+        hashes,
+        elapsed,
+        hashrate,
+        primitives: PrimitiveTimings::measure(),
+        memory_bandwidth: hashrate * BYTES_PER_HASH as f64,
+        #[cfg(feature = "perf")]
+        perf,
+    }
+}
+
+/// Which resource limits hashing on this machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkloadBound {
+    /// A round's compression takes longer than its scratchpad access
+    Compute,
+    /// A round's scratchpad access takes longer than its compression
+    Memory,
+}
+
+/// Result of a [`benchmark_memory`] run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemoryBenchmark {
+    /// Threads that ran concurrently, each on its own scratchpad-sized buffer
+    pub threads: usize,
+    /// Block accesses made across all threads
+    pub accesses: u64,
+    /// Wall-clock time spent accessing
+    pub elapsed: Duration,
+    /// Bytes per second across all threads, counting a 64-byte read and a
+    /// 32-byte write per access like a hashing round
+    pub bandwidth: f64,
+    /// Average time of one dependent access on one thread
+    pub access_time: Duration,
+    /// Average compression time of one round, for comparison
+    pub compress_time: Duration,
+}
+
+impl MemoryBenchmark {
+    /// Whether rounds on this many threads are limited by memory or compute
+    pub fn bound(&self) -> WorkloadBound {
+        if self.access_time > self.compress_time {
+            WorkloadBound::Memory
+        } else {
+            WorkloadBound::Compute
+        }
+    }
+}
+
+/// Measure random-access scratchpad bandwidth with `threads` concurrent
+/// threads for (at least) `duration`
+///
+/// Each thread walks its own [`TOTAL_MEMORY`] buffer with the access
+/// pattern of hashing rounds: each block address depends on the previous
+/// block's contents, so accesses cannot overlap. Run it with increasing
+/// thread counts: once [`bandwidth`](MemoryBenchmark::bandwidth) stops
+/// scaling, the memory channels are saturated and more threads will not
+/// raise the hashrate.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use uhash_core::benchmark_memory;
+///
+/// for threads in [1, 2, 4, 8] {
+///     let report = benchmark_memory(Duration::from_secs(1), threads);
+///     println!("{} threads: {:.1} GB/s, {:?}", threads, report.bandwidth / 1e9, report.bound());
+/// }
+/// ```
+pub fn benchmark_memory(duration: Duration, threads: usize) -> MemoryBenchmark {
+    let threads = threads.max(1);
+    let runs: Vec<(u64, Duration)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|seed| scope.spawn(move || access_loop(duration, seed as u64)))
+            .collect();
+        workers.into_iter().map(|w| w.join().unwrap()).collect()
+    });
+
+    let accesses = runs.iter().map(|&(accesses, _)| accesses).sum();
+    let elapsed = runs
+        .iter()
+        .map(|&(_, elapsed)| elapsed)
+        .max()
+        .unwrap_or_default();
+    let secs = elapsed.as_secs_f64();
+    // Each thread's own average, so thread start-up skew does not count
+    let access_secs = runs
+        .iter()
+        .map(|&(accesses, elapsed)| elapsed.as_secs_f64() / accesses as f64)
+        .sum::<f64>()
+        / threads as f64;
+    MemoryBenchmark {
+        threads,
+        accesses,
+        elapsed,
+        bandwidth: if secs > 0.0 {
+            accesses as f64 * (BLOCK_SIZE + 32) as f64 / secs
+        } else {
+            0.0
+        },
+        access_time: Duration::from_secs_f64(access_secs),
+        compress_time: PrimitiveTimings::measure().compress_average(),
+    }
+}
+
+/// Dependent random block accesses on a fresh buffer until `duration`
+/// has passed, returning the number of accesses and the time they took
+fn access_loop(duration: Duration, seed: u64) -> (u64, Duration) {
+    const BATCH: u64 = 1024;
+    let mut buffer = vec![0u8; TOTAL_MEMORY];
+    for (i, word) in buffer.chunks_exact_mut(8).enumerate() {
+        word.copy_from_slice(&(i as u64 ^ seed).wrapping_mul(GOLDEN_RATIO).to_le_bytes());
+    }
+
+    let mut state = seed.wrapping_mul(GOLDEN_RATIO);
+    let mut accesses = 0u64;
+    let start = Instant::now();
+    loop {
+        for _ in 0..BATCH {
+            let offset = (state % (TOTAL_MEMORY / BLOCK_SIZE) as u64) as usize * BLOCK_SIZE;
+            let block = black_box(&buffer[offset..offset + BLOCK_SIZE]);
+            let mixed = block
+                .chunks_exact(8)
+                .fold(state, |acc, word| {
+                    acc.rotate_left(7) ^ u64::from_le_bytes(word.try_into().unwrap())
+                })
+                .wrapping_mul(GOLDEN_RATIO);
+            for word in buffer[offset..offset + 32].chunks_exact_mut(8) {
+                word.copy_from_slice(&mixed.to_le_bytes());
+            }
+            state = mixed;
+        }
+        accesses += BATCH;
+        if start.elapsed() >= duration {
+            break;
+        }
+    }
+    let elapsed = start.elapsed();
+    black_box(&buffer);
+    (accesses, elapsed)
+}
+
+/// Average duration of `f` over `PRIMITIVE_ITERATIONS` calls
+fn time_per_call(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..PRIMITIVE_ITERATIONS {
+        f();
+    }
+    start.elapsed() / PRIMITIVE_ITERATIONS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_benchmark_memory() {
+        let report = benchmark_memory(Duration::from_millis(10), 2);
+        assert_eq!(report.threads, 2);
+        assert!(report.accesses >= 2 * 1024);
+        assert!(report.bandwidth > 0.0);
+        assert!(report.access_time > Duration::ZERO);
+        let expected = if report.access_time > report.compress_time {
+            WorkloadBound::Memory
+        } else {
+            WorkloadBound::Compute
+        };
+        assert_eq!(report.bound(), expected);
+    }
+}
@@ -0,0 +1,285 @@
+//! `uhash` command-line tool (`cli` feature)
+//!
+//! ```text
+//! uhash hash <hex>                                   hash raw input bytes
+//! uhash verify [--difficulty BITS | --target T] <header> [hash]
+//! uhash bench [--seconds N]
+//! uhash mine [--difficulty BITS | --target T] [--threads N] [--header <header>]
+//! ```
+//!
+//! Headers and hashes are hex. `--target` takes a difficulty in any form
+//! `Difficulty` parses (`1.5M`, `4096`, or a `0x`-prefixed hex target).
+
+use std::process::ExitCode;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use uhash_core::{
+    Difficulty, Hash256, MinerConfig, MinerPool, MiningHeader, MiningWork, PowError, PowProof,
+    Target, UniversalHash, benchmark, leading_zero_bits,
+};
+
+const USAGE: &str = "\
+usage:
+  uhash hash <hex>
+  uhash verify [--difficulty BITS | --target T] <header-hex> [hash-hex]
+  uhash bench [--seconds N]
+  uhash mine [--difficulty BITS | --target T] [--threads N] [--header <header-hex>]
+
+BITS is a number of leading zero bits; T is a difficulty such as 1.5M or a
+0x-prefixed hex target. Headers are the 68-byte encoding in hex.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, rest)) = args.split_first() else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+    let result = match command.as_str() {
+        "hash" => hash(rest),
+        "verify" => verify(rest),
+        "bench" => bench(rest),
+        "mine" => mine(rest),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        other => Err(format!("unknown command `{}`", other)),
+    };
+    match result {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// `uhash hash <hex>`
+fn hash(args: &[String]) -> Result<ExitCode, String> {
+    let options = Options::parse(args, &[])?;
+    let [input] = options.positional()?;
+    println!("{}", Hash256(uhash_core::hash(&decode_hex(input)?)));
+    Ok(ExitCode::SUCCESS)
+}
+
+/// `uhash verify [--difficulty BITS | --target T] <header> [hash]`
+///
+/// Exits with status 1 when the header does not verify.
+fn verify(args: &[String]) -> Result<ExitCode, String> {
+    let options = Options::parse(args, &["--difficulty", "--target"])?;
+    let target = options.target()?;
+    let (header, claimed) = match options.positional.as_slice() {
+        [header] => (parse_header(header)?, None),
+        [header, claimed] => (
+            parse_header(header)?,
+            Some(Hash256::from_hex(claimed.trim_start_matches("0x")).map_err(|e| e.to_string())?),
+        ),
+        _ => return Err("expected a header and optionally a claimed hash".into()),
+    };
+
+    let mut hasher = UniversalHash::new();
+    let computed = Hash256(hasher.hash(&header.to_bytes()));
+    println!("hash:       {}", computed);
+    println!("zero bits:  {}", leading_zero_bits(&computed));
+    println!(
+        "difficulty: {}",
+        Difficulty::from_target(&Target::from(computed.to_u256()))
+    );
+    println!("target:     {}", target);
+
+    let result = match claimed {
+        Some(claimed) => PowProof::new(&header, claimed).verify_target(&mut hasher, &target),
+        None if target.is_met_by(&computed) => Ok(()),
+        None => Err(PowError::AboveTarget { target }),
+    };
+    match result {
+        Ok(()) => {
+            println!("result:     ok");
+            Ok(ExitCode::SUCCESS)
+        }
+        Err(err) => {
+            println!("result:     rejected: {}", err);
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+/// `uhash bench [--seconds N]`
+fn bench(args: &[String]) -> Result<ExitCode, String> {
+    let options = Options::parse(args, &["--seconds"])?;
+    let [] = options.positional()?;
+    let seconds: u64 = options.number("--seconds")?.unwrap_or(5);
+
+    let report = benchmark(Duration::from_secs(seconds));
+    println!("hashes:      {}", report.hashes);
+    println!("elapsed:     {:.2?}", report.elapsed);
+    println!("hashrate:    {:.1} H/s", report.hashrate);
+    println!("per hash:    {:.2?}", report.time_per_hash());
+    println!("bandwidth:   {:.1} MB/s", report.memory_bandwidth / 1e6);
+    #[cfg(feature = "perf")]
+    for (name, count) in [
+        ("cycles:     ", report.perf.cycles),
+        ("LLC misses: ", report.perf.llc_misses),
+        ("dTLB misses:", report.perf.dtlb_misses),
+    ] {
+        if let Some(count) = count {
+            println!("{} {:.0} per hash", name, count);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// `uhash mine [--difficulty BITS | --target T] [--threads N] [--header H]`
+///
+/// Mines until the first solution and prints the solved header.
+fn mine(args: &[String]) -> Result<ExitCode, String> {
+    let options = Options::parse(args, &["--difficulty", "--target", "--threads", "--header"])?;
+    let [] = options.positional()?;
+    let target = options.target()?;
+    let header = match options.value("--header") {
+        Some(header) => parse_header(header)?,
+        None => MiningHeader {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            ..Default::default()
+        },
+    };
+    let mut config = MinerConfig::default();
+    if let Some(threads) = options.number("--threads")? {
+        config.threads = threads;
+    }
+
+    let pool = MinerPool::new(config);
+    eprintln!(
+        "mining at difficulty {} on {} threads",
+        Difficulty::from_target(&target),
+        pool.threads()
+    );
+    let started = Instant::now();
+    pool.submit(MiningWork {
+        id: 0,
+        job: header.job(),
+        target,
+        start_nonce: header.nonce,
+    });
+    let solution = pool
+        .solutions()
+        .recv()
+        .map_err(|_| "miner stopped without a solution".to_string())?;
+    let elapsed = started.elapsed();
+
+    println!("nonce:    {}", solution.nonce);
+    println!("hash:     {}", solution.hash);
+    println!(
+        "header:   {}",
+        Hex(&header.with_nonce(solution.nonce).to_bytes())
+    );
+    println!("elapsed:  {:.2?}", elapsed);
+    println!("hashrate: {:.1} H/s", pool.hashrate());
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Parsed `--flag value` pairs and positional arguments
+struct Options<'a> {
+    flags: Vec<(&'a str, &'a str)>,
+    positional: Vec<&'a str>,
+}
+
+impl<'a> Options<'a> {
+    /// Split `args`, accepting only the flags in `allowed`
+    fn parse(args: &'a [String], allowed: &[&str]) -> Result<Self, String> {
+        let mut options = Options {
+            flags: Vec::new(),
+            positional: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg.starts_with("--") {
+                if !allowed.contains(&arg.as_str()) {
+                    return Err(format!("unknown option `{}`", arg));
+                }
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("`{}` needs a value", arg))?;
+                options.flags.push((arg, value));
+            } else {
+                options.positional.push(arg);
+            }
+        }
+        Ok(options)
+    }
+
+    /// Exactly `N` positional arguments
+    fn positional<const N: usize>(&self) -> Result<[&'a str; N], String> {
+        self.positional
+            .as_slice()
+            .try_into()
+            .map_err(|_| format!("expected {} argument(s), got {}", N, self.positional.len()))
+    }
+
+    /// Last value given for `flag`
+    fn value(&self, flag: &str) -> Option<&'a str> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == flag)
+            .map(|(_, value)| *value)
+    }
+
+    /// Value of `flag` parsed as a number
+    fn number<T: std::str::FromStr>(&self, flag: &str) -> Result<Option<T>, String> {
+        self.value(flag)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("`{}` expects a number, got `{}`", flag, value))
+            })
+            .transpose()
+    }
+
+    /// Target from `--difficulty` bits or a `--target` difficulty; every
+    /// hash meets the default
+    fn target(&self) -> Result<Target, String> {
+        match (self.number::<u32>("--difficulty")?, self.value("--target")) {
+            (Some(_), Some(_)) => Err("give either --difficulty or --target".into()),
+            (Some(bits), None) => Ok(Target::from_difficulty_bits(bits)),
+            (None, Some(target)) => target
+                .parse::<Difficulty>()
+                .map(|difficulty| difficulty.to_target())
+                .map_err(|e| format!("invalid target `{}`: {}", target, e)),
+            (None, None) => Ok(Target::MAX),
+        }
+    }
+}
+
+/// Decode a 68-byte header from hex
+fn parse_header(hex: &str) -> Result<MiningHeader, String> {
+    MiningHeader::try_from_slice(&decode_hex(hex)?).map_err(|e| format!("header: {}", e))
+}
+
+/// Decode hex of any even length, with an optional `0x` prefix
+fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    let digits = hex.strip_prefix("0x").unwrap_or(hex);
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("odd number of hex digits in `{}`", hex));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            digits
+                .get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| format!("invalid hex `{}`", hex))
+        })
+        .collect()
+}
+
+/// Lowercase hex of a byte slice
+struct Hex<'a>(&'a [u8]);
+
+impl std::fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
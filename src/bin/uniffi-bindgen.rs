@@ -0,0 +1,7 @@
+//! Generate Swift and Kotlin bindings for the `uniffi` feature
+//!
+//! See the `uniffi_bindings` module docs for usage.
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
@@ -0,0 +1,194 @@
+//! LRU cache of verified header hashes
+//!
+//! Relay and mempool code often re-validates the same header when it
+//! arrives from several peers. Caching the result avoids paying the full
+//! memory-hard cost more than once per header.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::hash256::Hash256;
+use crate::header::MiningHeader;
+use crate::pow::{PowError, check_difficulty};
+use crate::uhash::UniversalHash;
+
+/// Bounded map from header bytes to their hash, evicting least recently used
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{UniversalHash, VerifiedCache, hash};
+///
+/// let mut hasher = UniversalHash::new();
+/// let mut cache = VerifiedCache::new(1024);
+///
+/// let header = b"header from peer A";
+/// let first = cache.hash(&mut hasher, header);
+/// let again = cache.hash(&mut hasher, header); // no recomputation
+/// assert_eq!(first, again);
+/// assert_eq!(first, hash(header));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VerifiedCache {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<Box<[u8]>, Entry>,
+    order: BTreeMap<u64, Box<[u8]>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    hash: [u8; 32],
+    last_used: u64,
+}
+
+impl VerifiedCache {
+    /// Create a cache holding at most `capacity` headers
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::with_capacity(capacity),
+            order: BTreeMap::new(),
+        }
+    }
+
+    /// Maximum number of cached headers
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of cached headers
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop all cached headers
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    /// Cached hash of `header`, marking it as recently used
+    pub fn get(&mut self, header: &[u8]) -> Option<[u8; 32]> {
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(header)?;
+        let key = self.order.remove(&entry.last_used).unwrap();
+        entry.last_used = tick;
+        self.order.insert(tick, key);
+        Some(entry.hash)
+    }
+
+    /// Record the hash of `header`, evicting the least recently used entry if full
+    pub fn insert(&mut self, header: &[u8], hash: [u8; 32]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let tick = self.next_tick();
+        if let Some(entry) = self.entries.get_mut(header) {
+            let key = self.order.remove(&entry.last_used).unwrap();
+            *entry = Entry {
+                hash,
+                last_used: tick,
+            };
+            self.order.insert(tick, key);
+            return;
+        }
+        if self.entries.len() >= self.capacity
+            && let Some((_, oldest)) = self.order.pop_first()
+        {
+            self.entries.remove(&oldest);
+        }
+        let key: Box<[u8]> = header.into();
+        self.entries.insert(
+            key.clone(),
+            Entry {
+                hash,
+                last_used: tick,
+            },
+        );
+        self.order.insert(tick, key);
+    }
+
+    /// Hash `header`, reusing a cached result when available
+    pub fn hash(&mut self, hasher: &mut UniversalHash, header: &[u8]) -> [u8; 32] {
+        if let Some(hash) = self.get(header) {
+            return hash;
+        }
+        let hash = hasher.hash(header);
+        self.insert(header, hash);
+        hash
+    }
+
+    /// [`UniversalHash::verify_pow`] backed by this cache
+    ///
+    /// The hash is cached whether or not it meets `difficulty`, so a header
+    /// rejected once is rejected again cheaply.
+    pub fn verify_pow(
+        &mut self,
+        hasher: &mut UniversalHash,
+        header: &MiningHeader,
+        difficulty: u32,
+    ) -> Result<Hash256, PowError> {
+        check_difficulty(Hash256(self.hash(hasher, &header.to_bytes())), difficulty)
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let mut cache = VerifiedCache::new(2);
+        cache.insert(b"a", [1; 32]);
+        cache.insert(b"b", [2; 32]);
+        assert_eq!(cache.get(b"a"), Some([1; 32]));
+
+        cache.insert(b"c", [3; 32]);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(b"b"), None);
+        assert_eq!(cache.get(b"a"), Some([1; 32]));
+        assert_eq!(cache.get(b"c"), Some([3; 32]));
+
+        cache.insert(b"c", [4; 32]);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(b"c"), Some([4; 32]));
+
+        let mut disabled = VerifiedCache::new(0);
+        disabled.insert(b"a", [1; 32]);
+        assert!(disabled.is_empty());
+    }
+
+    #[test]
+    fn test_cache_verify_pow_matches_uncached() {
+        let mut hasher = UniversalHash::new();
+        let mut cache = VerifiedCache::new(4);
+        let header = MiningHeader {
+            nonce: 3,
+            ..Default::default()
+        };
+        let expected = hasher.verify_pow(&header, 0);
+
+        assert_eq!(cache.verify_pow(&mut hasher, &header, 0), expected);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(
+            cache.get(&header.to_bytes()),
+            Some(hash(&header.to_bytes()))
+        );
+        assert!(matches!(
+            cache.verify_pow(&mut hasher, &header, 256),
+            Err(PowError::BelowTarget { .. })
+        ));
+    }
+}
@@ -0,0 +1,133 @@
+//! Checkpoint verification for light clients
+//!
+//! A checkpoint pins the header at some height to its known hash. A light
+//! client that recomputes the hashes of a list of checkpoints, embedded in
+//! its binary or fetched from a trusted source, knows it is looking at the
+//! canonical chain without validating everything in between.
+
+use core::fmt;
+
+use crate::hash256::Hash256;
+use crate::header::MiningHeader;
+use crate::pow::PowError;
+use crate::uhash::UniversalHash;
+#[cfg(feature = "std")]
+use crate::{batch::verify_batch, proof::PowProof, target::Target};
+
+/// A header at a known height and the hash it must produce
+///
+/// All fields are public and `const`-constructible, so a checkpoint list
+/// can be embedded as a `const` array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Checkpoint {
+    /// Block height of the header
+    pub height: u64,
+    /// The checkpointed header
+    pub header: MiningHeader,
+    /// Its UniversalHash
+    pub hash: Hash256,
+}
+
+/// A checkpoint that failed verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointError {
+    /// Height of the failing checkpoint
+    pub height: u64,
+    /// Why it failed
+    pub error: PowError,
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "checkpoint at height {}: {}", self.height, self.error)
+    }
+}
+
+impl core::error::Error for CheckpointError {}
+
+/// Check that the 68-byte header `header_bytes` hashes to `expected_hash`
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{Hash256, MiningHeader, hash, verify_checkpoint};
+///
+/// let header = MiningHeader::default().to_bytes();
+/// let expected = Hash256(hash(&header));
+/// assert!(verify_checkpoint(&header, &expected).is_ok());
+/// assert!(verify_checkpoint(&header, &Hash256::ZERO).is_err());
+/// ```
+pub fn verify_checkpoint(header_bytes: &[u8], expected_hash: &Hash256) -> Result<(), PowError> {
+    let header = MiningHeader::try_from_slice(header_bytes)?;
+    let computed = Hash256(UniversalHash::new().hash(&header.to_bytes()));
+    if computed != *expected_hash {
+        return Err(PowError::HashMismatch {
+            claimed: *expected_hash,
+            computed,
+        });
+    }
+    Ok(())
+}
+
+/// Verify every checkpoint in `checkpoints`, in parallel with the
+/// `parallel` feature
+///
+/// Stops at the first mismatch and reports the lowest failing position
+/// in the list.
+#[cfg(feature = "std")]
+pub fn verify_checkpoints(checkpoints: &[Checkpoint]) -> Result<(), CheckpointError> {
+    let proofs: Vec<_> = checkpoints
+        .iter()
+        .map(|checkpoint| PowProof::new(&checkpoint.header, checkpoint.hash))
+        .collect();
+    // Checkpoints carry no difficulty claim, only the hash
+    match verify_batch(&proofs, &Target::MAX).first_failure() {
+        None => Ok(()),
+        Some((index, error)) => Err(CheckpointError {
+            height: checkpoints[index].height,
+            error,
+        }),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::testing::header_stream;
+    use crate::uhash::hash;
+
+    fn checkpoints() -> Vec<Checkpoint> {
+        header_stream(3)
+            .take(3)
+            .enumerate()
+            .map(|(i, header)| Checkpoint {
+                height: 1000 * i as u64,
+                header,
+                hash: Hash256(hash(&header.to_bytes())),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_checkpoints() {
+        let mut list = checkpoints();
+        assert_eq!(verify_checkpoints(&list), Ok(()));
+        assert_eq!(verify_checkpoints(&[]), Ok(()));
+
+        list[1].header.timestamp += 1;
+        let err = verify_checkpoints(&list).unwrap_err();
+        assert_eq!(err.height, 1000);
+        assert!(matches!(err.error, PowError::HashMismatch { .. }));
+    }
+
+    #[test]
+    fn test_verify_checkpoint_bytes() {
+        let checkpoint = checkpoints()[0];
+        let bytes = checkpoint.header.to_bytes();
+        assert_eq!(verify_checkpoint(&bytes, &checkpoint.hash), Ok(()));
+        assert!(matches!(
+            verify_checkpoint(&bytes[..60], &checkpoint.hash),
+            Err(PowError::WrongLength { .. })
+        ));
+    }
+}
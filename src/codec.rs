@@ -0,0 +1,124 @@
+//! borsh and bincode encodings (`borsh` / `bincode` features)
+//!
+//! Both use the same canonical fixed-size layouts as `to_bytes`, with no
+//! length prefix or field tags, so an encoded value is byte-for-byte what
+//! the hash and the FFI see:
+//!
+//! ```text
+//! type          size  layout
+//! MiningHeader    68  epoch_seed || miner_address || timestamp (LE) || nonce (LE)
+//! PowProof       100  MiningHeader || hash
+//! Target          32  big-endian integer
+//! ```
+//!
+//! bincode's varint integer encoding does not apply: the layout is the same
+//! under every bincode configuration.
+
+/// Implement the enabled borsh/bincode traits for `$ty` through its
+/// fixed-size byte encoding `$to(&self) -> [u8; $size]` /
+/// `$from(&[u8; $size]) -> Self`
+macro_rules! impl_fixed_codec {
+    ($ty:ty, $size:expr, $to:ident, $from:ident) => {
+        #[cfg(feature = "borsh")]
+        impl borsh::BorshSerialize for $ty {
+            fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+                writer.write_all(&self.$to())
+            }
+        }
+
+        #[cfg(feature = "borsh")]
+        impl borsh::BorshDeserialize for $ty {
+            fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+                let mut bytes = [0u8; $size];
+                reader.read_exact(&mut bytes)?;
+                Ok(Self::$from(&bytes))
+            }
+        }
+
+        #[cfg(feature = "bincode")]
+        impl bincode::Encode for $ty {
+            fn encode<E: bincode::enc::Encoder>(
+                &self,
+                encoder: &mut E,
+            ) -> Result<(), bincode::error::EncodeError> {
+                bincode::Encode::encode(&self.$to(), encoder)
+            }
+        }
+
+        #[cfg(feature = "bincode")]
+        impl<Context> bincode::Decode<Context> for $ty {
+            fn decode<D: bincode::de::Decoder<Context = Context>>(
+                decoder: &mut D,
+            ) -> Result<Self, bincode::error::DecodeError> {
+                let bytes: [u8; $size] = bincode::Decode::decode(decoder)?;
+                Ok(Self::$from(&bytes))
+            }
+        }
+
+        #[cfg(feature = "bincode")]
+        bincode::impl_borrow_decode!($ty);
+    };
+}
+
+pub(crate) use impl_fixed_codec;
+
+#[cfg(test)]
+mod tests {
+    use crate::{Hash256, MiningHeader, POW_PROOF_SIZE, PowProof, Target};
+
+    fn sample_header() -> MiningHeader {
+        MiningHeader {
+            epoch_seed: [1u8; 32],
+            miner_address: [2u8; 20],
+            timestamp: 1_700_000_000,
+            nonce: 0x0102030405060708,
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "borsh")]
+    fn test_borsh_layout() {
+        let header = sample_header();
+        let bytes = borsh::to_vec(&header).unwrap();
+        assert_eq!(bytes, header.to_bytes());
+        assert_eq!(borsh::from_slice::<MiningHeader>(&bytes).unwrap(), header);
+
+        let proof = PowProof::new(&header, Hash256([3u8; 32]));
+        let bytes = borsh::to_vec(&proof).unwrap();
+        assert_eq!(bytes, proof.to_bytes());
+        assert_eq!(borsh::from_slice::<PowProof>(&bytes).unwrap(), proof);
+
+        let target = Target::from_difficulty_bits(20);
+        let bytes = borsh::to_vec(&target).unwrap();
+        assert_eq!(bytes, target.to_be_bytes());
+        assert_eq!(borsh::from_slice::<Target>(&bytes).unwrap(), target);
+
+        // Truncated input is an error, not a short read
+        assert!(borsh::from_slice::<PowProof>(&bytes).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "bincode")]
+    fn test_bincode_layout() {
+        let config = bincode::config::standard();
+        let proof = PowProof::new(&sample_header(), Hash256([3u8; 32]));
+
+        let mut buffer = [0u8; POW_PROOF_SIZE];
+        let written = bincode::encode_into_slice(proof, &mut buffer, config).unwrap();
+        assert_eq!(written, POW_PROOF_SIZE);
+        assert_eq!(buffer, proof.to_bytes());
+        let (decoded, read) = bincode::decode_from_slice::<PowProof, _>(&buffer, config).unwrap();
+        assert_eq!((decoded, read), (proof, POW_PROOF_SIZE));
+
+        // Varint configuration does not shrink the fixed fields
+        let header = sample_header();
+        let written = bincode::encode_into_slice(header, &mut buffer, config).unwrap();
+        assert_eq!(buffer[..written], header.to_bytes());
+
+        let target = Target::from_difficulty_bits(20);
+        let written = bincode::encode_into_slice(target, &mut buffer, config).unwrap();
+        assert_eq!(buffer[..written], target.to_be_bytes());
+        let (decoded, _) = bincode::decode_from_slice::<Target, _>(&buffer, config).unwrap();
+        assert_eq!(decoded, target);
+    }
+}
@@ -0,0 +1,210 @@
+//! Shareable hashing context with pooled scratchpads
+//!
+//! [`UniversalHash`] needs `&mut self` because it owns its scratchpads, so
+//! server code sharing one across request handlers ends up behind a
+//! `Mutex`, serializing every verification. [`UhashContext`] separates the
+//! two: the context is immutable configuration, `Send + Sync` and meant to
+//! live in an `Arc`, and each call checks a hasher out of an internal pool
+//! for the duration of one hash.
+//!
+//! Code that has no natural place to keep a context can use
+//! [`with_hasher`], which keeps idle hashers in a thread-local pool instead.
+
+use std::cell::RefCell;
+use std::sync::Mutex;
+
+use crate::error::UhashError;
+use crate::hash256::Hash256;
+use crate::header::MiningHeader;
+use crate::job::Job;
+use crate::pow::PowError;
+use crate::primitives::{ConsensusPrimitives, PrimitiveSet};
+use crate::uhash::UniversalHash;
+
+/// Immutable, thread-safe hashing context
+///
+/// Concurrent calls each get their own scratchpads: an idle hasher from the
+/// pool if there is one, otherwise a [`fork`](UniversalHash::fork) of the
+/// template. Hashers return to the pool afterwards, so it grows to the peak
+/// number of concurrent calls and no further.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use uhash_core::{MiningHeader, UhashContext};
+///
+/// let context = Arc::new(UhashContext::new());
+/// let handles: Vec<_> = (0..4u64)
+///     .map(|nonce| {
+///         let context = Arc::clone(&context);
+///         let header = MiningHeader::default().with_nonce(nonce);
+///         std::thread::spawn(move || context.verify_pow(&header, 0))
+///     })
+///     .collect();
+/// for handle in handles {
+///     assert!(handle.join().unwrap().is_ok());
+/// }
+/// ```
+pub struct UhashContext<P = ConsensusPrimitives> {
+    /// Configuration new pool members are forked from; never hashes, so its
+    /// scratchpad pages are never touched
+    template: UniversalHash<P>,
+    /// Idle hashers
+    pool: Mutex<Vec<UniversalHash<P>>>,
+}
+
+impl UhashContext {
+    /// Context with a default [`UniversalHash`] configuration
+    pub fn new() -> Self {
+        Self::from_hasher(UniversalHash::new())
+    }
+}
+
+impl Default for UhashContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: PrimitiveSet> UhashContext<P> {
+    /// Context whose pooled hashers are forks of `template`
+    ///
+    /// Configure the template first, e.g.
+    /// `UniversalHash::new_sequential().with_prefetch(true)`.
+    pub fn from_hasher(template: UniversalHash<P>) -> Self {
+        Self {
+            template,
+            pool: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Run `f` with a hasher checked out of the pool
+    pub fn with_hasher<R>(&self, f: impl FnOnce(&mut UniversalHash<P>) -> R) -> R {
+        let pooled = self.lock_pool().pop();
+        let mut hasher = pooled.unwrap_or_else(|| self.template.fork());
+        let result = f(&mut hasher);
+        self.lock_pool().push(hasher);
+        result
+    }
+
+    /// Compute the UniversalHash of input data, as [`UniversalHash::hash`]
+    pub fn hash(&self, input: &[u8]) -> [u8; 32] {
+        self.with_hasher(|hasher| hasher.hash(input))
+    }
+
+    /// Hash an exact mining header, as [`UniversalHash::hash_header`]
+    pub fn hash_header(&self, input: &[u8]) -> Result<[u8; 32], UhashError> {
+        self.with_hasher(|hasher| hasher.hash_header(input))
+    }
+
+    /// Hash `job`'s header prefix followed by `nonce`, as [`UniversalHash::hash_nonce`]
+    pub fn hash_nonce(&self, job: &Job, nonce: u64) -> [u8; 32] {
+        self.with_hasher(|hasher| hasher.hash_nonce(job, nonce))
+    }
+
+    /// Number of idle hashers in the pool
+    pub fn idle(&self) -> usize {
+        self.lock_pool().len()
+    }
+
+    /// The pool only ever holds complete hashers, so a panic in another
+    /// caller's `with_hasher` leaves nothing to repair
+    fn lock_pool(&self) -> std::sync::MutexGuard<'_, Vec<UniversalHash<P>>> {
+        self.pool
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl UhashContext {
+    /// Hash `header` and check it against `difficulty`, as [`UniversalHash::verify_pow`]
+    pub fn verify_pow(&self, header: &MiningHeader, difficulty: u32) -> Result<Hash256, PowError> {
+        self.with_hasher(|hasher| hasher.verify_pow(header, difficulty))
+    }
+}
+
+thread_local! {
+    /// Idle hashers owned by this thread, for [`with_hasher`]
+    static THREAD_POOL: RefCell<Vec<UniversalHash>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Run `f` with a hasher from this thread's pool
+///
+/// The first call on a thread allocates a hasher; later calls reuse it, so
+/// request handlers pay the 2MB scratchpad allocation once per worker
+/// thread instead of once per request. Nested calls get a second hasher.
+/// Pooled hashers live until their thread exits.
+///
+/// ```rust
+/// use uhash_core::{MiningHeader, with_hasher};
+///
+/// let header = MiningHeader::default();
+/// let result = with_hasher(|hasher| hasher.verify_pow(&header, 0));
+/// assert!(result.is_ok());
+/// ```
+pub fn with_hasher<R>(f: impl FnOnce(&mut UniversalHash) -> R) -> R {
+    let pooled = THREAD_POOL.with(|pool| pool.borrow_mut().pop());
+    let mut hasher = pooled.unwrap_or_default();
+    let result = f(&mut hasher);
+    THREAD_POOL.with(|pool| pool.borrow_mut().push(hasher));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uhash::hash;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_context_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<UhashContext>();
+    }
+
+    #[test]
+    fn test_context_reuses_hashers() {
+        let context = UhashContext::from_hasher(UniversalHash::new_sequential());
+        assert_eq!(context.idle(), 0);
+        assert_eq!(context.hash(b"one"), hash(b"one"));
+        assert_eq!(context.hash(b"two"), hash(b"two"));
+        assert_eq!(context.idle(), 1);
+    }
+
+    #[test]
+    fn test_context_shared_across_threads() {
+        let context = Arc::new(UhashContext::from_hasher(UniversalHash::new_sequential()));
+        let handles: Vec<_> = (0..3u64)
+            .map(|i| {
+                let context = Arc::clone(&context);
+                std::thread::spawn(move || (i, context.hash(&i.to_le_bytes())))
+            })
+            .collect();
+        for handle in handles {
+            let (i, result) = handle.join().unwrap();
+            assert_eq!(result, hash(&i.to_le_bytes()));
+        }
+        assert!((1..=3).contains(&context.idle()));
+    }
+
+    #[test]
+    fn test_with_hasher_reuses_thread_hasher() {
+        std::thread::spawn(|| {
+            assert_eq!(with_hasher(|h| h.hash(b"one")), hash(b"one"));
+            let first = with_hasher(|h| h as *const UniversalHash);
+            let second = with_hasher(|h| h as *const UniversalHash);
+            assert_eq!(first, second);
+
+            // Nested use gets its own hasher
+            with_hasher(|outer| {
+                let inner = with_hasher(|inner| inner.hash(b"inner"));
+                assert_eq!(inner, hash(b"inner"));
+                assert_eq!(outer.hash(b"outer"), hash(b"outer"));
+            });
+            assert_eq!(THREAD_POOL.with(|pool| pool.borrow().len()), 2);
+        })
+        .join()
+        .unwrap();
+    }
+}
@@ -0,0 +1,114 @@
+//! Constant-time comparisons of 256-bit values
+//!
+//! Services verifying client puzzles compare attacker-chosen hashes
+//! against a secret-free but attacker-probed target; an early-exit
+//! comparison would reveal through timing how many leading bytes of a
+//! submission were right. These helpers visit every byte and combine
+//! per-byte results with masks instead of branches. The final result is
+//! passed through [`black_box`] to keep the optimizer from
+//! reintroducing an early exit; like any pure-Rust constant-time code
+//! this is best effort, not a guarantee about the generated machine code.
+
+use core::hint::black_box;
+
+/// `a == b` without early exit
+#[inline]
+pub(crate) fn eq(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    let diff = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    black_box(diff) == 0
+}
+
+/// `a <= b` as big-endian integers, without early exit
+#[inline]
+pub(crate) fn le(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    // 1 once the most significant differing byte has been seen
+    let mut less = 0u8;
+    let mut greater = 0u8;
+    for (&x, &y) in a.iter().zip(b) {
+        let undecided = !(less | greater) & 1;
+        less |= lt_u8(x, y) & undecided;
+        greater |= lt_u8(y, x) & undecided;
+    }
+    black_box(greater) == 0
+}
+
+/// Whether `hash` has at least `difficulty` leading zero bits, without
+/// early exit
+///
+/// Masks every bit the difficulty requires to be zero; the masks depend
+/// only on the public difficulty.
+#[inline]
+pub(crate) fn has_leading_zeros(hash: &[u8; 32], difficulty: u32) -> bool {
+    if difficulty > 256 {
+        return false;
+    }
+    let set = hash.iter().enumerate().fold(0u8, |acc, (i, &byte)| {
+        let bits = difficulty.saturating_sub(8 * i as u32).min(8);
+        acc | (byte & (0xFF00u16 >> bits) as u8)
+    });
+    black_box(set) == 0
+}
+
+/// All ones if `a == b`, else zero, without a branch
+#[inline(always)]
+pub(crate) fn eq_mask(a: usize, b: usize) -> u64 {
+    let diff = (a ^ b) as u64;
+    // Top bit of `diff | -diff` is set exactly when diff is non-zero
+    ((diff | diff.wrapping_neg()) >> 63).wrapping_sub(1)
+}
+
+/// 1 if `x < y`, else 0
+#[inline(always)]
+fn lt_u8(x: u8, y: u8) -> u8 {
+    ((x as u16).wrapping_sub(y as u16) >> 15) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::u256::U256;
+
+    /// Values probing every byte position and carry-like edge
+    fn samples() -> [[u8; 32]; 8] {
+        let mut samples = [[0u8; 32]; 8];
+        samples[1] = [0xFF; 32];
+        samples[2][31] = 1;
+        samples[3][0] = 0x80;
+        samples[4][15] = 0x7F;
+        samples[5] = [0xFF; 32];
+        samples[5][31] = 0xFE;
+        samples[6] = [0x01; 32];
+        samples[7][..16].copy_from_slice(&[0xFF; 16]);
+        samples
+    }
+
+    #[test]
+    fn test_le_and_eq_match_integer_order() {
+        for a in samples() {
+            for b in samples() {
+                let (x, y) = (U256::from_be_bytes(&a), U256::from_be_bytes(&b));
+                assert_eq!(le(&a, &b), x <= y, "{:?} <= {:?}", x, y);
+                assert_eq!(eq(&a, &b), x == y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_eq_mask() {
+        assert_eq!(eq_mask(0, 0), u64::MAX);
+        assert_eq!(eq_mask(8191, 8191), u64::MAX);
+        assert_eq!(eq_mask(0, 1), 0);
+        assert_eq!(eq_mask(usize::MAX, 0), 0);
+        assert_eq!(eq_mask(1 << 20, 0), 0);
+    }
+
+    #[test]
+    fn test_has_leading_zeros_matches_count() {
+        for hash in samples() {
+            let zeros = crate::Hash256(hash).leading_zeros();
+            for difficulty in [0, 1, 7, 8, 9, 120, 255, 256, 257, u32::MAX] {
+                assert_eq!(has_leading_zeros(&hash, difficulty), zeros >= difficulty);
+            }
+        }
+    }
+}
@@ -0,0 +1,420 @@
+//! NVIDIA GPU mining through the CUDA driver API
+//!
+//! The kernel is [`cuda_kernel_source`](crate::cuda_kernel_source), which
+//! farms build once per architecture, e.g.
+//!
+//! ```text
+//! nvcc --ptx -arch=sm_86 -o uhash.ptx uhash.cu
+//! ```
+//!
+//! and hand the PTX (or cubin) image to [`CudaMiner::new`]. Linking needs
+//! the driver library (`libcuda.so` / `nvcuda.dll`) but not the toolkit.
+
+use core::ffi::{c_char, c_int, c_uint, c_void};
+use core::ptr;
+use std::ffi::CStr;
+use std::ops::Range;
+
+use crate::gpu::{GpuError, GpuWatchdog, finalize_lanes, find_solution, lane_inputs};
+use crate::hash256::Hash256;
+use crate::job::Job;
+use crate::opencl::OPENCL_KERNEL_NAME;
+use crate::params::{CHAINS, SCRATCHPAD_SIZE};
+use crate::target::Target;
+
+/// Threads per block
+const BLOCK_THREADS: u32 = 64;
+
+/// Upper bound on nonces per launch, to keep individual launches short
+const MAX_BATCH: usize = 4096;
+
+/// Share of free device memory left to the driver and other processes
+const MEMORY_HEADROOM: usize = 8;
+
+#[allow(non_camel_case_types)]
+mod ffi {
+    use core::ffi::{c_char, c_int, c_uint, c_void};
+
+    pub type CUresult = c_int;
+    pub type CUdevice = c_int;
+    pub type CUdeviceptr = u64;
+    pub type CUcontext = *mut c_void;
+    pub type CUmodule = *mut c_void;
+    pub type CUfunction = *mut c_void;
+
+    pub const CUDA_SUCCESS: CUresult = 0;
+    pub const CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR: c_int = 75;
+    pub const CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MINOR: c_int = 76;
+
+    #[link(name = "cuda")]
+    unsafe extern "C" {
+        pub fn cuInit(flags: c_uint) -> CUresult;
+        pub fn cuGetErrorName(error: CUresult, name: *mut *const c_char) -> CUresult;
+        pub fn cuDeviceGetCount(count: *mut c_int) -> CUresult;
+        pub fn cuDeviceGet(device: *mut CUdevice, ordinal: c_int) -> CUresult;
+        pub fn cuDeviceGetName(name: *mut c_char, len: c_int, device: CUdevice) -> CUresult;
+        pub fn cuDeviceTotalMem_v2(bytes: *mut usize, device: CUdevice) -> CUresult;
+        pub fn cuDeviceGetAttribute(value: *mut c_int, attrib: c_int, device: CUdevice)
+        -> CUresult;
+        pub fn cuCtxCreate_v2(ctx: *mut CUcontext, flags: c_uint, device: CUdevice) -> CUresult;
+        pub fn cuCtxDestroy_v2(ctx: CUcontext) -> CUresult;
+        pub fn cuCtxSetCurrent(ctx: CUcontext) -> CUresult;
+        pub fn cuCtxSynchronize() -> CUresult;
+        pub fn cuMemGetInfo_v2(free: *mut usize, total: *mut usize) -> CUresult;
+        pub fn cuModuleLoadData(module: *mut CUmodule, image: *const c_void) -> CUresult;
+        pub fn cuModuleUnload(module: CUmodule) -> CUresult;
+        pub fn cuModuleGetFunction(
+            function: *mut CUfunction,
+            module: CUmodule,
+            name: *const c_char,
+        ) -> CUresult;
+        pub fn cuMemAlloc_v2(ptr: *mut CUdeviceptr, bytes: usize) -> CUresult;
+        pub fn cuMemFree_v2(ptr: CUdeviceptr) -> CUresult;
+        pub fn cuMemcpyHtoD_v2(dst: CUdeviceptr, src: *const c_void, bytes: usize) -> CUresult;
+        pub fn cuMemcpyDtoH_v2(dst: *mut c_void, src: CUdeviceptr, bytes: usize) -> CUresult;
+        pub fn cuLaunchKernel(
+            function: CUfunction,
+            grid_x: c_uint,
+            grid_y: c_uint,
+            grid_z: c_uint,
+            block_x: c_uint,
+            block_y: c_uint,
+            block_z: c_uint,
+            shared_bytes: c_uint,
+            stream: *mut c_void,
+            params: *mut *mut c_void,
+            extra: *mut *mut c_void,
+        ) -> CUresult;
+    }
+}
+
+fn check(result: ffi::CUresult) -> Result<(), GpuError> {
+    if result == ffi::CUDA_SUCCESS {
+        return Ok(());
+    }
+    let mut name: *const c_char = ptr::null();
+    // SAFETY: cuGetErrorName only writes a pointer to a static string
+    let known = unsafe { ffi::cuGetErrorName(result, &mut name) } == ffi::CUDA_SUCCESS;
+    let message = if known && !name.is_null() {
+        // SAFETY: the driver returned a NUL-terminated static string
+        unsafe { CStr::from_ptr(name) }
+            .to_string_lossy()
+            .into_owned()
+    } else {
+        format!("CUDA error {}", result)
+    };
+    Err(GpuError::Backend(message))
+}
+
+fn init() -> Result<(), GpuError> {
+    // SAFETY: cuInit may be called any number of times
+    match unsafe { ffi::cuInit(0) } {
+        ffi::CUDA_SUCCESS => Ok(()),
+        // CUDA_ERROR_NO_DEVICE
+        100 => Err(GpuError::NoDevice),
+        result => check(result),
+    }
+}
+
+/// A CUDA device as reported by the driver
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CudaDevice {
+    /// Driver ordinal, as passed to [`CudaMiner::new`]
+    pub ordinal: usize,
+    /// Marketing name, e.g. "NVIDIA GeForce RTX 4090"
+    pub name: String,
+    /// Total device memory in bytes
+    pub total_memory: usize,
+    /// Compute capability as (major, minor)
+    pub compute_capability: (u32, u32),
+}
+
+impl CudaDevice {
+    fn query(ordinal: usize) -> Result<Self, GpuError> {
+        let mut device: ffi::CUdevice = 0;
+        let mut name = [0 as c_char; 256];
+        let mut total_memory = 0usize;
+        let mut major: c_int = 0;
+        let mut minor: c_int = 0;
+        // SAFETY: every out-pointer is valid for the duration of the call
+        unsafe {
+            check(ffi::cuDeviceGet(&mut device, ordinal as c_int))?;
+            check(ffi::cuDeviceGetName(
+                name.as_mut_ptr(),
+                name.len() as c_int,
+                device,
+            ))?;
+            check(ffi::cuDeviceTotalMem_v2(&mut total_memory, device))?;
+            check(ffi::cuDeviceGetAttribute(
+                &mut major,
+                ffi::CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR,
+                device,
+            ))?;
+            check(ffi::cuDeviceGetAttribute(
+                &mut minor,
+                ffi::CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MINOR,
+                device,
+            ))?;
+        }
+        // SAFETY: cuDeviceGetName NUL-terminates within `len`
+        let name = unsafe { CStr::from_ptr(name.as_ptr()) }
+            .to_string_lossy()
+            .into_owned();
+        Ok(Self {
+            ordinal,
+            name,
+            total_memory,
+            compute_capability: (major as u32, minor as u32),
+        })
+    }
+}
+
+/// Device allocation freed on drop (the owning context must be current)
+struct DeviceBuffer {
+    ptr: ffi::CUdeviceptr,
+    bytes: usize,
+}
+
+impl DeviceBuffer {
+    fn new(bytes: usize) -> Result<Self, GpuError> {
+        let mut ptr = 0;
+        // SAFETY: `ptr` is a valid out-pointer
+        check(unsafe { ffi::cuMemAlloc_v2(&mut ptr, bytes) })?;
+        Ok(Self { ptr, bytes })
+    }
+
+    fn from_slice<T: Copy>(data: &[T]) -> Result<Self, GpuError> {
+        let buffer = Self::new(size_of_val(data))?;
+        // SAFETY: the allocation holds exactly `bytes` bytes
+        check(unsafe { ffi::cuMemcpyHtoD_v2(buffer.ptr, data.as_ptr().cast(), buffer.bytes) })?;
+        Ok(buffer)
+    }
+}
+
+impl Drop for DeviceBuffer {
+    fn drop(&mut self) {
+        // SAFETY: `ptr` came from cuMemAlloc and is freed once
+        unsafe { ffi::cuMemFree_v2(self.ptr) };
+    }
+}
+
+/// CUDA miner bound to one device
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use uhash_core::{CudaMiner, MiningHeader, Target};
+///
+/// let ptx = std::fs::read("uhash.ptx").unwrap();
+/// for device in CudaMiner::devices()? {
+///     let mut miner = CudaMiner::new(device.ordinal, &ptx)?;
+///     println!("{}: {} nonces per launch", device.name, miner.batch_size());
+///     let job = MiningHeader::default().job();
+///     miner.mine(&job, 0..1 << 20, &Target::from_difficulty_bits(16))?;
+/// }
+/// # Ok::<(), uhash_core::GpuError>(())
+/// ```
+pub struct CudaMiner {
+    device: CudaDevice,
+    context: ffi::CUcontext,
+    module: ffi::CUmodule,
+    function: ffi::CUfunction,
+    // Dropped before the context is destroyed
+    scratch: Option<DeviceBuffer>,
+    batch_size: usize,
+}
+
+// SAFETY: the context is made current on the calling thread before every
+// driver call, and `&mut self` serializes use
+unsafe impl Send for CudaMiner {}
+
+impl CudaMiner {
+    /// All CUDA devices visible to the driver
+    pub fn devices() -> Result<Vec<CudaDevice>, GpuError> {
+        init()?;
+        let mut count: c_int = 0;
+        // SAFETY: `count` is a valid out-pointer
+        check(unsafe { ffi::cuDeviceGetCount(&mut count) })?;
+        (0..count.max(0) as usize).map(CudaDevice::query).collect()
+    }
+
+    /// Load a PTX or cubin `image` of [`cuda_kernel_source`](crate::cuda_kernel_source) on device `ordinal`
+    ///
+    /// The batch size is sized to the device's free memory.
+    pub fn new(ordinal: usize, image: &[u8]) -> Result<Self, GpuError> {
+        init()?;
+        let device = CudaDevice::query(ordinal)?;
+
+        let mut handle: ffi::CUdevice = 0;
+        let mut context = ptr::null_mut();
+        // SAFETY: out-pointers are valid; the context is destroyed on drop
+        unsafe {
+            check(ffi::cuDeviceGet(&mut handle, ordinal as c_int))?;
+            check(ffi::cuCtxCreate_v2(&mut context, 0, handle))?;
+        }
+        let mut miner = Self {
+            device,
+            context,
+            module: ptr::null_mut(),
+            function: ptr::null_mut(),
+            scratch: None,
+            batch_size: 0,
+        };
+
+        // PTX is text and must be NUL-terminated; a trailing zero is harmless for cubin
+        let mut image = image.to_vec();
+        image.push(0);
+        let name = format!("{}\0", OPENCL_KERNEL_NAME);
+        // SAFETY: the context is current and all pointers outlive the calls
+        unsafe {
+            check(ffi::cuModuleLoadData(
+                &mut miner.module,
+                image.as_ptr().cast(),
+            ))?;
+            check(ffi::cuModuleGetFunction(
+                &mut miner.function,
+                miner.module,
+                name.as_ptr().cast(),
+            ))?;
+        }
+
+        let mut free = 0usize;
+        let mut total = 0usize;
+        // SAFETY: out-pointers are valid
+        check(unsafe { ffi::cuMemGetInfo_v2(&mut free, &mut total) })?;
+        let per_nonce = CHAINS * SCRATCHPAD_SIZE;
+        let usable = free - free / MEMORY_HEADROOM;
+        miner.batch_size = (usable / per_nonce).min(MAX_BATCH);
+        if miner.batch_size == 0 {
+            return Err(GpuError::Backend("not enough free device memory".into()));
+        }
+        miner.scratch = Some(DeviceBuffer::new(miner.batch_size * per_nonce)?);
+        Ok(miner)
+    }
+
+    /// Device this miner runs on
+    pub fn device(&self) -> &CudaDevice {
+        &self.device
+    }
+
+    /// Nonces hashed per kernel launch
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Hash `job` with each of `nonces`, identical to [`UniversalHash::hash_nonce`](crate::UniversalHash::hash_nonce)
+    pub fn hash_batch(&mut self, job: &Job, nonces: &[u64]) -> Result<Vec<[u8; 32]>, GpuError> {
+        let mut hashes = Vec::with_capacity(nonces.len());
+        for chunk in nonces.chunks(self.batch_size) {
+            hashes.extend(self.launch(job, chunk)?);
+        }
+        Ok(hashes)
+    }
+
+    /// Search `range` for the first nonce whose hash meets `target`
+    pub fn mine(
+        &mut self,
+        job: &Job,
+        range: Range<u64>,
+        target: &Target,
+    ) -> Result<Option<(u64, Hash256)>, GpuError> {
+        let mut start = range.start;
+        while start < range.end {
+            let end = start.saturating_add(self.batch_size as u64).min(range.end);
+            let nonces: Vec<u64> = (start..end).collect();
+            let hashes = self.launch(job, &nonces)?;
+            if let Some(solution) = find_solution(&nonces, hashes, target) {
+                return Ok(Some(solution));
+            }
+            start = end;
+        }
+        Ok(None)
+    }
+
+    /// [`mine`](Self::mine), re-verifying solutions on the CPU through
+    /// `watchdog` and skipping past any the GPU got wrong
+    pub fn mine_watched(
+        &mut self,
+        job: &Job,
+        range: Range<u64>,
+        target: &Target,
+        watchdog: &mut GpuWatchdog,
+    ) -> Result<Option<(u64, Hash256)>, GpuError> {
+        let mut start = range.start;
+        while start < range.end {
+            let end = start.saturating_add(self.batch_size as u64).min(range.end);
+            let nonces: Vec<u64> = (start..end).collect();
+            let hashes = self.launch(job, &nonces)?;
+            if let Some(solution) = watchdog.find_solution(job, &nonces, hashes, target) {
+                return Ok(Some(solution));
+            }
+            start = end;
+        }
+        Ok(None)
+    }
+
+    /// Run one batch of at most `batch_size` nonces
+    fn launch(&mut self, job: &Job, nonces: &[u64]) -> Result<Vec<[u8; 32]>, GpuError> {
+        if nonces.is_empty() {
+            return Ok(Vec::new());
+        }
+        // SAFETY: the context stays alive as long as `self`
+        check(unsafe { ffi::cuCtxSetCurrent(self.context) })?;
+
+        let inputs = lane_inputs(job, nonces);
+        let mut lanes = (nonces.len() * CHAINS) as c_uint;
+        let seeds = DeviceBuffer::from_slice(&inputs.seeds)?;
+        let nonce_buffer = DeviceBuffer::from_slice(nonces)?;
+        let states = DeviceBuffer::new(lanes as usize * 32)?;
+        let mut scratch = self.scratch.as_ref().map_or(0, |buffer| buffer.ptr);
+        let (mut seeds_ptr, mut nonces_ptr, mut states_ptr) =
+            (seeds.ptr, nonce_buffer.ptr, states.ptr);
+
+        let mut params: [*mut c_void; 5] = [
+            (&mut seeds_ptr as *mut ffi::CUdeviceptr).cast(),
+            (&mut nonces_ptr as *mut ffi::CUdeviceptr).cast(),
+            (&mut scratch as *mut ffi::CUdeviceptr).cast(),
+            (&mut states_ptr as *mut ffi::CUdeviceptr).cast(),
+            (&mut lanes as *mut c_uint).cast(),
+        ];
+        // SAFETY: the parameters match the kernel signature and outlive the
+        // synchronous launch; the scratch buffer covers `lanes` scratchpads
+        unsafe {
+            check(ffi::cuLaunchKernel(
+                self.function,
+                lanes.div_ceil(BLOCK_THREADS),
+                1,
+                1,
+                BLOCK_THREADS,
+                1,
+                1,
+                0,
+                ptr::null_mut(),
+                params.as_mut_ptr(),
+                ptr::null_mut(),
+            ))?;
+            check(ffi::cuCtxSynchronize())?;
+        }
+
+        let mut words = vec![0u32; lanes as usize * 8];
+        // SAFETY: `words` holds exactly `states.bytes` bytes
+        check(unsafe {
+            ffi::cuMemcpyDtoH_v2(words.as_mut_ptr().cast(), states.ptr, states.bytes)
+        })?;
+        Ok(finalize_lanes(&words))
+    }
+}
+
+impl Drop for CudaMiner {
+    fn drop(&mut self) {
+        // SAFETY: the handles were created by this miner and are released once
+        unsafe {
+            ffi::cuCtxSetCurrent(self.context);
+            self.scratch = None;
+            if !self.module.is_null() {
+                ffi::cuModuleUnload(self.module);
+            }
+            ffi::cuCtxDestroy_v2(self.context);
+        }
+    }
+}
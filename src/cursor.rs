@@ -0,0 +1,134 @@
+//! Resumable position of a [`MinerPool`](crate::MinerPool) in the nonce space
+//!
+//! Workers walk their nonce sequences one step per hash, so where a pool
+//! stands on a piece of work is one step count per worker. A
+//! [`MiningCursor`] records those counts; saved periodically and handed
+//! back to [`MinerPool::resume`](crate::MinerPool::resume) after a restart,
+//! it lets the pool continue without rescanning nonces it already tried.
+//! Only the hash each worker had in flight is repeated.
+//!
+//! A cursor is only meaningful for the same work, worker count and
+//! [`NonceStrategy`](crate::NonceStrategy) (for [`RandomOrder`](crate::RandomOrder),
+//! the same key). The encoding is
+//!
+//! ```text
+//! work_id (u64 LE) || start_nonce (u64 LE) || workers (u32 LE) || steps (u64 LE each)
+//! ```
+
+use core::fmt;
+
+use crate::endian::{load_u32_le, load_u64_le};
+
+/// Bytes before the per-worker steps in [`MiningCursor::to_bytes`]
+const CURSOR_HEADER_SIZE: usize = 20;
+
+/// Where each worker of a pool stands on one piece of work
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MiningCursor {
+    /// [`MiningWork::id`](crate::MiningWork::id) of the work
+    pub work_id: u64,
+    /// [`MiningWork::start_nonce`](crate::MiningWork::start_nonce) of the work
+    pub start_nonce: u64,
+    /// Next step of each worker's nonce sequence, indexed by worker
+    pub steps: Vec<u64>,
+}
+
+/// Reason a cursor can't be decoded or resumed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorError {
+    /// Encoded cursor is truncated or its length doesn't match its
+    /// worker count
+    Malformed,
+    /// Cursor was taken on a pool with a different number of workers
+    WorkerCount {
+        /// Workers recorded in the cursor
+        cursor: usize,
+        /// Workers in the pool resuming it
+        pool: usize,
+    },
+    /// Cursor was taken on work with a different id or start nonce
+    WorkMismatch,
+}
+
+impl fmt::Display for CursorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CursorError::Malformed => write!(f, "malformed mining cursor"),
+            CursorError::WorkerCount { cursor, pool } => {
+                write!(f, "cursor has {} workers, pool has {}", cursor, pool)
+            }
+            CursorError::WorkMismatch => write!(f, "cursor was taken on different work"),
+        }
+    }
+}
+
+impl std::error::Error for CursorError {}
+
+impl MiningCursor {
+    /// Nonces the workers have tried, summed over all of them
+    pub fn hashes(&self) -> u64 {
+        self.steps
+            .iter()
+            .fold(0, |sum, step| sum.saturating_add(*step))
+    }
+
+    /// Fixed-layout encoding, for writing to a checkpoint file
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(CURSOR_HEADER_SIZE + self.steps.len() * 8);
+        bytes.extend_from_slice(&self.work_id.to_le_bytes());
+        bytes.extend_from_slice(&self.start_nonce.to_le_bytes());
+        bytes.extend_from_slice(&(self.steps.len() as u32).to_le_bytes());
+        for step in &self.steps {
+            bytes.extend_from_slice(&step.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode [`to_bytes`](Self::to_bytes) output
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CursorError> {
+        if bytes.len() < CURSOR_HEADER_SIZE {
+            return Err(CursorError::Malformed);
+        }
+        let workers = load_u32_le(bytes, 16) as usize;
+        let steps = &bytes[CURSOR_HEADER_SIZE..];
+        if steps.len() != workers.saturating_mul(8) {
+            return Err(CursorError::Malformed);
+        }
+        Ok(MiningCursor {
+            work_id: load_u64_le(bytes, 0),
+            start_nonce: load_u64_le(bytes, 8),
+            steps: steps
+                .chunks_exact(8)
+                .map(|step| load_u64_le(step, 0))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_encoding_round_trips() {
+        let cursor = MiningCursor {
+            work_id: 9,
+            start_nonce: u64::MAX,
+            steps: vec![0, 5, u64::MAX],
+        };
+        let bytes = cursor.to_bytes();
+        assert_eq!(bytes.len(), CURSOR_HEADER_SIZE + 24);
+        assert_eq!(MiningCursor::from_bytes(&bytes), Ok(cursor.clone()));
+        assert_eq!(cursor.hashes(), u64::MAX);
+
+        assert_eq!(
+            MiningCursor::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(CursorError::Malformed)
+        );
+        assert_eq!(
+            MiningCursor::from_bytes(&bytes[..10]),
+            Err(CursorError::Malformed)
+        );
+    }
+}
@@ -0,0 +1,195 @@
+//! Duplicate share detection for pool servers
+//!
+//! A miner resubmitting a share it already got credit for must be rejected
+//! before the share is paid twice. [`ShareDeduplicator`] remembers the most
+//! recent `capacity` accepted submissions as 64-bit fingerprints of the
+//! full header (nonce and extranonce included), so lookups are O(1) and
+//! memory stays at roughly 24 bytes per remembered share however long the
+//! server runs.
+//!
+//! Fingerprints are keyed with a random per-instance SipHash key, so
+//! miners cannot craft colliding headers; an honest collision, which would
+//! reject one valid share, has probability about `capacity / 2^64`.
+
+use std::collections::hash_map::RandomState;
+use std::collections::{HashSet, VecDeque};
+use std::hash::BuildHasher;
+
+use crate::header::HEADER_SIZE;
+use crate::target::{ShareResult, Target, check_share};
+
+/// Bounded set of recently accepted share submissions
+///
+/// Once `capacity` shares are remembered, each new one evicts the oldest,
+/// so size the capacity to cover the shares a job can receive before it
+/// goes stale (or call [`clear`](Self::clear) on each new block).
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{MiningHeader, ShareDeduplicator, ShareResult, Target};
+///
+/// let mut dedup = ShareDeduplicator::new(10_000);
+/// let header = MiningHeader::default().to_bytes();
+/// let hash = [0u8; 32];
+/// let (share, block) = (Target::MAX, Target::from_difficulty_bits(32));
+///
+/// assert!(dedup.check_share(&header, &hash, &share, &block).is_valid());
+/// assert_eq!(
+///     dedup.check_share(&header, &hash, &share, &block),
+///     ShareResult::Duplicate
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct ShareDeduplicator {
+    capacity: usize,
+    keys: RandomState,
+    seen: HashSet<u64>,
+    /// Insertion order, oldest first, for eviction
+    order: VecDeque<u64>,
+}
+
+impl ShareDeduplicator {
+    /// Empty set remembering up to `capacity` shares (at least one)
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            keys: RandomState::new(),
+            seen: HashSet::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Maximum number of shares remembered
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of shares currently remembered
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Whether no share is remembered
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Whether `header` was already recorded
+    ///
+    /// Cheap enough to call before hashing a submission, so duplicates do
+    /// not cost a hash.
+    pub fn contains(&self, header: &[u8; HEADER_SIZE]) -> bool {
+        self.seen.contains(&self.fingerprint(header))
+    }
+
+    /// Record `header`, returning `false` if it was already recorded
+    pub fn insert(&mut self, header: &[u8; HEADER_SIZE]) -> bool {
+        let fingerprint = self.fingerprint(header);
+        if !self.seen.insert(fingerprint) {
+            return false;
+        }
+        if self.order.len() == self.capacity
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.seen.remove(&oldest);
+        }
+        self.order.push_back(fingerprint);
+        true
+    }
+
+    /// Forget every recorded share
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.order.clear();
+    }
+
+    /// [`check_share`], rejecting resubmissions of an accepted share
+    ///
+    /// `hash` is the hash of `header`. Valid shares are recorded and
+    /// reported again as [`ShareResult::Duplicate`]; invalid ones are not
+    /// recorded, so they cannot crowd out real shares.
+    pub fn check_share(
+        &mut self,
+        header: &[u8; HEADER_SIZE],
+        hash: &[u8; 32],
+        share_target: &Target,
+        block_target: &Target,
+    ) -> ShareResult {
+        let result = check_share(hash, share_target, block_target);
+        if result.is_valid() && !self.insert(header) {
+            return ShareResult::Duplicate;
+        }
+        result
+    }
+
+    fn fingerprint(&self, header: &[u8; HEADER_SIZE]) -> u64 {
+        self.keys.hash_one(header)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::MiningHeader;
+
+    fn header(nonce: u64) -> [u8; HEADER_SIZE] {
+        MiningHeader {
+            nonce,
+            ..Default::default()
+        }
+        .to_bytes()
+    }
+
+    #[test]
+    fn test_insert_and_evict() {
+        let mut dedup = ShareDeduplicator::new(3);
+        assert!(dedup.is_empty());
+        for nonce in 0..3 {
+            assert!(dedup.insert(&header(nonce)));
+        }
+        assert!(!dedup.insert(&header(1)));
+        assert_eq!(dedup.len(), 3);
+
+        // The oldest share is evicted to make room
+        assert!(dedup.insert(&header(3)));
+        assert_eq!(dedup.len(), 3);
+        assert!(!dedup.contains(&header(0)));
+        assert!(dedup.contains(&header(1)) && dedup.contains(&header(3)));
+
+        dedup.clear();
+        assert!(dedup.is_empty() && !dedup.contains(&header(3)));
+    }
+
+    #[test]
+    fn test_check_share_duplicates() {
+        let mut dedup = ShareDeduplicator::new(16);
+        let share = Target::from_difficulty_bits(8);
+        let block = Target::from_difficulty_bits(16);
+        let good = [0u8; 32];
+        let bad = [0xFFu8; 32];
+
+        assert_eq!(
+            dedup.check_share(&header(1), &bad, &share, &block),
+            ShareResult::Invalid
+        );
+        assert!(dedup.is_empty());
+
+        assert!(
+            dedup
+                .check_share(&header(2), &good, &share, &block)
+                .is_block()
+        );
+        let again = dedup.check_share(&header(2), &good, &share, &block);
+        assert_eq!(again, ShareResult::Duplicate);
+        assert!(!again.is_valid() && !again.is_block());
+
+        // Same hash claimed for another nonce is a different submission
+        assert!(
+            dedup
+                .check_share(&header(3), &good, &share, &block)
+                .is_valid()
+        );
+    }
+}
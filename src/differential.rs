@@ -0,0 +1,401 @@
+//! Cross-backend differential testing
+//!
+//! Runs the same headers through every backend compiled into this build and
+//! reports any output that differs from the portable software primitives.
+//! With the `trace` feature, divergences between CPU backends also carry the
+//! first `(chain, round)` whose state differs; GPU kernels and the C
+//! reference only expose final hashes, so their divergences are reported
+//! without a round.
+
+use core::fmt;
+
+use crate::header::MiningHeader;
+use crate::params::*;
+use crate::primitives::{PORTABLE_PRIMITIVES, PrimitiveTable};
+use crate::testing::header_stream;
+use crate::uhash::{
+    UniversalHash, chain_seed, compute_address, extract_nonce, fill_scratchpad_with,
+    finalize_states, header_midstate, initial_primitive, round_primitive,
+};
+
+#[cfg(feature = "trace")]
+use crate::primitives::NATIVE_PRIMITIVES;
+#[cfg(feature = "trace")]
+use crate::trace::hash_traced_with;
+
+/// An implementation of the hash that can be checked against the reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Backend {
+    /// Software primitives, identical on every target (the reference)
+    Portable,
+    /// [`UniversalHash`] with the primitives selected at compile time
+    /// (AES-NI, NEON or software)
+    Native,
+    /// [`GpuMiner`](crate::GpuMiner) compute shader
+    #[cfg(feature = "gpu-wgpu")]
+    Wgpu,
+    /// Reference C implementation, linked with the `interop-c` feature
+    #[cfg(feature = "interop-c")]
+    ReferenceC,
+}
+
+impl Backend {
+    /// Human-readable backend name
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Portable => "portable",
+            #[cfg(all(
+                any(target_arch = "x86", target_arch = "x86_64"),
+                target_feature = "aes",
+                not(feature = "safe")
+            ))]
+            Backend::Native => "AES-NI",
+            #[cfg(all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")))]
+            Backend::Native => "NEON",
+            #[cfg(all(
+                target_arch = "riscv64",
+                target_feature = "zkne",
+                not(feature = "safe")
+            ))]
+            Backend::Native => "Zkn",
+            #[cfg(all(
+                target_arch = "aarch64",
+                not(target_feature = "aes"),
+                not(feature = "safe")
+            ))]
+            Backend::Native if crate::primitives::hardware_aes() => "NEON (runtime detected)",
+            #[cfg(not(any(
+                all(
+                    any(target_arch = "x86", target_arch = "x86_64"),
+                    target_feature = "aes",
+                    not(feature = "safe")
+                ),
+                all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")),
+                all(
+                    target_arch = "riscv64",
+                    target_feature = "zkne",
+                    not(feature = "safe")
+                )
+            )))]
+            Backend::Native => "native (software)",
+            #[cfg(feature = "gpu-wgpu")]
+            Backend::Wgpu => "wgpu",
+            #[cfg(feature = "interop-c")]
+            Backend::ReferenceC => "reference C",
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A header whose hash on `backend` differs from the portable reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Header that was hashed
+    pub header: MiningHeader,
+    /// Backend that disagreed
+    pub backend: Backend,
+    /// Hash from the portable reference
+    pub expected: [u8; 32],
+    /// Hash from `backend`
+    pub actual: [u8; 32],
+    /// First `(chain, round)` whose state differs, when it can be located
+    #[cfg(feature = "trace")]
+    pub first_round: Option<(usize, usize)>,
+}
+
+/// Outcome of [`differential_check`]
+#[derive(Debug, Clone, Default)]
+pub struct DifferentialReport {
+    /// Number of headers checked
+    pub headers: usize,
+    /// Backends compared against [`Backend::Portable`]
+    pub backends: Vec<Backend>,
+    /// Backends that are compiled in but could not run, with the reason
+    pub skipped: Vec<(Backend, String)>,
+    /// Every mismatch, in header order
+    pub divergences: Vec<Divergence>,
+}
+
+impl DifferentialReport {
+    /// Whether every backend produced bit-identical hashes
+    pub fn is_consistent(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+impl fmt::Display for DifferentialReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} headers checked against portable on", self.headers)?;
+        if self.backends.is_empty() {
+            f.write_str(" no other backends")?;
+        }
+        for backend in &self.backends {
+            write!(f, " {}", backend)?;
+        }
+        writeln!(f)?;
+        for (backend, reason) in &self.skipped {
+            writeln!(f, "skipped {}: {}", backend, reason)?;
+        }
+        for d in &self.divergences {
+            write!(
+                f,
+                "{} diverges on nonce {}: expected {}, got {}",
+                d.backend,
+                d.header.nonce,
+                crate::Hash256(d.expected),
+                crate::Hash256(d.actual)
+            )?;
+            #[cfg(feature = "trace")]
+            if let Some((chain, round)) = d.first_round {
+                write!(
+                    f,
+                    " (first differing state: chain {}, round {})",
+                    chain, round
+                )?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deterministic pseudo-random headers for differential runs
+///
+/// The first `count` headers of [`header_stream`]`(seed)`, so a failing run
+/// can be reproduced on another machine or in another implementation.
+pub fn random_headers(seed: u64, count: usize) -> Vec<MiningHeader> {
+    header_stream(seed).take(count).collect()
+}
+
+/// Hash `headers` on every available backend and compare with the reference
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{differential_check, random_headers};
+///
+/// let report = differential_check(&random_headers(7, 2));
+/// assert!(report.is_consistent(), "{}", report);
+/// ```
+pub fn differential_check(headers: &[MiningHeader]) -> DifferentialReport {
+    let mut report = DifferentialReport {
+        headers: headers.len(),
+        ..Default::default()
+    };
+    let expected: Vec<[u8; 32]> = headers
+        .iter()
+        .map(|h| hash_with(&h.to_bytes(), &PORTABLE_PRIMITIVES))
+        .collect();
+
+    report.backends.push(Backend::Native);
+    let mut hasher = UniversalHash::new();
+    for (header, &expected) in headers.iter().zip(&expected) {
+        let actual = hasher.hash(&header.to_bytes());
+        if actual != expected {
+            report.divergences.push(Divergence {
+                header: *header,
+                backend: Backend::Native,
+                expected,
+                actual,
+                #[cfg(feature = "trace")]
+                first_round: first_round(header, &NATIVE_PRIMITIVES),
+            });
+        }
+    }
+
+    #[cfg(feature = "gpu-wgpu")]
+    match wgpu_hashes(headers) {
+        Ok(hashes) => {
+            report.backends.push(Backend::Wgpu);
+            for ((header, &expected), actual) in headers.iter().zip(&expected).zip(hashes) {
+                if actual != expected {
+                    report.divergences.push(Divergence {
+                        header: *header,
+                        backend: Backend::Wgpu,
+                        expected,
+                        actual,
+                        #[cfg(feature = "trace")]
+                        first_round: None,
+                    });
+                }
+            }
+        }
+        Err(e) => report.skipped.push((Backend::Wgpu, e.to_string())),
+    }
+
+    #[cfg(feature = "interop-c")]
+    {
+        report.backends.push(Backend::ReferenceC);
+        for (header, &expected) in headers.iter().zip(&expected) {
+            let actual = crate::interop_c::hash(&header.to_bytes());
+            if actual != expected {
+                report.divergences.push(Divergence {
+                    header: *header,
+                    backend: Backend::ReferenceC,
+                    expected,
+                    actual,
+                    #[cfg(feature = "trace")]
+                    first_round: None,
+                });
+            }
+        }
+    }
+
+    report
+}
+
+/// Hash `input` with the portable software primitives
+///
+/// The reference every backend is compared against, for inputs of any
+/// length rather than just mining headers.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{hash, portable_hash};
+///
+/// assert_eq!(portable_hash(b"hello"), hash(b"hello"));
+/// ```
+pub fn portable_hash(input: &[u8]) -> [u8; 32] {
+    hash_with(input, &PORTABLE_PRIMITIVES)
+}
+
+/// Hash each header on the default wgpu adapter
+#[cfg(feature = "gpu-wgpu")]
+fn wgpu_hashes(headers: &[MiningHeader]) -> Result<Vec<[u8; 32]>, crate::GpuError> {
+    let mut miner = crate::GpuMiner::new()?;
+    let mut hashes = Vec::with_capacity(headers.len());
+    for header in headers {
+        hashes.extend(miner.hash_batch(&header.job(), &[header.nonce])?);
+    }
+    Ok(hashes)
+}
+
+/// First state where `primitives` departs from the portable reference
+#[cfg(feature = "trace")]
+fn first_round(header: &MiningHeader, primitives: &PrimitiveTable) -> Option<(usize, usize)> {
+    let input = header.to_bytes();
+    hash_traced_with(&input, &PORTABLE_PRIMITIVES)
+        .first_divergence(&hash_traced_with(&input, primitives))
+}
+
+/// Sequential hash of `input` using the given primitives
+fn hash_with(input: &[u8], primitives: &PrimitiveTable) -> [u8; 32] {
+    let nonce = extract_nonce(input);
+    let midstate = header_midstate(input);
+    let mut scratchpad = vec![0u8; SCRATCHPAD_SIZE];
+    let mut chain_states = [[0u8; 32]; CHAINS];
+
+    for (chain, state) in chain_states.iter_mut().enumerate() {
+        *state = chain_seed(&midstate, nonce, chain);
+        fill_scratchpad_with(&mut scratchpad, state, primitives.aes_expand);
+
+        let initial = initial_primitive(nonce, chain);
+        for round in 0..ROUNDS {
+            let address = compute_address(state, round);
+            let block: [u8; BLOCK_SIZE] = scratchpad[address..address + BLOCK_SIZE]
+                .try_into()
+                .unwrap();
+            *state = match round_primitive(initial, round) {
+                0 => (primitives.aes)(state, &block),
+                1 => (primitives.sha256)(state, &block),
+                _ => (primitives.blake3)(state, &block),
+            };
+            scratchpad[address..address + 32].copy_from_slice(state);
+        }
+    }
+
+    finalize_states(&chain_states)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    #[test]
+    fn test_random_headers_deterministic() {
+        let a = random_headers(1, 4);
+        assert_eq!(a, random_headers(1, 4));
+        assert_ne!(a, random_headers(2, 4));
+        assert_ne!(a[0], a[1]);
+    }
+
+    #[test]
+    fn test_portable_matches_hash() {
+        let input = random_headers(3, 1)[0].to_bytes();
+        assert_eq!(hash_with(&input, &PORTABLE_PRIMITIVES), hash(&input));
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let report = differential_check(&random_headers(0, 2));
+        assert_eq!(report.headers, 2);
+        assert!(report.backends.contains(&Backend::Native));
+        assert!(report.is_consistent(), "{}", report);
+    }
+
+    /// Inputs checked against the C library; set `UHASH_INTEROP_COUNT`
+    /// to cross-check more than the default 64
+    #[cfg(feature = "interop-c")]
+    #[test]
+    fn test_reference_c_agrees() {
+        let count: usize = std::env::var("UHASH_INTEROP_COUNT")
+            .ok()
+            .and_then(|count| count.parse().ok())
+            .unwrap_or(64);
+
+        // Headers through the harness, in batches to bound memory
+        for batch in 0..count.div_ceil(4096) {
+            let size = (count - batch * 4096).min(4096);
+            let report = differential_check(&random_headers(batch as u64, size));
+            assert!(report.backends.contains(&Backend::ReferenceC));
+            assert!(report.is_consistent(), "{}", report);
+        }
+
+        // Arbitrary lengths too, including inputs shorter than a nonce
+        let mut xof = blake3::Hasher::new()
+            .update(b"uhash-core interop inputs")
+            .finalize_xof();
+        let mut hasher = UniversalHash::new();
+        for i in 0..count.min(4096) {
+            let mut input = vec![0u8; i % 200];
+            xof.fill(&mut input);
+            assert_eq!(
+                crate::interop_c::hash(&input),
+                hasher.hash(&input),
+                "input {:02x?}",
+                input
+            );
+        }
+    }
+
+    #[cfg(feature = "trace")]
+    #[test]
+    fn test_first_round_located() {
+        fn broken_blake3(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+            let mut out = crate::primitives::blake3_compress(state, block);
+            out[31] ^= 1;
+            out
+        }
+        let broken = PrimitiveTable {
+            blake3: broken_blake3,
+            ..PORTABLE_PRIMITIVES
+        };
+
+        let header = random_headers(4, 1)[0];
+        let (chain, round) = first_round(&header, &broken).unwrap();
+        assert_eq!(chain, 0);
+        assert_eq!(
+            round_primitive(initial_primitive(header.nonce, 0), round),
+            2
+        );
+        assert!(round < 3);
+    }
+}
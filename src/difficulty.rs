@@ -0,0 +1,181 @@
+//! Reference difficulty adjustment (LWMA)
+//!
+//! Linearly weighted moving average over the last `window` solve times:
+//! recent blocks count more, so the target reacts quickly to hashrate
+//! changes without oscillating. Solve times are clamped to `±6T` to limit
+//! the effect of timestamp manipulation, following zawy12's LWMA-1.
+//!
+//! ```text
+//! next = avg(target) * Σ i·solvetime_i / (T · N(N+1)/2)
+//! ```
+
+use crate::target::Target;
+use crate::u256::U256;
+
+/// A solved block as seen by the retarget rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockRecord {
+    /// Block timestamp in seconds
+    pub timestamp: u64,
+    /// Target the block was solved at
+    pub target: Target,
+}
+
+/// Parameters of the LWMA retarget rule
+///
+/// With the `serde` feature, missing fields take their [`Default`] values,
+/// so a config file only needs the ones it changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DaaParams {
+    /// Desired average block interval in seconds
+    pub target_block_time: u64,
+    /// Number of solve times averaged (N)
+    pub window: usize,
+    /// Easiest target the rule may return
+    pub max_target: Target,
+}
+
+impl Default for DaaParams {
+    /// 60 second blocks averaged over 60 blocks
+    fn default() -> Self {
+        Self {
+            target_block_time: 60,
+            window: 60,
+            max_target: Target::MAX,
+        }
+    }
+}
+
+/// Target for the block following `history`
+///
+/// `history` lists blocks oldest first; the last `window + 1` are used.
+/// With fewer than two blocks there is no solve time to measure, so the
+/// last target (or `max_target` for an empty history) is returned.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{BlockRecord, DaaParams, Target, next_target};
+///
+/// let params = DaaParams { target_block_time: 60, window: 3, ..Default::default() };
+/// let target = Target::from_difficulty_bits(20);
+///
+/// // Blocks arrive twice as fast as intended: the target gets harder
+/// let history: Vec<_> = (0..4)
+///     .map(|i| BlockRecord { timestamp: i * 30, target })
+///     .collect();
+/// assert!(next_target(&history, &params) < target);
+/// ```
+pub fn next_target(history: &[BlockRecord], params: &DaaParams) -> Target {
+    let Some(last) = history.last() else {
+        return params.max_target;
+    };
+    let n = history.len().saturating_sub(1).min(params.window);
+    if n == 0 || params.target_block_time == 0 {
+        return last.target;
+    }
+    let blocks = &history[history.len() - n - 1..];
+    let t = params.target_block_time as i128;
+
+    let count = U256::from_u64(n as u64);
+
+    let mut weighted_solvetimes: i128 = 0;
+    let mut avg_target = U256::ZERO;
+    for (i, pair) in blocks.windows(2).enumerate() {
+        let solvetime =
+            (pair[1].timestamp as i128 - pair[0].timestamp as i128).clamp(-6 * t, 6 * t);
+        weighted_solvetimes += (i as i128 + 1) * solvetime;
+        // Divide first so the sum cannot overflow
+        let share = pair[1].target.0.checked_div(count).unwrap();
+        avg_target = avg_target.saturating_add(share);
+    }
+
+    // k = T · N(N+1)/2; keep the weighted sum from collapsing on bad timestamps
+    let k = t * (n as i128) * (n as i128 + 1) / 2;
+    let weighted_solvetimes = weighted_solvetimes.max(k / 10).max(1) as u128;
+    let k = k as u128;
+
+    let next = match avg_target.checked_mul(U256::from_u128(weighted_solvetimes)) {
+        Some(product) => product.checked_div(U256::from_u128(k)).unwrap(),
+        None => avg_target
+            .checked_div(U256::from_u128(k))
+            .unwrap()
+            .checked_mul(U256::from_u128(weighted_solvetimes))
+            .unwrap_or(U256::MAX),
+    };
+    Target(next.max(U256::ONE).min(params.max_target.0))
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
+    fn history(times: &[u64], target: Target) -> Vec<BlockRecord> {
+        times
+            .iter()
+            .map(|&timestamp| BlockRecord { timestamp, target })
+            .collect()
+    }
+
+    fn params(window: usize) -> DaaParams {
+        DaaParams {
+            target_block_time: 60,
+            window,
+            max_target: Target::MAX,
+        }
+    }
+
+    #[test]
+    fn test_steady_state_keeps_target() {
+        let target = Target(U256::ONE << 200);
+        let blocks = history(&[0, 60, 120, 180, 240], target);
+        assert_eq!(next_target(&blocks, &params(4)), target);
+        // Only the last window + 1 blocks matter
+        let mut longer = history(&[0, 1000], target);
+        longer.extend(history(&[1060, 1120, 1180, 1240, 1300], target));
+        assert_eq!(next_target(&longer, &params(4)), target);
+    }
+
+    #[test]
+    fn test_target_tracks_solve_times() {
+        let target = Target(U256::ONE << 200);
+        let slow = history(&[0, 120, 240, 360, 480], target);
+        assert_eq!(next_target(&slow, &params(4)), Target(U256::ONE << 201));
+
+        let fast = history(&[0, 30, 60, 90, 120], target);
+        assert_eq!(next_target(&fast, &params(4)), Target(U256::ONE << 199));
+
+        // Recent blocks weigh more: a slow last block moves the target more
+        let slow_last = history(&[0, 60, 60, 60, 300], target);
+        let slow_first = history(&[0, 240, 240, 240, 240], target);
+        assert!(next_target(&slow_last, &params(4)) > next_target(&slow_first, &params(4)));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let target = Target(U256::ONE << 200);
+        assert_eq!(next_target(&[], &params(4)), Target::MAX);
+        assert_eq!(next_target(&history(&[5], target), &params(4)), target);
+
+        // Timestamps going backwards cannot drive the target to zero
+        let backwards = history(&[1000, 900, 800, 700, 600], target);
+        assert!(next_target(&backwards, &params(4)) > Target(U256::ZERO));
+
+        // Huge gaps are capped at max_target
+        let capped = DaaParams {
+            max_target: target,
+            ..params(4)
+        };
+        let stalled = history(&[0, 10_000, 20_000, 30_000, 40_000], target);
+        assert_eq!(next_target(&stalled, &capped), target);
+        assert_eq!(
+            next_target(&history(&[0, 10_000], Target::MAX), &params(4)),
+            Target::MAX
+        );
+    }
+}
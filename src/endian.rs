@@ -0,0 +1,69 @@
+//! Explicit byte-order loads and stores
+//!
+//! The spec fixes the byte order of every multi-byte value (little-endian
+//! except inside SHA-256). Hashing code goes through these helpers instead
+//! of casting pointers, so results are identical on big-endian hosts; on a
+//! matching host each one compiles to a plain load or store.
+//!
+//! Offsets are never added to: slicing from `offset` and then taking a
+//! fixed-length prefix keeps this module free of arithmetic, checked by
+//! the lint below along with the rest of the consensus path.
+
+#![deny(clippy::arithmetic_side_effects)]
+
+/// Little-endian `u64` at `bytes[offset..offset + 8]`
+#[inline(always)]
+pub(crate) fn load_u64_le(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..][..8].try_into().unwrap())
+}
+
+/// Little-endian `u32` at `bytes[offset..offset + 4]`
+#[inline(always)]
+pub(crate) fn load_u32_le(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..][..4].try_into().unwrap())
+}
+
+/// Big-endian `u32` at `bytes[offset..offset + 4]`
+#[inline(always)]
+pub(crate) fn load_u32_be(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(bytes[offset..][..4].try_into().unwrap())
+}
+
+/// Write `value` little-endian to `bytes[offset..offset + 8]`
+#[inline(always)]
+pub(crate) fn store_u64_le(bytes: &mut [u8], offset: usize, value: u64) {
+    bytes[offset..][..8].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Write `value` little-endian to `bytes[offset..offset + 4]`
+#[inline(always)]
+pub(crate) fn store_u32_le(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..][..4].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Write `value` big-endian to `bytes[offset..offset + 4]`
+#[inline(always)]
+pub(crate) fn store_u32_be(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..][..4].copy_from_slice(&value.to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_order_is_host_independent() {
+        let bytes = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09];
+        assert_eq!(load_u64_le(&bytes, 1), 0x0908_0706_0504_0302);
+        assert_eq!(load_u32_le(&bytes, 0), 0x0403_0201);
+        assert_eq!(load_u32_be(&bytes, 0), 0x0102_0304);
+
+        let mut out = [0u8; 8];
+        store_u64_le(&mut out, 0, 0x0807_0605_0403_0201);
+        assert_eq!(out, [1, 2, 3, 4, 5, 6, 7, 8]);
+        store_u32_le(&mut out, 4, 0xAABB_CCDD);
+        assert_eq!(out[4..], [0xDD, 0xCC, 0xBB, 0xAA]);
+        store_u32_be(&mut out, 0, 0xAABB_CCDD);
+        assert_eq!(out[..4], [0xAA, 0xBB, 0xCC, 0xDD]);
+    }
+}
@@ -0,0 +1,134 @@
+//! Epochs and their seeds
+//!
+//! The spec fixes only where the epoch seed goes: the first 32 bytes of
+//! every mining header. How a chain picks each epoch's seed is up to the
+//! chain, so [`Epoch`] pairs an epoch number with whatever seed the chain
+//! supplies, and [`EpochManager`] switches miners from one epoch to the
+//! next at the boundary.
+
+#[cfg(feature = "std")]
+use std::sync::{Arc, RwLock};
+
+use crate::header::MiningHeader;
+use crate::job::Job;
+
+/// An epoch number with its seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Epoch {
+    /// Epoch number
+    pub number: u64,
+    /// Epoch seed (first 32 bytes of every mining header in this epoch)
+    pub seed: [u8; 32],
+}
+
+impl Epoch {
+    /// The epoch following this one, with the seed the chain chose for it
+    ///
+    /// `None` after epoch `u64::MAX`.
+    pub fn next(&self, seed: [u8; 32]) -> Option<Epoch> {
+        Some(Epoch {
+            number: self.number.checked_add(1)?,
+            seed,
+        })
+    }
+
+    /// Mining header in this epoch (nonce zero)
+    pub fn header(&self, miner_address: [u8; 20], timestamp: u64) -> MiningHeader {
+        MiningHeader {
+            epoch_seed: self.seed,
+            miner_address,
+            timestamp,
+            nonce: 0,
+        }
+    }
+
+    /// Precomputed mining job in this epoch
+    pub fn job(&self, miner_address: [u8; 20], timestamp: u64) -> Job {
+        self.header(miner_address, timestamp).job()
+    }
+}
+
+/// Tracks the current epoch and swaps in the next one at the boundary
+///
+/// [`advance`](Self::advance) replaces the epoch atomically, so readers of
+/// [`current`](Self::current) always see a complete epoch. Moving to the
+/// next epoch only records its seed, so there is nothing to prepare ahead
+/// of time.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{Epoch, EpochManager};
+///
+/// let manager = EpochManager::new(Epoch { number: 0, seed: [0u8; 32] });
+/// let next = manager.advance([0xAA; 32]).unwrap();
+/// assert_eq!(next.number, 1);
+/// assert_eq!(next.seed, [0xAA; 32]);
+/// assert_eq!(manager.current().number, 1);
+/// ```
+#[cfg(feature = "std")]
+pub struct EpochManager {
+    current: RwLock<Arc<Epoch>>,
+}
+
+#[cfg(feature = "std")]
+impl EpochManager {
+    /// Start tracking from `epoch`
+    pub fn new(epoch: Epoch) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(epoch)),
+        }
+    }
+
+    /// The current epoch
+    pub fn current(&self) -> Arc<Epoch> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Move to the next epoch, with `seed`, and return it
+    ///
+    /// `None`, leaving the current epoch in place, after epoch `u64::MAX`.
+    pub fn advance(&self, seed: [u8; 32]) -> Option<Arc<Epoch>> {
+        let mut current = self.current.write().unwrap();
+        let next = Arc::new(current.next(seed)?);
+        *current = next.clone();
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epoch_next_stops_at_max() {
+        let last = Epoch {
+            number: u64::MAX,
+            seed: [1u8; 32],
+        };
+        assert_eq!(last.next([2u8; 32]), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_epoch_manager_advances() {
+        let genesis = Epoch {
+            number: 0,
+            seed: [7u8; 32],
+        };
+        let manager = EpochManager::new(genesis);
+        assert_eq!(*manager.current(), genesis);
+        assert_eq!(
+            manager.advance([1u8; 32]).as_deref(),
+            genesis.next([1u8; 32]).as_ref()
+        );
+        assert_eq!(manager.current().number, 1);
+
+        let manager = EpochManager::new(Epoch {
+            number: u64::MAX,
+            seed: [7u8; 32],
+        });
+        assert_eq!(manager.advance([1u8; 32]), None);
+        assert_eq!(manager.current().number, u64::MAX);
+    }
+}
@@ -0,0 +1,113 @@
+//! Error types for the fallible public API
+
+use core::fmt;
+
+/// Error returned by the `try_*` API on malformed input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UhashError {
+    /// Input is shorter than the trailing nonce region
+    InputTooShort {
+        /// Length that was supplied
+        len: usize,
+        /// Minimum accepted length
+        min: usize,
+    },
+    /// Input is not the exact length required
+    InvalidLength {
+        /// Required length
+        expected: usize,
+        /// Length that was supplied
+        actual: usize,
+    },
+    /// Input is longer than the field it must fit into
+    InputTooLong {
+        /// Length that was supplied
+        len: usize,
+        /// Maximum accepted length
+        max: usize,
+    },
+    /// Caller-provided buffer cannot hold a scratchpad
+    BufferTooSmall {
+        /// Length that was supplied
+        len: usize,
+        /// Minimum accepted length
+        min: usize,
+    },
+}
+
+impl fmt::Display for UhashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UhashError::InputTooShort { len, min } => {
+                write!(f, "input is {} bytes, need at least {}", len, min)
+            }
+            UhashError::InvalidLength { expected, actual } => {
+                write!(f, "input is {} bytes, expected {}", actual, expected)
+            }
+            UhashError::InputTooLong { len, max } => {
+                write!(f, "input is {} bytes, at most {} allowed", len, max)
+            }
+            UhashError::BufferTooSmall { len, min } => {
+                write!(f, "buffer is {} bytes, need at least {}", len, min)
+            }
+        }
+    }
+}
+
+impl core::error::Error for UhashError {}
+
+/// Error returned when parsing a hex string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexError {
+    /// String does not have the required number of characters
+    InvalidLength {
+        /// Required number of hex digits
+        expected: usize,
+        /// Length of the string in bytes
+        actual: usize,
+    },
+    /// String contains something other than hex digits
+    InvalidDigit,
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HexError::InvalidLength { expected, actual } => {
+                write!(
+                    f,
+                    "hex string is {} characters, expected {}",
+                    actual, expected
+                )
+            }
+            HexError::InvalidDigit => write!(f, "invalid hex digit"),
+        }
+    }
+}
+
+impl core::error::Error for HexError {}
+
+/// Error returned when parsing a [`Difficulty`](crate::Difficulty)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseDifficultyError {
+    /// Not a number with an optional `k`/`M`/`G`/`T`/`P`/`E` suffix
+    InvalidNumber,
+    /// Number is below 1, infinite or NaN
+    OutOfRange,
+    /// `0x`-prefixed target is not valid hex
+    InvalidTarget(HexError),
+}
+
+impl fmt::Display for ParseDifficultyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseDifficultyError::InvalidNumber => write!(f, "invalid difficulty"),
+            ParseDifficultyError::OutOfRange => {
+                write!(f, "difficulty must be finite and at least 1")
+            }
+            ParseDifficultyError::InvalidTarget(err) => write!(f, "invalid target: {}", err),
+        }
+    }
+}
+
+impl core::error::Error for ParseDifficultyError {}
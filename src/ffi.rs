@@ -1,11 +1,13 @@
 //! C FFI bindings for mobile platforms
 
 use crate::UniversalHash;
+use crate::header::HEADER_SIZE;
+use crate::uhash::meets_difficulty;
 use core::slice;
 
 /// Opaque hasher handle for FFI
 pub struct UHasher {
-    inner: UniversalHash,
+    pub(crate) inner: UniversalHash,
 }
 
 /// Create a new hasher instance
@@ -47,13 +49,80 @@ pub extern "C" fn uhash_hash(
     unsafe {
         let hasher = &mut *hasher;
         let input_slice = slice::from_raw_parts(input, input_len);
-        let result = hasher.inner.hash(input_slice);
+        let output = &mut *(output as *mut [u8; 32]);
+        hasher.inner.hash_into(input_slice, output);
+    }
+}
+
+/// Verify a batch of mining headers in one call
+/// - hasher: pointer from uhash_new()
+/// - headers: `count` 68-byte headers laid out back to back
+/// - difficulty: required leading zero bits
+/// - results: pointer to `count` bytes, each set to 1 if that header meets
+///   the difficulty and 0 otherwise
+/// - hashes: pointer to `count * 32` bytes for the hashes, or null
+///
+/// Returns the number of headers that meet the difficulty, or 0 if an
+/// argument is null or `count` overflows the buffer sizes. One call per
+/// batch keeps per-call overhead (JNI transitions in particular) off the
+/// per-share cost.
+#[unsafe(no_mangle)]
+pub extern "C" fn uhash_verify_batch(
+    hasher: *mut UHasher,
+    headers: *const u8,
+    count: usize,
+    difficulty: u32,
+    results: *mut u8,
+    hashes: *mut u8,
+) -> usize {
+    if hasher.is_null() || headers.is_null() || results.is_null() {
+        return 0;
+    }
+    let (Some(headers_len), Some(hashes_len)) =
+        (count.checked_mul(HEADER_SIZE), count.checked_mul(32))
+    else {
+        return 0;
+    };
 
-        let output_slice = slice::from_raw_parts_mut(output, 32);
-        output_slice.copy_from_slice(&result);
+    unsafe {
+        let hasher = &mut *hasher;
+        let headers = slice::from_raw_parts(headers, headers_len);
+        let results = slice::from_raw_parts_mut(results, count);
+        let hashes = (!hashes.is_null()).then(|| slice::from_raw_parts_mut(hashes, hashes_len));
+        verify_headers(&mut hasher.inner, headers, difficulty, results, hashes)
     }
 }
 
+/// Whether AES rounds use hardware instructions on this device
+///
+/// Returns 1 or 0; see [`crate::hardware_aes`].
+#[unsafe(no_mangle)]
+pub extern "C" fn uhash_hardware_aes() -> u8 {
+    crate::primitives::hardware_aes() as u8
+}
+
+/// Hash each `HEADER_SIZE` chunk of `headers`, flagging in `results` the
+/// ones that meet `difficulty` and returning how many do
+pub(crate) fn verify_headers(
+    hasher: &mut UniversalHash,
+    headers: &[u8],
+    difficulty: u32,
+    results: &mut [u8],
+    mut hashes: Option<&mut [u8]>,
+) -> usize {
+    let mut valid = 0;
+    for (index, (header, result)) in headers.chunks_exact(HEADER_SIZE).zip(results).enumerate() {
+        let hash = hasher.hash(header);
+        let meets = meets_difficulty(&hash, difficulty);
+        *result = meets as u8;
+        valid += meets as usize;
+        if let Some(hashes) = hashes.as_deref_mut() {
+            hashes[index * 32..][..32].copy_from_slice(&hash);
+        }
+    }
+    valid
+}
+
 /// Benchmark: compute N hashes and return total microseconds
 #[unsafe(no_mangle)]
 pub extern "C" fn uhash_benchmark(iterations: u32) -> u64 {
@@ -81,3 +150,49 @@ pub extern "C" fn uhash_hashrate(iterations: u32, microseconds: u64) -> f64 {
     }
     (iterations as f64) / (microseconds as f64 / 1_000_000.0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::MiningHeader;
+
+    #[test]
+    fn test_verify_batch_matches_single_hashes() {
+        let headers: Vec<u8> = (0..3)
+            .flat_map(|nonce| MiningHeader::default().with_nonce(nonce).to_bytes())
+            .collect();
+        let expected: Vec<[u8; 32]> = headers.chunks(HEADER_SIZE).map(crate::hash).collect();
+        // The first hash's own zero count flags at least that header
+        let difficulty = crate::Hash256(expected[0]).leading_zeros();
+
+        let hasher = uhash_new();
+        let mut results = [0xFFu8; 3];
+        let mut hashes = [0u8; 3 * 32];
+        let valid = uhash_verify_batch(
+            hasher,
+            headers.as_ptr(),
+            3,
+            difficulty,
+            results.as_mut_ptr(),
+            hashes.as_mut_ptr(),
+        );
+        // A count whose buffer sizes overflow is rejected before any access
+        let overflowing = uhash_verify_batch(
+            hasher,
+            headers.as_ptr(),
+            usize::MAX / 32 + 1,
+            0,
+            results.as_mut_ptr(),
+            core::ptr::null_mut(),
+        );
+        assert_eq!(overflowing, 0);
+        uhash_free(hasher);
+
+        for (index, hash) in expected.iter().enumerate() {
+            assert_eq!(&hashes[index * 32..][..32], hash);
+            assert_eq!(results[index] == 1, meets_difficulty(hash, difficulty));
+        }
+        assert_eq!(valid, results.iter().map(|&r| r as usize).sum::<usize>());
+        assert_eq!(results[0], 1);
+    }
+}
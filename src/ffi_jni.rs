@@ -0,0 +1,141 @@
+//! JNI entry points for Android apps
+//!
+//! Native methods of a `com.uhash.UHash` class, callable straight from
+//! Kotlin or Java without generated bindings:
+//!
+//! ```text
+//! package com.uhash
+//!
+//! object UHash {
+//!     init { System.loadLibrary("uhash_core") }
+//!
+//!     @JvmStatic external fun nativeNew(): Long
+//!     @JvmStatic external fun nativeFree(handle: Long)
+//!     @JvmStatic external fun hash(handle: Long, input: ByteArray): ByteArray
+//!     @JvmStatic external fun verifyBatch(
+//!         handle: Long, headers: ByteArray, difficulty: Int, results: ByteArray
+//!     ): Int
+//!     @JvmStatic external fun hardwareAes(): Boolean
+//! }
+//! ```
+//!
+//! A JNI transition costs far less than a hash, but share verification on
+//! a pool client checks many headers at once; `verifyBatch` takes them
+//! back to back in one array so the whole batch crosses the boundary once.
+//! Handles are the `UHasher` pointers of the C API, so the same rules
+//! apply: one handle per thread, freed exactly once.
+//!
+//! The functions use `extern "system"`, the JNI calling convention, which
+//! is `extern "C"` on every target but 32-bit Windows.
+
+use jni::JNIEnv;
+use jni::objects::{JByteArray, JClass};
+use jni::sys::{JNI_FALSE, JNI_TRUE, jboolean, jint, jlong};
+
+use crate::ffi::{UHasher, uhash_free, uhash_new, verify_headers};
+use crate::header::HEADER_SIZE;
+
+/// Allocate a hasher and return its handle
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_uhash_UHash_nativeNew(_env: JNIEnv, _class: JClass) -> jlong {
+    uhash_new() as jlong
+}
+
+/// Free a handle from `nativeNew`; 0 is ignored
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_uhash_UHash_nativeFree(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+) {
+    uhash_free(handle as *mut UHasher);
+}
+
+/// UniversalHash of `input`, or null with an exception pending
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_uhash_UHash_hash<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    input: JByteArray<'local>,
+) -> JByteArray<'local> {
+    let result = (|| {
+        let Some(hasher) = hasher(&mut env, handle)? else {
+            return Ok(JByteArray::default());
+        };
+        let input = env.convert_byte_array(&input)?;
+        env.byte_array_from_slice(&hasher.inner.hash(&input))
+    })();
+    // A failed JNI call has already raised the Java exception
+    result.unwrap_or_default()
+}
+
+/// Verify `headers`, 68 bytes each, against `difficulty` leading zero bits
+///
+/// Writes 1 or 0 per header into `results`, which must hold exactly one
+/// byte per header, and returns how many met the difficulty. Throws
+/// `IllegalArgumentException` for mismatched lengths or a negative
+/// difficulty.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_uhash_UHash_verifyBatch<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    headers: JByteArray<'local>,
+    difficulty: jint,
+    results: JByteArray<'local>,
+) -> jint {
+    let outcome = (|| -> jni::errors::Result<jint> {
+        let Some(hasher) = hasher(&mut env, handle)? else {
+            return Ok(0);
+        };
+        let headers = env.convert_byte_array(&headers)?;
+        let count = env.get_array_length(&results)? as usize;
+        let Ok(difficulty) = u32::try_from(difficulty) else {
+            env.throw_new(ILLEGAL_ARGUMENT, "difficulty must not be negative")?;
+            return Ok(0);
+        };
+        if headers.len() != count * HEADER_SIZE {
+            env.throw_new(
+                ILLEGAL_ARGUMENT,
+                format!(
+                    "{} header bytes for {} results, expected {}",
+                    headers.len(),
+                    count,
+                    count * HEADER_SIZE
+                ),
+            )?;
+            return Ok(0);
+        }
+
+        let mut flags = vec![0u8; count];
+        let valid = verify_headers(&mut hasher.inner, &headers, difficulty, &mut flags, None);
+        let flags: Vec<i8> = flags.into_iter().map(|flag| flag as i8).collect();
+        env.set_byte_array_region(&results, 0, &flags)?;
+        Ok(valid as jint)
+    })();
+    outcome.unwrap_or(0)
+}
+
+/// Whether AES rounds use hardware instructions on this device
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_uhash_UHash_hardwareAes(_env: JNIEnv, _class: JClass) -> jboolean {
+    if crate::primitives::hardware_aes() {
+        JNI_TRUE
+    } else {
+        JNI_FALSE
+    }
+}
+
+const ILLEGAL_ARGUMENT: &str = "java/lang/IllegalArgumentException";
+
+/// Hasher behind `handle`, throwing `IllegalStateException` for a null one
+fn hasher<'h>(env: &mut JNIEnv, handle: jlong) -> jni::errors::Result<Option<&'h mut UHasher>> {
+    if handle == 0 {
+        env.throw_new("java/lang/IllegalStateException", "hasher handle is null")?;
+        return Ok(None);
+    }
+    // SAFETY: non-zero handles come from `nativeNew` and, per the class
+    // contract, are neither freed nor shared across threads while in use
+    Ok(Some(unsafe { &mut *(handle as *mut UHasher) }))
+}
@@ -0,0 +1,251 @@
+//! Host-side logic shared by the GPU backends
+//!
+//! GPU kernels run the memory-hard part of the algorithm (scratchpad fill
+//! and round loop) with one invocation per `(nonce, chain)` lane. Chain
+//! seeds and the final BLAKE3(SHA-256(...)) stage are cheap, so they stay
+//! on the CPU and every backend shares them.
+
+use core::fmt;
+
+use crate::endian::{load_u32_le, store_u32_le};
+use crate::hash256::Hash256;
+use crate::job::Job;
+use crate::params::CHAINS;
+use crate::target::Target;
+#[cfg(feature = "gpu-wgpu")]
+use crate::uhash::initial_primitive;
+use crate::uhash::{UniversalHash, chain_seed, finalize_states};
+
+/// Error from a GPU backend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GpuError {
+    /// No suitable GPU adapter or device was found
+    NoDevice,
+    /// The backend reported an error
+    Backend(String),
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuError::NoDevice => write!(f, "no suitable GPU device found"),
+            GpuError::Backend(msg) => write!(f, "GPU backend error: {}", msg),
+        }
+    }
+}
+
+impl core::error::Error for GpuError {}
+
+/// Kernel inputs for a batch of nonces, `CHAINS` lanes per nonce
+pub(crate) struct LaneInputs {
+    /// Chain seed of each lane as 8 little-endian words
+    pub seeds: Vec<u32>,
+    /// Initial primitive index of each lane (the CUDA and OpenCL kernels
+    /// derive it from the nonce themselves)
+    #[cfg(feature = "gpu-wgpu")]
+    pub initial: Vec<u32>,
+}
+
+/// Lane `n * CHAINS + c` is chain `c` of `nonces[n]`
+pub(crate) fn lane_inputs(job: &Job, nonces: &[u64]) -> LaneInputs {
+    let mut seeds = Vec::with_capacity(nonces.len() * CHAINS * 8);
+    #[cfg(feature = "gpu-wgpu")]
+    let mut initial = Vec::with_capacity(nonces.len() * CHAINS);
+    for &nonce in nonces {
+        for chain in 0..CHAINS {
+            let seed = chain_seed(&job.midstate, nonce, chain);
+            seeds.extend((0..8).map(|i| load_u32_le(&seed, i * 4)));
+            #[cfg(feature = "gpu-wgpu")]
+            initial.push(initial_primitive(nonce, chain) as u32);
+        }
+    }
+    LaneInputs {
+        seeds,
+        #[cfg(feature = "gpu-wgpu")]
+        initial,
+    }
+}
+
+/// Final hashes from the kernel's chain states (8 little-endian words per lane)
+pub(crate) fn finalize_lanes(states: &[u32]) -> Vec<[u8; 32]> {
+    states
+        .chunks_exact(CHAINS * 8)
+        .map(|lanes| {
+            let mut chain_states = [[0u8; 32]; CHAINS];
+            for (state, words) in chain_states.iter_mut().zip(lanes.chunks_exact(8)) {
+                for (i, word) in words.iter().enumerate() {
+                    store_u32_le(state, i * 4, *word);
+                }
+            }
+            finalize_states(&chain_states)
+        })
+        .collect()
+}
+
+/// First nonce of a batch whose hash meets `target`
+pub(crate) fn find_solution(
+    nonces: &[u64],
+    hashes: Vec<[u8; 32]>,
+    target: &Target,
+) -> Option<(u64, Hash256)> {
+    nonces
+        .iter()
+        .zip(hashes)
+        .map(|(&nonce, hash)| (nonce, Hash256(hash)))
+        .find(|(_, hash)| target.is_met_by(hash))
+}
+
+/// CPU re-verification of GPU-found solutions
+///
+/// A card with failing memory or an unstable overclock can return wrong
+/// hashes without reporting any error, and every share it finds is then
+/// rejected upstream. The watchdog re-hashes a sample of solutions on the
+/// CPU before they are surfaced, drops the ones the GPU got wrong, and
+/// counts both so a farm can take a card out of rotation when its
+/// [`error_rate`](WatchdogStats::error_rate) climbs.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use uhash_core::{GpuMiner, GpuWatchdog, MiningHeader, Target};
+///
+/// let mut miner = GpuMiner::new()?;
+/// let mut watchdog = GpuWatchdog::sampling(4);
+/// let job = MiningHeader::default().job();
+/// let target = Target::from_difficulty_bits(16);
+/// if let Some((nonce, hash)) = miner.mine_watched(&job, 0..1 << 20, &target, &mut watchdog)? {
+///     println!("nonce {} -> {}", nonce, hash);
+/// }
+/// if watchdog.stats().error_rate() > 0.01 {
+///     eprintln!("{}: {:?}", miner.adapter_name(), watchdog.stats());
+/// }
+/// # Ok::<(), uhash_core::GpuError>(())
+/// ```
+pub struct GpuWatchdog {
+    hasher: UniversalHash,
+    sample_every: u64,
+    stats: WatchdogStats,
+}
+
+/// Counters kept by a [`GpuWatchdog`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WatchdogStats {
+    /// Solutions passed to the watchdog
+    pub solutions: u64,
+    /// Solutions re-hashed on the CPU
+    pub verified: u64,
+    /// Re-hashed solutions whose GPU hash was wrong
+    pub rejected: u64,
+}
+
+impl WatchdogStats {
+    /// Fraction of re-hashed solutions the GPU got wrong, 0 before any
+    pub fn error_rate(&self) -> f64 {
+        if self.verified == 0 {
+            return 0.0;
+        }
+        self.rejected as f64 / self.verified as f64
+    }
+}
+
+impl GpuWatchdog {
+    /// Re-verify every solution
+    pub fn new() -> Self {
+        Self::sampling(1)
+    }
+
+    /// Re-verify one solution in `every`, starting with the first
+    ///
+    /// Unsampled solutions pass unchecked; a bad card is still caught, just
+    /// after more of its shares have gone out. `every` of 0 is treated as 1.
+    pub fn sampling(every: u64) -> Self {
+        GpuWatchdog {
+            hasher: UniversalHash::new_sequential(),
+            sample_every: every.max(1),
+            stats: WatchdogStats::default(),
+        }
+    }
+
+    /// Counters so far
+    pub fn stats(&self) -> WatchdogStats {
+        self.stats
+    }
+
+    /// Whether the GPU's `hash` for `nonce` may be surfaced
+    ///
+    /// Sampled solutions pass only if the CPU reference path produces the
+    /// same hash; the rest pass as is.
+    pub fn check(&mut self, job: &Job, nonce: u64, hash: &Hash256) -> bool {
+        let sampled = self.stats.solutions.is_multiple_of(self.sample_every);
+        self.stats.solutions += 1;
+        if !sampled {
+            return true;
+        }
+        self.stats.verified += 1;
+        let correct = Hash256(self.hasher.hash_nonce(job, nonce)) == *hash;
+        if !correct {
+            self.stats.rejected += 1;
+        }
+        correct
+    }
+
+    /// First nonce of a batch whose hash meets `target` and passes [`check`](Self::check)
+    pub(crate) fn find_solution(
+        &mut self,
+        job: &Job,
+        nonces: &[u64],
+        hashes: Vec<[u8; 32]>,
+        target: &Target,
+    ) -> Option<(u64, Hash256)> {
+        nonces
+            .iter()
+            .zip(hashes)
+            .map(|(&nonce, hash)| (nonce, Hash256(hash)))
+            .filter(|(_, hash)| target.is_met_by(hash))
+            .find(|(nonce, hash)| self.check(job, *nonce, hash))
+    }
+}
+
+impl Default for GpuWatchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::MiningHeader;
+
+    #[test]
+    fn test_watchdog_rejects_wrong_hashes() {
+        let job = MiningHeader::default().job();
+        let nonces = [0, 1, 2];
+        let mut hashes: Vec<[u8; 32]> = nonces
+            .iter()
+            .map(|&nonce| UniversalHash::new().hash_nonce(&job, nonce))
+            .collect();
+        // A defective card flips a bit in the first result
+        hashes[0][31] ^= 1;
+
+        let mut watchdog = GpuWatchdog::new();
+        let found = watchdog.find_solution(&job, &nonces, hashes.clone(), &Target::MAX);
+        assert_eq!(found.map(|(nonce, _)| nonce), Some(1));
+        assert_eq!(
+            watchdog.stats(),
+            WatchdogStats {
+                solutions: 2,
+                verified: 2,
+                rejected: 1
+            }
+        );
+        assert_eq!(watchdog.stats().error_rate(), 0.5);
+
+        // Sampling one in two checks the first solution and trusts the next
+        let mut sampling = GpuWatchdog::sampling(2);
+        assert!(sampling.check(&job, 1, &Hash256(hashes[1])));
+        assert!(sampling.check(&job, 0, &Hash256(hashes[0])));
+        assert!(!sampling.check(&job, 0, &Hash256(hashes[0])));
+        assert_eq!(sampling.stats().verified, 2);
+    }
+}
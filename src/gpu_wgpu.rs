@@ -0,0 +1,308 @@
+//! Portable GPU mining via wgpu (Vulkan, Metal, DX12)
+//!
+//! The WGSL kernel in `shaders/uhash.wgsl` performs the scratchpad fill and
+//! round loop; see [`crate::gpu`] for the split between host and device.
+//! Each nonce needs 2MB of device memory, so the batch size is bounded by
+//! the adapter's storage buffer limit.
+
+use std::ops::Range;
+use std::sync::mpsc;
+
+use wgpu::util::DeviceExt;
+
+use crate::gpu::{GpuError, GpuWatchdog, finalize_lanes, find_solution, lane_inputs};
+use crate::hash256::Hash256;
+use crate::job::Job;
+use crate::params::{CHAINS, SCRATCHPAD_SIZE};
+use crate::target::Target;
+
+/// WGSL source of the chain kernel
+pub const WGSL_SHADER: &str = include_str!("shaders/uhash.wgsl");
+
+/// Invocations per workgroup (must match `@workgroup_size` in the shader)
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Upper bound on nonces per dispatch, to keep individual submissions short
+const MAX_BATCH: usize = 64;
+
+/// GPU miner using wgpu compute shaders
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use uhash_core::{GpuMiner, MiningHeader, Target};
+///
+/// let mut miner = GpuMiner::new()?;
+/// let job = MiningHeader::default().job();
+/// if let Some((nonce, hash)) = miner.mine(&job, 0..1 << 20, &Target::from_difficulty_bits(16))? {
+///     println!("nonce {} -> {}", nonce, hash);
+/// }
+/// # Ok::<(), uhash_core::GpuError>(())
+/// ```
+pub struct GpuMiner {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    scratch: wgpu::Buffer,
+    batch_size: usize,
+    adapter_name: String,
+}
+
+impl GpuMiner {
+    /// Open the default high-performance adapter
+    pub fn new() -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .ok_or(GpuError::NoDevice)?;
+
+        let limits = adapter.limits();
+        let per_nonce = (CHAINS * SCRATCHPAD_SIZE) as u64;
+        let max_binding =
+            u64::from(limits.max_storage_buffer_binding_size).min(limits.max_buffer_size);
+        let batch_size = ((max_binding / per_nonce) as usize).min(MAX_BATCH);
+        if batch_size == 0 {
+            return Err(GpuError::NoDevice);
+        }
+
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("uhash"),
+                required_features: wgpu::Features::empty(),
+                required_limits: limits,
+                memory_hints: wgpu::MemoryHints::Performance,
+            },
+            None,
+        ))
+        .map_err(|e| GpuError::Backend(e.to_string()))?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("uhash"),
+            source: wgpu::ShaderSource::Wgsl(WGSL_SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("uhash"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        let scratch = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("uhash scratchpads"),
+            size: batch_size as u64 * per_nonce,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
+        Ok(Self {
+            device,
+            queue,
+            pipeline,
+            scratch,
+            batch_size,
+            adapter_name: adapter.get_info().name,
+        })
+    }
+
+    /// Name of the adapter in use
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    /// Nonces hashed per dispatch
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Hash `job` with each of `nonces`, identical to [`UniversalHash::hash_nonce`](crate::UniversalHash::hash_nonce)
+    pub fn hash_batch(&mut self, job: &Job, nonces: &[u64]) -> Result<Vec<[u8; 32]>, GpuError> {
+        let mut hashes = Vec::with_capacity(nonces.len());
+        for chunk in nonces.chunks(self.batch_size) {
+            hashes.extend(self.dispatch(job, chunk)?);
+        }
+        Ok(hashes)
+    }
+
+    /// Search `range` for the first nonce whose hash meets `target`
+    pub fn mine(
+        &mut self,
+        job: &Job,
+        range: Range<u64>,
+        target: &Target,
+    ) -> Result<Option<(u64, Hash256)>, GpuError> {
+        let mut start = range.start;
+        while start < range.end {
+            let end = start.saturating_add(self.batch_size as u64).min(range.end);
+            let nonces: Vec<u64> = (start..end).collect();
+            let hashes = self.dispatch(job, &nonces)?;
+            if let Some(solution) = find_solution(&nonces, hashes, target) {
+                return Ok(Some(solution));
+            }
+            start = end;
+        }
+        Ok(None)
+    }
+
+    /// [`mine`](Self::mine), re-verifying solutions on the CPU through
+    /// `watchdog` and skipping past any the GPU got wrong
+    pub fn mine_watched(
+        &mut self,
+        job: &Job,
+        range: Range<u64>,
+        target: &Target,
+        watchdog: &mut GpuWatchdog,
+    ) -> Result<Option<(u64, Hash256)>, GpuError> {
+        let mut start = range.start;
+        while start < range.end {
+            let end = start.saturating_add(self.batch_size as u64).min(range.end);
+            let nonces: Vec<u64> = (start..end).collect();
+            let hashes = self.dispatch(job, &nonces)?;
+            if let Some(solution) = watchdog.find_solution(job, &nonces, hashes, target) {
+                return Ok(Some(solution));
+            }
+            start = end;
+        }
+        Ok(None)
+    }
+
+    /// Run one batch of at most `batch_size` nonces
+    fn dispatch(&mut self, job: &Job, nonces: &[u64]) -> Result<Vec<[u8; 32]>, GpuError> {
+        if nonces.is_empty() {
+            return Ok(Vec::new());
+        }
+        let inputs = lane_inputs(job, nonces);
+        let lanes = inputs.initial.len() as u32;
+        let states_size = u64::from(lanes) * 32;
+
+        let seeds = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("uhash seeds"),
+                contents: &words_to_bytes(&inputs.seeds),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+        let initial = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("uhash initial primitives"),
+                contents: &words_to_bytes(&inputs.initial),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+        let states = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("uhash chain states"),
+            size: states_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("uhash readback"),
+            size: states_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("uhash"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: seeds.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: initial.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.scratch.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: states.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("uhash"),
+            });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("uhash"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(lanes.div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&states, 0, &readback, 0, states_size);
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (tx, rx) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        rx.recv()
+            .map_err(|e| GpuError::Backend(e.to_string()))?
+            .map_err(|e| GpuError::Backend(e.to_string()))?;
+
+        let words: Vec<u32> = slice
+            .get_mapped_range()
+            .chunks_exact(4)
+            .map(|w| u32::from_le_bytes(w.try_into().unwrap()))
+            .collect();
+        readback.unmap();
+        Ok(finalize_lanes(&words))
+    }
+}
+
+fn words_to_bytes(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|w| w.to_le_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::MiningHeader;
+    use crate::uhash::UniversalHash;
+
+    #[test]
+    fn test_shader_validates() {
+        use wgpu::naga;
+
+        let module = naga::front::wgsl::parse_str(WGSL_SHADER).expect("WGSL parse error");
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .expect("WGSL validation error");
+    }
+
+    /// Needs an adapter, so opt in with
+    /// `cargo test --features gpu-wgpu -- --ignored test_gpu_matches_cpu`;
+    /// CI runs it on the lavapipe software Vulkan driver
+    #[test]
+    #[ignore]
+    fn test_gpu_matches_cpu() {
+        let mut miner = GpuMiner::new().expect("no GPU adapter available");
+
+        let job = MiningHeader::default().job();
+        let nonces = [0, 1, 2, u64::MAX];
+        let mut hasher = UniversalHash::new();
+        let expected: Vec<_> = nonces.iter().map(|&n| hasher.hash_nonce(&job, n)).collect();
+        assert_eq!(miner.hash_batch(&job, &nonces).unwrap(), expected);
+
+        let (nonce, hash) = miner.mine(&job, 5..10, &Target::MAX).unwrap().unwrap();
+        assert_eq!(nonce, 5);
+        assert_eq!(hash.0, hasher.hash_nonce(&job, 5));
+    }
+}
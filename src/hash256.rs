@@ -0,0 +1,230 @@
+//! 256-bit hash output type
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{format, string::String};
+use core::fmt;
+use core::str::FromStr;
+
+use crate::error::HexError;
+use crate::hex::{self, HexDisplay};
+
+/// A 32-byte UniversalHash output
+///
+/// Ordering compares bytes lexicographically, which is the big-endian
+/// numeric order used by difficulty checks. Equality is constant-time.
+#[derive(Clone, Copy, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug, PartialEq, Eq), compare(PartialEq))
+)]
+pub struct Hash256(pub [u8; 32]);
+
+impl Hash256 {
+    /// All-zero hash
+    pub const ZERO: Hash256 = Hash256([0u8; 32]);
+
+    /// Borrow the raw bytes
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Copy out the raw bytes
+    pub fn to_bytes(self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Construct from raw bytes
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        Hash256(*bytes)
+    }
+
+    /// Lowercase hex encoding, 64 characters without prefix
+    ///
+    /// Same as `to_string()` and `format!("{:x}", hash)`.
+    ///
+    /// ```rust
+    /// use uhash_core::Hash256;
+    ///
+    /// let hash = Hash256([0xAB; 32]);
+    /// assert_eq!(Hash256::from_hex(&hash.to_hex()), Ok(hash));
+    /// assert_eq!(hash.to_hex_upper(), "AB".repeat(32));
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_hex(&self) -> String {
+        format!("{:x}", self)
+    }
+
+    /// Uppercase hex encoding, 64 characters without prefix
+    #[cfg(feature = "alloc")]
+    pub fn to_hex_upper(&self) -> String {
+        format!("{:X}", self)
+    }
+
+    /// Decode 64 hex digits, in either case and without prefix
+    ///
+    /// Also available as `str::parse`.
+    pub fn from_hex(hex: &str) -> Result<Self, HexError> {
+        if hex.len() != 64 {
+            return Err(HexError::InvalidLength {
+                expected: 64,
+                actual: hex.len(),
+            });
+        }
+        let mut bytes = [0u8; 32];
+        hex::decode(hex, &mut bytes).ok_or(HexError::InvalidDigit)?;
+        Ok(Hash256(bytes))
+    }
+
+    /// Number of leading zero bits
+    pub fn leading_zeros(&self) -> u32 {
+        let mut zero_bits = 0u32;
+        for byte in self.0.iter() {
+            if *byte == 0 {
+                zero_bits += 8;
+            } else {
+                zero_bits += byte.leading_zeros();
+                break;
+            }
+        }
+        zero_bits
+    }
+
+    /// Check whether this hash has at least `difficulty` leading zero bits
+    pub fn meets_difficulty(&self, difficulty: u32) -> bool {
+        crate::meets_difficulty(&self.0, difficulty)
+    }
+}
+
+impl PartialEq for Hash256 {
+    fn eq(&self, other: &Self) -> bool {
+        crate::ct::eq(&self.0, &other.0)
+    }
+}
+
+impl core::hash::Hash for Hash256 {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl From<[u8; 32]> for Hash256 {
+    fn from(bytes: [u8; 32]) -> Self {
+        Hash256(bytes)
+    }
+}
+
+impl From<Hash256> for [u8; 32] {
+    fn from(hash: Hash256) -> Self {
+        hash.0
+    }
+}
+
+impl AsRef<[u8]> for Hash256 {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Hash256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        HexDisplay(&self.0).fmt(f)
+    }
+}
+
+/// `{:#x}` adds a `0x` prefix
+impl fmt::LowerHex for Hash256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        fmt::LowerHex::fmt(&HexDisplay(&self.0), f)
+    }
+}
+
+/// `{:#X}` adds a `0x` prefix
+impl fmt::UpperHex for Hash256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        fmt::UpperHex::fmt(&HexDisplay(&self.0), f)
+    }
+}
+
+impl FromStr for Hash256 {
+    type Err = HexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+impl fmt::Debug for Hash256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Hash256({})", self)
+    }
+}
+
+// Serialized as a 64-character hex string in human-readable formats, raw
+// bytes otherwise
+#[cfg(feature = "serde")]
+crate::serde_fixed::impl_fixed_serde!(Hash256, 32, to_bytes, from_bytes);
+
+#[cfg(feature = "rkyv")]
+impl ArchivedHash256 {
+    /// Borrow the raw bytes in place
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_hex_roundtrip() {
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = (i as u8).wrapping_mul(0x1F);
+        }
+        let hash = Hash256(bytes);
+
+        let lower = hash.to_hex();
+        assert_eq!(lower.len(), 64);
+        assert_eq!(lower, hash.to_string());
+        assert_eq!(hash.to_hex_upper(), lower.to_uppercase());
+        assert_eq!(format!("{:#x}", hash), format!("0x{}", lower));
+        assert_eq!(format!("{:#X}", hash), format!("0x{}", hash.to_hex_upper()));
+
+        assert_eq!(Hash256::from_hex(&lower), Ok(hash));
+        assert_eq!(Hash256::from_hex(&hash.to_hex_upper()), Ok(hash));
+        assert_eq!(lower.parse::<Hash256>(), Ok(hash));
+    }
+
+    #[test]
+    fn test_from_hex_rejects_malformed() {
+        assert_eq!(
+            Hash256::from_hex("abcd"),
+            Err(HexError::InvalidLength {
+                expected: 64,
+                actual: 4
+            })
+        );
+        // A prefix makes the string too long
+        assert!(Hash256::from_hex(&format!("0x{}", "00".repeat(32))).is_err());
+        assert_eq!(
+            Hash256::from_hex(&"g0".repeat(32)),
+            Err(HexError::InvalidDigit)
+        );
+        // Multi-byte characters are rejected, not split
+        assert_eq!(
+            Hash256::from_hex(&format!("é{}", "0".repeat(62))),
+            Err(HexError::InvalidDigit)
+        );
+    }
+}
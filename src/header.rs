@@ -0,0 +1,158 @@
+//! Typed mining header with the canonical 68-byte spec layout
+//!
+//! ```text
+//! offset  size  field
+//!      0    32  epoch_seed
+//!     32    20  miner_address
+//!     52     8  timestamp (little-endian u64)
+//!     60     8  nonce     (little-endian u64)
+//! ```
+
+use crate::endian::{load_u64_le, store_u64_le};
+use crate::error::UhashError;
+use crate::job::Job;
+use crate::params::NONCE_SIZE;
+
+/// Size of an encoded mining header in bytes
+pub const HEADER_SIZE: usize = 68;
+
+/// Size of the header without the trailing nonce
+pub const HEADER_PREFIX_SIZE: usize = HEADER_SIZE - NONCE_SIZE;
+
+/// Mining header: `epoch_seed || miner_address || timestamp || nonce`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct MiningHeader {
+    /// Epoch seed (32 bytes)
+    pub epoch_seed: [u8; 32],
+    /// Miner address (20 bytes)
+    pub miner_address: [u8; 20],
+    /// Timestamp (encoded little-endian)
+    pub timestamp: u64,
+    /// Nonce (encoded little-endian, last 8 bytes)
+    pub nonce: u64,
+}
+
+impl MiningHeader {
+    /// Encode to the canonical 68-byte layout
+    pub fn to_bytes(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0u8; HEADER_SIZE];
+        bytes[..HEADER_PREFIX_SIZE].copy_from_slice(&self.prefix());
+        store_u64_le(&mut bytes, HEADER_PREFIX_SIZE, self.nonce);
+        bytes
+    }
+
+    /// Decode from the canonical 68-byte layout
+    pub fn from_bytes(bytes: &[u8; HEADER_SIZE]) -> Self {
+        let mut header = Self::from_prefix(bytes[..HEADER_PREFIX_SIZE].try_into().unwrap());
+        header.nonce = load_u64_le(bytes, HEADER_PREFIX_SIZE);
+        header
+    }
+
+    /// Decode from a slice, returning `None` unless it is exactly 68 bytes
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; HEADER_SIZE] = bytes.try_into().ok()?;
+        Some(Self::from_bytes(bytes))
+    }
+
+    /// Decode from a slice, reporting the length on mismatch
+    pub fn try_from_slice(bytes: &[u8]) -> Result<Self, UhashError> {
+        Self::from_slice(bytes).ok_or(UhashError::InvalidLength {
+            expected: HEADER_SIZE,
+            actual: bytes.len(),
+        })
+    }
+
+    /// Encode everything except the nonce (the first 60 bytes)
+    pub fn prefix(&self) -> [u8; HEADER_PREFIX_SIZE] {
+        let mut bytes = [0u8; HEADER_PREFIX_SIZE];
+        bytes[0..32].copy_from_slice(&self.epoch_seed);
+        bytes[32..52].copy_from_slice(&self.miner_address);
+        store_u64_le(&mut bytes, 52, self.timestamp);
+        bytes
+    }
+
+    /// Decode a 60-byte prefix, with the nonce set to zero
+    pub fn from_prefix(bytes: &[u8; HEADER_PREFIX_SIZE]) -> Self {
+        Self {
+            epoch_seed: bytes[0..32].try_into().unwrap(),
+            miner_address: bytes[32..52].try_into().unwrap(),
+            timestamp: load_u64_le(bytes, 52),
+            nonce: 0,
+        }
+    }
+
+    /// Copy of this header with a different nonce
+    pub fn with_nonce(&self, nonce: u64) -> Self {
+        Self { nonce, ..*self }
+    }
+
+    /// Precompute a [`Job`] for hashing this header with varying nonces
+    pub fn job(&self) -> Job {
+        Job::from_header(&self.prefix())
+    }
+}
+
+// Serialized as the canonical 68-byte layout: a 136-character hex string
+// in human-readable formats, raw bytes otherwise
+#[cfg(feature = "serde")]
+crate::serde_fixed::impl_fixed_serde!(MiningHeader, HEADER_SIZE, to_bytes, from_bytes);
+
+// Same fixed-size layout, see `codec`
+#[cfg(any(feature = "borsh", feature = "bincode"))]
+crate::codec::impl_fixed_codec!(MiningHeader, HEADER_SIZE, to_bytes, from_bytes);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "alloc")]
+    use crate::{UniversalHash, hash};
+
+    fn sample() -> MiningHeader {
+        MiningHeader {
+            epoch_seed: [0xAB; 32],
+            miner_address: [0x01; 20],
+            timestamp: 0x0102030405060708,
+            nonce: 0x1122334455667788,
+        }
+    }
+
+    #[test]
+    fn test_header_layout() {
+        let bytes = sample().to_bytes();
+
+        assert_eq!(&bytes[0..32], &[0xAB; 32]);
+        assert_eq!(&bytes[32..52], &[0x01; 20]);
+        assert_eq!(&bytes[52..60], &[8, 7, 6, 5, 4, 3, 2, 1]);
+        assert_eq!(
+            &bytes[60..68],
+            &[0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11]
+        );
+    }
+
+    #[test]
+    fn test_header_roundtrip() {
+        let header = sample();
+        assert_eq!(MiningHeader::from_bytes(&header.to_bytes()), header);
+        assert_eq!(MiningHeader::from_slice(&header.to_bytes()), Some(header));
+        assert_eq!(MiningHeader::from_slice(&[0u8; 67]), None);
+        assert_eq!(MiningHeader::from_slice(&[0u8; 69]), None);
+        assert_eq!(
+            MiningHeader::try_from_slice(&[0u8; 69]),
+            Err(UhashError::InvalidLength {
+                expected: HEADER_SIZE,
+                actual: 69
+            })
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_header_job_matches_hash() {
+        let header = sample();
+        let mut hasher = UniversalHash::new();
+        assert_eq!(
+            hasher.hash_nonce(&header.job(), header.nonce),
+            hash(&header.to_bytes())
+        );
+    }
+}
@@ -0,0 +1,71 @@
+//! Minimal hex encoding that works without `std` or `alloc`
+
+use core::fmt;
+
+/// Formats a byte slice as lowercase hex
+pub(crate) struct HexDisplay<'a>(pub(crate) &'a [u8]);
+
+impl fmt::Display for HexDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+impl fmt::LowerHex for HexDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::UpperHex for HexDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decode `hex` into `out`, which must be exactly half its length
+///
+/// Accepts upper- and lowercase digits. Returns `None` on any invalid
+/// character or length mismatch.
+pub(crate) fn decode(hex: &str, out: &mut [u8]) -> Option<()> {
+    let hex = hex.as_bytes();
+    if hex.len() != out.len() * 2 {
+        return None;
+    }
+    for (byte, pair) in out.iter_mut().zip(hex.chunks_exact(2)) {
+        *byte = (nibble(pair[0])? << 4) | nibble(pair[1])?;
+    }
+    Some(())
+}
+
+/// Decode up to `2 * out.len()` hex digits right-aligned into `out`
+///
+/// Missing leading digits are zero, so `"abc"` decodes to `[.., 0x0a, 0xbc]`.
+/// Returns `None` on any invalid character or if there are too many digits.
+pub(crate) fn decode_padded(hex: &str, out: &mut [u8]) -> Option<()> {
+    let hex = hex.as_bytes();
+    if hex.len() > out.len() * 2 {
+        return None;
+    }
+    out.fill(0);
+    let last = out.len() - 1;
+    for (i, &c) in hex.iter().rev().enumerate() {
+        out[last - i / 2] |= nibble(c)? << (4 * (i % 2));
+    }
+    Some(())
+}
+
+fn nibble(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
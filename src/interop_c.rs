@@ -0,0 +1,49 @@
+//! Link to the reference C implementation for interop testing
+//!
+//! The C and Rust codebases are both canonical, so spec drift between them
+//! must be caught by comparing outputs rather than by review. With the
+//! `interop-c` feature, [`differential_check`](crate::differential_check)
+//! also hashes every header through the C library and reports mismatches
+//! as [`Backend::ReferenceC`](crate::Backend::ReferenceC).
+//!
+//! Build the C implementation as a static or shared library named
+//! `uhash_ref` exporting
+//!
+//! ```text
+//! void uhash_ref_hash(const uint8_t *input, size_t len, uint8_t out[32]);
+//! ```
+//!
+//! and point the linker at it, e.g.
+//!
+//! ```text
+//! RUSTFLAGS="-L /path/to/uhash-c/build" cargo test --features interop-c differential
+//! ```
+//!
+//! The symbol is prefixed so it cannot clash with this crate's own C API
+//! (`uhash_hash`) in the same binary.
+
+use core::ffi::c_void;
+
+mod ffi {
+    use core::ffi::c_void;
+
+    #[link(name = "uhash_ref")]
+    unsafe extern "C" {
+        pub fn uhash_ref_hash(input: *const c_void, len: usize, out: *mut c_void);
+    }
+}
+
+/// Hash `input` with the reference C implementation
+pub(crate) fn hash(input: &[u8]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    // SAFETY: input is valid for `len` bytes and out for 32; the C side
+    // neither retains the pointers nor writes more than 32 bytes
+    unsafe {
+        ffi::uhash_ref_hash(
+            input.as_ptr() as *const c_void,
+            input.len(),
+            out.as_mut_ptr() as *mut c_void,
+        );
+    }
+    out
+}
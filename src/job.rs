@@ -0,0 +1,41 @@
+//! Mining jobs with precomputed header state
+//!
+//! The header prefix (everything before the nonce) is constant for a job,
+//! so its BLAKE3 absorption is done once and each nonce attempt only
+//! redoes nonce-dependent work.
+
+use blake3::Hasher as Blake3;
+
+/// A mining job: a fixed header prefix with precomputed midstate
+///
+/// Hash attempts are made with [`UniversalHash::hash_nonce`](crate::UniversalHash::hash_nonce).
+#[derive(Clone)]
+pub struct Job {
+    /// BLAKE3 state after absorbing the header prefix
+    pub(crate) midstate: Blake3,
+}
+
+impl Job {
+    /// Precompute the job state for `prefix`
+    ///
+    /// `prefix` is the input without the trailing 8-byte nonce, e.g.
+    /// `epoch_seed || miner_address || timestamp` (60 bytes).
+    pub fn from_header(prefix: &[u8]) -> Self {
+        let mut midstate = Blake3::new();
+        midstate.update(prefix);
+        Self { midstate }
+    }
+
+    /// Like [`from_header`](Self::from_header), for [`hash_keyed`](crate::hash_keyed) inputs
+    pub fn from_header_keyed(key: &[u8; 32], prefix: &[u8]) -> Self {
+        let mut midstate = Blake3::new_keyed(key);
+        midstate.update(prefix);
+        Self { midstate }
+    }
+}
+
+impl core::fmt::Debug for Job {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Job").finish_non_exhaustive()
+    }
+}
@@ -3,7 +3,7 @@
 //! A democratic proof-of-work algorithm designed for fair mining where
 //! smartphones can meaningfully compete with servers.
 //!
-//! **v0.2.0** - Full spec compliance with UniversalHash v4 specification.
+#![doc = concat!("**v", env!("CARGO_PKG_VERSION"), "** - Full spec compliance with UniversalHash v4 specification.")]
 //!
 //! ## Features
 //!
@@ -34,6 +34,7 @@
 //! ## Example
 //!
 //! ```rust
+//! # #[cfg(feature = "alloc")] {
 //! use uhash_core::{UniversalHash, hash, meets_difficulty};
 //!
 //! // Single-shot hashing
@@ -48,6 +49,7 @@
 //! let mut hasher = UniversalHash::new();
 //! let hash1 = hasher.hash(b"first");
 //! let hash2 = hasher.hash(b"second");
+//! # }
 //! ```
 //!
 //! ## no_std Support
@@ -56,23 +58,210 @@
 //!
 //! ```toml
 //! [dependencies]
-//! uhash-core = { version = "0.2", default-features = false }
+//! uhash-core = { version = "0.3", default-features = false, features = ["alloc"] }
 //! ```
+//!
+//! Without the `alloc` feature no allocator is needed; [`UniversalHashIn`]
+//! hashes in a scratchpad buffer supplied by the caller.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-#[cfg(not(feature = "std"))]
+#[cfg(all(feature = "alloc", not(feature = "std")))]
 extern crate alloc;
 
+mod absorb;
+#[cfg(feature = "analysis")]
+pub mod analysis;
+#[cfg(feature = "alloc")]
+mod checkpoint;
+#[cfg(any(feature = "borsh", feature = "bincode"))]
+mod codec;
+mod ct;
+mod difficulty;
+mod endian;
+mod epoch;
+mod error;
+mod hash256;
+mod header;
+mod hex;
+mod job;
+#[cfg(feature = "alloc")]
+pub mod merkle;
+#[cfg(feature = "alloc")]
+mod opencl;
 mod params;
+mod pow;
+mod prefilter;
 mod primitives;
+mod proof;
+#[cfg(feature = "alloc")]
+pub mod reference;
+#[cfg(feature = "alloc")]
+mod scratchpad;
+#[cfg(feature = "serde")]
+mod serde_fixed;
+#[cfg(feature = "alloc")]
+mod stratum;
+mod target;
+pub mod testing;
+mod timestamp;
+#[cfg(feature = "alloc")]
+pub mod tmto;
+mod u256;
 mod uhash;
+mod vectors;
 
+#[cfg(feature = "std")]
+mod affinity;
+#[cfg(feature = "std")]
+mod batch;
+#[cfg(feature = "std")]
+mod benchmark;
+#[cfg(feature = "std")]
+mod cache;
+#[cfg(feature = "std")]
+mod context;
+#[cfg(feature = "cuda")]
+mod cuda;
+#[cfg(feature = "std")]
+mod cursor;
+#[cfg(feature = "std")]
+mod dedup;
+#[cfg(feature = "differential")]
+mod differential;
 #[cfg(feature = "std")]
 mod ffi;
+#[cfg(feature = "jni")]
+mod ffi_jni;
+#[cfg(any(feature = "gpu-wgpu", feature = "cuda"))]
+mod gpu;
+#[cfg(feature = "gpu-wgpu")]
+mod gpu_wgpu;
+#[cfg(feature = "interop-c")]
+mod interop_c;
+#[cfg(feature = "std")]
+mod meter;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "std")]
+mod miner;
+#[cfg(feature = "std")]
+mod nonce;
+#[cfg(feature = "numa")]
+mod numa;
+#[cfg(feature = "perf")]
+mod perf;
+#[cfg(feature = "std")]
+pub mod profile;
+#[cfg(feature = "prometheus")]
+mod prometheus;
+#[cfg(feature = "puzzle")]
+pub mod puzzle;
+#[cfg(feature = "trace")]
+mod trace;
+#[cfg(feature = "uniffi")]
+mod uniffi_bindings;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
 
+pub use absorb::Absorber;
+#[cfg(feature = "std")]
+pub use affinity::{CoreSelection, logical_cpus, physical_cpus, pin_current_thread};
+#[cfg(feature = "std")]
+pub use batch::{BatchResult, audit_batch, verify_batch};
+#[cfg(feature = "std")]
+pub use cache::VerifiedCache;
+#[cfg(feature = "std")]
+pub use checkpoint::verify_checkpoints;
+#[cfg(feature = "alloc")]
+pub use checkpoint::{Checkpoint, CheckpointError, verify_checkpoint};
+#[cfg(feature = "std")]
+pub use context::{UhashContext, with_hasher};
+#[cfg(feature = "std")]
+pub use cursor::{CursorError, MiningCursor};
+#[cfg(feature = "std")]
+pub use dedup::ShareDeduplicator;
+pub use difficulty::{BlockRecord, DaaParams, next_target};
+pub use epoch::Epoch;
+#[cfg(feature = "std")]
+pub use epoch::EpochManager;
+pub use error::{HexError, ParseDifficultyError, UhashError};
+#[cfg(feature = "rkyv")]
+pub use hash256::ArchivedHash256;
+pub use hash256::Hash256;
+pub use header::{HEADER_PREFIX_SIZE, HEADER_SIZE, MiningHeader};
+pub use job::Job;
+#[cfg(feature = "std")]
+pub use meter::{HashrateMeter, HashrateSnapshot};
+#[cfg(feature = "metrics")]
+pub use metrics::Metrics;
+#[cfg(feature = "std")]
+pub use miner::{
+    MinerConfig, MinerPool, MinerStats, MiningWork, Solution, SwapMode, Throttle, WorkerStats,
+};
+#[cfg(feature = "std")]
+pub use nonce::{NonceStrategy, RandomOrder, Sequential, Strided};
+#[cfg(feature = "alloc")]
+pub use opencl::{OPENCL_KERNEL_NAME, cuda_kernel_source, opencl_kernel_source};
 pub use params::*;
-pub use uhash::{UniversalHash, hash, meets_difficulty};
+pub use pow::PowError;
+#[cfg(feature = "alloc")]
+pub use pow::{verify_pow, verify_pow_bytes};
+#[cfg(feature = "alloc")]
+pub use prefilter::verify_prefiltered;
+pub use prefilter::{PREFILTER_CONTEXT, passes_prefilter, prefilter};
+pub use primitives::{
+    AesCompress, Blake3Compress, CompressionPrimitive, ConsensusPrimitives, PrimitiveSet,
+    Primitives, Sha256Compress, hardware_aes,
+};
+#[cfg(feature = "prometheus")]
+pub use prometheus::{MetricsRegistry, OPENMETRICS_CONTENT_TYPE};
+#[cfg(feature = "rkyv")]
+pub use proof::ArchivedPowProof;
+pub use proof::{POW_PROOF_SIZE, PowProof};
+#[cfg(feature = "alloc")]
+pub use stratum::{ExtranonceRegion, StratumJob};
+pub use target::{
+    ChainWork, Difficulty, ShareResult, Target, check_share, estimate_time_to_block,
+    leading_zero_bits, meets_target, required_hashrate, share_quality, target_for,
+    work_from_target,
+};
+pub use timestamp::{MEDIAN_TIME_SPAN, TimestampError, median_time_past, validate_timestamp};
+pub use u256::U256;
+#[cfg(feature = "alloc")]
+pub use uhash::{
+    UniversalHash, double_hash, hash, hash_chain, hash_header, hash_keyed, hash_parts, hash_xof,
+    try_hash,
+};
+pub use uhash::{UniversalHashIn, meets_difficulty};
+pub use vectors::{AlgorithmVersion, KnownAnswer, vectors_for};
+
+#[cfg(feature = "cuda")]
+pub use cuda::{CudaDevice, CudaMiner};
+#[cfg(feature = "differential")]
+pub use differential::{
+    Backend, DifferentialReport, Divergence, differential_check, portable_hash, random_headers,
+};
+#[cfg(any(feature = "gpu-wgpu", feature = "cuda"))]
+pub use gpu::{GpuError, GpuWatchdog, WatchdogStats};
+#[cfg(feature = "gpu-wgpu")]
+pub use gpu_wgpu::{GpuMiner, WGSL_SHADER};
+#[cfg(feature = "numa")]
+pub use numa::{bind_to_node, current_node};
+#[cfg(feature = "perf")]
+pub use perf::PerfCounters;
+
+#[cfg(feature = "trace")]
+pub use trace::{ChainTrace, HashTrace, Primitive, RoundTrace, hash_traced};
+
+#[cfg(feature = "std")]
+pub use benchmark::{
+    BYTES_PER_HASH, BenchmarkReport, MemoryBenchmark, PrimitiveTimings, WorkloadBound, benchmark,
+    benchmark_memory,
+};
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
+mod proptests;
+#[cfg(all(test, feature = "alloc"))]
 mod tests;
@@ -0,0 +1,234 @@
+//! Merkle trees with crate-defined domain separation
+//!
+//! Block formats commit to their contents through a Merkle root, which
+//! every implementation must compute bit-for-bit alike. Nodes are hashed
+//! with the BLAKE3 stage UniversalHash finalizes with, not the full
+//! memory-hard function: a tree over `n` leaves needs `2n - 1` hashes, and
+//! the proof of work already sits on the header that commits to the root.
+//! Leaves may themselves be UniversalHash outputs.
+//!
+//! ```text
+//! leaf(data)    = BLAKE3_derive_key(MERKLE_CONTEXT, 0x00 || data)
+//! node(l, r)    = BLAKE3_derive_key(MERKLE_CONTEXT, 0x01 || l || r)
+//! root([])      = BLAKE3_derive_key(MERKLE_CONTEXT, 0x02)
+//! ```
+//!
+//! The tag byte keeps a leaf from ever being read as a node, so a proof
+//! cannot pass off an inner node as a leaf. A level with an odd number of
+//! nodes promotes its last node unchanged instead of pairing it with
+//! itself, so no two distinct leaf lists share a root.
+//!
+//! ```rust
+//! use uhash_core::merkle::MerkleTree;
+//!
+//! let leaves = [&b"tx0"[..], b"tx1", b"tx2"];
+//! let tree = MerkleTree::new(&leaves);
+//! let proof = tree.proof(2).unwrap();
+//! assert!(proof.verify(&tree.root(), b"tx2"));
+//! assert!(!proof.verify(&tree.root(), b"tx1"));
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::hash256::Hash256;
+
+/// BLAKE3 key-derivation context for Merkle hashing
+pub const MERKLE_CONTEXT: &str = "UniversalHash v4 2026-10-16 merkle tree";
+
+const LEAF_TAG: u8 = 0x00;
+const NODE_TAG: u8 = 0x01;
+const EMPTY_TAG: u8 = 0x02;
+
+/// Hash of one leaf's data
+pub fn leaf_hash(data: &[u8]) -> Hash256 {
+    let mut hasher = blake3::Hasher::new_derive_key(MERKLE_CONTEXT);
+    hasher.update(&[LEAF_TAG]);
+    hasher.update(data);
+    Hash256(hasher.finalize().into())
+}
+
+/// Hash of an inner node from its children
+pub fn node_hash(left: &Hash256, right: &Hash256) -> Hash256 {
+    let mut hasher = blake3::Hasher::new_derive_key(MERKLE_CONTEXT);
+    hasher.update(&[NODE_TAG]);
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    Hash256(hasher.finalize().into())
+}
+
+/// Root of the tree with no leaves
+pub fn empty_root() -> Hash256 {
+    let mut hasher = blake3::Hasher::new_derive_key(MERKLE_CONTEXT);
+    hasher.update(&[EMPTY_TAG]);
+    Hash256(hasher.finalize().into())
+}
+
+/// Root over `leaves` without keeping the tree
+pub fn root<T: AsRef<[u8]>>(leaves: &[T]) -> Hash256 {
+    MerkleTree::new(leaves).root()
+}
+
+/// Every level of a Merkle tree, leaves first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleTree {
+    levels: Vec<Vec<Hash256>>,
+}
+
+impl MerkleTree {
+    /// Build the tree over `leaves`, hashing each with [`leaf_hash`]
+    pub fn new<T: AsRef<[u8]>>(leaves: &[T]) -> Self {
+        let mut level: Vec<Hash256> = leaves.iter().map(|leaf| leaf_hash(leaf.as_ref())).collect();
+        let mut levels = Vec::new();
+        while level.len() > 1 {
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [promoted] => *promoted,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(core::mem::replace(&mut level, next));
+        }
+        levels.push(level);
+        MerkleTree { levels }
+    }
+
+    /// Number of leaves
+    pub fn leaf_count(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Root hash, [`empty_root`] for a tree without leaves
+    pub fn root(&self) -> Hash256 {
+        match self.levels.last().and_then(|level| level.first()) {
+            Some(root) => *root,
+            None => empty_root(),
+        }
+    }
+
+    /// Inclusion proof for the leaf at `index`, `None` if out of range
+    pub fn proof(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.leaf_count() {
+            return None;
+        }
+        let mut siblings = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(position ^ 1) {
+                siblings.push(*sibling);
+            }
+            position /= 2;
+        }
+        Some(MerkleProof {
+            index,
+            leaf_count: self.leaf_count(),
+            siblings,
+        })
+    }
+}
+
+/// Sibling hashes from one leaf up to the root
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MerkleProof {
+    /// Position of the proven leaf
+    pub index: usize,
+    /// Number of leaves in the tree, which fixes where nodes were promoted
+    pub leaf_count: usize,
+    /// Siblings bottom-up; promoted levels contribute none
+    pub siblings: Vec<Hash256>,
+}
+
+impl MerkleProof {
+    /// Whether `leaf` is at [`index`](Self::index) in the tree with `root`
+    pub fn verify(&self, root: &Hash256, leaf: &[u8]) -> bool {
+        self.root_for(&leaf_hash(leaf))
+            .is_some_and(|computed| computed == *root)
+    }
+
+    /// Root implied by a leaf whose [`leaf_hash`] is `leaf`, or `None` if
+    /// the proof's shape doesn't fit its `leaf_count`
+    pub fn root_for(&self, leaf: &Hash256) -> Option<Hash256> {
+        if self.index >= self.leaf_count {
+            return None;
+        }
+        let mut hash = *leaf;
+        let mut position = self.index;
+        let mut width = self.leaf_count;
+        let mut siblings = self.siblings.iter();
+        while width > 1 {
+            // The last node of an odd level has no sibling and moves up as is
+            if position != width - 1 || width.is_multiple_of(2) {
+                let sibling = siblings.next()?;
+                hash = if position.is_multiple_of(2) {
+                    node_hash(&hash, sibling)
+                } else {
+                    node_hash(sibling, &hash)
+                };
+            }
+            position /= 2;
+            width = width.div_ceil(2);
+        }
+        siblings.next().is_none().then_some(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
+
+    fn leaves(count: usize) -> Vec<[u8; 4]> {
+        (0..count as u32).map(u32::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn test_proofs_for_every_shape() {
+        for count in 1..=17 {
+            let leaves = leaves(count);
+            let tree = MerkleTree::new(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert!(proof.verify(&tree.root(), leaf), "{count} leaves, #{index}");
+                assert!(!proof.verify(&tree.root(), b"other"));
+
+                let mut moved = proof.clone();
+                moved.index = (index + 1) % count;
+                assert!(count == 1 || !moved.verify(&tree.root(), leaf));
+            }
+            assert_eq!(tree.proof(count), None);
+        }
+    }
+
+    #[test]
+    fn test_root_structure() {
+        assert_eq!(root::<&[u8]>(&[]), empty_root());
+        assert_eq!(root(&[b"a"]), leaf_hash(b"a"));
+
+        // Three leaves: the third is promoted, not paired with itself
+        let (a, b, c) = (leaf_hash(b"a"), leaf_hash(b"b"), leaf_hash(b"c"));
+        assert_eq!(root(&[b"a", b"b", b"c"]), node_hash(&node_hash(&a, &b), &c));
+        assert_ne!(root(&[b"a", b"b", b"c"]), root(&[b"a", b"b", b"c", b"c"]));
+    }
+
+    #[test]
+    fn test_leaf_and_node_are_domain_separated() {
+        let (a, b) = (leaf_hash(b"a"), leaf_hash(b"b"));
+        let mut concatenated = a.to_bytes().to_vec();
+        concatenated.extend_from_slice(b.as_bytes());
+        assert_ne!(leaf_hash(&concatenated), node_hash(&a, &b));
+
+        // An inner node cannot be proven as a leaf of the tree
+        let tree = MerkleTree::new(&[b"a", b"b", b"c", b"d"]);
+        let proof = MerkleProof {
+            index: 0,
+            leaf_count: 2,
+            siblings: vec![node_hash(&leaf_hash(b"c"), &leaf_hash(b"d"))],
+        };
+        assert!(!proof.verify(&tree.root(), &concatenated));
+    }
+}
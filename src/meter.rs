@@ -0,0 +1,253 @@
+//! Hashrate measurement with rolling averages
+//!
+//! Raw per-hash timings are far too noisy to display. [`HashrateMeter`]
+//! keeps a lifetime average, a sliding-window average and an exponentially
+//! weighted moving average so frontends can pick whichever suits them.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Point-in-time view of a [`HashrateMeter`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HashrateSnapshot {
+    /// Hashes recorded since the meter started
+    pub total_hashes: u64,
+    /// Time since the meter started
+    pub uptime: Duration,
+    /// Hashes per second over the whole uptime
+    pub average: f64,
+    /// Hashes per second over the sliding window
+    pub windowed: f64,
+    /// Exponentially weighted hashes per second
+    pub ewma: f64,
+}
+
+/// Thread-safe hashrate meter
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use uhash_core::HashrateMeter;
+///
+/// let meter = HashrateMeter::new(Duration::from_secs(60), Duration::from_secs(30));
+/// meter.record(1);
+/// assert_eq!(meter.snapshot().total_hashes, 1);
+/// ```
+#[derive(Debug)]
+pub struct HashrateMeter {
+    window: Duration,
+    /// EWMA time constant (half-life / ln 2), in seconds
+    tau: f64,
+    started: Instant,
+    state: Mutex<MeterState>,
+}
+
+#[derive(Debug)]
+struct MeterState {
+    total: u64,
+    samples: VecDeque<(Instant, u64)>,
+    in_window: u64,
+    /// EWMA as of `last`
+    ewma: f64,
+    /// Whether `ewma` holds a measured rate yet
+    seeded: bool,
+    /// Hashes recorded at `last` that the EWMA has not absorbed, because
+    /// no time had passed to divide them by
+    pending: u64,
+    /// Latest record time; earlier instants are clamped to it
+    last: Instant,
+}
+
+impl HashrateMeter {
+    /// Meter averaging over `window`, with an EWMA of the given `half_life`
+    pub fn new(window: Duration, half_life: Duration) -> Self {
+        Self::starting_at(window, half_life, Instant::now())
+    }
+
+    /// Like [`new`](Self::new), with an explicit start time
+    pub fn starting_at(window: Duration, half_life: Duration, started: Instant) -> Self {
+        Self {
+            window,
+            tau: half_life.as_secs_f64() / core::f64::consts::LN_2,
+            started,
+            state: Mutex::new(MeterState {
+                total: 0,
+                samples: VecDeque::new(),
+                in_window: 0,
+                ewma: 0.0,
+                seeded: false,
+                pending: 0,
+                last: started,
+            }),
+        }
+    }
+
+    /// Record `hashes` completed just now
+    pub fn record(&self, hashes: u64) {
+        self.record_at(hashes, Instant::now());
+    }
+
+    /// Record `hashes` completed at `now`
+    ///
+    /// Workers sharing a meter take their `Instant`s before the lock, so
+    /// records can arrive slightly out of order; an instant before the
+    /// latest record counts as that record's time. Records at the same
+    /// instant are held until time advances, then enter the EWMA together.
+    pub fn record_at(&self, hashes: u64, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        let now = now.max(state.last);
+        state.total += hashes;
+        state.samples.push_back((now, hashes));
+        state.in_window += hashes;
+        state.pending += hashes;
+        self.prune(&mut state, now);
+
+        if now > state.last {
+            state.ewma = self.ewma_at(&state, now);
+            state.seeded = true;
+            state.pending = 0;
+            state.last = now;
+        }
+    }
+
+    /// Current statistics
+    pub fn snapshot(&self) -> HashrateSnapshot {
+        self.snapshot_at(Instant::now())
+    }
+
+    /// Statistics as of `now`
+    pub fn snapshot_at(&self, now: Instant) -> HashrateSnapshot {
+        let mut state = self.state.lock().unwrap();
+        self.prune(&mut state, now);
+
+        let uptime = now.saturating_duration_since(self.started);
+        let span = uptime.min(self.window).as_secs_f64();
+        HashrateSnapshot {
+            total_hashes: state.total,
+            uptime,
+            average: rate(state.total, uptime.as_secs_f64()),
+            windowed: rate(state.in_window, span),
+            ewma: self.ewma_at(&state, now),
+        }
+    }
+
+    /// Hashes per second over the sliding window
+    pub fn windowed(&self) -> f64 {
+        self.snapshot().windowed
+    }
+
+    /// Exponentially weighted hashes per second
+    pub fn ewma(&self) -> f64 {
+        self.snapshot().ewma
+    }
+
+    /// EWMA after the pending hashes spread over `last..now`
+    ///
+    /// With nothing pending this decays the rate toward zero, so a stalled
+    /// miner stops reporting its last rate.
+    fn ewma_at(&self, state: &MeterState, now: Instant) -> f64 {
+        let dt = now.saturating_duration_since(state.last).as_secs_f64();
+        if dt <= 0.0 {
+            return state.ewma;
+        }
+        let rate = state.pending as f64 / dt;
+        // The first sample seeds the average instead of ramping up from zero
+        let alpha = if !state.seeded || self.tau <= 0.0 {
+            1.0
+        } else {
+            1.0 - (-dt / self.tau).exp()
+        };
+        state.ewma + alpha * (rate - state.ewma)
+    }
+
+    fn prune(&self, state: &mut MeterState, now: Instant) {
+        while let Some(&(at, hashes)) = state.samples.front() {
+            if now.saturating_duration_since(at) < self.window {
+                break;
+            }
+            state.samples.pop_front();
+            state.in_window -= hashes;
+        }
+    }
+}
+
+impl Default for HashrateMeter {
+    /// 60 second window, 30 second EWMA half-life
+    fn default() -> Self {
+        Self::new(Duration::from_secs(60), Duration::from_secs(30))
+    }
+}
+
+fn rate(hashes: u64, seconds: f64) -> f64 {
+    if seconds > 0.0 {
+        hashes as f64 / seconds
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meter_averages() {
+        let t0 = Instant::now();
+        let secs = |s: u64| t0 + Duration::from_secs(s);
+        let meter = HashrateMeter::starting_at(Duration::from_secs(10), Duration::from_secs(5), t0);
+
+        // 100 H/s for 20 seconds
+        for s in 1..=20 {
+            meter.record_at(100, secs(s));
+        }
+        let snap = meter.snapshot_at(secs(20));
+        assert_eq!(snap.total_hashes, 2000);
+        assert_eq!(snap.uptime, Duration::from_secs(20));
+        assert!((snap.average - 100.0).abs() < 1e-9);
+        assert!((snap.windowed - 100.0).abs() < 1e-9);
+        assert!((snap.ewma - 100.0).abs() < 1e-9);
+
+        // Rate doubles: window adapts fully, EWMA moves toward it, lifetime lags
+        for s in 21..=40 {
+            meter.record_at(200, secs(s));
+        }
+        let snap = meter.snapshot_at(secs(40));
+        assert!((snap.windowed - 200.0).abs() < 1e-9);
+        assert!(snap.ewma > 190.0 && snap.ewma < 200.0);
+        assert!((snap.average - 150.0).abs() < 1e-9);
+
+        // Idle: the window drains and the EWMA decays by half per half-life
+        let snap = meter.snapshot_at(secs(60));
+        assert_eq!(snap.windowed, 0.0);
+        assert_eq!(snap.total_hashes, 6000);
+        let ewma = meter.snapshot_at(secs(40)).ewma;
+        assert!((snap.ewma - ewma / 16.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_meter_counts_concurrent_records() {
+        let t0 = Instant::now();
+        let secs = |s: u64| t0 + Duration::from_secs(s);
+        let meter = HashrateMeter::starting_at(Duration::from_secs(10), Duration::from_secs(5), t0);
+
+        // Four workers recording 100 hashes per second: one at the new
+        // instant, one at the same instant, and two whose instants predate
+        // the lock, so three are held back each second (here from the start)
+        meter.record_at(300, t0);
+        for s in 1..=20 {
+            meter.record_at(100, secs(s));
+            meter.record_at(100, secs(s));
+            meter.record_at(100, secs(s - 1));
+            meter.record_at(100, secs(s) - Duration::from_millis(1));
+        }
+        let snap = meter.snapshot_at(secs(20));
+        assert_eq!(snap.total_hashes, 8300);
+        assert!((snap.ewma - 400.0).abs() < 1e-9);
+        // Records held at the last instant enter the EWMA once time moves on
+        let alpha = 1.0 - 0.5f64.powf(0.2);
+        let snap = meter.snapshot_at(secs(21));
+        assert!((snap.ewma - (400.0 + alpha * (300.0 - 400.0))).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,291 @@
+//! Process-wide hashing counters (`metrics` feature)
+//!
+//! Every hasher in the process adds to the same relaxed atomic counters,
+//! once per hash rather than once per round, so the hot loop is untouched.
+//! Proof verifications and miner shares are counted the same way.
+//! [`Metrics::snapshot`] reads them. With the `metrics-rs` feature the same
+//! events are also emitted through the [`metrics`](https://docs.rs/metrics)
+//! facade, for whichever recorder (e.g. a Prometheus exporter) the
+//! application installed.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::params::{CHAINS, ROUNDS};
+use crate::uhash::{initial_primitive, round_primitive};
+
+static HASHES: AtomicU64 = AtomicU64::new(0);
+/// Invocations per primitive, indexed like `round_primitive`
+static PRIMITIVES: [AtomicU64; 3] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+static INITS: AtomicU64 = AtomicU64::new(0);
+static INIT_NANOS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+static SHARES: AtomicU64 = AtomicU64::new(0);
+static VERIFICATIONS: AtomicU64 = AtomicU64::new(0);
+static VERIFICATION_FAILURES: AtomicU64 = AtomicU64::new(0);
+static VERIFICATION_NANOS: AtomicU64 = AtomicU64::new(0);
+/// Verifications per latency bucket, indexed like `VERIFICATION_BUCKETS`
+/// with a final overflow bucket; not cumulative
+static VERIFICATION_HISTOGRAM: [AtomicU64; VERIFICATION_BUCKETS.len() + 1] =
+    [const { AtomicU64::new(0) }; VERIFICATION_BUCKETS.len() + 1];
+
+/// Upper bounds of the verification latency histogram buckets, in seconds
+pub(crate) const VERIFICATION_BUCKETS: [f64; 10] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0,
+];
+
+/// Primitive labels, indexed like `round_primitive`
+#[cfg(feature = "metrics-rs")]
+const PRIMITIVE_NAMES: [&str; 3] = ["aes", "sha256", "blake3"];
+
+/// Snapshot of the process-wide hashing counters
+///
+/// Counters only grow; use [`since`](Self::since) for the activity between
+/// two snapshots.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{Metrics, hash};
+///
+/// let before = Metrics::snapshot();
+/// hash(b"input data");
+/// let delta = Metrics::snapshot().since(&before);
+/// assert!(delta.hashes >= 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Metrics {
+    /// Hashes computed
+    pub hashes: u64,
+    /// AES_Compress invocations
+    pub aes_compress: u64,
+    /// SHA256_Compress invocations
+    pub sha256_compress: u64,
+    /// BLAKE3_Compress invocations
+    pub blake3_compress: u64,
+    /// Scratchpad initialization phases (one per hash, all chains)
+    pub scratchpad_inits: u64,
+    /// Total wall-clock time spent initializing scratchpads
+    pub scratchpad_init_time: Duration,
+    /// Scratchpad allocations (one per `UniversalHash` constructed)
+    pub scratchpad_allocations: u64,
+    /// Bytes allocated for scratchpads
+    pub scratchpad_bytes: u64,
+    /// Solutions found by `MinerPool` workers
+    pub shares: u64,
+    /// Proof verifications, successful or not
+    pub verifications: u64,
+    /// Proof verifications that rejected the proof
+    pub verification_failures: u64,
+    /// Total wall-clock time spent verifying proofs
+    pub verification_time: Duration,
+}
+
+impl Metrics {
+    /// Read the current counters
+    pub fn snapshot() -> Self {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        Self {
+            hashes: load(&HASHES),
+            aes_compress: load(&PRIMITIVES[0]),
+            sha256_compress: load(&PRIMITIVES[1]),
+            blake3_compress: load(&PRIMITIVES[2]),
+            scratchpad_inits: load(&INITS),
+            scratchpad_init_time: Duration::from_nanos(load(&INIT_NANOS)),
+            scratchpad_allocations: load(&ALLOCATIONS),
+            scratchpad_bytes: load(&ALLOCATED_BYTES),
+            shares: load(&SHARES),
+            verifications: load(&VERIFICATIONS),
+            verification_failures: load(&VERIFICATION_FAILURES),
+            verification_time: Duration::from_nanos(load(&VERIFICATION_NANOS)),
+        }
+    }
+
+    /// Counter increments between `earlier` and `self`
+    pub fn since(&self, earlier: &Metrics) -> Metrics {
+        Metrics {
+            hashes: self.hashes.saturating_sub(earlier.hashes),
+            aes_compress: self.aes_compress.saturating_sub(earlier.aes_compress),
+            sha256_compress: self.sha256_compress.saturating_sub(earlier.sha256_compress),
+            blake3_compress: self.blake3_compress.saturating_sub(earlier.blake3_compress),
+            scratchpad_inits: self
+                .scratchpad_inits
+                .saturating_sub(earlier.scratchpad_inits),
+            scratchpad_init_time: self
+                .scratchpad_init_time
+                .saturating_sub(earlier.scratchpad_init_time),
+            scratchpad_allocations: self
+                .scratchpad_allocations
+                .saturating_sub(earlier.scratchpad_allocations),
+            scratchpad_bytes: self
+                .scratchpad_bytes
+                .saturating_sub(earlier.scratchpad_bytes),
+            shares: self.shares.saturating_sub(earlier.shares),
+            verifications: self.verifications.saturating_sub(earlier.verifications),
+            verification_failures: self
+                .verification_failures
+                .saturating_sub(earlier.verification_failures),
+            verification_time: self
+                .verification_time
+                .saturating_sub(earlier.verification_time),
+        }
+    }
+
+    /// Average scratchpad initialization time per hash
+    pub fn average_init_time(&self) -> Duration {
+        if self.scratchpad_inits == 0 {
+            return Duration::ZERO;
+        }
+        self.scratchpad_init_time / self.scratchpad_inits as u32
+    }
+}
+
+/// Verification counts per bucket of [`VERIFICATION_BUCKETS`], plus the
+/// overflow bucket; not cumulative
+pub(crate) fn verification_histogram() -> [u64; VERIFICATION_BUCKETS.len() + 1] {
+    core::array::from_fn(|i| VERIFICATION_HISTOGRAM[i].load(Ordering::Relaxed))
+}
+
+/// Count one completed hash and the primitive invocations it made
+pub(crate) fn record_hash(nonce: u64) {
+    HASHES.fetch_add(1, Ordering::Relaxed);
+    let counts = primitive_counts(nonce);
+    for (counter, &count) in PRIMITIVES.iter().zip(&counts) {
+        counter.fetch_add(count, Ordering::Relaxed);
+    }
+
+    #[cfg(feature = "metrics-rs")]
+    {
+        ::metrics::counter!("uhash_hashes_total").increment(1);
+        for (name, &count) in PRIMITIVE_NAMES.iter().zip(&counts) {
+            ::metrics::counter!("uhash_primitive_invocations_total", "primitive" => *name)
+                .increment(count);
+        }
+    }
+}
+
+/// Invocations of each primitive across all chains of one hash
+///
+/// The primitive sequence is fixed by the nonce, so the counts are derived
+/// instead of tallied per round.
+fn primitive_counts(nonce: u64) -> [u64; 3] {
+    let mut counts = [0u64; 3];
+    for chain in 0..CHAINS {
+        let initial = initial_primitive(nonce, chain);
+        for (primitive, count) in counts.iter_mut().enumerate() {
+            *count += (ROUNDS / 3) as u64;
+            // Rounds past the last full rotation repeat the first ones
+            if (0..ROUNDS % 3).any(|round| round_primitive(initial, round) == primitive) {
+                *count += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Count one scratchpad initialization phase that took `elapsed`
+pub(crate) fn record_init(elapsed: Duration) {
+    INITS.fetch_add(1, Ordering::Relaxed);
+    INIT_NANOS.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+
+    #[cfg(feature = "metrics-rs")]
+    ::metrics::histogram!("uhash_scratchpad_init_seconds").record(elapsed.as_secs_f64());
+}
+
+/// Count one scratchpad allocation of `bytes`
+pub(crate) fn record_allocation(bytes: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(bytes as u64, Ordering::Relaxed);
+
+    #[cfg(feature = "metrics-rs")]
+    {
+        ::metrics::counter!("uhash_scratchpad_allocations_total").increment(1);
+        ::metrics::counter!("uhash_scratchpad_bytes_total").increment(bytes as u64);
+    }
+}
+
+/// Count one solution reported by a miner worker
+pub(crate) fn record_share() {
+    SHARES.fetch_add(1, Ordering::Relaxed);
+
+    #[cfg(feature = "metrics-rs")]
+    ::metrics::counter!("uhash_shares_total").increment(1);
+}
+
+/// Count one proof verification that took `elapsed`
+pub(crate) fn record_verification(elapsed: Duration, valid: bool) {
+    VERIFICATIONS.fetch_add(1, Ordering::Relaxed);
+    if !valid {
+        VERIFICATION_FAILURES.fetch_add(1, Ordering::Relaxed);
+    }
+    VERIFICATION_NANOS.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    let seconds = elapsed.as_secs_f64();
+    let bucket = VERIFICATION_BUCKETS
+        .iter()
+        .position(|&bound| seconds <= bound)
+        .unwrap_or(VERIFICATION_BUCKETS.len());
+    VERIFICATION_HISTOGRAM[bucket].fetch_add(1, Ordering::Relaxed);
+
+    #[cfg(feature = "metrics-rs")]
+    {
+        let result = if valid { "ok" } else { "rejected" };
+        ::metrics::counter!("uhash_verifications_total", "result" => result).increment(1);
+        ::metrics::histogram!("uhash_verification_seconds").record(seconds);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uhash::UniversalHash;
+
+    #[test]
+    fn test_metrics_count_hashes() {
+        // Other tests hash concurrently, so only lower bounds hold
+        let before = Metrics::snapshot();
+        let mut hasher = UniversalHash::new();
+        hasher.hash(b"metrics");
+        hasher.hash(b"metrics 2");
+        let delta = Metrics::snapshot().since(&before);
+
+        assert!(delta.hashes >= 2);
+        assert!(delta.scratchpad_inits >= 2);
+        assert!(delta.scratchpad_init_time > Duration::ZERO);
+        assert!(delta.average_init_time() > Duration::ZERO);
+        assert!(delta.scratchpad_allocations >= 1);
+        assert!(delta.scratchpad_bytes >= (CHAINS * crate::SCRATCHPAD_SIZE) as u64);
+        let invocations = delta.aes_compress + delta.sha256_compress + delta.blake3_compress;
+        assert!(invocations >= (2 * ROUNDS * CHAINS) as u64);
+    }
+
+    #[test]
+    fn test_metrics_count_verifications() {
+        let before = Metrics::snapshot();
+        let histogram_before: u64 = verification_histogram().iter().sum();
+        let mut hasher = UniversalHash::new();
+        let header = crate::MiningHeader::default();
+        hasher.verify_pow(&header, 0).unwrap();
+        hasher.verify_pow(&header, 256).unwrap_err();
+        let delta = Metrics::snapshot().since(&before);
+
+        assert!(delta.verifications >= 2);
+        assert!(delta.verification_failures >= 1);
+        assert!(delta.verification_time > Duration::ZERO);
+        assert!(verification_histogram().iter().sum::<u64>() >= histogram_before + 2);
+    }
+
+    #[test]
+    fn test_primitive_counts_match_round_sequence() {
+        for nonce in [0, 1, 2, u64::MAX] {
+            let mut expected = [0u64; 3];
+            for chain in 0..CHAINS {
+                let initial = initial_primitive(nonce, chain);
+                for round in 0..ROUNDS {
+                    expected[round_primitive(initial, round)] += 1;
+                }
+            }
+            assert_eq!(primitive_counts(nonce), expected);
+        }
+    }
+}
@@ -0,0 +1,1159 @@
+//! Multi-threaded CPU miner
+//!
+//! [`MinerPool`] owns a set of worker threads, each with its own reusable
+//! [`UniversalHash`] (and therefore its own 2MB scratchpad). Work is pushed
+//! to every worker over a channel; solutions come back over a shared one,
+//! or go straight to a caller-supplied channel or callback
+//! ([`MinerPool::with_sender`], [`MinerPool::with_callback`]).
+//! New work replaces the old on the running threads
+//! ([`MinerPool::replace_work`]), taking effect after at most one hash.
+//! [`MinerPool::cursor`] snapshots how far each worker got, and
+//! [`MinerPool::resume`] picks the work up from there after a restart.
+//! Workers walk the nonce space in the order of the configured
+//! [`NonceStrategy`]; by default worker `i` of `n` tries nonces
+//! `start_nonce + i`, `start_nonce + i + n`, ..., so workers never
+//! duplicate effort.
+//!
+//! Besides raw hash counts, workers track the best hash they have seen and
+//! how many solutions their hashes should have found given each work's
+//! target, which [`MinerPool::summary`] turns into a luck figure.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::affinity::{CoreSelection, pin_current_thread};
+use crate::cursor::{CursorError, MiningCursor};
+use crate::hash256::Hash256;
+use crate::job::Job;
+use crate::meter::HashrateMeter;
+use crate::nonce::{NonceStrategy, Strided};
+use crate::target::{Difficulty, Target};
+use crate::uhash::UniversalHash;
+
+/// Work handed to every worker
+#[derive(Debug, Clone)]
+pub struct MiningWork {
+    /// Caller-chosen identifier, echoed back in [`Solution::work_id`]
+    pub id: u64,
+    /// Precomputed header prefix
+    pub job: Job,
+    /// Target a hash must meet to be reported
+    pub target: Target,
+    /// First nonce of the search
+    pub start_nonce: u64,
+}
+
+/// A nonce whose hash meets the work's target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Solution {
+    /// [`MiningWork::id`] the nonce was found for
+    pub work_id: u64,
+    /// Winning nonce
+    pub nonce: u64,
+    /// Hash of the header with this nonce
+    pub hash: Hash256,
+    /// Index of the worker that found it
+    pub worker: usize,
+    /// Generation of the work it solves, as returned by
+    /// [`MinerPool::replace_work`]
+    pub generation: u64,
+}
+
+impl Solution {
+    /// Whether the solution is for work older than `current_generation`
+    ///
+    /// A pool rejects shares for a job it has moved past, often with a
+    /// penalty; checking before submitting drops them locally instead.
+    pub fn is_stale(&self, current_generation: u64) -> bool {
+        self.generation < current_generation
+    }
+}
+
+/// Snapshot of one worker's counters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorkerStats {
+    /// Worker index
+    pub worker: usize,
+    /// Logical CPU the worker is pinned to, if pinning succeeded
+    pub cpu: Option<usize>,
+    /// Hashes computed since the pool started
+    pub hashes: u64,
+    /// Solutions found since the pool started
+    pub solutions: u64,
+    /// Average hashes per second since the pool started
+    pub hashrate: f64,
+    /// Candidate solutions discarded by fault detection
+    pub hardware_errors: u64,
+}
+
+/// Pool-wide share statistics since the pool started
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinerStats {
+    /// Hashes computed
+    pub hashes: u64,
+    /// Solutions found
+    pub solutions: u64,
+    /// Solutions the computed hashes should have found on average, given
+    /// the target of the work each was computed for
+    pub expected_solutions: f64,
+    /// Lowest hash computed, whether or not it met its target
+    pub best_hash: Option<Hash256>,
+}
+
+impl MinerStats {
+    /// Solutions found as a percentage of those expected, or `None` before
+    /// any hash: 100 is average luck, above is lucky, below unlucky
+    pub fn luck(&self) -> Option<f64> {
+        (self.expected_solutions > 0.0)
+            .then(|| self.solutions as f64 / self.expected_solutions * 100.0)
+    }
+
+    /// Difficulty of [`best_hash`](Self::best_hash), the hardest target it
+    /// would have met
+    pub fn best_difficulty(&self) -> Option<Difficulty> {
+        self.best_hash
+            .map(|hash| Difficulty::from_target(&Target(hash.to_u256())))
+    }
+}
+
+/// Limit on how hard workers run, for background mining
+///
+/// Workers idle between hashes by waiting on their job channel, so new
+/// work is still picked up immediately while throttled.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Throttle {
+    /// Hash continuously
+    #[default]
+    Unlimited,
+    /// Keep each worker busy for roughly this percentage of the time (1-100)
+    CpuPercent(u8),
+    /// Cap the pool's total hashes per second
+    ///
+    /// Like an out-of-range percentage, a zero, negative or NaN rate clamps
+    /// to the strictest limit rather than disabling the throttle; only an
+    /// infinite rate hashes continuously.
+    MaxHashrate(f64),
+}
+
+impl Throttle {
+    /// Idle time after a hash that took `busy`, for one of `threads` workers
+    pub fn pause_after(&self, busy: Duration, threads: usize) -> Duration {
+        match *self {
+            Throttle::Unlimited => Duration::ZERO,
+            Throttle::CpuPercent(percent) => {
+                let percent = u32::from(percent.clamp(1, 100));
+                busy * (100 - percent) / percent
+            }
+            Throttle::MaxHashrate(rate) => {
+                // `max` also replaces NaN. A tiny rate overflows `Duration`;
+                // wait as long as it can express
+                let rate = rate.max(f64::MIN_POSITIVE);
+                let interval = Duration::try_from_secs_f64(threads.max(1) as f64 / rate)
+                    .unwrap_or(Duration::MAX);
+                interval.saturating_sub(busy)
+            }
+        }
+    }
+}
+
+/// Miner configuration
+#[derive(Debug, Clone)]
+pub struct MinerConfig {
+    /// Number of worker threads
+    pub threads: usize,
+    /// CPUs to pin workers to
+    pub affinity: CoreSelection,
+    /// Duty-cycle limit
+    pub throttle: Throttle,
+    /// Check scratchpad integrity and re-hash every candidate solution
+    /// before reporting it, dropping those a memory fault corrupted
+    pub fault_detection: bool,
+    /// Order in which workers try nonces
+    pub nonce_strategy: Arc<dyn NonceStrategy>,
+}
+
+impl Default for MinerConfig {
+    /// One unpinned worker per available CPU
+    fn default() -> Self {
+        Self {
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            affinity: CoreSelection::Unpinned,
+            throttle: Throttle::Unlimited,
+            fault_detection: false,
+            nonce_strategy: Arc::new(Strided),
+        }
+    }
+}
+
+impl MinerConfig {
+    /// One worker pinned to each physical core, skipping SMT siblings
+    pub fn physical_cores() -> Self {
+        let affinity = CoreSelection::PhysicalOnly;
+        Self {
+            threads: affinity.cpus().len().max(1),
+            affinity,
+            throttle: Throttle::Unlimited,
+            fault_detection: false,
+            nonce_strategy: Arc::new(Strided),
+        }
+    }
+}
+
+/// What happens to hashes in flight when work is replaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SwapMode {
+    /// Workers finish the hash they are computing and still report it if
+    /// it solves the old work, then move on
+    #[default]
+    FinishInFlight,
+    /// Solutions to older work are dropped from the moment
+    /// [`MinerPool::replace_work`] returns, e.g. when a new block makes
+    /// the old template worthless
+    DiscardInFlight,
+}
+
+enum Command {
+    /// Work, the generation it was submitted as, and the worker's first step
+    Work(Arc<MiningWork>, u64, u64),
+    Pause,
+}
+
+/// Work generations shared by the pool and its workers
+#[derive(Default)]
+struct Generations {
+    /// Generation of the most recently submitted work
+    current: AtomicU64,
+    /// Solutions to work older than this are dropped; workers hold the
+    /// read lock while checking and delivering, so a swap that takes the
+    /// write lock knows no older solution is delivered after it
+    discard_below: RwLock<u64>,
+    /// Most recently submitted work
+    work: Mutex<Option<CurrentWork>>,
+}
+
+/// Work as last submitted to the workers
+struct CurrentWork {
+    work: Arc<MiningWork>,
+    generation: u64,
+    /// Step each worker was told to start from
+    first_steps: Vec<u64>,
+}
+
+struct Counters {
+    hashes: AtomicU64,
+    solutions: AtomicU64,
+    hardware_errors: AtomicU64,
+    /// Expected solutions as `f64` bits; only the worker writes it
+    expected: AtomicU64,
+    best: Mutex<Option<Hash256>>,
+    /// Pinned CPU, or `NOT_PINNED`
+    cpu: AtomicU64,
+    /// Next step of the worker's nonce sequence, for work of `generation`;
+    /// `step` is stored first, so a reader seeing `generation` sees its step
+    step: AtomicU64,
+    generation: AtomicU64,
+}
+
+const NOT_PINNED: u64 = u64::MAX;
+
+impl Default for Counters {
+    fn default() -> Self {
+        Self {
+            hashes: AtomicU64::new(0),
+            solutions: AtomicU64::new(0),
+            hardware_errors: AtomicU64::new(0),
+            expected: AtomicU64::new(0f64.to_bits()),
+            best: Mutex::new(None),
+            cpu: AtomicU64::new(NOT_PINNED),
+            step: AtomicU64::new(0),
+            generation: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Where workers deliver solutions
+#[derive(Clone)]
+enum SolutionSink {
+    Channel(Sender<Solution>),
+    Callback(Arc<dyn Fn(Solution) + Send + Sync>),
+}
+
+impl SolutionSink {
+    fn deliver(&self, solution: Solution) {
+        match self {
+            // A receiver that hung up just stops hearing about solutions
+            SolutionSink::Channel(sender) => {
+                let _ = sender.send(solution);
+            }
+            SolutionSink::Callback(callback) => callback(solution),
+        }
+    }
+}
+
+struct Worker {
+    commands: Sender<Command>,
+    counters: Arc<Counters>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// Pool of mining threads
+///
+/// Dropping the pool stops and joins all workers.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use uhash_core::{MinerConfig, MinerPool, MiningWork, MiningHeader, Target};
+///
+/// let pool = MinerPool::new(MinerConfig {
+///     threads: 4,
+///     ..Default::default()
+/// });
+/// let header = MiningHeader::default();
+/// pool.submit(MiningWork {
+///     id: 1,
+///     job: header.job(),
+///     target: Target::from_difficulty_bits(16),
+///     start_nonce: 0,
+/// });
+///
+/// let solution = pool.solutions().recv().unwrap();
+/// println!("nonce {} -> {}", solution.nonce, solution.hash);
+/// ```
+pub struct MinerPool {
+    workers: Vec<Worker>,
+    solutions: Receiver<Solution>,
+    meter: Arc<HashrateMeter>,
+    generations: Arc<Generations>,
+    started: Instant,
+}
+
+impl MinerPool {
+    /// Spawn `config.threads` idle workers (at least one)
+    pub fn new(config: MinerConfig) -> Self {
+        let (sender, solutions) = mpsc::channel();
+        Self::spawn(config, SolutionSink::Channel(sender), solutions)
+    }
+
+    /// Spawn workers that send solutions to `sender`
+    ///
+    /// Lets node software select over solutions alongside its other
+    /// channels; [`solutions`](Self::solutions) is then disconnected.
+    pub fn with_sender(config: MinerConfig, sender: Sender<Solution>) -> Self {
+        let (_, solutions) = mpsc::channel();
+        Self::spawn(config, SolutionSink::Channel(sender), solutions)
+    }
+
+    /// Spawn workers that call `callback` with each solution
+    ///
+    /// The callback runs on the worker that found the solution, as soon as
+    /// it is found, and that worker resumes hashing when it returns; keep
+    /// it short (submit the block, forward to another channel) so the
+    /// worker isn't held up. Adapt other channel types this way, e.g.
+    /// `move |solution| { let _ = crossbeam_sender.send(solution); }`.
+    /// [`solutions`](Self::solutions) is disconnected.
+    pub fn with_callback<F>(config: MinerConfig, callback: F) -> Self
+    where
+        F: Fn(Solution) + Send + Sync + 'static,
+    {
+        let (_, solutions) = mpsc::channel();
+        Self::spawn(
+            config,
+            SolutionSink::Callback(Arc::new(callback)),
+            solutions,
+        )
+    }
+
+    fn spawn(config: MinerConfig, sink: SolutionSink, solutions: Receiver<Solution>) -> Self {
+        let threads = config.threads.max(1);
+        let cpus = config.affinity.cpus();
+        let meter = Arc::new(HashrateMeter::default());
+        let generations = Arc::new(Generations::default());
+
+        let workers = (0..threads)
+            .map(|index| {
+                let (commands, command_rx) = mpsc::channel();
+                let counters = Arc::new(Counters::default());
+                let ctx = WorkerContext {
+                    index,
+                    stride: threads as u64,
+                    nonce_strategy: config.nonce_strategy.clone(),
+                    cpu: (!cpus.is_empty()).then(|| cpus[index % cpus.len()]),
+                    throttle: config.throttle,
+                    fault_detection: config.fault_detection,
+                    commands: command_rx,
+                    solutions: sink.clone(),
+                    counters: counters.clone(),
+                    meter: meter.clone(),
+                    generations: generations.clone(),
+                };
+                let handle = thread::Builder::new()
+                    .name(format!("uhash-miner-{}", index))
+                    .spawn(move || ctx.run())
+                    .expect("failed to spawn miner thread");
+                Worker {
+                    commands,
+                    counters,
+                    handle: Some(handle),
+                }
+            })
+            .collect();
+
+        Self {
+            workers,
+            solutions,
+            meter,
+            generations,
+            started: Instant::now(),
+        }
+    }
+
+    /// Number of worker threads
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Replace the current work on every worker
+    ///
+    /// Same as [`replace_work`](Self::replace_work) with
+    /// [`SwapMode::FinishInFlight`].
+    pub fn submit(&self, work: MiningWork) -> u64 {
+        self.replace_work(work, SwapMode::FinishInFlight)
+    }
+
+    /// Switch every worker to `work` without restarting any thread
+    ///
+    /// Each worker picks the new work up after the hash it is computing
+    /// (a throttled worker immediately), so a new block template is mined
+    /// within one hash time. `mode` decides whether those in-flight hashes
+    /// may still report solutions to the old work. Returns the new work's
+    /// generation, which its [`Solution`]s carry.
+    pub fn replace_work(&self, work: MiningWork, mode: SwapMode) -> u64 {
+        self.start_work(work, mode, vec![0; self.workers.len()])
+    }
+
+    /// Continue `work` where `cursor` left it
+    ///
+    /// `cursor` must come from [`cursor`](Self::cursor) on a pool with the
+    /// same number of workers and nonce strategy. Returns the work's
+    /// generation, as [`replace_work`](Self::replace_work) does.
+    pub fn resume(&self, work: MiningWork, cursor: &MiningCursor) -> Result<u64, CursorError> {
+        if cursor.steps.len() != self.workers.len() {
+            return Err(CursorError::WorkerCount {
+                cursor: cursor.steps.len(),
+                pool: self.workers.len(),
+            });
+        }
+        if (cursor.work_id, cursor.start_nonce) != (work.id, work.start_nonce) {
+            return Err(CursorError::WorkMismatch);
+        }
+        Ok(self.start_work(work, SwapMode::FinishInFlight, cursor.steps.clone()))
+    }
+
+    /// How far each worker has got on the most recent work, `None` before
+    /// any work was submitted
+    ///
+    /// Workers that haven't picked the work up yet are at their first step.
+    pub fn cursor(&self) -> Option<MiningCursor> {
+        let current = self.generations.work.lock().unwrap();
+        let current = current.as_ref()?;
+        let steps = self
+            .workers
+            .iter()
+            .zip(&current.first_steps)
+            .map(|(worker, &first)| {
+                if worker.counters.generation.load(Ordering::Acquire) == current.generation {
+                    worker.counters.step.load(Ordering::Relaxed)
+                } else {
+                    first
+                }
+            })
+            .collect();
+        Some(MiningCursor {
+            work_id: current.work.id,
+            start_nonce: current.work.start_nonce,
+            steps,
+        })
+    }
+
+    fn start_work(&self, work: MiningWork, mode: SwapMode, first_steps: Vec<u64>) -> u64 {
+        let mut current = self.generations.work.lock().unwrap();
+        let generation = self.generations.current.fetch_add(1, Ordering::Relaxed) + 1;
+        if mode == SwapMode::DiscardInFlight {
+            let mut discard_below = self.generations.discard_below.write().unwrap();
+            *discard_below = (*discard_below).max(generation);
+        }
+        let work = Arc::new(work);
+        for (worker, &step) in self.workers.iter().zip(&first_steps) {
+            let _ = worker
+                .commands
+                .send(Command::Work(work.clone(), generation, step));
+        }
+        *current = Some(CurrentWork {
+            work,
+            generation,
+            first_steps,
+        });
+        generation
+    }
+
+    /// Generation of the most recently submitted work, 0 before any
+    pub fn generation(&self) -> u64 {
+        self.generations.current.load(Ordering::Relaxed)
+    }
+
+    /// Whether `solution` is for work this pool has since replaced
+    pub fn is_stale(&self, solution: &Solution) -> bool {
+        solution.is_stale(self.generation())
+    }
+
+    /// Stop hashing until new work is submitted
+    pub fn pause(&self) {
+        for worker in &self.workers {
+            let _ = worker.commands.send(Command::Pause);
+        }
+    }
+
+    /// Channel receiving solutions from all workers
+    ///
+    /// Disconnected for pools built with [`with_sender`](Self::with_sender)
+    /// or [`with_callback`](Self::with_callback).
+    pub fn solutions(&self) -> &Receiver<Solution> {
+        &self.solutions
+    }
+
+    /// Per-worker counters
+    pub fn stats(&self) -> Vec<WorkerStats> {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        self.workers
+            .iter()
+            .enumerate()
+            .map(|(worker, w)| {
+                let hashes = w.counters.hashes.load(Ordering::Relaxed);
+                let cpu = w.counters.cpu.load(Ordering::Relaxed);
+                WorkerStats {
+                    worker,
+                    cpu: (cpu != NOT_PINNED).then_some(cpu as usize),
+                    hashes,
+                    solutions: w.counters.solutions.load(Ordering::Relaxed),
+                    hashrate: if elapsed > 0.0 {
+                        hashes as f64 / elapsed
+                    } else {
+                        0.0
+                    },
+                    hardware_errors: w.counters.hardware_errors.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+
+    /// Pool-wide hashes, solutions, luck and best hash
+    pub fn summary(&self) -> MinerStats {
+        let mut stats = MinerStats {
+            hashes: 0,
+            solutions: 0,
+            expected_solutions: 0.0,
+            best_hash: None,
+        };
+        for worker in &self.workers {
+            let counters = &worker.counters;
+            stats.hashes += counters.hashes.load(Ordering::Relaxed);
+            stats.solutions += counters.solutions.load(Ordering::Relaxed);
+            stats.expected_solutions += f64::from_bits(counters.expected.load(Ordering::Relaxed));
+            if let Some(best) = *counters.best.lock().unwrap() {
+                stats.best_hash = Some(stats.best_hash.map_or(best, |b| b.min(best)));
+            }
+        }
+        stats
+    }
+
+    /// Pool-wide hashrate meter with rolling averages
+    pub fn meter(&self) -> &HashrateMeter {
+        &self.meter
+    }
+
+    /// Total hashes per second across all workers
+    pub fn hashrate(&self) -> f64 {
+        self.stats().iter().map(|s| s.hashrate).sum()
+    }
+
+    /// Time since the pool was started
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+impl Drop for MinerPool {
+    fn drop(&mut self) {
+        for worker in &mut self.workers {
+            // Closing the command channel tells the worker to exit
+            let (closed, _) = mpsc::channel();
+            worker.commands = closed;
+            if let Some(handle) = worker.handle.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+struct WorkerContext {
+    index: usize,
+    stride: u64,
+    nonce_strategy: Arc<dyn NonceStrategy>,
+    cpu: Option<usize>,
+    throttle: Throttle,
+    fault_detection: bool,
+    commands: Receiver<Command>,
+    solutions: SolutionSink,
+    counters: Arc<Counters>,
+    meter: Arc<HashrateMeter>,
+    generations: Arc<Generations>,
+}
+
+impl WorkerContext {
+    fn run(self) {
+        if let Some(cpu) = self.cpu
+            && pin_current_thread(cpu)
+        {
+            self.counters.cpu.store(cpu as u64, Ordering::Relaxed);
+        }
+        // Allocate after pinning so the scratchpad lands on this CPU's node.
+        // Chains run on this thread: handing them to rayon would put the
+        // actual hashing on unpinned pool threads and oversubscribe the CPUs
+        #[cfg(feature = "numa")]
+        let hasher = UniversalHash::new_numa_local();
+        #[cfg(not(feature = "numa"))]
+        let hasher = UniversalHash::new_sequential();
+        let mut hasher = hasher.with_integrity_check(self.fault_detection);
+        // Fault in the scratchpads now so the first job's hashrate is not skewed
+        hasher.warm_up();
+        // Work, its generation, step in the nonce sequence, and the chance
+        // of one hash meeting its target
+        let mut current: Option<(Arc<MiningWork>, u64, u64, f64)> = None;
+        let mut best: Option<Hash256> = None;
+        let mut pause = Duration::ZERO;
+
+        loop {
+            // Block while idle, wait out the throttle pause, otherwise just poll
+            let command = match current {
+                None => match self.commands.recv() {
+                    Ok(command) => Some(command),
+                    Err(_) => return,
+                },
+                Some(_) if !pause.is_zero() => match self.commands.recv_timeout(pause) {
+                    Ok(command) => Some(command),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                },
+                Some(_) => match self.commands.try_recv() {
+                    Ok(command) => Some(command),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => return,
+                },
+            };
+            match command {
+                Some(Command::Work(work, generation, step)) => {
+                    let chance = Difficulty::from_target(&work.target).value().recip();
+                    current = Some((work, generation, step, chance));
+                }
+                Some(Command::Pause) => current = None,
+                None => {}
+            }
+
+            let Some((work, generation, step, chance)) = &mut current else {
+                continue;
+            };
+            let nonce = self.nonce_strategy.nonce(
+                work.start_nonce,
+                self.index,
+                self.stride as usize,
+                *step,
+            );
+            let started = Instant::now();
+            let hash = Hash256(hasher.hash_nonce(&work.job, nonce));
+            pause = self
+                .throttle
+                .pause_after(started.elapsed(), self.stride as usize);
+            self.counters.hashes.fetch_add(1, Ordering::Relaxed);
+            let expected = f64::from_bits(self.counters.expected.load(Ordering::Relaxed));
+            self.counters
+                .expected
+                .store((expected + *chance).to_bits(), Ordering::Relaxed);
+            if best.is_none_or(|best| hash < best) {
+                best = Some(hash);
+                *self.counters.best.lock().unwrap() = best;
+            }
+            self.meter.record(1);
+            if work.target.is_met_by(&hash) {
+                if self.fault_detection && !confirm_solution(&mut hasher, &work.job, nonce, &hash) {
+                    self.counters
+                        .hardware_errors
+                        .fetch_add(1, Ordering::Relaxed);
+                } else {
+                    let discard_below = self.generations.discard_below.read().unwrap();
+                    if *generation >= *discard_below {
+                        self.counters.solutions.fetch_add(1, Ordering::Relaxed);
+                        #[cfg(feature = "metrics")]
+                        crate::metrics::record_share();
+                        self.solutions.deliver(Solution {
+                            work_id: work.id,
+                            nonce,
+                            hash,
+                            worker: self.index,
+                            generation: *generation,
+                        });
+                    }
+                }
+            }
+            *step = step.wrapping_add(1);
+            self.counters.step.store(*step, Ordering::Relaxed);
+            self.counters
+                .generation
+                .store(*generation, Ordering::Release);
+        }
+    }
+}
+
+/// Whether a candidate survives fault detection: the scratchpads still
+/// match their checksums and hashing the nonce again gives the same result
+fn confirm_solution(hasher: &mut UniversalHash, job: &Job, nonce: u64, hash: &Hash256) -> bool {
+    hasher.scratchpads_intact()
+        && hasher.hash_nonce(job, nonce) == hash.0
+        && hasher.scratchpads_intact()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::MiningHeader;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_miner_pool_finds_solutions() {
+        let pool = MinerPool::new(MinerConfig {
+            threads: 2,
+            ..Default::default()
+        });
+        let header = MiningHeader::default();
+        pool.submit(MiningWork {
+            id: 7,
+            job: header.job(),
+            target: Target::MAX,
+            start_nonce: 100,
+        });
+
+        let mut hasher = UniversalHash::new();
+        for _ in 0..4 {
+            let solution = pool
+                .solutions()
+                .recv_timeout(Duration::from_secs(60))
+                .unwrap();
+            assert_eq!(solution.work_id, 7);
+            assert!(solution.nonce >= 100);
+            assert_eq!(solution.nonce % 2, (100 + solution.worker as u64) % 2);
+            assert_eq!(
+                solution.hash.0,
+                hasher.hash_nonce(&header.job(), solution.nonce)
+            );
+        }
+        pool.pause();
+
+        let stats = pool.stats();
+        assert_eq!(stats.len(), 2);
+        assert!(stats.iter().map(|s| s.hashes).sum::<u64>() >= 4);
+        assert!(pool.hashrate() > 0.0);
+        assert!(pool.meter().snapshot().total_hashes >= 4);
+        assert!(stats.iter().all(|s| s.cpu.is_none()));
+    }
+
+    #[test]
+    fn test_miner_pool_external_sinks() {
+        let work = MiningWork {
+            id: 5,
+            job: MiningHeader::default().job(),
+            target: Target::MAX,
+            start_nonce: 0,
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let pool = MinerPool::with_sender(
+            MinerConfig {
+                threads: 1,
+                ..Default::default()
+            },
+            sender,
+        );
+        pool.submit(work.clone());
+        let solution = receiver.recv_timeout(Duration::from_secs(60)).unwrap();
+        assert_eq!(solution.work_id, 5);
+        assert!(pool.solutions().try_recv().is_err());
+        drop(pool);
+
+        // The callback sees each solution on the worker that found it
+        let (sender, receiver) = mpsc::channel();
+        let pool = MinerPool::with_callback(
+            MinerConfig {
+                threads: 2,
+                ..Default::default()
+            },
+            move |solution: Solution| {
+                let on_worker = thread::current()
+                    .name()
+                    .is_some_and(|name| name == format!("uhash-miner-{}", solution.worker));
+                let _ = sender.send((solution, on_worker));
+            },
+        );
+        pool.submit(work);
+        let (solution, on_worker) = receiver.recv_timeout(Duration::from_secs(60)).unwrap();
+        assert_eq!(solution.work_id, 5);
+        assert!(on_worker);
+        assert_eq!(
+            pool.solutions().recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn test_replace_work_discards_in_flight() {
+        let pool = MinerPool::new(MinerConfig {
+            threads: 2,
+            ..Default::default()
+        });
+        let work = |id| MiningWork {
+            id,
+            job: MiningHeader::default().job(),
+            target: Target::MAX,
+            start_nonce: id << 32,
+        };
+        pool.submit(work(1));
+        let solution = pool
+            .solutions()
+            .recv_timeout(Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(solution.work_id, 1);
+
+        // Anything for work 1 was sent before the swap returned
+        pool.replace_work(work(2), SwapMode::DiscardInFlight);
+        while pool.solutions().try_recv().is_ok() {}
+        for _ in 0..4 {
+            let solution = pool
+                .solutions()
+                .recv_timeout(Duration::from_secs(60))
+                .unwrap();
+            assert_eq!(solution.work_id, 2);
+            assert_eq!(solution.nonce >> 32, 2);
+        }
+    }
+
+    #[test]
+    fn test_solutions_carry_generation() {
+        let pool = MinerPool::new(MinerConfig {
+            threads: 1,
+            ..Default::default()
+        });
+        assert_eq!(pool.generation(), 0);
+        let work = MiningWork {
+            id: 1,
+            job: MiningHeader::default().job(),
+            target: Target::MAX,
+            start_nonce: 0,
+        };
+        let first = pool.submit(work.clone());
+        let solution = pool
+            .solutions()
+            .recv_timeout(Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(solution.generation, first);
+        assert!(!pool.is_stale(&solution));
+
+        // Same work id resubmitted is still a new generation
+        let second = pool.submit(work);
+        assert_eq!(second, first + 1);
+        assert!(pool.is_stale(&solution));
+        assert!(solution.is_stale(second));
+        assert!(!solution.is_stale(first));
+    }
+
+    #[test]
+    fn test_miner_pool_nonce_strategy() {
+        let strategy = crate::nonce::RandomOrder::with_key(42);
+        let pool = MinerPool::new(MinerConfig {
+            threads: 2,
+            nonce_strategy: Arc::new(strategy),
+            ..Default::default()
+        });
+        pool.submit(MiningWork {
+            id: 1,
+            job: MiningHeader::default().job(),
+            target: Target::MAX,
+            start_nonce: 0,
+        });
+        let solution = pool
+            .solutions()
+            .recv_timeout(Duration::from_secs(60))
+            .unwrap();
+        let first_steps: Vec<u64> = (0..1000)
+            .map(|step| strategy.nonce(0, solution.worker, 2, step))
+            .collect();
+        assert!(first_steps.contains(&solution.nonce));
+    }
+
+    #[test]
+    fn test_miner_pool_resumes_from_cursor() {
+        let config = || MinerConfig {
+            threads: 2,
+            ..Default::default()
+        };
+        let work = MiningWork {
+            id: 4,
+            job: MiningHeader::default().job(),
+            target: Target::MAX,
+            start_nonce: 1000,
+        };
+        let pool = MinerPool::new(config());
+        assert_eq!(pool.cursor(), None);
+        pool.submit(work.clone());
+        let mut tried = HashSet::new();
+        for _ in 0..4 {
+            let solution = pool
+                .solutions()
+                .recv_timeout(Duration::from_secs(60))
+                .unwrap();
+            tried.insert(solution.nonce);
+        }
+        pool.pause();
+        // Wait for the in-flight hashes to land and be counted
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let cursor = loop {
+            while let Ok(solution) = pool.solutions().try_recv() {
+                tried.insert(solution.nonce);
+            }
+            let cursor = pool.cursor().unwrap();
+            if cursor.hashes() == tried.len() as u64 || Instant::now() > deadline {
+                break cursor;
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(cursor.hashes(), tried.len() as u64);
+        let cursor = MiningCursor::from_bytes(&cursor.to_bytes()).unwrap();
+        drop(pool);
+
+        let pool = MinerPool::new(config());
+        let other = MiningWork {
+            id: 5,
+            ..work.clone()
+        };
+        assert_eq!(pool.resume(other, &cursor), Err(CursorError::WorkMismatch));
+        pool.resume(work, &cursor).unwrap();
+        for _ in 0..4 {
+            let solution = pool
+                .solutions()
+                .recv_timeout(Duration::from_secs(60))
+                .unwrap();
+            assert!(
+                !tried.contains(&solution.nonce),
+                "rescanned {}",
+                solution.nonce
+            );
+        }
+
+        let single = MinerPool::new(MinerConfig {
+            threads: 1,
+            ..Default::default()
+        });
+        assert_eq!(
+            single.resume(
+                MiningWork {
+                    id: 4,
+                    job: MiningHeader::default().job(),
+                    target: Target::MAX,
+                    start_nonce: 1000,
+                },
+                &cursor
+            ),
+            Err(CursorError::WorkerCount { cursor: 2, pool: 1 })
+        );
+    }
+
+    #[test]
+    fn test_miner_pool_fault_detection() {
+        let pool = MinerPool::new(MinerConfig {
+            threads: 1,
+            fault_detection: true,
+            ..Default::default()
+        });
+        let header = MiningHeader::default();
+        pool.submit(MiningWork {
+            id: 3,
+            job: header.job(),
+            target: Target::MAX,
+            start_nonce: 0,
+        });
+
+        let solution = pool
+            .solutions()
+            .recv_timeout(Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(
+            solution.hash.0,
+            UniversalHash::new().hash_nonce(&header.job(), solution.nonce)
+        );
+        pool.pause();
+        assert_eq!(pool.stats()[0].hardware_errors, 0);
+    }
+
+    #[test]
+    fn test_miner_pool_pinned_workers() {
+        let cpu = crate::affinity::logical_cpus()[0];
+        let pool = MinerPool::new(MinerConfig {
+            threads: 2,
+            affinity: CoreSelection::Cpus(vec![cpu]),
+            ..Default::default()
+        });
+        pool.submit(MiningWork {
+            id: 1,
+            job: MiningHeader::default().job(),
+            target: Target::MAX,
+            start_nonce: 0,
+        });
+        pool.solutions()
+            .recv_timeout(Duration::from_secs(60))
+            .unwrap();
+
+        #[cfg(target_os = "linux")]
+        assert!(pool.stats().iter().any(|s| s.cpu == Some(cpu)));
+    }
+
+    #[test]
+    fn test_miner_pool_summary() {
+        let pool = MinerPool::new(MinerConfig {
+            threads: 2,
+            ..Default::default()
+        });
+        pool.submit(MiningWork {
+            id: 1,
+            job: MiningHeader::default().job(),
+            target: Target::from_difficulty_bits(1),
+            start_nonce: 0,
+        });
+        let solution = pool
+            .solutions()
+            .recv_timeout(Duration::from_secs(60))
+            .unwrap();
+        pool.pause();
+
+        let summary = pool.summary();
+        assert!(summary.hashes >= 1 && summary.solutions >= 1);
+        // Each hash has a 1 in 2 chance
+        assert!(summary.expected_solutions >= 0.5);
+        assert!(summary.expected_solutions <= summary.hashes as f64 / 2.0 + 1.0);
+        assert!(summary.luck().unwrap() > 0.0);
+        let best = summary.best_hash.unwrap();
+        assert!(best <= solution.hash);
+        assert!(summary.best_difficulty().unwrap().value() >= 2.0);
+    }
+
+    #[test]
+    fn test_miner_stats_luck() {
+        let mut stats = MinerStats {
+            hashes: 0,
+            solutions: 0,
+            expected_solutions: 0.0,
+            best_hash: None,
+        };
+        assert_eq!(stats.luck(), None);
+        assert_eq!(stats.best_difficulty(), None);
+
+        stats.hashes = 3000;
+        stats.solutions = 3;
+        stats.expected_solutions = 1.5;
+        assert_eq!(stats.luck(), Some(200.0));
+
+        let mut hash = Hash256([0xFF; 32]);
+        hash.0[0] = 0x0F; // just under 2^252
+        stats.best_hash = Some(hash);
+        assert_eq!(stats.best_difficulty().unwrap().value(), 16.0);
+    }
+
+    #[test]
+    fn test_throttle_pause() {
+        let busy = Duration::from_millis(10);
+        assert_eq!(Throttle::Unlimited.pause_after(busy, 4), Duration::ZERO);
+        assert_eq!(
+            Throttle::CpuPercent(100).pause_after(busy, 4),
+            Duration::ZERO
+        );
+        assert_eq!(
+            Throttle::CpuPercent(25).pause_after(busy, 4),
+            Duration::from_millis(30)
+        );
+        // 4 workers sharing 100 H/s: one hash per 40ms each
+        assert_eq!(
+            Throttle::MaxHashrate(100.0).pause_after(busy, 4),
+            Duration::from_millis(30)
+        );
+        assert_eq!(
+            Throttle::MaxHashrate(1e9).pause_after(busy, 4),
+            Duration::ZERO
+        );
+        assert_eq!(
+            Throttle::MaxHashrate(f64::INFINITY).pause_after(busy, 4),
+            Duration::ZERO
+        );
+        // Too slow for `Duration` to express: wait as long as possible
+        assert_eq!(
+            Throttle::MaxHashrate(1e-20).pause_after(busy, 4),
+            Duration::MAX - busy
+        );
+        // Invalid rates throttle hardest instead of switching the limit off
+        for rate in [0.0, -0.0, -100.0, f64::NEG_INFINITY, f64::NAN] {
+            assert_eq!(
+                Throttle::MaxHashrate(rate).pause_after(busy, 4),
+                Duration::MAX - busy,
+                "{}",
+                rate
+            );
+        }
+    }
+
+    #[test]
+    fn test_throttled_pool_still_takes_new_work() {
+        let pool = MinerPool::new(MinerConfig {
+            threads: 1,
+            throttle: Throttle::MaxHashrate(0.01),
+            ..Default::default()
+        });
+        let job = MiningHeader::default().job();
+        pool.submit(MiningWork {
+            id: 1,
+            job: job.clone(),
+            target: Target::MAX,
+            start_nonce: 0,
+        });
+        assert_eq!(
+            pool.solutions()
+                .recv_timeout(Duration::from_secs(60))
+                .unwrap()
+                .work_id,
+            1
+        );
+
+        // Throttled to one hash per 100s, yet the new work is hashed right away
+        pool.submit(MiningWork {
+            id: 2,
+            job,
+            target: Target::MAX,
+            start_nonce: 0,
+        });
+        assert_eq!(
+            pool.solutions()
+                .recv_timeout(Duration::from_secs(60))
+                .unwrap()
+                .work_id,
+            2
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Nonce iteration strategies for miner workers
+//!
+//! A [`NonceStrategy`] maps a worker and a step count to the nonce that
+//! worker tries next, so the pool only keeps a step counter per worker.
+//! Every strategy gives each worker of a pool a disjoint sequence for its
+//! first `u64::MAX / n` steps; a worker that gets that far ahead of its
+//! peers may retry their nonces.
+//!
+//! - [`Strided`] (the default): worker `i` of `n` tries `start + i`,
+//!   `start + i + n`, ...
+//! - [`Sequential`]: the nonce space is split into `n` contiguous ranges,
+//!   each walked in order
+//! - [`RandomOrder`]: the strided sequence passed through a keyed
+//!   permutation of the 64-bit space. Independent miners left at default
+//!   settings (same header template, `start_nonce` 0) would otherwise all
+//!   try the same nonces first; with a random key they scan in unrelated
+//!   orders.
+
+use core::fmt;
+use std::hash::{BuildHasher, RandomState};
+
+/// Order in which pool workers walk the nonce space
+pub trait NonceStrategy: fmt::Debug + Send + Sync {
+    /// The nonce worker `worker` of `workers` tries at `step`, for work
+    /// starting at `start_nonce`
+    ///
+    /// Must be a pure function of its arguments: distinct `(worker, step)`
+    /// pairs with `step < u64::MAX / workers` give distinct nonces. Past
+    /// that a worker may run into another's nonces: [`Sequential`] reaches
+    /// the start of the next worker's range exactly there.
+    fn nonce(&self, start_nonce: u64, worker: usize, workers: usize, step: u64) -> u64;
+}
+
+/// Interleaved nonces: worker `i` of `n` takes every `n`th nonce from
+/// `start + i`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Strided;
+
+impl NonceStrategy for Strided {
+    fn nonce(&self, start_nonce: u64, worker: usize, workers: usize, step: u64) -> u64 {
+        start_nonce.wrapping_add(counter(worker, workers, step))
+    }
+}
+
+/// Contiguous ranges: worker `i` of `n` walks `start + i * 2^64 / n` upwards
+///
+/// Each range holds `u64::MAX / n` nonces. Nothing stops a worker at the
+/// end of its range: at step `u64::MAX / n` it continues into worker
+/// `i + 1`'s range, and the two then hash the same nonces.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sequential;
+
+impl NonceStrategy for Sequential {
+    fn nonce(&self, start_nonce: u64, worker: usize, workers: usize, step: u64) -> u64 {
+        let span = u64::MAX / workers.max(1) as u64;
+        start_nonce
+            .wrapping_add(span.wrapping_mul(worker as u64))
+            .wrapping_add(step)
+    }
+}
+
+/// Strided counters scattered over the nonce space by a keyed permutation
+///
+/// The permutation is a bijection, so workers still never repeat each
+/// other's nonces. Use [`with_key`](Self::with_key) when a run must be
+/// reproducible, e.g. to resume it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RandomOrder {
+    key: u64,
+}
+
+impl RandomOrder {
+    /// Permutation under a key drawn from the process's hash seed
+    pub fn new() -> Self {
+        Self::with_key(RandomState::new().hash_one(0u64))
+    }
+
+    /// Permutation under a fixed `key`
+    pub fn with_key(key: u64) -> Self {
+        RandomOrder { key }
+    }
+
+    /// Key of the permutation
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+}
+
+impl Default for RandomOrder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NonceStrategy for RandomOrder {
+    fn nonce(&self, start_nonce: u64, worker: usize, workers: usize, step: u64) -> u64 {
+        start_nonce.wrapping_add(permute(counter(worker, workers, step) ^ self.key))
+    }
+}
+
+/// Position of `(worker, step)` in the interleaved sequence
+fn counter(worker: usize, workers: usize, step: u64) -> u64 {
+    (worker as u64).wrapping_add(step.wrapping_mul(workers.max(1) as u64))
+}
+
+/// SplitMix64's finalizer: xor-shifts and odd multipliers, each invertible
+fn permute(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_strategies_give_workers_disjoint_nonces() {
+        let strategies: [&dyn NonceStrategy; 3] =
+            [&Strided, &Sequential, &RandomOrder::with_key(0x5EED)];
+        for strategy in strategies {
+            let mut seen = HashSet::new();
+            for worker in 0..3 {
+                for step in 0..100 {
+                    let nonce = strategy.nonce(u64::MAX - 50, worker, 3, step);
+                    assert!(seen.insert(nonce), "{strategy:?} repeated {nonce}");
+                }
+            }
+        }
+
+        assert_eq!(Strided.nonce(10, 1, 4, 2), 19);
+        assert_eq!(Sequential.nonce(0, 1, 2, 5), u64::MAX / 2 + 5);
+        assert_eq!(Sequential.nonce(7, 0, 1, 3), 10);
+
+        // The per-worker limit: one step past its range, a sequential
+        // worker lands on the next worker's first nonce
+        let limit = u64::MAX / 3;
+        assert_ne!(
+            Sequential.nonce(0, 0, 3, limit - 1),
+            Sequential.nonce(0, 1, 3, 0)
+        );
+        assert_eq!(
+            Sequential.nonce(0, 0, 3, limit),
+            Sequential.nonce(0, 1, 3, 0)
+        );
+    }
+
+    #[test]
+    fn test_random_order_depends_on_key() {
+        let (a, b) = (RandomOrder::with_key(1), RandomOrder::with_key(2));
+        let first = |order: RandomOrder| {
+            (0..8)
+                .map(|step| order.nonce(0, 0, 1, step))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(first(a), first(RandomOrder::with_key(1)));
+        assert_ne!(first(a), first(b));
+        assert_ne!(first(a), (0..8).collect::<Vec<_>>());
+    }
+}
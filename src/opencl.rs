@@ -0,0 +1,189 @@
+//! OpenCL C and CUDA kernel sources for external GPU miners
+//!
+//! The kernel body in `shaders/uhash.cl` never hardcodes algorithm
+//! parameters: [`opencl_kernel_source`] prepends them as `UHASH_*` macros
+//! generated from [`crate::params`], so a miner that builds its program
+//! from this function follows any change to the constants.
+//! [`cuda_kernel_source`] is the same kernel behind a prelude mapping the
+//! OpenCL qualifiers onto CUDA C.
+//!
+//! Host side, work item `n * CHAINS + c` computes chain `c` of `nonces[n]`:
+//!
+//! ```text
+//! __kernel void uhash_chains(
+//!     __global const uint *seeds,   // chain seeds, 8 little-endian words per lane
+//!     __global const ulong *nonces, // one per CHAINS lanes
+//!     __global uint *scratch,       // SCRATCHPAD_SIZE bytes per lane
+//!     __global uint *states,        // output chain states, 8 words per lane
+//!     uint lanes)
+//! ```
+//!
+//! Each nonce's final hash is BLAKE3(SHA-256(XOR of its `CHAINS` states)).
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String};
+
+use crate::params::{BLOCK_SIZE, BLOCKS_PER_SCRATCHPAD, CHAINS, ROUNDS, SCRATCHPAD_SIZE};
+
+/// Name of the kernel function in [`opencl_kernel_source`]
+pub const OPENCL_KERNEL_NAME: &str = "uhash_chains";
+
+const KERNEL_BODY: &str = include_str!("shaders/uhash.cl");
+
+const CUDA_PRELUDE: &str = "\
+#define uint unsigned int
+#define ulong unsigned long long
+#define uchar unsigned char
+#define __kernel extern \"C\" __global__
+#define __global
+#define __constant __constant__
+#define UHASH_FN __device__
+#define get_global_id(dim) ((size_t)blockIdx.x * blockDim.x + threadIdx.x)
+";
+
+// Addresses are masked rather than reduced modulo the block count
+const _: () = assert!(BLOCKS_PER_SCRATCHPAD.is_power_of_two());
+
+/// Complete OpenCL C source of the chain kernel
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{OPENCL_KERNEL_NAME, ROUNDS, opencl_kernel_source};
+///
+/// let source = opencl_kernel_source();
+/// assert!(source.contains(&format!("#define UHASH_ROUNDS {}u", ROUNDS)));
+/// assert!(source.contains(OPENCL_KERNEL_NAME));
+/// ```
+pub fn opencl_kernel_source() -> String {
+    kernel_source("")
+}
+
+/// Complete CUDA C source of the chain kernel
+///
+/// The entry point is `extern "C"` and named [`OPENCL_KERNEL_NAME`]; compile
+/// it to PTX or cubin with e.g. `nvcc --ptx`.
+pub fn cuda_kernel_source() -> String {
+    kernel_source(CUDA_PRELUDE)
+}
+
+/// Kernel source with `prelude` ahead of the generated parameters
+fn kernel_source(prelude: &str) -> String {
+    format!(
+        "{}#define UHASH_CHAINS {}u\n\
+         #define UHASH_ROUNDS {}u\n\
+         #define UHASH_BLOCKS {}u\n\
+         #define UHASH_BLOCK_WORDS {}u\n\
+         #define UHASH_SCRATCHPAD_WORDS {}u\n\
+         \n{}",
+        prelude,
+        CHAINS,
+        ROUNDS,
+        BLOCKS_PER_SCRATCHPAD,
+        BLOCK_SIZE / 4,
+        SCRATCHPAD_SIZE / 4,
+        KERNEL_BODY
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_uses_params() {
+        let source = opencl_kernel_source();
+        for (name, value) in [
+            ("UHASH_CHAINS", CHAINS),
+            ("UHASH_ROUNDS", ROUNDS),
+            ("UHASH_BLOCKS", BLOCKS_PER_SCRATCHPAD),
+            ("UHASH_BLOCK_WORDS", BLOCK_SIZE / 4),
+            ("UHASH_SCRATCHPAD_WORDS", SCRATCHPAD_SIZE / 4),
+        ] {
+            assert!(source.contains(&format!("#define {} {}u\n", name, value)));
+            // Every parameter comes from the generated header, never the body
+            assert!(!KERNEL_BODY.contains(&format!("#define {} ", name)));
+        }
+        assert!(source.contains(&format!("__kernel void {}(", OPENCL_KERNEL_NAME)));
+    }
+
+    /// Compile the kernel as C11 behind `shaders/host.c` with the host's C
+    /// compiler (`$CC`, default `cc`) and compare it with the CPU hash
+    #[cfg(all(feature = "std", unix, target_arch = "x86_64"))]
+    #[test]
+    fn test_kernel_matches_cpu() {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        use crate::endian::load_u32_le;
+        use crate::header::MiningHeader;
+        use crate::uhash::{UniversalHash, chain_seed, finalize_states};
+
+        let dir = std::env::temp_dir().join(format!("uhash-opencl-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("kernel.cl"), opencl_kernel_source()).unwrap();
+        std::fs::write(dir.join("host.c"), include_str!("shaders/host.c")).unwrap();
+        let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".into());
+        let status = Command::new(&compiler)
+            .current_dir(&dir)
+            .args(["-std=c11", "-O2", "-o", "host", "host.c"])
+            .status()
+            .unwrap_or_else(|e| panic!("cannot run C compiler `{}`: {}", compiler, e));
+        assert!(status.success(), "kernel failed to compile as C");
+
+        let job = MiningHeader::default().job();
+        let nonces = [0u64, 1, 2, u64::MAX];
+        let lanes = (nonces.len() * CHAINS) as u32;
+        let mut input = lanes.to_ne_bytes().to_vec();
+        for &nonce in &nonces {
+            for chain in 0..CHAINS {
+                let seed = chain_seed(&job.midstate, nonce, chain);
+                for i in 0..8 {
+                    input.extend(load_u32_le(&seed, i * 4).to_ne_bytes());
+                }
+            }
+        }
+        for nonce in nonces {
+            input.extend(nonce.to_ne_bytes());
+        }
+
+        let mut host = Command::new(dir.join("host"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        host.stdin.take().unwrap().write_all(&input).unwrap();
+        let output = host.wait_with_output().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(output.status.success());
+
+        let words: Vec<u32> = output
+            .stdout
+            .chunks_exact(4)
+            .map(|word| u32::from_ne_bytes(word.try_into().unwrap()))
+            .collect();
+        assert_eq!(words.len(), lanes as usize * 8);
+        let mut hasher = UniversalHash::new_sequential();
+        for (lanes, &nonce) in words.chunks_exact(CHAINS * 8).zip(&nonces) {
+            let mut chain_states = [[0u8; 32]; CHAINS];
+            for (state, lane) in chain_states.iter_mut().zip(lanes.chunks_exact(8)) {
+                for (bytes, word) in state.chunks_exact_mut(4).zip(lane) {
+                    bytes.copy_from_slice(&word.to_le_bytes());
+                }
+            }
+            assert_eq!(
+                finalize_states(&chain_states),
+                hasher.hash_nonce(&job, nonce),
+                "nonce {}",
+                nonce
+            );
+        }
+    }
+
+    #[test]
+    fn test_cuda_source_wraps_opencl() {
+        let source = cuda_kernel_source();
+        assert!(source.starts_with(CUDA_PRELUDE));
+        assert!(source.ends_with(&opencl_kernel_source()));
+    }
+}
@@ -2,6 +2,12 @@
 //!
 //! These parameters are tuned for democratic mining where phones
 //! can compete meaningfully with desktops (1:3-5 ratio).
+//!
+//! The values are frozen: const assertions below fail the build if any of
+//! them changes, whatever features or cfgs are set. Changing one is a hard
+//! fork and must come with a new [`VERSION`] and new assertions. Tests and
+//! experiments that need other values construct a custom [`Params`] and
+//! can tell it apart with [`Params::is_mainnet`].
 
 /// Number of parallel computation chains
 pub const CHAINS: usize = 4;
@@ -21,6 +27,9 @@ pub const BLOCK_SIZE: usize = 64;
 /// Number of blocks per scratchpad
 pub const BLOCKS_PER_SCRATCHPAD: usize = SCRATCHPAD_SIZE / BLOCK_SIZE;
 
+/// Nonce size in bytes (last 8 bytes of input, little-endian u64)
+pub const NONCE_SIZE: usize = 8;
+
 /// AES block size
 pub const AES_BLOCK_SIZE: usize = 16;
 
@@ -32,3 +41,178 @@ pub const BLAKE3_SIZE: usize = 32;
 
 /// Algorithm version
 pub const VERSION: u8 = 4;
+
+/// Golden ratio constant for seed generation (Fibonacci hashing constant)
+pub(crate) const GOLDEN_RATIO: u64 = 0x9E3779B97F4A7C15;
+
+/// Compression primitives in rotation order: a chain uses
+/// `PRIMITIVE_ORDER[(nonce + chain + 1 + round) mod 3]` at each round
+pub const PRIMITIVE_ORDER: [&str; 3] = ["AES_Compress", "SHA256_Compress", "BLAKE3_Compress"];
+
+// Consensus freeze
+const _: () = {
+    assert!(VERSION == 4);
+    assert!(CHAINS == 4);
+    assert!(SCRATCHPAD_SIZE == 512 * 1024);
+    assert!(ROUNDS == 12_288);
+    assert!(BLOCK_SIZE == 64);
+    assert!(NONCE_SIZE == 8);
+    assert!(GOLDEN_RATIO == 0x9E3779B97F4A7C15);
+    assert!(str_eq(PRIMITIVE_ORDER[0], "AES_Compress"));
+    assert!(str_eq(PRIMITIVE_ORDER[1], "SHA256_Compress"));
+    assert!(str_eq(PRIMITIVE_ORDER[2], "BLAKE3_Compress"));
+};
+
+/// `a == b`, usable in const assertions
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// BLAKE3 key-derivation context for [`Params::fingerprint`]
+pub const PARAMS_FINGERPRINT_CONTEXT: &str = "UniversalHash v4 2026-02-12 params fingerprint";
+
+/// The consensus-relevant parameters, as one value
+///
+/// Two builds that disagree on any of these compute different hashes for
+/// the same header. Nodes can exchange [`fingerprint`](Self::fingerprint)s
+/// at handshake to detect a peer compiled with a different parameter set
+/// before it silently forks.
+///
+/// Fields are public so tests can build custom parameter sets.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::Params;
+///
+/// let ours = Params::MAINNET.fingerprint();
+/// # let theirs = ours;
+/// // `theirs` as received from a peer
+/// assert_eq!(ours, theirs, "peer runs a different parameter set");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Params {
+    /// Algorithm version
+    pub version: u8,
+    /// Number of parallel computation chains
+    pub chains: usize,
+    /// Scratchpad size per chain in bytes
+    pub scratchpad_size: usize,
+    /// Number of rounds per chain
+    pub rounds: usize,
+    /// Block size in bytes for memory operations
+    pub block_size: usize,
+    /// Nonce size in bytes
+    pub nonce_size: usize,
+    /// Per-chain nonce offset multiplier for seed generation
+    pub chain_seed_offset: u64,
+    /// Compression primitives in rotation order
+    pub primitive_order: [&'static str; 3],
+}
+
+impl Params {
+    /// The parameters this crate implements
+    pub const MAINNET: Params = Params {
+        version: VERSION,
+        chains: CHAINS,
+        scratchpad_size: SCRATCHPAD_SIZE,
+        rounds: ROUNDS,
+        block_size: BLOCK_SIZE,
+        nonce_size: NONCE_SIZE,
+        chain_seed_offset: GOLDEN_RATIO,
+        primitive_order: PRIMITIVE_ORDER,
+    };
+
+    /// 32-byte digest identifying this parameter set
+    ///
+    /// `BLAKE3_derive_key(PARAMS_FINGERPRINT_CONTEXT, encoding)`, where the
+    /// encoding is every field in declaration order as a little-endian u64,
+    /// with each primitive name as its le64 length followed by its bytes.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new_derive_key(PARAMS_FINGERPRINT_CONTEXT);
+        for value in [
+            u64::from(self.version),
+            self.chains as u64,
+            self.scratchpad_size as u64,
+            self.rounds as u64,
+            self.block_size as u64,
+            self.nonce_size as u64,
+            self.chain_seed_offset,
+        ] {
+            hasher.update(&value.to_le_bytes());
+        }
+        for name in self.primitive_order {
+            hasher.update(&(name.len() as u64).to_le_bytes());
+            hasher.update(name.as_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    /// Whether these are the mainnet parameters
+    pub fn is_mainnet(&self) -> bool {
+        *self == Params::MAINNET
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::string::ToString;
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_params_fingerprint() {
+        let fingerprint = Params::MAINNET.fingerprint();
+        // Pinned: a change here is a consensus change
+        assert_eq!(
+            crate::Hash256(fingerprint).to_string(),
+            "d1722eda6b01823e1fa796101ccf7a78b6ae0f7bd6515533f0e7ee81ea546b2c"
+        );
+
+        // Every field feeds the fingerprint
+        let variants = [
+            Params {
+                rounds: ROUNDS - 1,
+                ..Params::MAINNET
+            },
+            Params {
+                chains: 8,
+                ..Params::MAINNET
+            },
+            Params {
+                chain_seed_offset: 1,
+                ..Params::MAINNET
+            },
+            Params {
+                primitive_order: ["SHA256_Compress", "AES_Compress", "BLAKE3_Compress"],
+                ..Params::MAINNET
+            },
+        ];
+        for params in variants {
+            assert_ne!(params.fingerprint(), fingerprint, "{:?}", params);
+        }
+    }
+
+    #[test]
+    fn test_params_is_mainnet() {
+        assert!(Params::MAINNET.is_mainnet());
+        let custom = Params {
+            scratchpad_size: 64 * 1024,
+            ..Params::MAINNET
+        };
+        assert!(!custom.is_mainnet());
+    }
+}
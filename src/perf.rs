@@ -0,0 +1,209 @@
+//! Hardware performance counters around hashing (Linux `perf_event`)
+//!
+//! The round loop is dominated by dependent scratchpad reads, so cache and
+//! TLB misses per hash are the numbers to watch when tuning prefetch
+//! distance or trying huge pages. Counters are opened on the calling
+//! thread only, so measure a
+//! [`new_sequential`](crate::UniversalHash::new_sequential) hasher: a
+//! parallel one runs its chains on the thread pool, out of the counters'
+//! sight. Only user-space events are counted, which works at the default
+//! `perf_event_paranoid` level. Where a counter cannot be opened (no PMU,
+//! a VM without passthrough, non-Linux platforms) its value is `None`.
+
+/// Hardware counter averages per hash
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PerfCounters {
+    /// CPU cycles
+    pub cycles: Option<f64>,
+    /// Last-level cache read misses
+    pub llc_misses: Option<f64>,
+    /// Data TLB read misses
+    pub dtlb_misses: Option<f64>,
+}
+
+impl PerfCounters {
+    /// Count hardware events on the calling thread while `f` runs
+    ///
+    /// `f` returns the number of hashes it computed; the counts are
+    /// divided by it. A return value of zero yields all-`None` counters.
+    /// Work `f` hands to other threads is not counted.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use uhash_core::{PerfCounters, UniversalHash};
+    ///
+    /// let mut hasher = UniversalHash::new_sequential();
+    /// let counters = PerfCounters::measure(|| {
+    ///     for nonce in 0..16u64 {
+    ///         hasher.hash(&nonce.to_le_bytes());
+    ///     }
+    ///     16
+    /// });
+    /// println!("LLC misses/hash: {:?}", counters.llc_misses);
+    /// ```
+    pub fn measure(f: impl FnOnce() -> u64) -> Self {
+        #[cfg(target_os = "linux")]
+        {
+            let counters = [
+                linux::Counter::open(linux::CYCLES),
+                linux::Counter::open(linux::LLC_READ_MISSES),
+                linux::Counter::open(linux::DTLB_READ_MISSES),
+            ];
+            for counter in counters.iter().flatten() {
+                counter.enable();
+            }
+            let hashes = f();
+            let [cycles, llc_misses, dtlb_misses] = counters.map(|counter| {
+                let count = counter?.finish()?;
+                (hashes > 0).then(|| count as f64 / hashes as f64)
+            });
+            Self {
+                cycles,
+                llc_misses,
+                dtlb_misses,
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = f();
+            Self::default()
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    const PERF_TYPE_HARDWARE: u32 = 0;
+    const PERF_TYPE_HW_CACHE: u32 = 3;
+
+    const PERF_COUNT_HW_CPU_CYCLES: u64 = 0;
+    const PERF_COUNT_HW_CACHE_LL: u64 = 2;
+    const PERF_COUNT_HW_CACHE_DTLB: u64 = 3;
+    const PERF_COUNT_HW_CACHE_OP_READ: u64 = 0;
+    const PERF_COUNT_HW_CACHE_RESULT_MISS: u64 = 1;
+
+    const ATTR_DISABLED: u64 = 1 << 0;
+    const ATTR_EXCLUDE_KERNEL: u64 = 1 << 5;
+    const ATTR_EXCLUDE_HV: u64 = 1 << 6;
+
+    const PERF_EVENT_IOC_ENABLE: libc::c_ulong = 0x2400;
+    const PERF_EVENT_IOC_DISABLE: libc::c_ulong = 0x2401;
+
+    pub(super) const CYCLES: (u32, u64) = (PERF_TYPE_HARDWARE, PERF_COUNT_HW_CPU_CYCLES);
+    pub(super) const LLC_READ_MISSES: (u32, u64) = (
+        PERF_TYPE_HW_CACHE,
+        PERF_COUNT_HW_CACHE_LL
+            | (PERF_COUNT_HW_CACHE_OP_READ << 8)
+            | (PERF_COUNT_HW_CACHE_RESULT_MISS << 16),
+    );
+    pub(super) const DTLB_READ_MISSES: (u32, u64) = (
+        PERF_TYPE_HW_CACHE,
+        PERF_COUNT_HW_CACHE_DTLB
+            | (PERF_COUNT_HW_CACHE_OP_READ << 8)
+            | (PERF_COUNT_HW_CACHE_RESULT_MISS << 16),
+    );
+
+    /// `struct perf_event_attr` up to `config2` (`PERF_ATTR_SIZE_VER1`)
+    #[repr(C)]
+    #[derive(Default)]
+    struct PerfEventAttr {
+        kind: u32,
+        size: u32,
+        config: u64,
+        sample_period: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        wakeup_events: u32,
+        bp_type: u32,
+        config1: u64,
+        config2: u64,
+    }
+
+    /// One counter on the calling thread, closed on drop
+    pub(super) struct Counter(libc::c_int);
+
+    impl Counter {
+        pub(super) fn open((kind, config): (u32, u64)) -> Option<Self> {
+            let attr = PerfEventAttr {
+                kind,
+                size: core::mem::size_of::<PerfEventAttr>() as u32,
+                config,
+                flags: ATTR_DISABLED | ATTR_EXCLUDE_KERNEL | ATTR_EXCLUDE_HV,
+                ..Default::default()
+            };
+            // SAFETY: attr is a valid perf_event_attr of the declared size;
+            // pid 0 / cpu -1 counts the calling thread on any CPU
+            let fd = unsafe {
+                libc::syscall(
+                    libc::SYS_perf_event_open,
+                    &attr as *const PerfEventAttr,
+                    0 as libc::pid_t,
+                    -1 as libc::c_int,
+                    -1 as libc::c_int,
+                    0 as libc::c_ulong,
+                )
+            };
+            (fd >= 0).then_some(Counter(fd as libc::c_int))
+        }
+
+        pub(super) fn enable(&self) {
+            // SAFETY: self.0 is an open perf event descriptor
+            unsafe { libc::ioctl(self.0, PERF_EVENT_IOC_ENABLE as _, 0) };
+        }
+
+        /// Stop counting and return the count
+        pub(super) fn finish(self) -> Option<u64> {
+            let mut count = 0u64;
+            // SAFETY: self.0 is an open perf event descriptor; without a
+            // read_format it reads back a single u64
+            let read = unsafe {
+                libc::ioctl(self.0, PERF_EVENT_IOC_DISABLE as _, 0);
+                libc::read(
+                    self.0,
+                    &mut count as *mut u64 as *mut libc::c_void,
+                    core::mem::size_of::<u64>(),
+                )
+            };
+            (read == core::mem::size_of::<u64>() as isize).then_some(count)
+        }
+    }
+
+    impl Drop for Counter {
+        fn drop(&mut self) {
+            // SAFETY: self.0 is owned by this counter and closed once
+            unsafe { libc::close(self.0) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UniversalHash;
+
+    #[test]
+    fn test_perf_counters_per_hash() {
+        // Counters may be unavailable (VMs, containers, perf_event_paranoid);
+        // whatever is reported must be a per-hash average
+        let mut hasher = UniversalHash::new_sequential();
+        let counters = PerfCounters::measure(|| {
+            for nonce in 0..4u64 {
+                hasher.hash(&nonce.to_le_bytes());
+            }
+            4
+        });
+        for count in [counters.cycles, counters.llc_misses, counters.dtlb_misses]
+            .into_iter()
+            .flatten()
+        {
+            assert!(count.is_finite() && count >= 0.0);
+        }
+        if let Some(cycles) = counters.cycles {
+            assert!(cycles > 0.0);
+        }
+
+        assert_eq!(PerfCounters::measure(|| 0), PerfCounters::default());
+    }
+}
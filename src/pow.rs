@@ -0,0 +1,214 @@
+//! One-call proof-of-work verification
+
+use core::fmt;
+
+use crate::error::UhashError;
+use crate::hash256::Hash256;
+use crate::header::MiningHeader;
+use crate::target::Target;
+#[cfg(feature = "alloc")]
+use crate::uhash::UniversalHash;
+use crate::uhash::UniversalHashIn;
+
+/// Reason a proof-of-work check failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowError {
+    /// Encoded header is not `expected` bytes long
+    WrongLength {
+        /// Required length
+        expected: usize,
+        /// Length that was supplied
+        actual: usize,
+    },
+    /// Hash has fewer leading zero bits than required
+    BelowTarget {
+        /// Required leading zero bits
+        difficulty: u32,
+        /// Leading zero bits the hash actually has
+        achieved: u32,
+    },
+    /// Hash is numerically above the required target
+    AboveTarget {
+        /// Target the hash had to meet
+        target: Target,
+    },
+    /// Header's [`prefilter`](crate::prefilter) hash is above the
+    /// prefilter target, so the full hash was not computed
+    PrefilterFailed {
+        /// Prefilter target the header had to meet
+        target: Target,
+    },
+    /// Recomputed hash differs from the one claimed in a proof
+    HashMismatch {
+        /// Hash carried by the proof
+        claimed: Hash256,
+        /// Hash computed from the proof's header
+        computed: Hash256,
+    },
+}
+
+impl fmt::Display for PowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PowError::WrongLength { expected, actual } => {
+                write!(f, "header is {} bytes, expected {}", actual, expected)
+            }
+            PowError::BelowTarget {
+                difficulty,
+                achieved,
+            } => write!(
+                f,
+                "hash has {} leading zero bits, difficulty requires {}",
+                achieved, difficulty
+            ),
+            PowError::AboveTarget { target } => write!(f, "hash exceeds target {}", target),
+            PowError::PrefilterFailed { target } => {
+                write!(f, "prefilter hash exceeds prefilter target {}", target)
+            }
+            PowError::HashMismatch { claimed, computed } => {
+                write!(
+                    f,
+                    "claimed hash {} but header hashes to {}",
+                    claimed, computed
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for PowError {}
+
+impl From<UhashError> for PowError {
+    fn from(err: UhashError) -> Self {
+        match err {
+            UhashError::InputTooShort { len, min } => PowError::WrongLength {
+                expected: min,
+                actual: len,
+            },
+            UhashError::InvalidLength { expected, actual } => {
+                PowError::WrongLength { expected, actual }
+            }
+            UhashError::InputTooLong { len, max } => PowError::WrongLength {
+                expected: max,
+                actual: len,
+            },
+            UhashError::BufferTooSmall { len, min } => PowError::WrongLength {
+                expected: min,
+                actual: len,
+            },
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl UniversalHash {
+    /// Hash `header` and check it against `difficulty` (leading zero bits)
+    ///
+    /// Returns the hash on success so callers don't need to recompute it.
+    pub fn verify_pow(
+        &mut self,
+        header: &MiningHeader,
+        difficulty: u32,
+    ) -> Result<Hash256, PowError> {
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+        let result = check_difficulty(Hash256(self.hash(&header.to_bytes())), difficulty);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_verification(started.elapsed(), result.is_ok());
+        result
+    }
+}
+
+impl UniversalHashIn<'_> {
+    /// Hash `header` and check it against `difficulty`, as [`UniversalHash::verify_pow`]
+    pub fn verify_pow(
+        &mut self,
+        header: &MiningHeader,
+        difficulty: u32,
+    ) -> Result<Hash256, PowError> {
+        check_difficulty(Hash256(self.hash(&header.to_bytes())), difficulty)
+    }
+}
+
+/// Pass `hash` through if it has at least `difficulty` leading zero bits
+///
+/// The decision is constant-time; only a rejected hash, whose leading zero
+/// count goes into the error anyway, is counted with an early exit.
+pub(crate) fn check_difficulty(hash: Hash256, difficulty: u32) -> Result<Hash256, PowError> {
+    if !crate::ct::has_leading_zeros(hash.as_bytes(), difficulty) {
+        return Err(PowError::BelowTarget {
+            difficulty,
+            achieved: hash.leading_zeros(),
+        });
+    }
+    Ok(hash)
+}
+
+/// Hash `header` and check it against `difficulty` in one call
+///
+/// For repeated verification, prefer [`UniversalHash::verify_pow`] to
+/// reuse the scratchpad allocation.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{MiningHeader, PowError, verify_pow};
+///
+/// let header = MiningHeader::default();
+/// assert!(verify_pow(&header, 0).is_ok());
+/// assert!(matches!(
+///     verify_pow(&header, 256),
+///     Err(PowError::BelowTarget { difficulty: 256, .. })
+/// ));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn verify_pow(header: &MiningHeader, difficulty: u32) -> Result<Hash256, PowError> {
+    UniversalHash::new().verify_pow(header, difficulty)
+}
+
+/// Like [`verify_pow`], for a header still in its 68-byte encoded form
+#[cfg(feature = "alloc")]
+pub fn verify_pow_bytes(bytes: &[u8], difficulty: u32) -> Result<Hash256, PowError> {
+    verify_pow(&MiningHeader::try_from_slice(bytes)?, difficulty)
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::hash;
+    use crate::header::HEADER_SIZE;
+
+    #[test]
+    fn test_verify_pow_returns_hash() {
+        let header = MiningHeader::default();
+        let result = verify_pow(&header, 0).unwrap();
+        assert_eq!(result.to_bytes(), hash(&header.to_bytes()));
+    }
+
+    #[test]
+    fn test_verify_pow_below_target() {
+        let header = MiningHeader::default();
+        let achieved = Hash256(hash(&header.to_bytes())).leading_zeros();
+
+        let mut hasher = UniversalHash::new();
+        assert!(hasher.verify_pow(&header, achieved).is_ok());
+        assert_eq!(
+            hasher.verify_pow(&header, achieved + 1),
+            Err(PowError::BelowTarget {
+                difficulty: achieved + 1,
+                achieved
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_pow_bytes_wrong_length() {
+        assert_eq!(
+            verify_pow_bytes(&[0u8; 60], 0),
+            Err(PowError::WrongLength {
+                expected: HEADER_SIZE,
+                actual: 60
+            })
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Cheap first-stage check before memory-hard verification
+//!
+//! Fully verifying a header fills 2MB of scratchpad and runs 49,152
+//! compressions, so a peer announcing junk headers costs a node far more
+//! than it costs the peer. A network can require, on top of the
+//! proof-of-work target, that [`prefilter`] of the header meets an easy
+//! prefilter target. Checking that takes one BLAKE3 call over 68 bytes,
+//! while producing a header that passes it takes the announcer
+//! `1 / p` BLAKE3 calls on average, where `p` is the chance of one
+//! header passing. Miners check the prefilter before the full hash and
+//! skip nonces that fail, so honest mining loses nothing but those calls.
+//!
+//! The prefilter target is a protocol rule layered on v4, not part of the
+//! hash itself: [`hash`](crate::hash) results are unchanged, and the rule
+//! only protects nodes on networks that enforce it.
+//!
+//! ```text
+//! prefilter(header) = BLAKE3_derive_key(PREFILTER_CONTEXT, header[0..68])
+//! ```
+
+use crate::hash256::Hash256;
+use crate::header::MiningHeader;
+#[cfg(feature = "alloc")]
+use crate::pow::PowError;
+use crate::target::Target;
+#[cfg(feature = "alloc")]
+use crate::uhash::UniversalHash;
+
+/// BLAKE3 key-derivation context for prefilter hashes
+pub const PREFILTER_CONTEXT: &str = "UniversalHash v4 2026-10-16 header prefilter";
+
+/// First-stage hash of `header`, compared against a prefilter target
+///
+/// Domain-separated from every other use of BLAKE3 in the algorithm, so
+/// it reveals nothing about the header's proof-of-work hash.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{MiningHeader, Target, passes_prefilter};
+///
+/// let prefilter_target = Target::from_difficulty_bits(8);
+/// let mut header = MiningHeader::default();
+/// while !passes_prefilter(&header, &prefilter_target) {
+///     header.nonce += 1;
+/// }
+/// // Only now is the header worth a full hash
+/// ```
+pub fn prefilter(header: &MiningHeader) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new_derive_key(PREFILTER_CONTEXT);
+    hasher.update(&header.to_bytes());
+    hasher.finalize().into()
+}
+
+/// Whether `header` passes the first stage under `prefilter_target`
+pub fn passes_prefilter(header: &MiningHeader, prefilter_target: &Target) -> bool {
+    prefilter_target.is_met_by(&Hash256(prefilter(header)))
+}
+
+#[cfg(feature = "alloc")]
+impl UniversalHash {
+    /// Check the prefilter, then hash `header` against `target`
+    ///
+    /// Headers failing the prefilter are rejected before any scratchpad
+    /// is touched. Returns the proof-of-work hash on success.
+    pub fn verify_prefiltered(
+        &mut self,
+        header: &MiningHeader,
+        prefilter_target: &Target,
+        target: &Target,
+    ) -> Result<Hash256, PowError> {
+        if !passes_prefilter(header, prefilter_target) {
+            return Err(PowError::PrefilterFailed {
+                target: *prefilter_target,
+            });
+        }
+        let hash = Hash256(self.hash(&header.to_bytes()));
+        if !target.is_met_by(&hash) {
+            return Err(PowError::AboveTarget { target: *target });
+        }
+        Ok(hash)
+    }
+}
+
+/// Two-stage verification of `header` in one call
+///
+/// For repeated verification, prefer
+/// [`UniversalHash::verify_prefiltered`] to reuse the scratchpad
+/// allocation.
+#[cfg(feature = "alloc")]
+pub fn verify_prefiltered(
+    header: &MiningHeader,
+    prefilter_target: &Target,
+    target: &Target,
+) -> Result<Hash256, PowError> {
+    UniversalHash::new().verify_prefiltered(header, prefilter_target, target)
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    /// First header from nonce 0 passing `prefilter_target`
+    fn first_passing(prefilter_target: &Target) -> MiningHeader {
+        let mut header = MiningHeader::default();
+        while !passes_prefilter(&header, prefilter_target) {
+            header.nonce += 1;
+        }
+        header
+    }
+
+    #[test]
+    fn test_prefilter_is_domain_separated() {
+        let header = MiningHeader::default();
+        let bytes = header.to_bytes();
+        assert_eq!(prefilter(&header), prefilter(&header));
+        assert_ne!(prefilter(&header), *blake3::hash(&bytes).as_bytes());
+        assert_ne!(prefilter(&header), hash(&bytes));
+    }
+
+    #[test]
+    fn test_verify_prefiltered_stages() {
+        let prefilter_target = Target::from_difficulty_bits(4);
+        let easy = Target::from_difficulty_bits(0);
+        let header = first_passing(&prefilter_target);
+        let expected = Hash256(hash(&header.to_bytes()));
+        assert_eq!(
+            verify_prefiltered(&header, &prefilter_target, &easy),
+            Ok(expected)
+        );
+
+        let impossible = Target::from_be_bytes(&[0u8; 32]);
+        assert_eq!(
+            verify_prefiltered(&header, &prefilter_target, &impossible),
+            Err(PowError::AboveTarget { target: impossible })
+        );
+
+        let mut failing = header;
+        while passes_prefilter(&failing, &prefilter_target) {
+            failing.nonce += 1;
+        }
+        assert_eq!(
+            UniversalHash::new().verify_prefiltered(&failing, &prefilter_target, &easy),
+            Err(PowError::PrefilterFailed {
+                target: prefilter_target
+            })
+        );
+    }
+}
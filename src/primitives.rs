@@ -2,42 +2,302 @@
 //!
 //! These implement the spec's AES_Compress, SHA256_Compress, and BLAKE3_Compress
 //! using low-level operations for maximum performance.
+//!
+//! With the `safe` feature every hardware path is compiled out in favour of
+//! the software implementations, so the module contains no `unsafe` code.
+
+#![cfg_attr(feature = "safe", forbid(unsafe_code))]
+
+use core::marker::PhantomData;
 
+use crate::endian::{load_u32_be, load_u32_le, store_u32_be, store_u32_le};
+#[cfg(all(
+    target_arch = "riscv64",
+    target_feature = "zkne",
+    not(feature = "safe")
+))]
+use crate::endian::{load_u64_le, store_u64_le};
 use crate::params::BLOCK_SIZE;
 
+/// Whether AES rounds run on hardware instructions in this build on this CPU
+///
+/// Mostly decided at compile time. The exception is aarch64 targets that
+/// don't enable `aes` (notably `aarch64-linux-android`): with `std` they
+/// check the CPU at runtime and switch to the NEON crypto path when the
+/// Crypto Extension is present, as it is on practically every phone SoC.
+pub fn hardware_aes() -> bool {
+    #[cfg(any(
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "aes",
+            not(feature = "safe")
+        ),
+        all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")),
+        all(
+            target_arch = "riscv64",
+            target_feature = "zkne",
+            not(feature = "safe")
+        )
+    ))]
+    {
+        true
+    }
+
+    #[cfg(all(
+        target_arch = "aarch64",
+        not(target_feature = "aes"),
+        feature = "std",
+        not(feature = "safe")
+    ))]
+    {
+        std::arch::is_aarch64_feature_detected!("aes")
+    }
+
+    #[cfg(not(any(
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "aes",
+            not(feature = "safe")
+        ),
+        all(
+            target_arch = "aarch64",
+            any(target_feature = "aes", feature = "std"),
+            not(feature = "safe")
+        ),
+        all(
+            target_arch = "riscv64",
+            target_feature = "zkne",
+            not(feature = "safe")
+        )
+    )))]
+    {
+        false
+    }
+}
+
 /// AES expansion: 4 AESENC rounds with a single key (for scratchpad init)
 /// Input: 128-bit state, 128-bit key
 /// Output: 128-bit state after 4 AESENC rounds
+#[cfg(feature = "alloc")]
 #[inline(always)]
 pub fn aes_expand_block(state: &[u8; 16], key: &[u8; 16]) -> [u8; 16] {
-    #[cfg(all(target_arch = "x86_64", target_feature = "aes"))]
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "aes",
+        not(feature = "safe")
+    ))]
     {
         aes_expand_x86(state, key)
     }
 
-    #[cfg(all(target_arch = "aarch64", target_feature = "aes"))]
+    #[cfg(all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")))]
     {
         aes_expand_arm(state, key)
     }
 
+    #[cfg(all(
+        target_arch = "aarch64",
+        not(target_feature = "aes"),
+        feature = "std",
+        not(feature = "safe")
+    ))]
+    {
+        if std::arch::is_aarch64_feature_detected!("aes") {
+            // SAFETY: the CPU supports the AES instructions
+            return unsafe { aes_expand_arm_detected(state, key) };
+        }
+    }
+
+    #[cfg(all(
+        target_arch = "riscv64",
+        target_feature = "zkne",
+        not(feature = "safe")
+    ))]
+    {
+        aes_expand_riscv(state, key)
+    }
+
     #[cfg(not(any(
-        all(target_arch = "x86_64", target_feature = "aes"),
-        all(target_arch = "aarch64", target_feature = "aes")
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "aes",
+            not(feature = "safe")
+        ),
+        all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")),
+        all(
+            target_arch = "riscv64",
+            target_feature = "zkne",
+            not(feature = "safe")
+        )
     )))]
     {
         aes_expand_soft(state, key)
     }
 }
 
-/// x86_64 AES expansion
-#[cfg(all(target_arch = "x86_64", target_feature = "aes"))]
+/// Key of the 4-round scratchpad expansion, prepared once per seed
+///
+/// Every block of a scratchpad is expanded under the same key, yet
+/// [`aes_expand_block`] reloads it into a vector register, and on aarch64
+/// builds without compile-time AES checks the CPU, on each call. A fill
+/// prepares the key once and expands all of its blocks through it.
+#[derive(Clone, Copy)]
+pub(crate) struct ExpandKey {
+    #[cfg(not(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "aes",
+        not(feature = "safe")
+    )))]
+    key: [u8; 16],
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "aes",
+        not(feature = "safe")
+    ))]
+    loaded: X86Block,
+    #[cfg(all(
+        target_arch = "aarch64",
+        not(target_feature = "aes"),
+        feature = "std",
+        not(feature = "safe")
+    ))]
+    hardware: bool,
+}
+
+impl ExpandKey {
+    /// Prepare `key` for repeated expansions
+    #[inline(always)]
+    pub(crate) fn new(key: &[u8; 16]) -> Self {
+        Self {
+            #[cfg(not(all(
+                any(target_arch = "x86", target_arch = "x86_64"),
+                target_feature = "aes",
+                not(feature = "safe")
+            )))]
+            key: *key,
+            #[cfg(all(
+                any(target_arch = "x86", target_arch = "x86_64"),
+                target_feature = "aes",
+                not(feature = "safe")
+            ))]
+            loaded: x86_load(key),
+            #[cfg(all(
+                target_arch = "aarch64",
+                not(target_feature = "aes"),
+                feature = "std",
+                not(feature = "safe")
+            ))]
+            hardware: std::arch::is_aarch64_feature_detected!("aes"),
+        }
+    }
+
+    /// Same as [`aes_expand_block`] with this key
+    #[inline(always)]
+    pub(crate) fn expand(&self, state: &[u8; 16]) -> [u8; 16] {
+        #[cfg(all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "aes",
+            not(feature = "safe")
+        ))]
+        {
+            aes_expand_x86_loaded(state, self.loaded)
+        }
+
+        #[cfg(all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")))]
+        {
+            aes_expand_arm(state, &self.key)
+        }
+
+        #[cfg(all(
+            target_arch = "aarch64",
+            not(target_feature = "aes"),
+            feature = "std",
+            not(feature = "safe")
+        ))]
+        {
+            if self.hardware {
+                // SAFETY: `new` found the CPU supports the AES instructions
+                return unsafe { aes_expand_arm_detected(state, &self.key) };
+            }
+        }
+
+        #[cfg(all(
+            target_arch = "riscv64",
+            target_feature = "zkne",
+            not(feature = "safe")
+        ))]
+        {
+            aes_expand_riscv(state, &self.key)
+        }
+
+        #[cfg(not(any(
+            all(
+                any(target_arch = "x86", target_arch = "x86_64"),
+                target_feature = "aes",
+                not(feature = "safe")
+            ),
+            all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")),
+            all(
+                target_arch = "riscv64",
+                target_feature = "zkne",
+                not(feature = "safe")
+            )
+        )))]
+        {
+            aes_expand_soft(state, &self.key)
+        }
+    }
+}
+
+/// 128-bit x86 vector register
+#[cfg(all(target_arch = "x86", target_feature = "aes", not(feature = "safe")))]
+type X86Block = core::arch::x86::__m128i;
+#[cfg(all(target_arch = "x86_64", target_feature = "aes", not(feature = "safe")))]
+type X86Block = core::arch::x86_64::__m128i;
+
+/// Load 16 bytes into an x86 vector register
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "aes",
+    not(feature = "safe")
+))]
+#[inline(always)]
+fn x86_load(bytes: &[u8; 16]) -> X86Block {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::_mm_loadu_si128;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::_mm_loadu_si128;
+
+    unsafe { _mm_loadu_si128(bytes.as_ptr() as *const X86Block) }
+}
+
+/// x86 AES expansion
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "aes",
+    not(feature = "safe"),
+    feature = "alloc"
+))]
 #[inline(always)]
 fn aes_expand_x86(state: &[u8; 16], key: &[u8; 16]) -> [u8; 16] {
-    use core::arch::x86_64::{__m128i, _mm_aesenc_si128, _mm_loadu_si128, _mm_storeu_si128};
+    aes_expand_x86_loaded(state, x86_load(key))
+}
+
+/// x86 AES expansion with the key already in a register
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "aes",
+    not(feature = "safe")
+))]
+#[inline(always)]
+fn aes_expand_x86_loaded(state: &[u8; 16], k: X86Block) -> [u8; 16] {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::{_mm_aesenc_si128, _mm_storeu_si128};
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::{_mm_aesenc_si128, _mm_storeu_si128};
 
     unsafe {
-        let mut s = _mm_loadu_si128(state.as_ptr() as *const __m128i);
-        let k = _mm_loadu_si128(key.as_ptr() as *const __m128i);
+        let mut s = x86_load(state);
 
         // 4 AESENC rounds with same key
         s = _mm_aesenc_si128(s, k);
@@ -46,26 +306,31 @@ fn aes_expand_x86(state: &[u8; 16], key: &[u8; 16]) -> [u8; 16] {
         s = _mm_aesenc_si128(s, k);
 
         let mut result = [0u8; 16];
-        _mm_storeu_si128(result.as_mut_ptr() as *mut __m128i, s);
+        _mm_storeu_si128(result.as_mut_ptr() as *mut X86Block, s);
         result
     }
 }
 
 /// ARM AES expansion
-#[cfg(all(target_arch = "aarch64", target_feature = "aes"))]
+#[cfg(all(
+    target_arch = "aarch64",
+    any(target_feature = "aes", feature = "std"),
+    not(feature = "safe")
+))]
 #[inline(always)]
 fn aes_expand_arm(state: &[u8; 16], key: &[u8; 16]) -> [u8; 16] {
-    use core::arch::aarch64::{vaeseq_u8, vaesmcq_u8, vld1q_u8, vst1q_u8};
+    use core::arch::aarch64::{vdupq_n_u8, vld1q_u8, vst1q_u8};
 
     unsafe {
         let mut s = vld1q_u8(state.as_ptr());
         let k = vld1q_u8(key.as_ptr());
+        let zero = vdupq_n_u8(0);
 
-        // 4 AESENC rounds (AESE + AESMC)
-        s = vaesmcq_u8(vaeseq_u8(s, k));
-        s = vaesmcq_u8(vaeseq_u8(s, k));
-        s = vaesmcq_u8(vaeseq_u8(s, k));
-        s = vaesmcq_u8(vaeseq_u8(s, k));
+        // 4 AESENC rounds with same key
+        s = aesenc_arm(s, k, zero);
+        s = aesenc_arm(s, k, zero);
+        s = aesenc_arm(s, k, zero);
+        s = aesenc_arm(s, k, zero);
 
         let mut result = [0u8; 16];
         vst1q_u8(result.as_mut_ptr(), s);
@@ -73,11 +338,58 @@ fn aes_expand_arm(state: &[u8; 16], key: &[u8; 16]) -> [u8; 16] {
     }
 }
 
+/// ARM AES expansion on a CPU found at runtime to have the AES instructions
+#[cfg(all(
+    target_arch = "aarch64",
+    not(target_feature = "aes"),
+    feature = "std",
+    not(feature = "safe")
+))]
+#[target_feature(enable = "aes")]
+fn aes_expand_arm_detected(state: &[u8; 16], key: &[u8; 16]) -> [u8; 16] {
+    aes_expand_arm(state, key)
+}
+
+/// RISC-V scalar crypto (Zkne) AES expansion
+#[cfg(all(
+    target_arch = "riscv64",
+    target_feature = "zkne",
+    not(feature = "safe")
+))]
+#[inline(always)]
+fn aes_expand_riscv(state: &[u8; 16], key: &[u8; 16]) -> [u8; 16] {
+    let key = (load_u64_le(key, 0), load_u64_le(key, 8));
+    let mut s = (load_u64_le(state, 0), load_u64_le(state, 8));
+
+    // 4 AESENC rounds with same key
+    s = aesenc_riscv(s, key);
+    s = aesenc_riscv(s, key);
+    s = aesenc_riscv(s, key);
+    s = aesenc_riscv(s, key);
+
+    let mut result = [0u8; 16];
+    store_u64_le(&mut result, 0, s.0);
+    store_u64_le(&mut result, 8, s.1);
+    result
+}
+
 /// Software AES expansion (for WASM and targets without hardware AES)
-#[cfg(not(any(
-    all(target_arch = "x86_64", target_feature = "aes"),
-    all(target_arch = "aarch64", target_feature = "aes")
-)))]
+#[cfg(any(
+    feature = "differential",
+    not(any(
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "aes",
+            not(feature = "safe")
+        ),
+        all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")),
+        all(
+            target_arch = "riscv64",
+            target_feature = "zkne",
+            not(feature = "safe")
+        )
+    ))
+))]
 #[inline(always)]
 fn aes_expand_soft(state: &[u8; 16], key: &[u8; 16]) -> [u8; 16] {
     let mut s = *state;
@@ -95,29 +407,71 @@ fn aes_expand_soft(state: &[u8; 16], key: &[u8; 16]) -> [u8; 16] {
 /// Input: 256-bit state, 512-bit block (we use first 256 bits as round keys)
 #[inline(always)]
 pub fn aes_compress(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
-    #[cfg(all(target_arch = "x86_64", target_feature = "aes"))]
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "aes",
+        not(feature = "safe")
+    ))]
     {
         aes_compress_x86(state, block)
     }
 
-    #[cfg(all(target_arch = "aarch64", target_feature = "aes"))]
+    #[cfg(all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")))]
     {
         aes_compress_arm(state, block)
     }
 
+    #[cfg(all(
+        target_arch = "aarch64",
+        not(target_feature = "aes"),
+        feature = "std",
+        not(feature = "safe")
+    ))]
+    {
+        if std::arch::is_aarch64_feature_detected!("aes") {
+            // SAFETY: the CPU supports the AES instructions
+            return unsafe { aes_compress_arm_detected(state, block) };
+        }
+    }
+
+    #[cfg(all(
+        target_arch = "riscv64",
+        target_feature = "zkne",
+        not(feature = "safe")
+    ))]
+    {
+        aes_compress_riscv(state, block)
+    }
+
     #[cfg(not(any(
-        all(target_arch = "x86_64", target_feature = "aes"),
-        all(target_arch = "aarch64", target_feature = "aes")
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "aes",
+            not(feature = "safe")
+        ),
+        all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")),
+        all(
+            target_arch = "riscv64",
+            target_feature = "zkne",
+            not(feature = "safe")
+        )
     )))]
     {
         aes_compress_soft(state, block)
     }
 }
 
-/// x86_64 AES-NI implementation
-#[cfg(all(target_arch = "x86_64", target_feature = "aes"))]
+/// x86 AES-NI implementation
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "aes",
+    not(feature = "safe")
+))]
 #[inline(always)]
 fn aes_compress_x86(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::{__m128i, _mm_aesenc_si128, _mm_loadu_si128, _mm_storeu_si128};
+    #[cfg(target_arch = "x86_64")]
     use core::arch::x86_64::{__m128i, _mm_aesenc_si128, _mm_loadu_si128, _mm_storeu_si128};
 
     unsafe {
@@ -152,10 +506,14 @@ fn aes_compress_x86(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
 }
 
 /// ARM NEON + Crypto implementation
-#[cfg(all(target_arch = "aarch64", target_feature = "aes"))]
+#[cfg(all(
+    target_arch = "aarch64",
+    any(target_feature = "aes", feature = "std"),
+    not(feature = "safe")
+))]
 #[inline(always)]
 fn aes_compress_arm(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
-    use core::arch::aarch64::{vaeseq_u8, vaesmcq_u8, vld1q_u8, vst1q_u8};
+    use core::arch::aarch64::{vdupq_n_u8, vld1q_u8, vst1q_u8};
 
     unsafe {
         // Load state halves
@@ -167,21 +525,19 @@ fn aes_compress_arm(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
         let key1 = vld1q_u8(block.as_ptr().add(16));
         let key2 = vld1q_u8(block.as_ptr().add(32));
         let key3 = vld1q_u8(block.as_ptr().add(48));
-
-        // ARM AES: vaeseq does SubBytes+ShiftRows, vaesmcq does MixColumns
-        // We need to XOR the key, then apply AESE+AESMC
+        let zero = vdupq_n_u8(0);
 
         // 4 rounds on low half
-        state_lo = vaesmcq_u8(vaeseq_u8(state_lo, key0));
-        state_lo = vaesmcq_u8(vaeseq_u8(state_lo, key1));
-        state_lo = vaesmcq_u8(vaeseq_u8(state_lo, key2));
-        state_lo = vaesmcq_u8(vaeseq_u8(state_lo, key3));
+        state_lo = aesenc_arm(state_lo, key0, zero);
+        state_lo = aesenc_arm(state_lo, key1, zero);
+        state_lo = aesenc_arm(state_lo, key2, zero);
+        state_lo = aesenc_arm(state_lo, key3, zero);
 
         // 4 rounds on high half (rotated keys)
-        state_hi = vaesmcq_u8(vaeseq_u8(state_hi, key2));
-        state_hi = vaesmcq_u8(vaeseq_u8(state_hi, key3));
-        state_hi = vaesmcq_u8(vaeseq_u8(state_hi, key0));
-        state_hi = vaesmcq_u8(vaeseq_u8(state_hi, key1));
+        state_hi = aesenc_arm(state_hi, key2, zero);
+        state_hi = aesenc_arm(state_hi, key3, zero);
+        state_hi = aesenc_arm(state_hi, key0, zero);
+        state_hi = aesenc_arm(state_hi, key1, zero);
 
         // Store result
         let mut result = [0u8; 32];
@@ -191,12 +547,123 @@ fn aes_compress_arm(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
     }
 }
 
+/// ARM AES compression on a CPU found at runtime to have the AES instructions
+#[cfg(all(
+    target_arch = "aarch64",
+    not(target_feature = "aes"),
+    feature = "std",
+    not(feature = "safe")
+))]
+#[target_feature(enable = "aes")]
+fn aes_compress_arm_detected(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+    aes_compress_arm(state, block)
+}
+
+/// Single x86-style AESENC round from the ARM instructions
+///
+/// AESE XORs its key in *before* SubBytes + ShiftRows, while AESENC adds
+/// the round key after MixColumns. Running AESE with a zero key and XORing
+/// the round key last gives AESENC's result.
+#[cfg(all(
+    target_arch = "aarch64",
+    any(target_feature = "aes", feature = "std"),
+    not(feature = "safe")
+))]
+#[inline(always)]
+unsafe fn aesenc_arm(
+    state: core::arch::aarch64::uint8x16_t,
+    round_key: core::arch::aarch64::uint8x16_t,
+    zero: core::arch::aarch64::uint8x16_t,
+) -> core::arch::aarch64::uint8x16_t {
+    use core::arch::aarch64::{vaeseq_u8, vaesmcq_u8, veorq_u8};
+
+    unsafe { veorq_u8(vaesmcq_u8(vaeseq_u8(state, zero)), round_key) }
+}
+
+/// RISC-V scalar crypto (Zkne) implementation
+#[cfg(all(
+    target_arch = "riscv64",
+    target_feature = "zkne",
+    not(feature = "safe")
+))]
+#[inline(always)]
+fn aes_compress_riscv(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+    // Round keys from block, each as (low, high) little-endian words
+    let key0 = (load_u64_le(block, 0), load_u64_le(block, 8));
+    let key1 = (load_u64_le(block, 16), load_u64_le(block, 24));
+    let key2 = (load_u64_le(block, 32), load_u64_le(block, 40));
+    let key3 = (load_u64_le(block, 48), load_u64_le(block, 56));
+
+    // 4 rounds on low half
+    let mut state_lo = (load_u64_le(state, 0), load_u64_le(state, 8));
+    state_lo = aesenc_riscv(state_lo, key0);
+    state_lo = aesenc_riscv(state_lo, key1);
+    state_lo = aesenc_riscv(state_lo, key2);
+    state_lo = aesenc_riscv(state_lo, key3);
+
+    // 4 rounds on high half (rotated keys)
+    let mut state_hi = (load_u64_le(state, 16), load_u64_le(state, 24));
+    state_hi = aesenc_riscv(state_hi, key2);
+    state_hi = aesenc_riscv(state_hi, key3);
+    state_hi = aesenc_riscv(state_hi, key0);
+    state_hi = aesenc_riscv(state_hi, key1);
+
+    let mut result = [0u8; 32];
+    store_u64_le(&mut result, 0, state_lo.0);
+    store_u64_le(&mut result, 8, state_lo.1);
+    store_u64_le(&mut result, 16, state_hi.0);
+    store_u64_le(&mut result, 24, state_hi.1);
+    result
+}
+
+/// Single AESENC round on a 128-bit state held as (low, high) 64-bit words
+///
+/// `aes64esm` yields one half of ShiftRows + SubBytes + MixColumns; swapping
+/// the operands gives the other half. The round key is XORed afterwards,
+/// matching x86 AESENC.
+#[cfg(all(
+    target_arch = "riscv64",
+    target_feature = "zkne",
+    not(feature = "safe")
+))]
+#[inline(always)]
+fn aesenc_riscv(state: (u64, u64), round_key: (u64, u64)) -> (u64, u64) {
+    let (lo, hi) = state;
+    let (mixed_lo, mixed_hi): (u64, u64);
+    // SAFETY: aes64esm is a pure register-to-register instruction, and the
+    // enclosing cfg guarantees the Zkne extension is enabled.
+    unsafe {
+        core::arch::asm!(
+            "aes64esm {mixed_lo}, {lo}, {hi}",
+            "aes64esm {mixed_hi}, {hi}, {lo}",
+            lo = in(reg) lo,
+            hi = in(reg) hi,
+            mixed_lo = out(reg) mixed_lo,
+            mixed_hi = out(reg) mixed_hi,
+            options(pure, nomem, nostack),
+        );
+    }
+    (mixed_lo ^ round_key.0, mixed_hi ^ round_key.1)
+}
+
 /// Software fallback for AES compression (WASM, older CPUs)
 /// Implements actual AESENC rounds: SubBytes + ShiftRows + MixColumns + AddRoundKey
-#[cfg(not(any(
-    all(target_arch = "x86_64", target_feature = "aes"),
-    all(target_arch = "aarch64", target_feature = "aes")
-)))]
+#[cfg(any(
+    feature = "differential",
+    not(any(
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "aes",
+            not(feature = "safe")
+        ),
+        all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")),
+        all(
+            target_arch = "riscv64",
+            target_feature = "zkne",
+            not(feature = "safe")
+        )
+    ))
+))]
 #[inline(always)]
 fn aes_compress_soft(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
     let mut result = [0u8; 32];
@@ -222,10 +689,22 @@ fn aes_compress_soft(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
 }
 
 /// Single AESENC round: SubBytes + ShiftRows + MixColumns + AddRoundKey
-#[cfg(not(any(
-    all(target_arch = "x86_64", target_feature = "aes"),
-    all(target_arch = "aarch64", target_feature = "aes")
-)))]
+#[cfg(any(
+    feature = "differential",
+    not(any(
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "aes",
+            not(feature = "safe")
+        ),
+        all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")),
+        all(
+            target_arch = "riscv64",
+            target_feature = "zkne",
+            not(feature = "safe")
+        )
+    ))
+))]
 #[inline(always)]
 fn aesenc_round(state: &[u8; 16], round_key: &[u8]) -> [u8; 16] {
     // SubBytes
@@ -279,10 +758,22 @@ fn aesenc_round(state: &[u8; 16], round_key: &[u8]) -> [u8; 16] {
 }
 
 /// Multiply by 2 in GF(2^8) with reduction polynomial x^8 + x^4 + x^3 + x + 1
-#[cfg(not(any(
-    all(target_arch = "x86_64", target_feature = "aes"),
-    all(target_arch = "aarch64", target_feature = "aes")
-)))]
+#[cfg(any(
+    feature = "differential",
+    not(any(
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "aes",
+            not(feature = "safe")
+        ),
+        all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")),
+        all(
+            target_arch = "riscv64",
+            target_feature = "zkne",
+            not(feature = "safe")
+        )
+    ))
+))]
 #[inline(always)]
 fn gf_mul2(x: u8) -> u8 {
     let hi = x >> 7;
@@ -291,20 +782,44 @@ fn gf_mul2(x: u8) -> u8 {
 }
 
 /// Multiply by 3 in GF(2^8): 3*x = 2*x + x
-#[cfg(not(any(
-    all(target_arch = "x86_64", target_feature = "aes"),
-    all(target_arch = "aarch64", target_feature = "aes")
-)))]
+#[cfg(any(
+    feature = "differential",
+    not(any(
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "aes",
+            not(feature = "safe")
+        ),
+        all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")),
+        all(
+            target_arch = "riscv64",
+            target_feature = "zkne",
+            not(feature = "safe")
+        )
+    ))
+))]
 #[inline(always)]
 fn gf_mul3(x: u8) -> u8 {
     gf_mul2(x) ^ x
 }
 
 /// AES S-box (for software fallback only)
-#[cfg(not(any(
-    all(target_arch = "x86_64", target_feature = "aes"),
-    all(target_arch = "aarch64", target_feature = "aes")
-)))]
+#[cfg(any(
+    feature = "differential",
+    not(any(
+        all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            target_feature = "aes",
+            not(feature = "safe")
+        ),
+        all(target_arch = "aarch64", target_feature = "aes", not(feature = "safe")),
+        all(
+            target_arch = "riscv64",
+            target_feature = "zkne",
+            not(feature = "safe")
+        )
+    ))
+))]
 const SBOX: [u8; 256] = [
     0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
     0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
@@ -329,19 +844,47 @@ const SBOX: [u8; 256] = [
 /// Uses the raw compression function, not the full hash
 #[inline(always)]
 pub fn sha256_compress(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
-    #[cfg(all(target_arch = "aarch64", target_feature = "sha2"))]
+    #[cfg(all(
+        target_arch = "aarch64",
+        target_feature = "sha2",
+        not(feature = "safe")
+    ))]
     {
         sha256_compress_arm(state, block)
     }
 
-    #[cfg(not(all(target_arch = "aarch64", target_feature = "sha2")))]
+    #[cfg(all(
+        target_arch = "riscv64",
+        target_feature = "zknh",
+        not(feature = "safe")
+    ))]
+    {
+        sha256_compress_riscv(state, block)
+    }
+
+    #[cfg(not(any(
+        all(
+            target_arch = "aarch64",
+            target_feature = "sha2",
+            not(feature = "safe")
+        ),
+        all(
+            target_arch = "riscv64",
+            target_feature = "zknh",
+            not(feature = "safe")
+        )
+    )))]
     {
         sha256_compress_soft(state, block)
     }
 }
 
 /// ARM SHA256 compression using hardware intrinsics
-#[cfg(all(target_arch = "aarch64", target_feature = "sha2"))]
+#[cfg(all(
+    target_arch = "aarch64",
+    target_feature = "sha2",
+    not(feature = "safe")
+))]
 #[inline(always)]
 fn sha256_compress_arm(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
     use core::arch::aarch64::*;
@@ -500,20 +1043,124 @@ fn sha256_compress_arm(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
     }
 }
 
+/// RISC-V SHA-256 compression using the scalar crypto (Zknh) sigma instructions
+#[cfg(all(
+    target_arch = "riscv64",
+    target_feature = "zknh",
+    not(feature = "safe")
+))]
+#[inline(always)]
+fn sha256_compress_riscv(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+    // SHA256 round constants
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+
+    // Message schedule
+    let mut w = [0u32; 64];
+    for (i, word) in w.iter_mut().take(16).enumerate() {
+        *word = load_u32_be(block, i * 4);
+    }
+    for i in 16..64 {
+        w[i] = zknh::sig1(w[i - 2])
+            .wrapping_add(w[i - 7])
+            .wrapping_add(zknh::sig0(w[i - 15]))
+            .wrapping_add(w[i - 16]);
+    }
+
+    let h: [u32; 8] = core::array::from_fn(|i| load_u32_be(state, i * 4));
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+    for i in 0..64 {
+        let ch = (e & f) ^ (!e & g);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t1 = hh
+            .wrapping_add(zknh::sum1(e))
+            .wrapping_add(ch)
+            .wrapping_add(K[i])
+            .wrapping_add(w[i]);
+        let t2 = zknh::sum0(a).wrapping_add(maj);
+        hh = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    let mut result = [0u8; 32];
+    for (i, word) in [a, b, c, d, e, f, g, hh].iter().enumerate() {
+        store_u32_be(&mut result, i * 4, h[i].wrapping_add(*word));
+    }
+    result
+}
+
+/// Zknh SHA-256 sigma functions
+///
+/// On RV64 each instruction reads the low 32 bits of its operand and
+/// sign-extends the 32-bit result, so truncating back to `u32` is exact.
+#[cfg(all(
+    target_arch = "riscv64",
+    target_feature = "zknh",
+    not(feature = "safe")
+))]
+mod zknh {
+    macro_rules! sigma {
+        ($name:ident, $insn:literal) => {
+            #[inline(always)]
+            pub(super) fn $name(x: u32) -> u32 {
+                let out: u64;
+                // SAFETY: register-only instruction; the enclosing cfg
+                // guarantees the Zknh extension is enabled.
+                unsafe {
+                    core::arch::asm!(
+                        concat!($insn, " {out}, {x}"),
+                        x = in(reg) x as u64,
+                        out = lateout(reg) out,
+                        options(pure, nomem, nostack),
+                    );
+                }
+                out as u32
+            }
+        };
+    }
+
+    sigma!(sig0, "sha256sig0");
+    sigma!(sig1, "sha256sig1");
+    sigma!(sum0, "sha256sum0");
+    sigma!(sum1, "sha256sum1");
+}
+
 /// Software SHA-256 compression fallback
-#[cfg(not(all(target_arch = "aarch64", target_feature = "sha2")))]
+#[cfg(any(
+    feature = "differential",
+    not(any(
+        all(
+            target_arch = "aarch64",
+            target_feature = "sha2",
+            not(feature = "safe")
+        ),
+        all(
+            target_arch = "riscv64",
+            target_feature = "zknh",
+            not(feature = "safe")
+        )
+    ))
+))]
 #[inline(always)]
 fn sha256_compress_soft(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
     // Convert state to u32 words (SHA-256 internal state)
-    let mut hash_state = [0u32; 8];
-    for i in 0..8 {
-        hash_state[i] = u32::from_be_bytes([
-            state[i * 4],
-            state[i * 4 + 1],
-            state[i * 4 + 2],
-            state[i * 4 + 3],
-        ]);
-    }
+    let mut hash_state: [u32; 8] = core::array::from_fn(|i| load_u32_be(state, i * 4));
 
     // Prepare message block
     let mut msg_block = [0u8; 64];
@@ -524,9 +1171,8 @@ fn sha256_compress_soft(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32]
 
     // Convert back to bytes
     let mut result = [0u8; 32];
-    for i in 0..8 {
-        let bytes = hash_state[i].to_be_bytes();
-        result[i * 4..i * 4 + 4].copy_from_slice(&bytes);
+    for (i, word) in hash_state.iter().enumerate() {
+        store_u32_be(&mut result, i * 4, *word);
     }
 
     result
@@ -555,26 +1201,10 @@ pub fn blake3_compress(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
     ];
 
     // Convert state to words
-    let mut h = [0u32; 8];
-    for i in 0..8 {
-        h[i] = u32::from_le_bytes([
-            state[i * 4],
-            state[i * 4 + 1],
-            state[i * 4 + 2],
-            state[i * 4 + 3],
-        ]);
-    }
+    let mut h: [u32; 8] = core::array::from_fn(|i| load_u32_le(state, i * 4));
 
     // Convert block to message words
-    let mut m = [0u32; 16];
-    for i in 0..16 {
-        m[i] = u32::from_le_bytes([
-            block[i * 4],
-            block[i * 4 + 1],
-            block[i * 4 + 2],
-            block[i * 4 + 3],
-        ]);
-    }
+    let m: [u32; 16] = core::array::from_fn(|i| load_u32_le(block, i * 4));
 
     // Initialize state matrix
     let mut v = [0u32; 16];
@@ -603,9 +1233,8 @@ pub fn blake3_compress(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
 
     // Convert back to bytes
     let mut result = [0u8; 32];
-    for i in 0..8 {
-        let bytes = h[i].to_le_bytes();
-        result[i * 4..i * 4 + 4].copy_from_slice(&bytes);
+    for (i, word) in h.iter().enumerate() {
+        store_u32_le(&mut result, i * 4, *word);
     }
 
     result
@@ -624,6 +1253,145 @@ fn g(v: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, mx: u32, my: u32
     v[b] = (v[b] ^ v[c]).rotate_right(7);
 }
 
+/// A compression function the round loop can apply
+///
+/// Implement this to put a different function into one of the three
+/// primitive slots of a research hasher (see [`Primitives`]). Hashes
+/// computed with anything but [`ConsensusPrimitives`] are not UniversalHash
+/// v4 hashes and will not verify.
+pub trait CompressionPrimitive {
+    /// Compress a 256-bit chain state with a 512-bit scratchpad block
+    fn compress(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32];
+}
+
+/// Spec AES_Compress: 4 AESENC rounds keyed by the block
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AesCompress;
+
+impl CompressionPrimitive for AesCompress {
+    #[inline(always)]
+    fn compress(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+        aes_compress(state, block)
+    }
+}
+
+/// Spec SHA256_Compress: the raw SHA-256 compression function
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sha256Compress;
+
+impl CompressionPrimitive for Sha256Compress {
+    #[inline(always)]
+    fn compress(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+        sha256_compress(state, block)
+    }
+}
+
+/// Spec BLAKE3_Compress: 7-round BLAKE3 compression
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Blake3Compress;
+
+impl CompressionPrimitive for Blake3Compress {
+    #[inline(always)]
+    fn compress(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+        blake3_compress(state, block)
+    }
+}
+
+/// Three compression functions filling the round loop's primitive slots
+///
+/// `A`, `S` and `B` run in the rounds where the spec selects AES, SHA-256
+/// and BLAKE3 respectively; rotation, addressing and write-back are
+/// unchanged. Swap one slot to measure the effect of a different function:
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use uhash_core::{
+///     AesCompress, Blake3Compress, CompressionPrimitive, Primitives, UniversalHash, BLOCK_SIZE,
+/// };
+///
+/// /// SHA-256 slot replaced by a cheap XOR fold
+/// struct XorFold;
+///
+/// impl CompressionPrimitive for XorFold {
+///     fn compress(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+///         core::array::from_fn(|i| state[i] ^ block[i] ^ block[i + 32])
+///     }
+/// }
+///
+/// let mut hasher = UniversalHash::<Primitives<AesCompress, XorFold, Blake3Compress>>::with_primitives();
+/// let research = hasher.hash(b"input data");
+/// assert_ne!(research, uhash_core::hash(b"input data"));
+/// # }
+/// ```
+pub struct Primitives<A, S, B>(PhantomData<(A, S, B)>);
+
+/// The spec's primitives, the only set producing consensus-valid hashes
+pub type ConsensusPrimitives = Primitives<AesCompress, Sha256Compress, Blake3Compress>;
+
+/// Primitive set the round loop dispatches over
+///
+/// Sealed: [`Primitives`] is the only implementation, so whatever fills the
+/// slots, the dispatch by spec primitive index stays as specified.
+pub trait PrimitiveSet: sealed::Sealed {
+    /// Apply the primitive with spec index `primitive` (0 AES, 1 SHA-256,
+    /// 2 BLAKE3)
+    fn compress(primitive: usize, state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32];
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+impl<A, S, B> sealed::Sealed for Primitives<A, S, B> {}
+
+impl<A, S, B> PrimitiveSet for Primitives<A, S, B>
+where
+    A: CompressionPrimitive,
+    S: CompressionPrimitive,
+    B: CompressionPrimitive,
+{
+    #[inline(always)]
+    fn compress(primitive: usize, state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+        match primitive {
+            0 => A::compress(state, block),
+            1 => S::compress(state, block),
+            _ => B::compress(state, block),
+        }
+    }
+}
+
+/// One implementation of every primitive the hash uses
+#[cfg(any(feature = "trace", feature = "differential"))]
+#[derive(Clone, Copy)]
+pub(crate) struct PrimitiveTable {
+    /// Scratchpad fill expansion
+    pub aes_expand: fn(&[u8; 16], &[u8; 16]) -> [u8; 16],
+    /// Primitive index 0
+    pub aes: fn(&[u8; 32], &[u8; BLOCK_SIZE]) -> [u8; 32],
+    /// Primitive index 1
+    pub sha256: fn(&[u8; 32], &[u8; BLOCK_SIZE]) -> [u8; 32],
+    /// Primitive index 2
+    pub blake3: fn(&[u8; 32], &[u8; BLOCK_SIZE]) -> [u8; 32],
+}
+
+/// Primitives selected for this target (hardware where available)
+#[cfg(feature = "trace")]
+pub(crate) const NATIVE_PRIMITIVES: PrimitiveTable = PrimitiveTable {
+    aes_expand: aes_expand_block,
+    aes: aes_compress,
+    sha256: sha256_compress,
+    blake3: blake3_compress,
+};
+
+/// Software primitives, regardless of target features
+#[cfg(feature = "differential")]
+pub(crate) const PORTABLE_PRIMITIVES: PrimitiveTable = PrimitiveTable {
+    aes_expand: aes_expand_soft,
+    aes: aes_compress_soft,
+    sha256: sha256_compress_soft,
+    blake3: blake3_compress,
+};
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -663,4 +1431,17 @@ mod tests {
         assert_eq!(result1, result2);
         assert_ne!(result1, state);
     }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_expand_key_matches_aes_expand_block() {
+        let key: [u8; 16] = core::array::from_fn(|i| i as u8 * 17);
+        let expand_key = ExpandKey::new(&key);
+        let mut state = [0xA5u8; 16];
+        for _ in 0..64 {
+            let next = expand_key.expand(&state);
+            assert_eq!(next, aes_expand_block(&state, &key));
+            state = next;
+        }
+    }
 }
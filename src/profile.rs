@@ -0,0 +1,138 @@
+//! Per-phase timing breakdown of a hash
+//!
+//! [`breakdown()`] times the compression primitives, a real scratchpad
+//! initialization and a full hash on the current machine, and estimates
+//! where the remaining time goes. Its [`Display`](fmt::Display) output is
+//! meant to be pasted into bug reports; tuning tools can read the fields.
+//!
+//! ```rust,no_run
+//! use uhash_core::profile;
+//!
+//! let breakdown = profile::breakdown();
+//! println!("{}", breakdown);
+//! ```
+
+use core::fmt;
+use core::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::params::{BLOCKS_PER_SCRATCHPAD, CHAINS, ROUNDS, SCRATCHPAD_SIZE};
+use crate::uhash::fill_scratchpad_aes;
+use crate::{PrimitiveTimings, UniversalHash};
+
+/// Full hashes timed for [`Breakdown::per_hash`]
+const HASH_ITERATIONS: u32 = 10;
+
+/// Scratchpad initializations timed for [`Breakdown::scratchpad_init`]
+const INIT_ITERATIONS: u32 = 10;
+
+/// Where the time of one hash goes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Breakdown {
+    /// Measured time of one hash, chains run sequentially
+    pub per_hash: Duration,
+    /// Average time of a single primitive invocation
+    pub primitives: PrimitiveTimings,
+    /// Measured time to fill all chains' scratchpads
+    pub scratchpad_init: Duration,
+    /// Estimated round execution time (all chains, from primitive timings)
+    pub rounds: Duration,
+}
+
+impl Breakdown {
+    /// Hashes per second on one thread
+    pub fn hashrate(&self) -> f64 {
+        let secs = self.per_hash.as_secs_f64();
+        if secs > 0.0 { 1.0 / secs } else { 0.0 }
+    }
+
+    /// Time not explained by scratchpad init and round compression:
+    /// scratchpad access, address computation and finalization
+    pub fn overhead(&self) -> Duration {
+        self.per_hash
+            .saturating_sub(self.scratchpad_init + self.rounds)
+    }
+}
+
+impl fmt::Display for Breakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Total per hash:  {:?}", self.per_hash)?;
+        writeln!(f, "Hashrate:        {:.1} H/s", self.hashrate())?;
+        writeln!(f, "Primitives:")?;
+        writeln!(f, "  AES_Compress:    {:?}", self.primitives.aes_compress)?;
+        writeln!(
+            f,
+            "  SHA256_Compress: {:?}",
+            self.primitives.sha256_compress
+        )?;
+        writeln!(
+            f,
+            "  BLAKE3_Compress: {:?}",
+            self.primitives.blake3_compress
+        )?;
+        writeln!(f, "  AES_Expand:      {:?}", self.primitives.aes_expand)?;
+        writeln!(
+            f,
+            "Parameters:      {} rounds x {} chains, {} blocks per scratchpad",
+            ROUNDS, CHAINS, BLOCKS_PER_SCRATCHPAD
+        )?;
+        writeln!(f, "Scratchpad init: {:?}", self.scratchpad_init)?;
+        writeln!(f, "Rounds (est.):   {:?}", self.rounds)?;
+        write!(f, "Overhead:        {:?}", self.overhead())
+    }
+}
+
+/// Time each phase of hashing on the current machine
+///
+/// Takes roughly a dozen hash times. The full hash runs on a
+/// [`UniversalHash::new_sequential`] hasher so that its parts add up.
+pub fn breakdown() -> Breakdown {
+    let mut hasher = UniversalHash::new_sequential();
+    let input = b"timing test input";
+
+    hasher.warm_up();
+
+    let start = Instant::now();
+    for _ in 0..HASH_ITERATIONS {
+        black_box(hasher.hash(black_box(input)));
+    }
+    let per_hash = start.elapsed() / HASH_ITERATIONS;
+
+    let mut scratchpads = vec![vec![0u8; SCRATCHPAD_SIZE]; CHAINS];
+    let seed = [0x5Au8; 32];
+    for scratchpad in &mut scratchpads {
+        fill_scratchpad_aes(scratchpad, &seed);
+    }
+    let start = Instant::now();
+    for _ in 0..INIT_ITERATIONS {
+        for scratchpad in &mut scratchpads {
+            fill_scratchpad_aes(black_box(scratchpad), black_box(&seed));
+        }
+    }
+    let scratchpad_init = start.elapsed() / INIT_ITERATIONS;
+    black_box(&scratchpads);
+
+    let primitives = PrimitiveTimings::measure();
+    Breakdown {
+        per_hash,
+        primitives,
+        scratchpad_init,
+        rounds: primitives.rounds_estimate(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_breakdown() {
+        let breakdown = breakdown();
+        assert!(breakdown.per_hash > Duration::ZERO);
+        assert!(breakdown.scratchpad_init > Duration::ZERO);
+        assert_eq!(breakdown.rounds, breakdown.primitives.rounds_estimate());
+        assert!(breakdown.hashrate() > 0.0);
+        assert!(breakdown.overhead() <= breakdown.per_hash);
+        assert!(breakdown.to_string().contains("Scratchpad init"));
+    }
+}
@@ -0,0 +1,271 @@
+//! Prometheus / OpenMetrics exposition (`prometheus` feature)
+//!
+//! [`MetricsRegistry`] renders the process-wide [`Metrics`] counters and the
+//! proof verification latency histogram in the OpenMetrics text format,
+//! which Prometheus scrapes directly. Serve [`MetricsRegistry::encode`]
+//! from an HTTP `/metrics` endpoint with [`OPENMETRICS_CONTENT_TYPE`]; no
+//! metrics client library is needed.
+//!
+//! ```text
+//! uhash_hashes_total                          counter
+//! uhash_primitive_invocations_total{primitive} counter
+//! uhash_scratchpad_init_seconds_total          counter
+//! uhash_scratchpad_allocations_total           counter
+//! uhash_scratchpad_bytes_total                 counter
+//! uhash_shares_total                           counter
+//! uhash_verifications_total{result}            counter, result = ok | rejected
+//! uhash_verification_seconds                   histogram
+//! ```
+
+use std::fmt::{self, Write};
+
+use crate::metrics::{Metrics, VERIFICATION_BUCKETS, verification_histogram};
+
+/// `Content-Type` header for [`MetricsRegistry::encode`] output
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Scrapeable view of the crate's telemetry
+///
+/// Holds only the metric name prefix and constant labels; every
+/// [`encode`](Self::encode) reads the live counters.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{MetricsRegistry, hash};
+///
+/// let registry = MetricsRegistry::new().with_label("node", "validator-1");
+/// hash(b"input data");
+///
+/// let body = registry.encode();
+/// assert!(body.contains("# TYPE uhash_hashes counter"));
+/// assert!(body.contains("uhash_hashes_total{node=\"validator-1\"}"));
+/// assert!(body.ends_with("# EOF\n"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MetricsRegistry {
+    prefix: String,
+    labels: Vec<(String, String)>,
+}
+
+impl Default for MetricsRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsRegistry {
+    /// Registry with the `uhash` prefix and no constant labels
+    pub fn new() -> Self {
+        Self {
+            prefix: "uhash".into(),
+            labels: Vec::new(),
+        }
+    }
+
+    /// Use `prefix` instead of `uhash` in metric names
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Attach a constant `name="value"` label to every sample
+    pub fn with_label(mut self, name: &str, value: &str) -> Self {
+        self.labels.push((name.into(), value.into()));
+        self
+    }
+
+    /// Render the current counters in the OpenMetrics text format
+    pub fn encode(&self) -> String {
+        let mut out = String::new();
+        self.encode_into(&mut out)
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    /// Render into `out`, as [`encode`](Self::encode)
+    pub fn encode_into<W: Write>(&self, out: &mut W) -> fmt::Result {
+        let metrics = Metrics::snapshot();
+
+        self.counter(
+            out,
+            "hashes",
+            "Hashes computed",
+            &[(&[], metrics.hashes as f64)],
+        )?;
+        self.counter(
+            out,
+            "primitive_invocations",
+            "Compression primitive invocations",
+            &[
+                (&[("primitive", "aes")], metrics.aes_compress as f64),
+                (&[("primitive", "sha256")], metrics.sha256_compress as f64),
+                (&[("primitive", "blake3")], metrics.blake3_compress as f64),
+            ],
+        )?;
+        self.counter(
+            out,
+            "scratchpad_init_seconds",
+            "Time spent initializing scratchpads",
+            &[(&[], metrics.scratchpad_init_time.as_secs_f64())],
+        )?;
+        self.counter(
+            out,
+            "scratchpad_allocations",
+            "Scratchpad allocations",
+            &[(&[], metrics.scratchpad_allocations as f64)],
+        )?;
+        self.counter(
+            out,
+            "scratchpad_bytes",
+            "Bytes allocated for scratchpads",
+            &[(&[], metrics.scratchpad_bytes as f64)],
+        )?;
+        self.counter(
+            out,
+            "shares",
+            "Solutions found by miner workers",
+            &[(&[], metrics.shares as f64)],
+        )?;
+        let rejected = metrics.verification_failures;
+        self.counter(
+            out,
+            "verifications",
+            "Proof verifications",
+            &[
+                (
+                    &[("result", "ok")],
+                    metrics.verifications.saturating_sub(rejected) as f64,
+                ),
+                (&[("result", "rejected")], rejected as f64),
+            ],
+        )?;
+
+        let name = format!("{}_verification_seconds", self.prefix);
+        writeln!(out, "# TYPE {} histogram", name)?;
+        writeln!(out, "# UNIT {} seconds", name)?;
+        writeln!(out, "# HELP {} Proof verification latency", name)?;
+        let counts = verification_histogram();
+        let mut cumulative = 0;
+        for (bound, count) in VERIFICATION_BUCKETS.iter().zip(&counts) {
+            cumulative += count;
+            let le = bound.to_string();
+            self.sample(out, &name, "_bucket", &[("le", &le)], cumulative as f64)?;
+        }
+        // Read separately from the counters, so derive the total from the buckets
+        let total = cumulative + counts[VERIFICATION_BUCKETS.len()];
+        self.sample(out, &name, "_bucket", &[("le", "+Inf")], total as f64)?;
+        self.sample(out, &name, "_count", &[], total as f64)?;
+        self.sample(
+            out,
+            &name,
+            "_sum",
+            &[],
+            metrics.verification_time.as_secs_f64(),
+        )?;
+
+        writeln!(out, "# EOF")
+    }
+
+    /// Write a counter family with one `_total` sample per label set
+    fn counter<W: Write>(
+        &self,
+        out: &mut W,
+        name: &str,
+        help: &str,
+        samples: &[(&[(&str, &str)], f64)],
+    ) -> fmt::Result {
+        let name = format!("{}_{}", self.prefix, name);
+        writeln!(out, "# TYPE {} counter", name)?;
+        if let Some(unit) = ["seconds", "bytes"].iter().find(|u| name.ends_with(*u)) {
+            writeln!(out, "# UNIT {} {}", name, unit)?;
+        }
+        writeln!(out, "# HELP {} {}", name, help)?;
+        for (labels, value) in samples {
+            self.sample(out, &name, "_total", labels, *value)?;
+        }
+        Ok(())
+    }
+
+    /// Write one sample line, constant labels first
+    fn sample<W: Write>(
+        &self,
+        out: &mut W,
+        name: &str,
+        suffix: &str,
+        labels: &[(&str, &str)],
+        value: f64,
+    ) -> fmt::Result {
+        write!(out, "{}{}", name, suffix)?;
+        let constant = self.labels.iter().map(|(n, v)| (n.as_str(), v.as_str()));
+        for (i, (label, value)) in constant.chain(labels.iter().copied()).enumerate() {
+            out.write_char(if i == 0 { '{' } else { ',' })?;
+            write!(out, "{}=\"", label)?;
+            write_escaped(out, value)?;
+            out.write_char('"')?;
+        }
+        if !self.labels.is_empty() || !labels.is_empty() {
+            out.write_char('}')?;
+        }
+        writeln!(out, " {}", value)
+    }
+}
+
+/// Escape a label value: backslash, double quote and newline
+fn write_escaped<W: Write>(out: &mut W, value: &str) -> fmt::Result {
+    for c in value.chars() {
+        match c {
+            '\\' => out.write_str("\\\\")?,
+            '"' => out.write_str("\\\"")?,
+            '\n' => out.write_str("\\n")?,
+            c => out.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::MiningHeader;
+    use crate::uhash::UniversalHash;
+
+    #[test]
+    fn test_encode_openmetrics() {
+        UniversalHash::new()
+            .verify_pow(&MiningHeader::default(), 0)
+            .unwrap();
+        let body = MetricsRegistry::new()
+            .with_prefix("pow")
+            .with_label("node", "a\"b\\c\nd")
+            .encode();
+
+        assert!(body.contains("# TYPE pow_verifications counter\n"));
+        assert!(body.contains("pow_verifications_total{node=\"a\\\"b\\\\c\\nd\",result=\"ok\"} "));
+        assert!(body.contains("# UNIT pow_scratchpad_bytes bytes\n"));
+        assert!(body.contains("# TYPE pow_verification_seconds histogram\n"));
+        assert!(
+            body.contains(
+                "pow_verification_seconds_bucket{node=\"a\\\"b\\\\c\\nd\",le=\"0.0005\"} "
+            )
+        );
+        assert!(body.ends_with("# EOF\n"));
+        assert!(!body.contains("uhash_"));
+
+        // Buckets are cumulative and end at the sample count
+        let bucket_values: Vec<f64> = body
+            .lines()
+            .filter(|line| line.starts_with("pow_verification_seconds_bucket"))
+            .map(|line| line.rsplit(' ').next().unwrap().parse().unwrap())
+            .collect();
+        assert_eq!(bucket_values.len(), VERIFICATION_BUCKETS.len() + 1);
+        assert!(bucket_values.windows(2).all(|w| w[0] <= w[1]));
+        assert!(*bucket_values.last().unwrap() >= 1.0);
+        let count_line = body
+            .lines()
+            .find(|line| line.starts_with("pow_verification_seconds_count"))
+            .unwrap();
+        assert!(count_line.ends_with(&format!(" {}", bucket_values.last().unwrap())));
+    }
+}
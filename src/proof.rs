@@ -0,0 +1,249 @@
+//! Canonical proof-of-work object for share submission and block gossip
+//!
+//! Fixed 100-byte binary encoding:
+//!
+//! ```text
+//! offset  size  field
+//!      0    60  header (epoch_seed || miner_address || timestamp)
+//!     60     8  nonce (little-endian u64)
+//!     68    32  hash
+//! ```
+
+use crate::hash256::Hash256;
+use crate::header::{HEADER_PREFIX_SIZE, HEADER_SIZE, MiningHeader};
+#[cfg(feature = "alloc")]
+use crate::pow::PowError;
+#[cfg(feature = "alloc")]
+use crate::target::Target;
+#[cfg(feature = "alloc")]
+use crate::uhash::UniversalHash;
+
+/// Size of an encoded [`PowProof`] in bytes
+pub const POW_PROOF_SIZE: usize = HEADER_SIZE + 32;
+
+/// A header, the nonce that solves it, and the resulting hash
+///
+/// With the `rkyv` feature, [`ArchivedPowProof`] can be checked and
+/// verified directly inside an archive (e.g. a memory-mapped file) without
+/// deserializing it first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(derive(Debug), compare(PartialEq))
+)]
+pub struct PowProof {
+    /// Header without the nonce (60 bytes)
+    pub header: [u8; HEADER_PREFIX_SIZE],
+    /// Winning nonce
+    pub nonce: u64,
+    /// Claimed UniversalHash of `header || nonce`
+    pub hash: Hash256,
+}
+
+impl PowProof {
+    /// Build a proof from a header (including its nonce) and claimed hash
+    pub fn new(header: &MiningHeader, hash: Hash256) -> Self {
+        Self {
+            header: header.prefix(),
+            nonce: header.nonce,
+            hash,
+        }
+    }
+
+    /// Hash `header` and package the result as a proof
+    #[cfg(feature = "alloc")]
+    pub fn compute(hasher: &mut UniversalHash, header: &MiningHeader) -> Self {
+        Self::new(header, Hash256(hasher.hash(&header.to_bytes())))
+    }
+
+    /// The full mining header this proof commits to
+    pub fn mining_header(&self) -> MiningHeader {
+        MiningHeader::from_prefix(&self.header).with_nonce(self.nonce)
+    }
+
+    /// Encode to the fixed 100-byte layout
+    pub fn to_bytes(&self) -> [u8; POW_PROOF_SIZE] {
+        let mut bytes = [0u8; POW_PROOF_SIZE];
+        bytes[..HEADER_PREFIX_SIZE].copy_from_slice(&self.header);
+        bytes[HEADER_PREFIX_SIZE..HEADER_SIZE].copy_from_slice(&self.nonce.to_le_bytes());
+        bytes[HEADER_SIZE..].copy_from_slice(self.hash.as_bytes());
+        bytes
+    }
+
+    /// Decode from the fixed 100-byte layout
+    pub fn from_bytes(bytes: &[u8; POW_PROOF_SIZE]) -> Self {
+        Self {
+            header: bytes[..HEADER_PREFIX_SIZE].try_into().unwrap(),
+            nonce: u64::from_le_bytes(bytes[HEADER_PREFIX_SIZE..HEADER_SIZE].try_into().unwrap()),
+            hash: Hash256(bytes[HEADER_SIZE..].try_into().unwrap()),
+        }
+    }
+
+    /// Decode from a slice, returning `None` unless it is exactly 100 bytes
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        let bytes: &[u8; POW_PROOF_SIZE] = bytes.try_into().ok()?;
+        Some(Self::from_bytes(bytes))
+    }
+
+    /// Recompute the hash and check it matches the claim and `difficulty`
+    #[cfg(feature = "alloc")]
+    pub fn verify(&self, hasher: &mut UniversalHash, difficulty: u32) -> Result<(), PowError> {
+        let computed = hasher.verify_pow(&self.mining_header(), difficulty)?;
+        if computed != self.hash {
+            return Err(PowError::HashMismatch {
+                claimed: self.hash,
+                computed,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check the claim against `target`, then recompute the hash
+    ///
+    /// A claimed hash above the target is rejected before hashing, so
+    /// invalid proofs cost almost nothing.
+    #[cfg(feature = "alloc")]
+    pub fn verify_target(
+        &self,
+        hasher: &mut UniversalHash,
+        target: &Target,
+    ) -> Result<(), PowError> {
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+        let result = self.check_target(hasher, target);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_verification(started.elapsed(), result.is_ok());
+        result
+    }
+
+    #[cfg(feature = "alloc")]
+    fn check_target(&self, hasher: &mut UniversalHash, target: &Target) -> Result<(), PowError> {
+        if !target.is_met_by(&self.hash) {
+            return Err(PowError::AboveTarget { target: *target });
+        }
+        let computed = Hash256(hasher.hash(&self.mining_header().to_bytes()));
+        if computed != self.hash {
+            return Err(PowError::HashMismatch {
+                claimed: self.hash,
+                computed,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl ArchivedPowProof {
+    /// The full mining header this archived proof commits to
+    pub fn mining_header(&self) -> MiningHeader {
+        MiningHeader::from_prefix(&self.header).with_nonce(self.nonce.to_native())
+    }
+
+    /// Recompute the hash and check it matches the archived claim and
+    /// `difficulty`, as [`PowProof::verify`]
+    #[cfg(feature = "alloc")]
+    pub fn verify(&self, hasher: &mut UniversalHash, difficulty: u32) -> Result<(), PowError> {
+        let computed = hasher.verify_pow(&self.mining_header(), difficulty)?;
+        if computed.as_bytes() != self.hash.as_bytes() {
+            return Err(PowError::HashMismatch {
+                claimed: Hash256(*self.hash.as_bytes()),
+                computed,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+crate::serde_fixed::impl_fixed_serde!(PowProof, POW_PROOF_SIZE, to_bytes, from_bytes);
+
+// Same fixed-size layout, see `codec`
+#[cfg(any(feature = "borsh", feature = "bincode"))]
+crate::codec::impl_fixed_codec!(PowProof, POW_PROOF_SIZE, to_bytes, from_bytes);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_header() -> MiningHeader {
+        MiningHeader {
+            epoch_seed: [3u8; 32],
+            miner_address: [4u8; 20],
+            timestamp: 1_700_000_000,
+            nonce: 99,
+        }
+    }
+
+    #[test]
+    fn test_proof_roundtrip() {
+        let proof = PowProof::new(&sample_header(), Hash256([9u8; 32]));
+        let bytes = proof.to_bytes();
+
+        assert_eq!(&bytes[..HEADER_SIZE], &sample_header().to_bytes());
+        assert_eq!(PowProof::from_bytes(&bytes), proof);
+        assert_eq!(PowProof::from_slice(&bytes[..99]), None);
+        assert_eq!(proof.mining_header(), sample_header());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_proof_verify() {
+        let mut hasher = UniversalHash::new();
+        let proof = PowProof::compute(&mut hasher, &sample_header());
+        assert_eq!(proof.verify(&mut hasher, 0), Ok(()));
+
+        let mut forged = proof;
+        forged.hash.0[31] ^= 1;
+        assert!(matches!(
+            forged.verify(&mut hasher, 0),
+            Err(PowError::HashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_proof_serde_json() {
+        let proof = PowProof::new(&sample_header(), Hash256([9u8; 32]));
+        let json = serde_json::to_string(&proof).unwrap();
+
+        assert_eq!(json.len(), POW_PROOF_SIZE * 2 + 2);
+        assert_eq!(serde_json::from_str::<PowProof>(&json).unwrap(), proof);
+    }
+
+    #[test]
+    #[cfg(feature = "rkyv")]
+    fn test_rkyv_archive_verifies_in_place() {
+        use rkyv::rancor::Error;
+
+        let mut hasher = UniversalHash::new();
+        let proofs: Vec<_> = (0..2)
+            .map(|nonce| PowProof::compute(&mut hasher, &sample_header().with_nonce(nonce)))
+            .collect();
+        let bytes = rkyv::to_bytes::<Error>(&proofs).unwrap();
+
+        let archived = rkyv::access::<rkyv::Archived<Vec<PowProof>>, Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 2);
+        for (archived, proof) in archived.iter().zip(&proofs) {
+            assert_eq!(archived, proof);
+            assert_eq!(archived.mining_header(), proof.mining_header());
+            assert_eq!(archived.hash.as_bytes(), proof.hash.as_bytes());
+            assert_eq!(archived.verify(&mut hasher, 0), Ok(()));
+        }
+
+        // Validation rejects a damaged archive instead of reading garbage
+        assert!(rkyv::access::<rkyv::Archived<Vec<PowProof>>, Error>(&bytes[..8]).is_err());
+
+        let mut forged = proofs[1];
+        forged.hash.0[0] ^= 1;
+        let forged_bytes = rkyv::to_bytes::<Error>(&forged).unwrap();
+        let archived_forged = rkyv::access::<ArchivedPowProof, Error>(&forged_bytes).unwrap();
+        assert!(matches!(
+            archived_forged.verify(&mut hasher, 0),
+            Err(PowError::HashMismatch { .. })
+        ));
+
+        let restored = rkyv::deserialize::<Vec<PowProof>, Error>(archived).unwrap();
+        assert_eq!(restored.len(), 2);
+    }
+}
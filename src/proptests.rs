@@ -0,0 +1,85 @@
+//! Property tests over random inputs
+//!
+//! Each case costs several full hashes, so case counts are kept low; set
+//! `PROPTEST_CASES` to run more, e.g. in a nightly job.
+
+use proptest::prelude::*;
+
+use crate::{
+    HEADER_PREFIX_SIZE, HEADER_SIZE, Job, MiningHeader, SCRATCHPAD_SIZE, UniversalHash,
+    UniversalHashIn, hash, hash_xof, reference,
+};
+
+/// Cases per property unless `PROPTEST_CASES` overrides it
+const CASES: u32 = 8;
+
+/// Inputs from empty through a few header lengths
+fn input() -> impl Strategy<Value = Vec<u8>> {
+    prop::collection::vec(any::<u8>(), 0..160)
+}
+
+fn config() -> ProptestConfig {
+    ProptestConfig::with_cases(
+        std::env::var("PROPTEST_CASES")
+            .ok()
+            .and_then(|cases| cases.parse().ok())
+            .unwrap_or(CASES),
+    )
+}
+
+proptest! {
+    #![proptest_config(config())]
+
+    #[test]
+    fn prop_deterministic_across_hashers(a in input(), b in input()) {
+        let expected = hash(&a);
+        prop_assert_eq!(hash(&a), expected);
+
+        // A reused hasher carries nothing over from the previous input
+        let mut hasher = UniversalHash::new();
+        hasher.hash(&b);
+        prop_assert_eq!(hasher.hash(&a), expected);
+    }
+
+    #[test]
+    fn prop_xof_prefix_is_hash(input in input(), len in 0usize..200) {
+        let mut out = vec![0u8; len];
+        hash_xof(&input, &mut out);
+        let expected = hash(&input);
+        let shared = len.min(32);
+        prop_assert_eq!(&out[..shared], &expected[..shared]);
+    }
+
+    #[test]
+    fn prop_single_bit_avalanche(input in prop::collection::vec(any::<u8>(), 1..100), bit in any::<usize>()) {
+        let mut flipped = input.clone();
+        let bit = bit % (input.len() * 8);
+        flipped[bit / 8] ^= 1 << (bit % 8);
+
+        let (a, b) = (hash(&input), hash(&flipped));
+        let changed: u32 = a.iter().zip(&b).map(|(x, y)| (x ^ y).count_ones()).sum();
+        // 128 ± 64 is eight standard deviations for a random 256-bit output
+        prop_assert!((64..=192).contains(&changed), "{} bits changed", changed);
+    }
+
+    #[test]
+    fn prop_backends_agree(input in input()) {
+        let expected = reference::hash(&input);
+        prop_assert_eq!(hash(&input), expected);
+        prop_assert_eq!(UniversalHash::new_sequential().hash(&input), expected);
+
+        let mut buffer = vec![0u8; SCRATCHPAD_SIZE];
+        let mut hasher_in = UniversalHashIn::new_in(&mut buffer).unwrap();
+        prop_assert_eq!(hasher_in.hash(&input), expected);
+    }
+
+    #[test]
+    fn prop_midstate_matches_header(bytes in prop::collection::vec(any::<u8>(), HEADER_SIZE)) {
+        let header = MiningHeader::try_from_slice(&bytes).unwrap();
+        let job = Job::from_header(&bytes[..HEADER_PREFIX_SIZE]);
+        prop_assert_eq!(
+            UniversalHash::new().hash_nonce(&job, header.nonce),
+            hash(&header.to_bytes())
+        );
+    }
+}
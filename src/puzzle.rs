@@ -0,0 +1,654 @@
+//! Hashcash-style client puzzles for anti-spam and anti-DoS
+//!
+//! A service hands each client a fresh random challenge and admits the
+//! request only once the client presents a nonce whose hash has enough
+//! leading zero bits. The memory-hard hash keeps GPU farms from solving
+//! puzzles much faster than the phones and laptops of real users.
+//!
+//! ```text
+//! key      = BLAKE3_derive_key(PUZZLE_CONTEXT, "")
+//! solution = nonce such that leading_zeros(hash_keyed(key, challenge || nonce_le)) >= difficulty
+//! ```
+//!
+//! Keyed hashing keeps puzzle work useless for mining and vice versa. The
+//! server keeps the issued [`Puzzle`] (e.g. in the session store, keyed by
+//! its challenge) and calls [`Puzzle::verify`], which rejects expired
+//! puzzles and accepts each puzzle's solution only once. Servers that
+//! would rather not store puzzles use a [`ChallengeIssuer`], whose
+//! challenges are salted, bound to a client identifier and authenticated
+//! under a server secret.
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use uhash_core::puzzle::{Puzzle, PuzzleError};
+//!
+//! // Server
+//! let puzzle = Puzzle::issue(4, Duration::from_secs(60));
+//!
+//! // Client, given the challenge and difficulty
+//! let solution = Puzzle::new(*puzzle.challenge(), puzzle.difficulty(), puzzle.expires_at())?.solve();
+//!
+//! // Server
+//! assert!(puzzle.verify(&solution).is_ok());
+//! assert_eq!(puzzle.verify(&solution), Err(PuzzleError::AlreadySolved));
+//! # Ok::<(), PuzzleError>(())
+//! ```
+
+use core::fmt;
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+
+use crate::context::with_hasher;
+use crate::ct;
+use crate::hash256::Hash256;
+use crate::params::NONCE_SIZE;
+use crate::uhash::{UniversalHash, meets_difficulty};
+
+/// BLAKE3 key-derivation context for the puzzle hashing key
+pub const PUZZLE_CONTEXT: &str = "UniversalHash v4 2026-10-16 client puzzle";
+
+/// BLAKE3 key-derivation context for [`ChallengeIssuer`] tag keys
+pub const CHALLENGE_CONTEXT: &str = "UniversalHash v4 2026-10-16 salted challenge";
+
+/// Highest puzzle difficulty: every bit of the hash is zero
+pub const MAX_DIFFICULTY: u32 = 256;
+
+/// Bytes of a puzzle challenge
+pub const CHALLENGE_SIZE: usize = 32;
+
+/// Bytes of a [`Challenge`] salt
+pub const SALT_SIZE: usize = 16;
+
+/// Bytes of an encoded [`Challenge`]
+pub const ENCODED_CHALLENGE_SIZE: usize = SALT_SIZE + 8 + 4 + CHALLENGE_SIZE;
+
+/// Reason a puzzle solution was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PuzzleError {
+    /// The puzzle's time to live has run out
+    Expired,
+    /// A solution to this puzzle was already accepted
+    AlreadySolved,
+    /// Challenge was not issued by this server to this client, or was
+    /// altered in transit
+    InvalidChallenge,
+    /// Difficulty exceeds [`MAX_DIFFICULTY`], so no nonce can meet it
+    DifficultyTooHigh {
+        /// Difficulty the puzzle asked for
+        difficulty: u32,
+    },
+    /// Hash has fewer leading zero bits than required
+    ///
+    /// Deliberately silent on how many it has, which would tell a
+    /// submitter how close an attempt came.
+    InsufficientWork {
+        /// Required leading zero bits
+        difficulty: u32,
+    },
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PuzzleError::Expired => write!(f, "puzzle has expired"),
+            PuzzleError::AlreadySolved => write!(f, "puzzle was already solved"),
+            PuzzleError::InvalidChallenge => write!(f, "challenge is not valid for this client"),
+            PuzzleError::DifficultyTooHigh { difficulty } => write!(
+                f,
+                "puzzle difficulty {} exceeds {} bits",
+                difficulty, MAX_DIFFICULTY
+            ),
+            PuzzleError::InsufficientWork { difficulty } => write!(
+                f,
+                "hash has fewer than the {} leading zero bits the puzzle requires",
+                difficulty
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PuzzleError {}
+
+/// A client's answer to a [`Puzzle`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PuzzleSolution {
+    /// Nonce appended to the challenge
+    pub nonce: u64,
+}
+
+/// A proof-of-work challenge with an expiry time
+#[derive(Debug)]
+pub struct Puzzle {
+    challenge: [u8; CHALLENGE_SIZE],
+    difficulty: u32,
+    expires_at: SystemTime,
+    solved: AtomicBool,
+}
+
+impl Puzzle {
+    /// Issue a puzzle with a random challenge, valid for `ttl` from now
+    ///
+    /// `difficulty` is in leading zero bits; each bit doubles the expected
+    /// client work, which starts at one hash for difficulty 0.
+    ///
+    /// # Panics
+    ///
+    /// If `difficulty` exceeds [`MAX_DIFFICULTY`], which no hash can meet,
+    /// or the operating system's random number generator fails.
+    pub fn issue(difficulty: u32, ttl: Duration) -> Self {
+        let mut challenge = [0u8; CHALLENGE_SIZE];
+        getrandom::fill(&mut challenge).expect("OS random number generator failed");
+        Self::new(challenge, difficulty, SystemTime::now() + ttl)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// A puzzle from its parts, as a client rebuilds one it was sent
+    ///
+    /// Fails with [`PuzzleError::DifficultyTooHigh`] above
+    /// [`MAX_DIFFICULTY`], so a client never starts an endless search.
+    pub fn new(
+        challenge: [u8; CHALLENGE_SIZE],
+        difficulty: u32,
+        expires_at: SystemTime,
+    ) -> Result<Self, PuzzleError> {
+        check_difficulty(difficulty)?;
+        Ok(Puzzle {
+            challenge,
+            difficulty,
+            expires_at,
+            solved: AtomicBool::new(false),
+        })
+    }
+
+    /// Random challenge the solution is bound to
+    pub fn challenge(&self) -> &[u8; CHALLENGE_SIZE] {
+        &self.challenge
+    }
+
+    /// Required leading zero bits
+    pub fn difficulty(&self) -> u32 {
+        self.difficulty
+    }
+
+    /// Time after which solutions are rejected
+    pub fn expires_at(&self) -> SystemTime {
+        self.expires_at
+    }
+
+    /// Search nonces from zero until one meets the difficulty
+    pub fn solve(&self) -> PuzzleSolution {
+        solve(&self.challenge, self.difficulty)
+    }
+
+    /// Check `solution` now and mark the puzzle solved if it is accepted
+    pub fn verify(&self, solution: &PuzzleSolution) -> Result<Hash256, PuzzleError> {
+        self.verify_at(solution, SystemTime::now())
+    }
+
+    /// [`verify`](Self::verify) as of `now`
+    ///
+    /// Wrong solutions leave the puzzle open, so a client can retry until
+    /// it expires. Of concurrent correct submissions exactly one succeeds.
+    pub fn verify_at(
+        &self,
+        solution: &PuzzleSolution,
+        now: SystemTime,
+    ) -> Result<Hash256, PuzzleError> {
+        if now >= self.expires_at {
+            return Err(PuzzleError::Expired);
+        }
+        if self.solved.load(Ordering::Acquire) {
+            return Err(PuzzleError::AlreadySolved);
+        }
+        let hash = check_work(&self.challenge, self.difficulty, solution)?;
+        if self.solved.swap(true, Ordering::AcqRel) {
+            return Err(PuzzleError::AlreadySolved);
+        }
+        Ok(hash)
+    }
+}
+
+/// Server-salted challenge bound to one client
+///
+/// Issued by a [`ChallengeIssuer`] and sent to the client as
+/// [`to_bytes`](Self::to_bytes) (or through serde). The server keeps no
+/// per-challenge state until a solution arrives: the tag authenticates the
+/// salt, expiry and difficulty under the issuer's secret together with the
+/// client identifier, and it is the value the client's work is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Challenge {
+    /// Random per-challenge salt chosen by the server
+    pub salt: [u8; SALT_SIZE],
+    /// Expiry in seconds since the Unix epoch
+    pub expires_at: u64,
+    /// Required leading zero bits
+    pub difficulty: u32,
+    /// Keyed BLAKE3 over the fields above and the client identifier
+    pub tag: [u8; CHALLENGE_SIZE],
+}
+
+impl Challenge {
+    /// Encode as `salt || expires_at (LE) || difficulty (LE) || tag`
+    pub fn to_bytes(&self) -> [u8; ENCODED_CHALLENGE_SIZE] {
+        let mut bytes = [0u8; ENCODED_CHALLENGE_SIZE];
+        bytes[..16].copy_from_slice(&self.salt);
+        bytes[16..24].copy_from_slice(&self.expires_at.to_le_bytes());
+        bytes[24..28].copy_from_slice(&self.difficulty.to_le_bytes());
+        bytes[28..].copy_from_slice(&self.tag);
+        bytes
+    }
+
+    /// Decode the layout written by [`to_bytes`](Self::to_bytes)
+    ///
+    /// Fails with [`PuzzleError::DifficultyTooHigh`] above
+    /// [`MAX_DIFFICULTY`]; the tag is only checked by the issuer.
+    pub fn from_bytes(bytes: &[u8; ENCODED_CHALLENGE_SIZE]) -> Result<Self, PuzzleError> {
+        let difficulty = u32::from_le_bytes(bytes[24..28].try_into().unwrap());
+        check_difficulty(difficulty)?;
+        Ok(Challenge {
+            salt: bytes[..16].try_into().unwrap(),
+            expires_at: u64::from_le_bytes(bytes[16..24].try_into().unwrap()),
+            difficulty,
+            tag: bytes[28..].try_into().unwrap(),
+        })
+    }
+
+    /// Search nonces from zero until one meets the difficulty
+    ///
+    /// Fails with [`PuzzleError::DifficultyTooHigh`] above
+    /// [`MAX_DIFFICULTY`] instead of searching forever.
+    pub fn solve(&self) -> Result<PuzzleSolution, PuzzleError> {
+        check_difficulty(self.difficulty)?;
+        Ok(solve(&self.tag, self.difficulty))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Challenge {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serde_fixed::serialize(&self.to_bytes(), serializer)
+    }
+}
+
+// The fixed-size encoding, rejecting the same difficulties as `from_bytes`
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Challenge {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = crate::serde_fixed::deserialize::<D, ENCODED_CHALLENGE_SIZE>(deserializer)?;
+        Challenge::from_bytes(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Issues and verifies [`Challenge`]s under a server secret
+///
+/// Suited to rate-limiting proxies: challenges carry their own
+/// authenticated state, so issuing stores nothing, and a challenge solved
+/// for one client identifier (an IP address, account or API key) is
+/// useless to any other. Each challenge is accepted once; accepted salts
+/// are remembered until their challenge expires.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use uhash_core::puzzle::{ChallengeIssuer, PuzzleError};
+///
+/// let issuer = ChallengeIssuer::new([0x42; 32], 4, Duration::from_secs(60));
+/// let challenge = issuer.issue(b"203.0.113.7");
+///
+/// let solution = challenge.solve().unwrap();
+/// assert_eq!(
+///     issuer.verify(b"198.51.100.1", &challenge, &solution),
+///     Err(PuzzleError::InvalidChallenge)
+/// );
+/// assert!(issuer.verify(b"203.0.113.7", &challenge, &solution).is_ok());
+/// ```
+#[derive(Debug)]
+pub struct ChallengeIssuer {
+    key: [u8; 32],
+    difficulty: u32,
+    ttl: Duration,
+    spent: Mutex<SpentSalts>,
+}
+
+/// Salts of accepted challenges, oldest expiry first
+#[derive(Debug, Default)]
+struct SpentSalts {
+    salts: HashSet<[u8; SALT_SIZE]>,
+    expiries: VecDeque<(u64, [u8; SALT_SIZE])>,
+}
+
+impl ChallengeIssuer {
+    /// Issuer with a server `secret`, issuing challenges of `difficulty`
+    /// leading zero bits that stay valid for `ttl`
+    ///
+    /// All servers verifying each other's challenges must share `secret`;
+    /// rotating it invalidates every outstanding challenge.
+    ///
+    /// # Panics
+    ///
+    /// If `difficulty` exceeds [`MAX_DIFFICULTY`], which no hash can meet.
+    pub fn new(secret: [u8; 32], difficulty: u32, ttl: Duration) -> Self {
+        check_difficulty(difficulty).unwrap_or_else(|e| panic!("{}", e));
+        ChallengeIssuer {
+            key: blake3::derive_key(CHALLENGE_CONTEXT, &secret),
+            difficulty,
+            ttl,
+            spent: Mutex::new(SpentSalts::default()),
+        }
+    }
+
+    /// Issue a challenge for `client_id` with a random salt
+    ///
+    /// # Panics
+    ///
+    /// If the operating system's random number generator fails.
+    pub fn issue(&self, client_id: &[u8]) -> Challenge {
+        self.issue_at(client_id, SystemTime::now())
+    }
+
+    /// [`issue`](Self::issue) as of `now`
+    pub fn issue_at(&self, client_id: &[u8], now: SystemTime) -> Challenge {
+        let mut salt = [0u8; SALT_SIZE];
+        getrandom::fill(&mut salt).expect("OS random number generator failed");
+        let expires_at = unix_seconds(now).saturating_add(self.ttl.as_secs());
+        Challenge {
+            salt,
+            expires_at,
+            difficulty: self.difficulty,
+            tag: self.tag(client_id, &salt, expires_at, self.difficulty),
+        }
+    }
+
+    /// Check that `challenge` was issued to `client_id`, is unexpired and
+    /// unused, and that `solution` meets its difficulty
+    pub fn verify(
+        &self,
+        client_id: &[u8],
+        challenge: &Challenge,
+        solution: &PuzzleSolution,
+    ) -> Result<Hash256, PuzzleError> {
+        self.verify_at(client_id, challenge, solution, SystemTime::now())
+    }
+
+    /// [`verify`](Self::verify) as of `now`
+    ///
+    /// The tag is compared in constant time, so probing the server reveals
+    /// nothing about the tag a forged challenge would need.
+    pub fn verify_at(
+        &self,
+        client_id: &[u8],
+        challenge: &Challenge,
+        solution: &PuzzleSolution,
+        now: SystemTime,
+    ) -> Result<Hash256, PuzzleError> {
+        let expected = self.tag(
+            client_id,
+            &challenge.salt,
+            challenge.expires_at,
+            challenge.difficulty,
+        );
+        if !ct::eq(&expected, &challenge.tag) {
+            return Err(PuzzleError::InvalidChallenge);
+        }
+        let now = unix_seconds(now);
+        if now >= challenge.expires_at {
+            return Err(PuzzleError::Expired);
+        }
+        let hash = check_work(&challenge.tag, challenge.difficulty, solution)?;
+
+        let mut spent = self.spent.lock().unwrap_or_else(|e| e.into_inner());
+        while let Some(&(expiry, salt)) = spent.expiries.front() {
+            if expiry > now {
+                break;
+            }
+            spent.expiries.pop_front();
+            spent.salts.remove(&salt);
+        }
+        if !spent.salts.insert(challenge.salt) {
+            return Err(PuzzleError::AlreadySolved);
+        }
+        spent
+            .expiries
+            .push_back((challenge.expires_at, challenge.salt));
+        Ok(hash)
+    }
+
+    /// MAC binding the challenge fields to `client_id`
+    fn tag(
+        &self,
+        client_id: &[u8],
+        salt: &[u8; SALT_SIZE],
+        expires_at: u64,
+        difficulty: u32,
+    ) -> [u8; CHALLENGE_SIZE] {
+        let mut hasher = blake3::Hasher::new_keyed(&self.key);
+        hasher.update(salt);
+        hasher.update(&expires_at.to_le_bytes());
+        hasher.update(&difficulty.to_le_bytes());
+        // Last and only variable-length field, so no length prefix needed
+        hasher.update(client_id);
+        hasher.finalize().into()
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+/// Reject difficulties no hash can meet
+fn check_difficulty(difficulty: u32) -> Result<(), PuzzleError> {
+    if difficulty > MAX_DIFFICULTY {
+        return Err(PuzzleError::DifficultyTooHigh { difficulty });
+    }
+    Ok(())
+}
+
+/// First nonce, counting from zero, whose puzzle hash meets `difficulty`
+fn solve(challenge: &[u8; CHALLENGE_SIZE], difficulty: u32) -> PuzzleSolution {
+    let key = puzzle_key();
+    let nonce = with_hasher(|hasher| {
+        (0..=u64::MAX).find(|&nonce| {
+            let hash = puzzle_hash(hasher, &key, challenge, nonce);
+            meets_difficulty(&hash, difficulty)
+        })
+    })
+    .expect("no nonce meets the difficulty");
+    PuzzleSolution { nonce }
+}
+
+/// Puzzle hash of `solution`, if it meets `difficulty`
+fn check_work(
+    challenge: &[u8; CHALLENGE_SIZE],
+    difficulty: u32,
+    solution: &PuzzleSolution,
+) -> Result<Hash256, PuzzleError> {
+    let hash = Hash256(with_hasher(|hasher| {
+        puzzle_hash(hasher, &puzzle_key(), challenge, solution.nonce)
+    }));
+    if !ct::has_leading_zeros(hash.as_bytes(), difficulty) {
+        return Err(PuzzleError::InsufficientWork { difficulty });
+    }
+    Ok(hash)
+}
+
+/// Key separating puzzle hashes from mining hashes
+fn puzzle_key() -> [u8; 32] {
+    blake3::derive_key(PUZZLE_CONTEXT, b"")
+}
+
+/// Keyed hash of `challenge || nonce`
+fn puzzle_hash(
+    hasher: &mut UniversalHash,
+    key: &[u8; 32],
+    challenge: &[u8; CHALLENGE_SIZE],
+    nonce: u64,
+) -> [u8; 32] {
+    let mut input = [0u8; CHALLENGE_SIZE + NONCE_SIZE];
+    input[..CHALLENGE_SIZE].copy_from_slice(challenge);
+    input[CHALLENGE_SIZE..].copy_from_slice(&nonce.to_le_bytes());
+    hasher.hash_keyed(key, &input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_solve_verify() {
+        let puzzle = Puzzle::issue(3, Duration::from_secs(60));
+        assert_ne!(
+            puzzle.challenge(),
+            Puzzle::issue(3, Duration::from_secs(60)).challenge()
+        );
+
+        let solution = puzzle.solve();
+        let hash = puzzle.verify(&solution).unwrap();
+        assert!(hash.leading_zeros() >= 3);
+        assert_eq!(puzzle.verify(&solution), Err(PuzzleError::AlreadySolved));
+    }
+
+    #[test]
+    fn test_rejections_leave_puzzle_open() {
+        let puzzle = Puzzle::new(
+            [7; CHALLENGE_SIZE],
+            4,
+            SystemTime::now() + Duration::from_secs(60),
+        )
+        .unwrap();
+        let solution = puzzle.solve();
+
+        assert_eq!(
+            puzzle.verify_at(&solution, puzzle.expires_at()),
+            Err(PuzzleError::Expired)
+        );
+        // Nonces below the first solution all fall short
+        if solution.nonce > 0 {
+            assert!(matches!(
+                puzzle.verify(&PuzzleSolution { nonce: 0 }),
+                Err(PuzzleError::InsufficientWork { difficulty: 4 })
+            ));
+        }
+        assert!(puzzle.verify(&solution).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds 256 bits")]
+    fn test_issue_rejects_unsolvable_difficulty() {
+        Puzzle::issue(MAX_DIFFICULTY + 1, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_client_rejects_unsolvable_difficulty() {
+        let too_high = Err(PuzzleError::DifficultyTooHigh {
+            difficulty: MAX_DIFFICULTY + 1,
+        });
+        let expires_at = SystemTime::now() + Duration::from_secs(60);
+        assert!(Puzzle::new([7; CHALLENGE_SIZE], MAX_DIFFICULTY, expires_at).is_ok());
+        assert_eq!(
+            Puzzle::new([7; CHALLENGE_SIZE], MAX_DIFFICULTY + 1, expires_at).err(),
+            too_high.err()
+        );
+
+        let challenge = Challenge {
+            salt: [0; SALT_SIZE],
+            expires_at: u64::MAX,
+            difficulty: MAX_DIFFICULTY + 1,
+            tag: [0; CHALLENGE_SIZE],
+        };
+        assert_eq!(Challenge::from_bytes(&challenge.to_bytes()), too_high);
+        assert_eq!(challenge.solve().err(), too_high.err());
+    }
+
+    #[test]
+    fn test_puzzle_hash_is_not_mining_hash() {
+        let challenge = [1; CHALLENGE_SIZE];
+        let mut input = challenge.to_vec();
+        input.extend_from_slice(&5u64.to_le_bytes());
+        let mut hasher = UniversalHash::new();
+        assert_ne!(
+            puzzle_hash(&mut hasher, &puzzle_key(), &challenge, 5),
+            hasher.hash(&input)
+        );
+    }
+
+    #[test]
+    fn test_challenge_binding() {
+        let issuer = ChallengeIssuer::new([9; 32], 2, Duration::from_secs(30));
+        let now = SystemTime::now();
+        let challenge = issuer.issue_at(b"client-a", now);
+        assert_eq!(Challenge::from_bytes(&challenge.to_bytes()), Ok(challenge));
+        let solution = challenge.solve().unwrap();
+
+        // Another client, another server secret, or any altered field
+        assert_eq!(
+            issuer.verify_at(b"client-b", &challenge, &solution, now),
+            Err(PuzzleError::InvalidChallenge)
+        );
+        let other = ChallengeIssuer::new([8; 32], 2, Duration::from_secs(30));
+        assert_eq!(
+            other.verify_at(b"client-a", &challenge, &solution, now),
+            Err(PuzzleError::InvalidChallenge)
+        );
+        for tampered in [
+            Challenge {
+                difficulty: 0,
+                ..challenge
+            },
+            Challenge {
+                expires_at: u64::MAX,
+                ..challenge
+            },
+            Challenge {
+                salt: [0; SALT_SIZE],
+                ..challenge
+            },
+        ] {
+            assert_eq!(
+                issuer.verify_at(b"client-a", &tampered, &solution, now),
+                Err(PuzzleError::InvalidChallenge)
+            );
+        }
+
+        let late = now + Duration::from_secs(31);
+        assert_eq!(
+            issuer.verify_at(b"client-a", &challenge, &solution, late),
+            Err(PuzzleError::Expired)
+        );
+        assert!(
+            issuer
+                .verify_at(b"client-a", &challenge, &solution, now)
+                .is_ok()
+        );
+        assert_eq!(
+            issuer.verify_at(b"client-a", &challenge, &solution, now),
+            Err(PuzzleError::AlreadySolved)
+        );
+    }
+
+    #[test]
+    fn test_spent_salts_pruned_after_expiry() {
+        let issuer = ChallengeIssuer::new([9; 32], 0, Duration::from_secs(10));
+        let start = SystemTime::now();
+        let first = issuer.issue_at(b"client", start);
+        let solution = PuzzleSolution { nonce: 0 };
+        assert!(
+            issuer
+                .verify_at(b"client", &first, &solution, start)
+                .is_ok()
+        );
+
+        let later = start + Duration::from_secs(20);
+        let second = issuer.issue_at(b"client", later);
+        assert!(
+            issuer
+                .verify_at(b"client", &second, &solution, later)
+                .is_ok()
+        );
+        let spent = issuer.spent.lock().unwrap();
+        assert_eq!(spent.salts.len(), 1);
+        assert!(spent.salts.contains(&second.salt));
+    }
+}
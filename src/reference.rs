@@ -0,0 +1,465 @@
+//! Reference implementation of UniversalHash v4
+//!
+//! A direct transcription of the specification, written to be read rather
+//! than to be fast: no intrinsics, no unrolling, the AES S-box computed from
+//! its field definition, one chain at a time. The optimized paths in this
+//! crate are tested against it.
+//!
+//! ```rust
+//! use uhash_core::{hash, reference};
+//!
+//! assert_eq!(reference::hash(b"input data"), hash(b"input data"));
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+
+use sha2::{Digest, Sha256};
+
+use crate::params::{
+    BLOCK_SIZE, BLOCKS_PER_SCRATCHPAD, CHAINS, NONCE_SIZE, ROUNDS, SCRATCHPAD_SIZE,
+};
+
+/// Golden ratio constant used to separate chain seeds
+const GOLDEN_RATIO: u64 = 0x9E3779B97F4A7C15;
+
+/// Multiplier mixing the round number into the scratchpad address
+const ADDRESS_MULTIPLIER: u64 = 0x517cc1b727220a95;
+
+/// Compute UniversalHash v4 of `input`
+///
+/// The nonce is the last 8 bytes of `input` (little-endian); shorter inputs
+/// take it from the first 8 bytes of BLAKE3(input).
+pub fn hash(input: &[u8]) -> [u8; 32] {
+    let (header, nonce) = if input.len() >= NONCE_SIZE {
+        let (header, nonce) = input.split_at(input.len() - NONCE_SIZE);
+        (header, u64::from_le_bytes(nonce.try_into().unwrap()))
+    } else {
+        let digest = blake3::hash(input);
+        (
+            &[][..],
+            u64::from_le_bytes(digest.as_bytes()[..8].try_into().unwrap()),
+        )
+    };
+
+    let mut combined = [0u8; 32];
+    for chain in 0..CHAINS {
+        let state = run_chain(header, nonce, chain);
+        for i in 0..32 {
+            combined[i] ^= state[i];
+        }
+    }
+
+    let sha = Sha256::digest(combined);
+    *blake3::hash(&sha).as_bytes()
+}
+
+/// Seed, fill and run one chain, returning its final state
+fn run_chain(header: &[u8], nonce: u64, chain: usize) -> [u8; 32] {
+    // seed = BLAKE3(header || (nonce XOR chain * golden_ratio))
+    let chain_nonce = nonce ^ (chain as u64).wrapping_mul(GOLDEN_RATIO);
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(header);
+    hasher.update(&chain_nonce.to_le_bytes());
+    let seed: [u8; 32] = *hasher.finalize().as_bytes();
+
+    let mut scratchpad = vec![0u8; SCRATCHPAD_SIZE];
+    fill_scratchpad(&mut scratchpad, &seed);
+
+    // primitive = (nonce + chain) mod 3, incremented before every round
+    let mut primitive = (nonce.wrapping_add(chain as u64) % 3) as usize;
+    let mut state = seed;
+    for round in 0..ROUNDS {
+        let address = address(&state, round);
+        let block: [u8; BLOCK_SIZE] = scratchpad[address..address + BLOCK_SIZE]
+            .try_into()
+            .unwrap();
+
+        primitive = (primitive + 1) % 3;
+        state = match primitive {
+            0 => aes_compress(&state, &block),
+            1 => sha256_compress(&state, &block),
+            _ => blake3_compress(&state, &block),
+        };
+
+        // The new state goes back where the block was read from
+        scratchpad[address..address + 32].copy_from_slice(&state);
+    }
+    state
+}
+
+/// Fill a scratchpad from a chain seed
+///
+/// With key = seed[0..16] and x = seed[16..32], every block is
+/// `x' || E(x') || x' || E(x')` where `x' = E(x)` and E is [`aes_expand`].
+fn fill_scratchpad(scratchpad: &mut [u8], seed: &[u8; 32]) {
+    let key: [u8; 16] = seed[..16].try_into().unwrap();
+    let mut x: [u8; 16] = seed[16..].try_into().unwrap();
+
+    for block in scratchpad.chunks_exact_mut(BLOCK_SIZE) {
+        x = aes_expand(&x, &key);
+        let y = aes_expand(&x, &key);
+        block[0..16].copy_from_slice(&x);
+        block[16..32].copy_from_slice(&y);
+        block[32..48].copy_from_slice(&x);
+        block[48..64].copy_from_slice(&y);
+    }
+}
+
+/// Scratchpad byte offset read in `round`
+fn address(state: &[u8; 32], round: usize) -> usize {
+    let lo = u64::from_le_bytes(state[0..8].try_into().unwrap());
+    let hi = u64::from_le_bytes(state[8..16].try_into().unwrap());
+    let r = round as u64;
+    let mixed = lo ^ hi ^ r.rotate_left(13) ^ r.wrapping_mul(ADDRESS_MULTIPLIER);
+    (mixed % BLOCKS_PER_SCRATCHPAD as u64) as usize * BLOCK_SIZE
+}
+
+// ---------------------------------------------------------------------------
+// AES
+// ---------------------------------------------------------------------------
+
+/// Four AESENC rounds of `state` with the same round key (scratchpad fill)
+pub fn aes_expand(state: &[u8; 16], key: &[u8; 16]) -> [u8; 16] {
+    let mut s = *state;
+    for _ in 0..4 {
+        s = aesenc(&s, key);
+    }
+    s
+}
+
+/// AES_Compress: four AESENC rounds on each 16-byte half of `state`
+///
+/// The low half uses the block's 16-byte words 0, 1, 2, 3 as round keys,
+/// the high half uses 2, 3, 0, 1.
+pub fn aes_compress(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+    let key = |i: usize| -> [u8; 16] { block[i * 16..i * 16 + 16].try_into().unwrap() };
+
+    let mut lo: [u8; 16] = state[..16].try_into().unwrap();
+    let mut hi: [u8; 16] = state[16..].try_into().unwrap();
+    for i in [0, 1, 2, 3] {
+        lo = aesenc(&lo, &key(i));
+    }
+    for i in [2, 3, 0, 1] {
+        hi = aesenc(&hi, &key(i));
+    }
+
+    let mut out = [0u8; 32];
+    out[..16].copy_from_slice(&lo);
+    out[16..].copy_from_slice(&hi);
+    out
+}
+
+/// One AES encryption round, as the x86 AESENC instruction
+///
+/// ShiftRows, SubBytes, MixColumns, then XOR with the round key. The state
+/// is column-major: byte `4 * column + row`.
+fn aesenc(state: &[u8; 16], round_key: &[u8; 16]) -> [u8; 16] {
+    let mut shifted = [0u8; 16];
+    for column in 0..4 {
+        for row in 0..4 {
+            shifted[4 * column + row] = state[4 * ((column + row) % 4) + row];
+        }
+    }
+
+    let substituted = shifted.map(|b| SBOX[b as usize]);
+
+    let mut out = [0u8; 16];
+    for column in 0..4 {
+        let a = &substituted[4 * column..4 * column + 4];
+        for row in 0..4 {
+            // Row `row` of the circulant matrix [2 3 1 1]
+            out[4 * column + row] = gf_mul(2, a[row])
+                ^ gf_mul(3, a[(row + 1) % 4])
+                ^ a[(row + 2) % 4]
+                ^ a[(row + 3) % 4];
+        }
+    }
+
+    for i in 0..16 {
+        out[i] ^= round_key[i];
+    }
+    out
+}
+
+/// AES S-box, derived from its definition at compile time
+const SBOX: [u8; 256] = build_sbox();
+
+/// S(x) = affine(x^-1) in GF(2^8), with 0^-1 taken as 0
+const fn build_sbox() -> [u8; 256] {
+    let mut sbox = [0u8; 256];
+    let mut x = 0;
+    while x < 256 {
+        // x^254 is the multiplicative inverse (and 0 for x = 0)
+        let b = gf_pow(x as u8, 254);
+        sbox[x] =
+            b ^ b.rotate_left(1) ^ b.rotate_left(2) ^ b.rotate_left(3) ^ b.rotate_left(4) ^ 0x63;
+        x += 1;
+    }
+    sbox
+}
+
+/// `base^exponent` in GF(2^8) by square-and-multiply
+const fn gf_pow(mut base: u8, mut exponent: u32) -> u8 {
+    let mut result = 1;
+    while exponent != 0 {
+        if exponent & 1 != 0 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1
+const fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+// ---------------------------------------------------------------------------
+// SHA-256
+// ---------------------------------------------------------------------------
+
+/// SHA-256 round constants
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA256_Compress: the FIPS 180-4 compression function
+///
+/// `state` is the eight working words and `block` the sixteen message
+/// words, all big-endian; no padding or length is involved.
+pub fn sha256_compress(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+    let h: [u32; 8] = core::array::from_fn(|i| be_word(state, i));
+
+    let mut w = [0u32; 64];
+    for t in 0..64 {
+        w[t] = if t < 16 {
+            be_word(block, t)
+        } else {
+            let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+            let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+            w[t - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[t - 7])
+                .wrapping_add(s1)
+        };
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+    for t in 0..64 {
+        let big_s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = hh
+            .wrapping_add(big_s1)
+            .wrapping_add(ch)
+            .wrapping_add(SHA256_K[t])
+            .wrapping_add(w[t]);
+        let big_s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = big_s0.wrapping_add(maj);
+
+        hh = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    let out = [a, b, c, d, e, f, g, hh];
+    let mut result = [0u8; 32];
+    for i in 0..8 {
+        result[4 * i..4 * i + 4].copy_from_slice(&h[i].wrapping_add(out[i]).to_be_bytes());
+    }
+    result
+}
+
+fn be_word(bytes: &[u8], i: usize) -> u32 {
+    u32::from_be_bytes(bytes[4 * i..4 * i + 4].try_into().unwrap())
+}
+
+// ---------------------------------------------------------------------------
+// BLAKE3
+// ---------------------------------------------------------------------------
+
+/// BLAKE3 initialization vector (same as SHA-256's)
+const BLAKE3_IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+/// Message word permutation applied between BLAKE3 rounds
+const BLAKE3_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+/// BLAKE3_Compress: seven rounds of the BLAKE3 round function
+///
+/// The 16-word working state is `state || IV` (no counter, length or flags),
+/// the message is `block`, all little-endian; the output is the XOR of the
+/// two halves of the working state.
+pub fn blake3_compress(state: &[u8; 32], block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+    let mut v = [0u32; 16];
+    for i in 0..8 {
+        v[i] = le_word(state, i);
+        v[i + 8] = BLAKE3_IV[i];
+    }
+    let mut m: [u32; 16] = core::array::from_fn(|i| le_word(block, i));
+
+    for round in 0..7 {
+        // Columns
+        blake3_g(&mut v, 0, 4, 8, 12, m[0], m[1]);
+        blake3_g(&mut v, 1, 5, 9, 13, m[2], m[3]);
+        blake3_g(&mut v, 2, 6, 10, 14, m[4], m[5]);
+        blake3_g(&mut v, 3, 7, 11, 15, m[6], m[7]);
+        // Diagonals
+        blake3_g(&mut v, 0, 5, 10, 15, m[8], m[9]);
+        blake3_g(&mut v, 1, 6, 11, 12, m[10], m[11]);
+        blake3_g(&mut v, 2, 7, 8, 13, m[12], m[13]);
+        blake3_g(&mut v, 3, 4, 9, 14, m[14], m[15]);
+
+        if round < 6 {
+            m = core::array::from_fn(|i| m[BLAKE3_PERMUTATION[i]]);
+        }
+    }
+
+    let mut result = [0u8; 32];
+    for i in 0..8 {
+        result[4 * i..4 * i + 4].copy_from_slice(&(v[i] ^ v[i + 8]).to_le_bytes());
+    }
+    result
+}
+
+/// BLAKE3 quarter-round mixing function
+fn blake3_g(v: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, x: u32, y: u32) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(12);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(8);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(7);
+}
+
+fn le_word(bytes: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes(bytes[4 * i..4 * i + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives;
+
+    /// Deterministic (state, block) pairs for comparing primitives
+    fn samples(count: usize) -> impl Iterator<Item = ([u8; 32], [u8; BLOCK_SIZE])> {
+        let mut xof = blake3::Hasher::new()
+            .update(b"reference samples")
+            .finalize_xof();
+        (0..count).map(move |_| {
+            let mut state = [0u8; 32];
+            let mut block = [0u8; BLOCK_SIZE];
+            xof.fill(&mut state);
+            xof.fill(&mut block);
+            (state, block)
+        })
+    }
+
+    #[test]
+    fn test_sbox_known_values() {
+        assert_eq!(SBOX[0x00], 0x63);
+        assert_eq!(SBOX[0x01], 0x7c);
+        assert_eq!(SBOX[0x53], 0xed);
+        assert_eq!(SBOX[0xff], 0x16);
+    }
+
+    #[test]
+    fn test_sha256_compress_is_fips() {
+        // SHA-256("abc") is one compression of the padded block from the IV
+        let iv: [u8; 32] = core::array::from_fn(|i| be_word_bytes(&BLAKE3_IV, i));
+        let mut block = [0u8; BLOCK_SIZE];
+        block[..3].copy_from_slice(b"abc");
+        block[3] = 0x80;
+        block[63] = 24;
+        assert_eq!(
+            sha256_compress(&iv, &block),
+            <[u8; 32]>::from(Sha256::digest(b"abc"))
+        );
+    }
+
+    fn be_word_bytes(words: &[u32; 8], i: usize) -> u8 {
+        words[i / 4].to_be_bytes()[i % 4]
+    }
+
+    #[test]
+    fn test_primitives_match_optimized() {
+        for (state, block) in samples(256) {
+            assert_eq!(
+                aes_compress(&state, &block),
+                primitives::aes_compress(&state, &block)
+            );
+            assert_eq!(
+                sha256_compress(&state, &block),
+                primitives::sha256_compress(&state, &block)
+            );
+            assert_eq!(
+                blake3_compress(&state, &block),
+                primitives::blake3_compress(&state, &block)
+            );
+
+            let half: [u8; 16] = state[..16].try_into().unwrap();
+            let key: [u8; 16] = block[..16].try_into().unwrap();
+            assert_eq!(
+                aes_expand(&half, &key),
+                primitives::aes_expand_block(&half, &key)
+            );
+        }
+    }
+
+    #[test]
+    fn test_address_matches_optimized() {
+        // Covers the word-size-specific path on 32-bit hosts, including
+        // rounds whose high bits would reach the rotation's wrap-around
+        for (i, (state, _)) in samples(256).enumerate() {
+            for round in [0, i, ROUNDS - 1, u32::MAX as usize, usize::MAX] {
+                assert_eq!(
+                    address(&state, round),
+                    crate::uhash::compute_address(&state, round)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_matches_optimized() {
+        for input in [
+            &b""[..],
+            b"short",
+            b"reference implementation input",
+            &crate::MiningHeader::default().to_bytes(),
+            &[0xff; 68],
+        ] {
+            assert_eq!(hash(input), crate::hash(input), "input {:02x?}", input);
+        }
+    }
+}
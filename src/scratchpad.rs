@@ -0,0 +1,122 @@
+//! Contiguous backing memory for a hasher's scratchpads
+//!
+//! All [`CHAINS`] scratchpads live in one allocation: chain `c` starts
+//! `c * SCRATCHPAD_STRIDE` bytes after a base aligned to
+//! [`SCRATCHPAD_ALIGN`]. The 2MB working set then covers the fewest pages
+//! and TLB entries, and with transparent huge pages the kernel can back it
+//! with a single 2MB page, which per-chain allocations at arbitrary
+//! addresses never line up for.
+//!
+//! The region comes straight from the global allocator with that
+//! alignment and zeroed, so it stays lazily mapped: pages nothing writes
+//! to cost no physical memory, and no slack is reserved for alignment.
+
+#[cfg(not(feature = "std"))]
+use alloc::alloc::{Layout, alloc_zeroed, dealloc, handle_alloc_error};
+use core::ptr::NonNull;
+#[cfg(feature = "std")]
+use std::alloc::{Layout, alloc_zeroed, dealloc, handle_alloc_error};
+
+use crate::params::{BLOCK_SIZE, CHAINS, SCRATCHPAD_SIZE};
+
+/// Distance in bytes between the starts of consecutive chains' scratchpads
+pub(crate) const SCRATCHPAD_STRIDE: usize = SCRATCHPAD_SIZE;
+
+/// Alignment of the first scratchpad: one x86-64 / aarch64 huge page
+pub(crate) const SCRATCHPAD_ALIGN: usize = 2 * 1024 * 1024;
+
+/// Size and alignment of the allocation holding every chain
+const LAYOUT: Layout = match Layout::from_size_align(CHAINS * SCRATCHPAD_STRIDE, SCRATCHPAD_ALIGN) {
+    Ok(layout) => layout,
+    Err(_) => panic!("invalid scratchpad layout"),
+};
+
+const _: () = {
+    assert!(SCRATCHPAD_STRIDE >= SCRATCHPAD_SIZE);
+    assert!(SCRATCHPAD_STRIDE.is_multiple_of(BLOCK_SIZE));
+    assert!(SCRATCHPAD_ALIGN.is_power_of_two());
+    assert!(LAYOUT.size() > 0);
+};
+
+/// Every chain's scratchpad in one aligned allocation
+pub(crate) struct Scratchpads {
+    /// Start of a zeroed allocation of [`LAYOUT`], owned by this value
+    base: NonNull<u8>,
+}
+
+// SAFETY: `Scratchpads` uniquely owns its allocation, like a `Box<[u8]>`
+unsafe impl Send for Scratchpads {}
+// SAFETY: shared access only hands out shared slices
+unsafe impl Sync for Scratchpads {}
+
+impl Scratchpads {
+    /// Allocate zeroed scratchpads for all chains
+    pub(crate) fn new() -> Self {
+        // SAFETY: `LAYOUT` has a nonzero size
+        let base = unsafe { alloc_zeroed(LAYOUT) };
+        match NonNull::new(base) {
+            Some(base) => Self { base },
+            None => handle_alloc_error(LAYOUT),
+        }
+    }
+
+    /// Scratchpad of each chain, indexed by chain
+    pub(crate) fn chains(&self) -> [&[u8; SCRATCHPAD_SIZE]; CHAINS] {
+        let mut chains = self.region().chunks_exact(SCRATCHPAD_STRIDE);
+        core::array::from_fn(|_| {
+            chains.next().unwrap()[..SCRATCHPAD_SIZE]
+                .try_into()
+                .unwrap()
+        })
+    }
+
+    /// Mutable scratchpad of each chain, indexed by chain
+    pub(crate) fn chains_mut(&mut self) -> [&mut [u8; SCRATCHPAD_SIZE]; CHAINS] {
+        let mut chains = self.region_mut().chunks_exact_mut(SCRATCHPAD_STRIDE);
+        core::array::from_fn(|_| {
+            (&mut chains.next().unwrap()[..SCRATCHPAD_SIZE])
+                .try_into()
+                .unwrap()
+        })
+    }
+
+    /// The aligned region holding all chains
+    fn region(&self) -> &[u8] {
+        // SAFETY: `base` points to `LAYOUT.size()` initialized (zeroed) bytes
+        // that live as long as `self`
+        unsafe { core::slice::from_raw_parts(self.base.as_ptr(), LAYOUT.size()) }
+    }
+
+    /// Mutable [`region`](Self::region)
+    fn region_mut(&mut self) -> &mut [u8] {
+        // SAFETY: as in `region`, and `&mut self` makes the access unique
+        unsafe { core::slice::from_raw_parts_mut(self.base.as_ptr(), LAYOUT.size()) }
+    }
+}
+
+impl Drop for Scratchpads {
+    fn drop(&mut self) {
+        // SAFETY: `base` was allocated in `new` with `LAYOUT` and is freed once
+        unsafe { dealloc(self.base.as_ptr(), LAYOUT) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scratchpads_are_contiguous_and_aligned() {
+        let mut scratchpads = Scratchpads::new();
+        let chains = scratchpads.chains_mut();
+        let base = chains[0].as_ptr() as usize;
+        assert!(base.is_multiple_of(SCRATCHPAD_ALIGN));
+        for (chain, scratchpad) in chains.iter().enumerate() {
+            assert_eq!(scratchpad.len(), SCRATCHPAD_SIZE);
+            assert_eq!(
+                scratchpad.as_ptr() as usize,
+                base + chain * SCRATCHPAD_STRIDE
+            );
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Serde helpers for fixed-size byte encodings
+//!
+//! Human-readable formats (JSON, TOML) get a lowercase hex string;
+//! binary formats get the raw bytes.
+
+use core::fmt;
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::Serializer;
+
+use crate::hex::{self, HexDisplay};
+
+/// Serialize `bytes` as hex (human-readable) or raw bytes (binary)
+pub(crate) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.collect_str(&HexDisplay(bytes))
+    } else {
+        serializer.serialize_bytes(bytes)
+    }
+}
+
+/// Deserialize exactly `N` bytes written by [`serialize`]
+pub(crate) fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+    deserializer: D,
+) -> Result<[u8; N], D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_str(FixedVisitor::<N>)
+    } else {
+        deserializer.deserialize_bytes(FixedVisitor::<N>)
+    }
+}
+
+struct FixedVisitor<const N: usize>;
+
+impl<'de, const N: usize> Visitor<'de> for FixedVisitor<N> {
+    type Value = [u8; N];
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes or a {}-character hex string", N, N * 2)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        let mut out = [0u8; N];
+        hex::decode(v, &mut out).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))?;
+        Ok(out)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        v.try_into().map_err(|_| E::invalid_length(v.len(), &self))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut out = [0u8; N];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<u8>()?.is_some() {
+            return Err(de::Error::invalid_length(N + 1, &self));
+        }
+        Ok(out)
+    }
+}
+
+/// Implement `Serialize`/`Deserialize` for `$ty` through its fixed-size
+/// byte encoding `$to(&self) -> [u8; $size]` / `$from(&[u8; $size]) -> Self`
+macro_rules! impl_fixed_serde {
+    ($ty:ty, $size:expr, $to:ident, $from:ident) => {
+        impl serde::Serialize for $ty {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                $crate::serde_fixed::serialize(&self.$to(), serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                $crate::serde_fixed::deserialize::<D, { $size }>(deserializer)
+                    .map(|bytes| Self::$from(&bytes))
+            }
+        }
+    };
+}
+
+pub(crate) use impl_fixed_serde;
@@ -0,0 +1,56 @@
+// Host shim running the OpenCL kernel as plain C11 (tests only)
+//
+// Maps the OpenCL qualifiers away, includes the generated kernel source
+// from `kernel.cl` and runs every work item in turn. Reads from stdin the
+// lane count (uint), then 8 seed words per lane and one nonce (ulong) per
+// UHASH_CHAINS lanes, all native-endian; writes 8 state words per lane to
+// stdout.
+
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+typedef uint32_t uint;
+typedef uint64_t ulong;
+typedef uint8_t uchar;
+
+#define __kernel
+#define __global
+#define __constant static const
+
+static size_t global_id;
+#define get_global_id(dim) global_id
+
+#include "kernel.cl"
+
+static void read_exact(void *buf, size_t size)
+{
+    if (fread(buf, 1, size, stdin) != size) {
+        fputs("short read\n", stderr);
+        exit(1);
+    }
+}
+
+int main(void)
+{
+    uint lanes;
+    read_exact(&lanes, sizeof lanes);
+    uint *seeds = malloc((size_t)lanes * 8 * sizeof(uint));
+    ulong *nonces = malloc((size_t)lanes / UHASH_CHAINS * sizeof(ulong));
+    uint *scratch = malloc((size_t)lanes * UHASH_SCRATCHPAD_WORDS * sizeof(uint));
+    uint *states = malloc((size_t)lanes * 8 * sizeof(uint));
+    if (!seeds || !nonces || !scratch || !states) {
+        fputs("out of memory\n", stderr);
+        return 1;
+    }
+    read_exact(seeds, (size_t)lanes * 8 * sizeof(uint));
+    read_exact(nonces, (size_t)lanes / UHASH_CHAINS * sizeof(ulong));
+
+    for (global_id = 0; global_id < lanes; global_id++) {
+        uhash_chains(seeds, nonces, scratch, states, lanes);
+    }
+
+    fwrite(states, sizeof(uint), (size_t)lanes * 8, stdout);
+    return 0;
+}
@@ -0,0 +1,289 @@
+// UniversalHash v4 chain kernel (OpenCL C 1.2)
+//
+// One work item computes one (nonce, chain) lane: work item `n * UHASH_CHAINS
+// + c` is chain `c` of `nonces[n]`. The host supplies each lane's chain seed
+// and finalizes the returned chain states, so this file only mirrors
+// `fill_scratchpad_aes`, `compute_address` and the three compression
+// functions in `src/primitives.rs`.
+//
+// The UHASH_* parameter macros are not defined here: `opencl_kernel_source()`
+// prepends them from `src/params.rs`. `cuda_kernel_source()` additionally
+// prepends a prelude mapping the OpenCL qualifiers onto CUDA, so helper
+// functions carry `UHASH_FN` (`__device__` there, empty here).
+//
+// Byte strings are stored as little-endian uint words, so an AES column is
+// one word and SHA-256 byte-swaps on entry and exit.
+
+#ifndef UHASH_FN
+#define UHASH_FN
+#endif
+
+#define UHASH_ADDRESS_MUL 0x27220a95u // low 32 bits of 0x517cc1b727220a95
+
+__constant uint SBOX[256] = {
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+};
+
+__constant uint K[64] = {
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+};
+
+__constant uchar MSG_SCHEDULE[7][16] = {
+    {0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15},
+    {2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8},
+    {3, 4, 10, 12, 13, 2, 7, 14, 6, 5, 9, 0, 11, 15, 8, 1},
+    {10, 7, 12, 9, 14, 3, 13, 15, 4, 0, 11, 2, 5, 8, 1, 6},
+    {12, 13, 9, 11, 15, 10, 14, 8, 7, 2, 5, 3, 0, 1, 6, 4},
+    {9, 14, 11, 5, 8, 12, 15, 1, 13, 3, 0, 10, 2, 6, 4, 7},
+    {11, 15, 5, 0, 1, 9, 8, 6, 14, 10, 2, 12, 3, 4, 7, 13},
+};
+
+#define ROTR(x, n) (((x) >> (n)) | ((x) << (32 - (n))))
+
+// ---- AES ----
+
+UHASH_FN uint sub_word(uint w)
+{
+    return SBOX[w & 0xff]
+        | (SBOX[(w >> 8) & 0xff] << 8)
+        | (SBOX[(w >> 16) & 0xff] << 16)
+        | (SBOX[w >> 24] << 24);
+}
+
+UHASH_FN uint xtime(uint w)
+{
+    return ((w & 0x7f7f7f7fu) << 1) ^ (((w >> 7) & 0x01010101u) * 0x1b);
+}
+
+UHASH_FN uint mix_column(uint w)
+{
+    uint r1 = ROTR(w, 8);
+    uint r2 = ROTR(w, 16);
+    uint r3 = ROTR(w, 24);
+    return xtime(w ^ r1) ^ r1 ^ r2 ^ r3;
+}
+
+// ShiftRows: row r of column c comes from column c + r
+UHASH_FN uint shift_column(uint a, uint b, uint c, uint d)
+{
+    return (a & 0x000000ffu) | (b & 0x0000ff00u) | (c & 0x00ff0000u) | (d & 0xff000000u);
+}
+
+// AESENC: SubBytes, ShiftRows, MixColumns, AddRoundKey
+UHASH_FN void aesenc(uint *s, const uint *k)
+{
+    uint t0 = mix_column(sub_word(shift_column(s[0], s[1], s[2], s[3])));
+    uint t1 = mix_column(sub_word(shift_column(s[1], s[2], s[3], s[0])));
+    uint t2 = mix_column(sub_word(shift_column(s[2], s[3], s[0], s[1])));
+    uint t3 = mix_column(sub_word(shift_column(s[3], s[0], s[1], s[2])));
+    s[0] = t0 ^ k[0];
+    s[1] = t1 ^ k[1];
+    s[2] = t2 ^ k[2];
+    s[3] = t3 ^ k[3];
+}
+
+UHASH_FN void aes_expand(uint *s, const uint *k)
+{
+    aesenc(s, k);
+    aesenc(s, k);
+    aesenc(s, k);
+    aesenc(s, k);
+}
+
+// ---- Compression functions (state: 8 words, block: 16 words) ----
+
+UHASH_FN void aes_compress(uint *state, const uint *m)
+{
+    aesenc(state, m);
+    aesenc(state, m + 4);
+    aesenc(state, m + 8);
+    aesenc(state, m + 12);
+
+    aesenc(state + 4, m + 8);
+    aesenc(state + 4, m + 12);
+    aesenc(state + 4, m);
+    aesenc(state + 4, m + 4);
+}
+
+UHASH_FN uint bswap(uint x)
+{
+    return (x << 24) | ((x & 0xff00u) << 8) | ((x >> 8) & 0xff00u) | (x >> 24);
+}
+
+UHASH_FN void sha256_compress(uint *state, const uint *m)
+{
+    uint w[64];
+    for (int i = 0; i < 16; i++) {
+        w[i] = bswap(m[i]);
+    }
+    for (int i = 16; i < 64; i++) {
+        uint s0 = ROTR(w[i - 15], 7) ^ ROTR(w[i - 15], 18) ^ (w[i - 15] >> 3);
+        uint s1 = ROTR(w[i - 2], 17) ^ ROTR(w[i - 2], 19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16] + s0 + w[i - 7] + s1;
+    }
+
+    uint h[8];
+    for (int i = 0; i < 8; i++) {
+        h[i] = bswap(state[i]);
+    }
+    uint a = h[0], b = h[1], c = h[2], d = h[3];
+    uint e = h[4], f = h[5], g = h[6], hh = h[7];
+    for (int i = 0; i < 64; i++) {
+        uint s1 = ROTR(e, 6) ^ ROTR(e, 11) ^ ROTR(e, 25);
+        uint ch = (e & f) ^ (~e & g);
+        uint t1 = hh + s1 + ch + K[i] + w[i];
+        uint s0 = ROTR(a, 2) ^ ROTR(a, 13) ^ ROTR(a, 22);
+        uint maj = (a & b) ^ (a & c) ^ (b & c);
+        uint t2 = s0 + maj;
+        hh = g;
+        g = f;
+        f = e;
+        e = d + t1;
+        d = c;
+        c = b;
+        b = a;
+        a = t1 + t2;
+    }
+
+    state[0] = bswap(h[0] + a);
+    state[1] = bswap(h[1] + b);
+    state[2] = bswap(h[2] + c);
+    state[3] = bswap(h[3] + d);
+    state[4] = bswap(h[4] + e);
+    state[5] = bswap(h[5] + f);
+    state[6] = bswap(h[6] + g);
+    state[7] = bswap(h[7] + hh);
+}
+
+#define BLAKE3_G(v, a, b, c, d, mx, my) \
+    do { \
+        v[a] = v[a] + v[b] + (mx); \
+        v[d] = ROTR(v[d] ^ v[a], 16); \
+        v[c] = v[c] + v[d]; \
+        v[b] = ROTR(v[b] ^ v[c], 12); \
+        v[a] = v[a] + v[b] + (my); \
+        v[d] = ROTR(v[d] ^ v[a], 8); \
+        v[c] = v[c] + v[d]; \
+        v[b] = ROTR(v[b] ^ v[c], 7); \
+    } while (0)
+
+UHASH_FN void blake3_compress(uint *state, const uint *m)
+{
+    uint v[16] = {
+        state[0], state[1], state[2], state[3],
+        state[4], state[5], state[6], state[7],
+        0x6a09e667u, 0xbb67ae85u, 0x3c6ef372u, 0xa54ff53au,
+        0x510e527fu, 0x9b05688cu, 0x1f83d9abu, 0x5be0cd19u,
+    };
+
+    for (int r = 0; r < 7; r++) {
+#define M(i) m[MSG_SCHEDULE[r][i]]
+        BLAKE3_G(v, 0, 4, 8, 12, M(0), M(1));
+        BLAKE3_G(v, 1, 5, 9, 13, M(2), M(3));
+        BLAKE3_G(v, 2, 6, 10, 14, M(4), M(5));
+        BLAKE3_G(v, 3, 7, 11, 15, M(6), M(7));
+        BLAKE3_G(v, 0, 5, 10, 15, M(8), M(9));
+        BLAKE3_G(v, 1, 6, 11, 12, M(10), M(11));
+        BLAKE3_G(v, 2, 7, 8, 13, M(12), M(13));
+        BLAKE3_G(v, 3, 4, 9, 14, M(14), M(15));
+#undef M
+    }
+
+    for (int i = 0; i < 8; i++) {
+        state[i] = v[i] ^ v[i + 8];
+    }
+}
+
+// ---- Kernel ----
+
+__kernel void uhash_chains(
+    __global const uint *seeds,   // 8 words per lane
+    __global const ulong *nonces, // one per UHASH_CHAINS lanes
+    __global uint *scratch,       // UHASH_SCRATCHPAD_WORDS per lane
+    __global uint *states,        // 8 words per lane
+    uint lanes)
+{
+    size_t lane = get_global_id(0);
+    if (lane >= lanes) {
+        return;
+    }
+    __global uint *pad = scratch + lane * UHASH_SCRATCHPAD_WORDS;
+    __global const uint *seed = seeds + lane * 8;
+
+    // Phase 1: scratchpad fill (see `fill_scratchpad_aes`)
+    uint key[4], s[4], s2[4];
+    for (int i = 0; i < 4; i++) {
+        key[i] = seed[i];
+        s[i] = seed[4 + i];
+    }
+    for (uint b = 0; b < UHASH_BLOCKS; b++) {
+        aes_expand(s, key);
+        for (int i = 0; i < 4; i++) {
+            s2[i] = s[i];
+        }
+        aes_expand(s2, key);
+        __global uint *dst = pad + b * UHASH_BLOCK_WORDS;
+        for (int i = 0; i < 4; i++) {
+            dst[i] = s[i];
+            dst[4 + i] = s2[i];
+            dst[8 + i] = s[i];
+            dst[12 + i] = s2[i];
+        }
+    }
+
+    // Phase 2: rounds, writing each new state back where its block was read
+    uint state[8], block[16];
+    for (int i = 0; i < 8; i++) {
+        state[i] = seed[i];
+    }
+    uint first = (uint)((nonces[lane / UHASH_CHAINS] + lane % UHASH_CHAINS) % 3);
+    for (uint r = 0; r < UHASH_ROUNDS; r++) {
+        uint addr = (state[0] ^ state[2] ^ (r << 13) ^ (r * UHASH_ADDRESS_MUL)) & (UHASH_BLOCKS - 1);
+        __global uint *src = pad + addr * UHASH_BLOCK_WORDS;
+        for (int i = 0; i < 16; i++) {
+            block[i] = src[i];
+        }
+
+        switch ((first + r + 1) % 3) {
+        case 0:
+            aes_compress(state, block);
+            break;
+        case 1:
+            sha256_compress(state, block);
+            break;
+        default:
+            blake3_compress(state, block);
+            break;
+        }
+
+        for (int i = 0; i < 8; i++) {
+            src[i] = state[i];
+        }
+    }
+
+    for (int i = 0; i < 8; i++) {
+        states[lane * 8 + i] = state[i];
+    }
+}
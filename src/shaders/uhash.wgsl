@@ -0,0 +1,302 @@
+// UniversalHash v4 chain kernel: scratchpad fill and round loop
+//
+// One invocation computes one (nonce, chain) lane. The host supplies each
+// lane's chain seed and initial primitive and finalizes the returned chain
+// states, so this file only mirrors `fill_scratchpad_aes`, `compute_address`
+// and the three compression functions in `src/primitives.rs`.
+//
+// Byte strings are stored as little-endian u32 words packed into vec4s: a
+// chain state is two vec4s, a scratchpad block four, and an AES column one
+// word. SHA-256 byte-swaps on entry and exit.
+//
+// The only loops are the fill and the round loop; the compression functions
+// are unrolled. Software Vulkan drivers such as llvmpipe stop loops after
+// 65535 iterations in total per invocation, which nested loops would exceed.
+
+const BLOCKS: u32 = 8192u;          // 512KB / 64B
+const BLOCK_VECS: u32 = 4u;
+const SCRATCHPAD_VECS: u32 = 32768u;
+const ROUNDS: u32 = 12288u;
+const ADDRESS_MUL: u32 = 0x27220a95u; // low 32 bits of 0x517cc1b727220a95
+
+@group(0) @binding(0) var<storage, read> seeds: array<u32>;              // 8 words per lane
+@group(0) @binding(1) var<storage, read> initial: array<u32>;            // initial primitive per lane
+@group(0) @binding(2) var<storage, read_write> scratch: array<vec4<u32>>; // SCRATCHPAD_VECS per lane
+@group(0) @binding(3) var<storage, read_write> states: array<u32>;        // 8 words per lane
+
+const SBOX = array<u32, 256>(
+    0x63u, 0x7cu, 0x77u, 0x7bu, 0xf2u, 0x6bu, 0x6fu, 0xc5u, 0x30u, 0x01u, 0x67u, 0x2bu, 0xfeu, 0xd7u, 0xabu, 0x76u,
+    0xcau, 0x82u, 0xc9u, 0x7du, 0xfau, 0x59u, 0x47u, 0xf0u, 0xadu, 0xd4u, 0xa2u, 0xafu, 0x9cu, 0xa4u, 0x72u, 0xc0u,
+    0xb7u, 0xfdu, 0x93u, 0x26u, 0x36u, 0x3fu, 0xf7u, 0xccu, 0x34u, 0xa5u, 0xe5u, 0xf1u, 0x71u, 0xd8u, 0x31u, 0x15u,
+    0x04u, 0xc7u, 0x23u, 0xc3u, 0x18u, 0x96u, 0x05u, 0x9au, 0x07u, 0x12u, 0x80u, 0xe2u, 0xebu, 0x27u, 0xb2u, 0x75u,
+    0x09u, 0x83u, 0x2cu, 0x1au, 0x1bu, 0x6eu, 0x5au, 0xa0u, 0x52u, 0x3bu, 0xd6u, 0xb3u, 0x29u, 0xe3u, 0x2fu, 0x84u,
+    0x53u, 0xd1u, 0x00u, 0xedu, 0x20u, 0xfcu, 0xb1u, 0x5bu, 0x6au, 0xcbu, 0xbeu, 0x39u, 0x4au, 0x4cu, 0x58u, 0xcfu,
+    0xd0u, 0xefu, 0xaau, 0xfbu, 0x43u, 0x4du, 0x33u, 0x85u, 0x45u, 0xf9u, 0x02u, 0x7fu, 0x50u, 0x3cu, 0x9fu, 0xa8u,
+    0x51u, 0xa3u, 0x40u, 0x8fu, 0x92u, 0x9du, 0x38u, 0xf5u, 0xbcu, 0xb6u, 0xdau, 0x21u, 0x10u, 0xffu, 0xf3u, 0xd2u,
+    0xcdu, 0x0cu, 0x13u, 0xecu, 0x5fu, 0x97u, 0x44u, 0x17u, 0xc4u, 0xa7u, 0x7eu, 0x3du, 0x64u, 0x5du, 0x19u, 0x73u,
+    0x60u, 0x81u, 0x4fu, 0xdcu, 0x22u, 0x2au, 0x90u, 0x88u, 0x46u, 0xeeu, 0xb8u, 0x14u, 0xdeu, 0x5eu, 0x0bu, 0xdbu,
+    0xe0u, 0x32u, 0x3au, 0x0au, 0x49u, 0x06u, 0x24u, 0x5cu, 0xc2u, 0xd3u, 0xacu, 0x62u, 0x91u, 0x95u, 0xe4u, 0x79u,
+    0xe7u, 0xc8u, 0x37u, 0x6du, 0x8du, 0xd5u, 0x4eu, 0xa9u, 0x6cu, 0x56u, 0xf4u, 0xeau, 0x65u, 0x7au, 0xaeu, 0x08u,
+    0xbau, 0x78u, 0x25u, 0x2eu, 0x1cu, 0xa6u, 0xb4u, 0xc6u, 0xe8u, 0xddu, 0x74u, 0x1fu, 0x4bu, 0xbdu, 0x8bu, 0x8au,
+    0x70u, 0x3eu, 0xb5u, 0x66u, 0x48u, 0x03u, 0xf6u, 0x0eu, 0x61u, 0x35u, 0x57u, 0xb9u, 0x86u, 0xc1u, 0x1du, 0x9eu,
+    0xe1u, 0xf8u, 0x98u, 0x11u, 0x69u, 0xd9u, 0x8eu, 0x94u, 0x9bu, 0x1eu, 0x87u, 0xe9u, 0xceu, 0x55u, 0x28u, 0xdfu,
+    0x8cu, 0xa1u, 0x89u, 0x0du, 0xbfu, 0xe6u, 0x42u, 0x68u, 0x41u, 0x99u, 0x2du, 0x0fu, 0xb0u, 0x54u, 0xbbu, 0x16u,
+);
+
+const K = array<u32, 64>(
+    0x428a2f98u, 0x71374491u, 0xb5c0fbcfu, 0xe9b5dba5u, 0x3956c25bu, 0x59f111f1u, 0x923f82a4u, 0xab1c5ed5u,
+    0xd807aa98u, 0x12835b01u, 0x243185beu, 0x550c7dc3u, 0x72be5d74u, 0x80deb1feu, 0x9bdc06a7u, 0xc19bf174u,
+    0xe49b69c1u, 0xefbe4786u, 0x0fc19dc6u, 0x240ca1ccu, 0x2de92c6fu, 0x4a7484aau, 0x5cb0a9dcu, 0x76f988dau,
+    0x983e5152u, 0xa831c66du, 0xb00327c8u, 0xbf597fc7u, 0xc6e00bf3u, 0xd5a79147u, 0x06ca6351u, 0x14292967u,
+    0x27b70a85u, 0x2e1b2138u, 0x4d2c6dfcu, 0x53380d13u, 0x650a7354u, 0x766a0abbu, 0x81c2c92eu, 0x92722c85u,
+    0xa2bfe8a1u, 0xa81a664bu, 0xc24b8b70u, 0xc76c51a3u, 0xd192e819u, 0xd6990624u, 0xf40e3585u, 0x106aa070u,
+    0x19a4c116u, 0x1e376c08u, 0x2748774cu, 0x34b0bcb5u, 0x391c0cb3u, 0x4ed8aa4au, 0x5b9cca4fu, 0x682e6ff3u,
+    0x748f82eeu, 0x78a5636fu, 0x84c87814u, 0x8cc70208u, 0x90befffau, 0xa4506cebu, 0xbef9a3f7u, 0xc67178f2u,
+);
+
+// ---- AES ----
+
+fn sub_word(w: u32) -> u32 {
+    return SBOX[w & 0xffu]
+        | (SBOX[(w >> 8u) & 0xffu] << 8u)
+        | (SBOX[(w >> 16u) & 0xffu] << 16u)
+        | (SBOX[w >> 24u] << 24u);
+}
+
+fn xtime(w: u32) -> u32 {
+    return ((w & 0x7f7f7f7fu) << 1u) ^ (((w >> 7u) & 0x01010101u) * 0x1bu);
+}
+
+fn mix_column(w: u32) -> u32 {
+    let r1 = (w >> 8u) | (w << 24u);
+    let r2 = (w >> 16u) | (w << 16u);
+    let r3 = (w >> 24u) | (w << 8u);
+    return xtime(w ^ r1) ^ r1 ^ r2 ^ r3;
+}
+
+// ShiftRows: row r of column c comes from column c + r
+fn shift_column(a: u32, b: u32, c: u32, d: u32) -> u32 {
+    return (a & 0x000000ffu) | (b & 0x0000ff00u) | (c & 0x00ff0000u) | (d & 0xff000000u);
+}
+
+// AESENC: SubBytes, ShiftRows, MixColumns, AddRoundKey
+fn aesenc(s: vec4<u32>, k: vec4<u32>) -> vec4<u32> {
+    return vec4<u32>(
+        mix_column(sub_word(shift_column(s.x, s.y, s.z, s.w))),
+        mix_column(sub_word(shift_column(s.y, s.z, s.w, s.x))),
+        mix_column(sub_word(shift_column(s.z, s.w, s.x, s.y))),
+        mix_column(sub_word(shift_column(s.w, s.x, s.y, s.z))),
+    ) ^ k;
+}
+
+fn aes_expand(s: vec4<u32>, k: vec4<u32>) -> vec4<u32> {
+    return aesenc(aesenc(aesenc(aesenc(s, k), k), k), k);
+}
+
+// ---- Compression functions (state: 2 vec4s, block: 4 vec4s) ----
+
+fn aes_compress(state: array<vec4<u32>, 2>, m: array<vec4<u32>, 4>) -> array<vec4<u32>, 2> {
+    let lo = aesenc(aesenc(aesenc(aesenc(state[0], m[0]), m[1]), m[2]), m[3]);
+    let hi = aesenc(aesenc(aesenc(aesenc(state[1], m[2]), m[3]), m[0]), m[1]);
+    return array<vec4<u32>, 2>(lo, hi);
+}
+
+fn bswap(x: u32) -> u32 {
+    return (x << 24u) | ((x & 0xff00u) << 8u) | ((x >> 8u) & 0xff00u) | (x >> 24u);
+}
+
+fn bswap4(x: vec4<u32>) -> vec4<u32> {
+    return vec4<u32>(bswap(x.x), bswap(x.y), bswap(x.z), bswap(x.w));
+}
+
+fn rotr(x: u32, n: u32) -> u32 {
+    return (x >> n) | (x << (32u - n));
+}
+
+// Round t of SHA-256 with the working variables rotated by j: slot
+// (k - j) & 7 holds variable k (a = 0 .. h = 7), so no values are moved.
+// The message schedule lives in a 16-word ring.
+fn sha256_round(v: ptr<function, array<u32, 8>>, w: ptr<function, array<u32, 16>>, t: u32, j: u32) {
+    if (t >= 16u) {
+        let w15 = (*w)[(t + 1u) & 15u];
+        let w2 = (*w)[(t + 14u) & 15u];
+        let s0 = rotr(w15, 7u) ^ rotr(w15, 18u) ^ (w15 >> 3u);
+        let s1 = rotr(w2, 17u) ^ rotr(w2, 19u) ^ (w2 >> 10u);
+        (*w)[t & 15u] = (*w)[t & 15u] + s0 + (*w)[(t + 9u) & 15u] + s1;
+    }
+
+    let a = (8u - j) & 7u;
+    let b = (9u - j) & 7u;
+    let c = (10u - j) & 7u;
+    let d = (11u - j) & 7u;
+    let e = (12u - j) & 7u;
+    let f = (13u - j) & 7u;
+    let g = (14u - j) & 7u;
+    let h = (15u - j) & 7u;
+
+    let ev = (*v)[e];
+    let av = (*v)[a];
+    let s1 = rotr(ev, 6u) ^ rotr(ev, 11u) ^ rotr(ev, 25u);
+    let ch = (ev & (*v)[f]) ^ (~ev & (*v)[g]);
+    let t1 = (*v)[h] + s1 + ch + K[t] + (*w)[t & 15u];
+    let s0 = rotr(av, 2u) ^ rotr(av, 13u) ^ rotr(av, 22u);
+    let maj = (av & (*v)[b]) ^ (av & (*v)[c]) ^ ((*v)[b] & (*v)[c]);
+    (*v)[d] = (*v)[d] + t1;
+    (*v)[h] = t1 + s0 + maj;
+}
+
+// Rounds t .. t + 8, after which the slots are back in place
+fn sha256_rounds8(v: ptr<function, array<u32, 8>>, w: ptr<function, array<u32, 16>>, t: u32) {
+    sha256_round(v, w, t, 0u);
+    sha256_round(v, w, t + 1u, 1u);
+    sha256_round(v, w, t + 2u, 2u);
+    sha256_round(v, w, t + 3u, 3u);
+    sha256_round(v, w, t + 4u, 4u);
+    sha256_round(v, w, t + 5u, 5u);
+    sha256_round(v, w, t + 6u, 6u);
+    sha256_round(v, w, t + 7u, 7u);
+}
+
+fn sha256_compress(state: array<vec4<u32>, 2>, m: array<vec4<u32>, 4>) -> array<vec4<u32>, 2> {
+    let m0 = bswap4(m[0]);
+    let m1 = bswap4(m[1]);
+    let m2 = bswap4(m[2]);
+    let m3 = bswap4(m[3]);
+    var w = array<u32, 16>(
+        m0.x, m0.y, m0.z, m0.w, m1.x, m1.y, m1.z, m1.w,
+        m2.x, m2.y, m2.z, m2.w, m3.x, m3.y, m3.z, m3.w,
+    );
+
+    let h0 = bswap4(state[0]);
+    let h1 = bswap4(state[1]);
+    var v = array<u32, 8>(h0.x, h0.y, h0.z, h0.w, h1.x, h1.y, h1.z, h1.w);
+    sha256_rounds8(&v, &w, 0u);
+    sha256_rounds8(&v, &w, 8u);
+    sha256_rounds8(&v, &w, 16u);
+    sha256_rounds8(&v, &w, 24u);
+    sha256_rounds8(&v, &w, 32u);
+    sha256_rounds8(&v, &w, 40u);
+    sha256_rounds8(&v, &w, 48u);
+    sha256_rounds8(&v, &w, 56u);
+
+    return array<vec4<u32>, 2>(
+        bswap4(h0 + vec4<u32>(v[0], v[1], v[2], v[3])),
+        bswap4(h1 + vec4<u32>(v[4], v[5], v[6], v[7])),
+    );
+}
+
+fn blake3_g(v: ptr<function, array<u32, 16>>, a: u32, b: u32, c: u32, d: u32, mx: u32, my: u32) {
+    (*v)[a] = (*v)[a] + (*v)[b] + mx;
+    (*v)[d] = rotr((*v)[d] ^ (*v)[a], 16u);
+    (*v)[c] = (*v)[c] + (*v)[d];
+    (*v)[b] = rotr((*v)[b] ^ (*v)[c], 12u);
+    (*v)[a] = (*v)[a] + (*v)[b] + my;
+    (*v)[d] = rotr((*v)[d] ^ (*v)[a], 8u);
+    (*v)[c] = (*v)[c] + (*v)[d];
+    (*v)[b] = rotr((*v)[b] ^ (*v)[c], 7u);
+}
+
+// One round of column and diagonal mixing, then the message permutation
+fn blake3_round(v: ptr<function, array<u32, 16>>, m: ptr<function, array<u32, 16>>) {
+    blake3_g(v, 0u, 4u, 8u, 12u, (*m)[0], (*m)[1]);
+    blake3_g(v, 1u, 5u, 9u, 13u, (*m)[2], (*m)[3]);
+    blake3_g(v, 2u, 6u, 10u, 14u, (*m)[4], (*m)[5]);
+    blake3_g(v, 3u, 7u, 11u, 15u, (*m)[6], (*m)[7]);
+    blake3_g(v, 0u, 5u, 10u, 15u, (*m)[8], (*m)[9]);
+    blake3_g(v, 1u, 6u, 11u, 12u, (*m)[10], (*m)[11]);
+    blake3_g(v, 2u, 7u, 8u, 13u, (*m)[12], (*m)[13]);
+    blake3_g(v, 3u, 4u, 9u, 14u, (*m)[14], (*m)[15]);
+
+    let p = *m;
+    *m = array<u32, 16>(
+        p[2], p[6], p[3], p[10], p[7], p[0], p[4], p[13],
+        p[1], p[11], p[12], p[5], p[9], p[14], p[15], p[8],
+    );
+}
+
+fn blake3_compress(state: array<vec4<u32>, 2>, block: array<vec4<u32>, 4>) -> array<vec4<u32>, 2> {
+    var v = array<u32, 16>(
+        state[0].x, state[0].y, state[0].z, state[0].w,
+        state[1].x, state[1].y, state[1].z, state[1].w,
+        0x6a09e667u, 0xbb67ae85u, 0x3c6ef372u, 0xa54ff53au,
+        0x510e527fu, 0x9b05688cu, 0x1f83d9abu, 0x5be0cd19u,
+    );
+    var m = array<u32, 16>(
+        block[0].x, block[0].y, block[0].z, block[0].w,
+        block[1].x, block[1].y, block[1].z, block[1].w,
+        block[2].x, block[2].y, block[2].z, block[2].w,
+        block[3].x, block[3].y, block[3].z, block[3].w,
+    );
+
+    blake3_round(&v, &m);
+    blake3_round(&v, &m);
+    blake3_round(&v, &m);
+    blake3_round(&v, &m);
+    blake3_round(&v, &m);
+    blake3_round(&v, &m);
+    blake3_round(&v, &m);
+
+    return array<vec4<u32>, 2>(
+        vec4<u32>(v[0], v[1], v[2], v[3]) ^ vec4<u32>(v[8], v[9], v[10], v[11]),
+        vec4<u32>(v[4], v[5], v[6], v[7]) ^ vec4<u32>(v[12], v[13], v[14], v[15]),
+    );
+}
+
+// ---- Kernel ----
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let lane = id.x;
+    if (lane >= arrayLength(&initial)) {
+        return;
+    }
+    let base = lane * SCRATCHPAD_VECS;
+    let seed = lane * 8u;
+    let key = vec4<u32>(seeds[seed], seeds[seed + 1u], seeds[seed + 2u], seeds[seed + 3u]);
+    let tail = vec4<u32>(seeds[seed + 4u], seeds[seed + 5u], seeds[seed + 6u], seeds[seed + 7u]);
+
+    // Phase 1: scratchpad fill (see `fill_scratchpad_aes`)
+    var s = tail;
+    for (var i = 0u; i < BLOCKS; i++) {
+        s = aes_expand(s, key);
+        let s2 = aes_expand(s, key);
+        let off = base + i * BLOCK_VECS;
+        scratch[off] = s;
+        scratch[off + 1u] = s2;
+        scratch[off + 2u] = s;
+        scratch[off + 3u] = s2;
+    }
+
+    // Phase 2: rounds, writing each new state back where its block was read
+    var state = array<vec4<u32>, 2>(key, tail);
+    let first = initial[lane];
+    for (var r = 0u; r < ROUNDS; r++) {
+        let addr = (state[0].x ^ state[0].z ^ (r << 13u) ^ (r * ADDRESS_MUL)) & (BLOCKS - 1u);
+        let off = base + addr * BLOCK_VECS;
+        let block = array<vec4<u32>, 4>(scratch[off], scratch[off + 1u], scratch[off + 2u], scratch[off + 3u]);
+
+        switch ((first + r + 1u) % 3u) {
+            case 0u: {
+                state = aes_compress(state, block);
+            }
+            case 1u: {
+                state = sha256_compress(state, block);
+            }
+            default: {
+                state = blake3_compress(state, block);
+            }
+        }
+
+        scratch[off] = state[0];
+        scratch[off + 1u] = state[1];
+    }
+
+    states[seed] = state[0].x;
+    states[seed + 1u] = state[0].y;
+    states[seed + 2u] = state[0].z;
+    states[seed + 3u] = state[0].w;
+    states[seed + 4u] = state[1].x;
+    states[seed + 5u] = state[1].y;
+    states[seed + 6u] = state[1].z;
+    states[seed + 7u] = state[1].w;
+}
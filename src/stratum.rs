@@ -0,0 +1,280 @@
+//! Stratum-style pool jobs
+//!
+//! A pool hands out a header prefix, a share target and a job id; miners
+//! search nonces and submit `(job_id, extranonce, nonce)`. [`StratumJob`]
+//! assembles the exact bytes that get hashed on both sides.
+//!
+//! By default the extranonce occupies the most significant bytes of the
+//! 8-byte nonce field, so the bytes hashed for a given `(extranonce, nonce)`
+//! pair are:
+//!
+//! ```text
+//! prefix (60B) || nonce low bytes || extranonce
+//!                 ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ 8 bytes
+//! ```
+//!
+//! Pools that want the extranonce elsewhere (e.g. so miners keep the whole
+//! 64-bit nonce space) configure an [`ExtranonceRegion`]. The extranonce is
+//! `extranonce1 || extranonce2`: the pool assigns extranonce1 per
+//! connection and the miner rolls extranonce2.
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::error::UhashError;
+use crate::hash256::Hash256;
+use crate::header::{HEADER_PREFIX_SIZE, HEADER_SIZE, MiningHeader};
+use crate::params::NONCE_SIZE;
+use crate::target::{ShareResult, Target, check_share};
+use crate::uhash::UniversalHash;
+
+/// Bytes of the header reserved for the extranonce
+///
+/// The region may cover any bytes after the epoch seed (which consensus
+/// fixes per epoch). Where it overlaps the nonce field, those nonce bytes
+/// are taken by the extranonce and the miner rolls only the rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExtranonceRegion {
+    offset: usize,
+    width: usize,
+}
+
+impl ExtranonceRegion {
+    /// Region of `width` bytes at `offset` in the 68-byte header
+    ///
+    /// Returns `None` if the region overlaps the epoch seed or runs past
+    /// the end of the header.
+    pub fn new(offset: usize, width: usize) -> Option<Self> {
+        let end = offset.checked_add(width)?;
+        if offset < 32 || end > HEADER_SIZE {
+            return None;
+        }
+        Some(Self { offset, width })
+    }
+
+    /// The top `width` bytes of the nonce field (the default layout)
+    ///
+    /// Returns `None` if `width` exceeds the 8-byte nonce.
+    pub fn in_nonce(width: usize) -> Option<Self> {
+        if width > NONCE_SIZE {
+            return None;
+        }
+        Self::new(HEADER_SIZE - width, width)
+    }
+
+    /// Byte offset in the header
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Width in bytes
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Width left for extranonce2 once the pool assigns `extranonce1_len` bytes
+    pub fn extranonce2_width(&self, extranonce1_len: usize) -> Option<usize> {
+        self.width.checked_sub(extranonce1_len)
+    }
+
+    /// Number of nonce bytes the miner may still roll
+    pub fn free_nonce_bytes(&self) -> usize {
+        let start = self.offset.max(HEADER_PREFIX_SIZE);
+        let end = (self.offset + self.width).max(HEADER_PREFIX_SIZE);
+        NONCE_SIZE - (end - start)
+    }
+
+    /// Concatenate `extranonce1 || extranonce2`, checking it fills the region exactly
+    pub fn join(&self, extranonce1: &[u8], extranonce2: &[u8]) -> Result<Vec<u8>, UhashError> {
+        let mut extranonce = Vec::with_capacity(self.width);
+        extranonce.extend_from_slice(extranonce1);
+        extranonce.extend_from_slice(extranonce2);
+        self.check(&extranonce)?;
+        Ok(extranonce)
+    }
+
+    /// Write `extranonce` into `header`
+    pub fn apply(
+        &self,
+        header: &mut [u8; HEADER_SIZE],
+        extranonce: &[u8],
+    ) -> Result<(), UhashError> {
+        self.check(extranonce)?;
+        header[self.offset..self.offset + self.width].copy_from_slice(extranonce);
+        Ok(())
+    }
+
+    fn check(&self, extranonce: &[u8]) -> Result<(), UhashError> {
+        if extranonce.len() != self.width {
+            return Err(UhashError::InvalidLength {
+                expected: self.width,
+                actual: extranonce.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A pool job as distributed over a stratum-like protocol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StratumJob {
+    /// Pool-assigned job identifier
+    pub job_id: String,
+    /// Header without the trailing nonce
+    pub prefix: [u8; HEADER_PREFIX_SIZE],
+    /// Target a hash must meet to be accepted as a share
+    pub share_target: Target,
+    /// Whether miners must abandon all previous jobs
+    pub clean_jobs: bool,
+    /// Fixed extranonce placement; `None` puts it at the top of the nonce
+    pub extranonce_region: Option<ExtranonceRegion>,
+}
+
+impl StratumJob {
+    /// Create a job for `header` (its nonce is ignored)
+    pub fn new(
+        job_id: impl Into<String>,
+        header: &MiningHeader,
+        share_target: Target,
+        clean_jobs: bool,
+    ) -> Self {
+        Self {
+            job_id: job_id.into(),
+            prefix: header.prefix(),
+            share_target,
+            clean_jobs,
+            extranonce_region: None,
+        }
+    }
+
+    /// Place the extranonce in `region` instead of the top of the nonce
+    pub fn with_extranonce_region(mut self, region: ExtranonceRegion) -> Self {
+        self.extranonce_region = Some(region);
+        self
+    }
+
+    /// Assemble the 68-byte header hashed for `(nonce, extranonce)`
+    ///
+    /// `extranonce` is written over the job's extranonce region. Without
+    /// one, it replaces the top `extranonce.len()` bytes of the nonce
+    /// field and only the remaining low bytes of `nonce` are used.
+    pub fn build_header(
+        &self,
+        nonce: u64,
+        extranonce: &[u8],
+    ) -> Result<[u8; HEADER_SIZE], UhashError> {
+        let region = match self.extranonce_region {
+            Some(region) => region,
+            None => {
+                ExtranonceRegion::in_nonce(extranonce.len()).ok_or(UhashError::InputTooLong {
+                    len: extranonce.len(),
+                    max: NONCE_SIZE,
+                })?
+            }
+        };
+        let mut bytes = [0u8; HEADER_SIZE];
+        bytes[..HEADER_PREFIX_SIZE].copy_from_slice(&self.prefix);
+        bytes[HEADER_PREFIX_SIZE..].copy_from_slice(&nonce.to_le_bytes());
+        region.apply(&mut bytes, extranonce)?;
+        Ok(bytes)
+    }
+
+    /// Hash a miner's submission and classify it against this job
+    ///
+    /// Returns the hash together with [`check_share`] against the job's
+    /// share target and `block_target`.
+    pub fn verify_submission(
+        &self,
+        hasher: &mut UniversalHash,
+        nonce: u64,
+        extranonce: &[u8],
+        block_target: &Target,
+    ) -> Result<(Hash256, ShareResult), UhashError> {
+        let hash = hasher.hash(&self.build_header(nonce, extranonce)?);
+        Ok((
+            Hash256(hash),
+            check_share(&hash, &self.share_target, block_target),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+    use crate::u256::U256;
+
+    fn sample() -> StratumJob {
+        let header = MiningHeader {
+            epoch_seed: [0x11; 32],
+            miner_address: [0x22; 20],
+            timestamp: 1_700_000_000,
+            nonce: 0,
+        };
+        StratumJob::new("job-1", &header, Target::MAX, true)
+    }
+
+    #[test]
+    fn test_build_header_layout() {
+        let job = sample();
+
+        let plain = job.build_header(0x0807060504030201, &[]).unwrap();
+        assert_eq!(&plain[..HEADER_PREFIX_SIZE], &job.prefix);
+        assert_eq!(MiningHeader::from_bytes(&plain).nonce, 0x0807060504030201);
+
+        let rolled = job.build_header(0x0807060504030201, &[0xAA, 0xBB]).unwrap();
+        assert_eq!(&rolled[60..], &[1, 2, 3, 4, 5, 6, 0xAA, 0xBB]);
+
+        assert_eq!(
+            job.build_header(0, &[0u8; 9]),
+            Err(UhashError::InputTooLong { len: 9, max: 8 })
+        );
+    }
+
+    #[test]
+    fn test_extranonce_region() {
+        assert_eq!(ExtranonceRegion::new(31, 4), None);
+        assert_eq!(ExtranonceRegion::new(66, 3), None);
+        assert_eq!(ExtranonceRegion::in_nonce(9), None);
+
+        let in_nonce = ExtranonceRegion::in_nonce(3).unwrap();
+        assert_eq!(in_nonce.offset(), 65);
+        assert_eq!(in_nonce.free_nonce_bytes(), 5);
+        assert_eq!(in_nonce.extranonce2_width(2), Some(1));
+        assert_eq!(in_nonce.extranonce2_width(4), None);
+
+        // Region in the miner address leaves the whole nonce to the miner
+        let region = ExtranonceRegion::new(48, 4).unwrap();
+        assert_eq!(region.free_nonce_bytes(), 8);
+
+        let job = sample().with_extranonce_region(region);
+        let extranonce = region.join(&[0xE1, 0xE1], &[0xE2, 0xE2]).unwrap();
+        let bytes = job.build_header(u64::MAX, &extranonce).unwrap();
+        assert_eq!(&bytes[48..52], &[0xE1, 0xE1, 0xE2, 0xE2]);
+        assert_eq!(&bytes[60..], &[0xFF; 8]);
+        assert_eq!(&bytes[..48], &job.prefix[..48]);
+
+        assert_eq!(
+            job.build_header(0, &[0u8; 3]),
+            Err(UhashError::InvalidLength {
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert!(region.join(&[1, 2, 3], &[4, 5]).is_err());
+    }
+
+    #[test]
+    fn test_verify_submission() {
+        let job = sample();
+        let mut hasher = UniversalHash::new();
+
+        let (hash_value, result) = job
+            .verify_submission(&mut hasher, 5, &[0x01], &Target(U256::ZERO))
+            .unwrap();
+        assert_eq!(hash_value.0, hash(&job.build_header(5, &[0x01]).unwrap()));
+        assert!(matches!(result, ShareResult::Share { .. }));
+    }
+}
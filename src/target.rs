@@ -0,0 +1,737 @@
+//! Difficulty targets and chainwork
+//!
+//! A hash meets a target when, read as a big-endian integer, it is less
+//! than or equal to the target. Leading-zero-bit difficulty `d` is the
+//! target `(2^256 - 1) >> d`.
+
+use core::fmt;
+use core::ops::{Add, AddAssign};
+use core::str::FromStr;
+use core::time::Duration;
+
+use crate::error::{HexError, ParseDifficultyError};
+use crate::hash256::Hash256;
+use crate::hex;
+use crate::u256::U256;
+
+/// Upper bound a hash must not exceed
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Target(pub U256);
+
+impl Target {
+    /// Easiest possible target: every hash meets it
+    pub const MAX: Target = Target(U256::MAX);
+
+    /// Target equivalent to `bits` leading zero bits
+    ///
+    /// `Target::from_difficulty_bits(d).is_met_by(h)` agrees with
+    /// `meets_difficulty(h, d)` for every hash.
+    pub fn from_difficulty_bits(bits: u32) -> Self {
+        Target(U256::MAX >> bits)
+    }
+
+    /// Construct from 32 big-endian bytes
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> Self {
+        Target(U256::from_be_bytes(bytes))
+    }
+
+    /// Encode as 32 big-endian bytes
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        self.0.to_be_bytes()
+    }
+
+    /// The target as an integer
+    pub fn as_u256(&self) -> U256 {
+        self.0
+    }
+
+    /// Whether `hash` is at or below this target
+    pub fn is_met_by(&self, hash: &Hash256) -> bool {
+        meets_target(&hash.0, self)
+    }
+
+    /// Expected number of hashes to find one meeting this target
+    pub fn work(&self) -> U256 {
+        work_from_target(self)
+    }
+
+    /// Parse 1 to 64 hex digits, with or without a `0x` prefix
+    ///
+    /// Accepts the `Display` output, so `target.to_string().parse()`
+    /// round-trips.
+    pub fn from_hex(hex: &str) -> Result<Self, HexError> {
+        let digits = hex
+            .strip_prefix("0x")
+            .or_else(|| hex.strip_prefix("0X"))
+            .unwrap_or(hex);
+        if digits.is_empty() || digits.len() > 64 {
+            return Err(HexError::InvalidLength {
+                expected: 64,
+                actual: digits.len(),
+            });
+        }
+        let mut bytes = [0u8; 32];
+        hex::decode_padded(digits, &mut bytes).ok_or(HexError::InvalidDigit)?;
+        Ok(Target::from_be_bytes(&bytes))
+    }
+}
+
+impl FromStr for Target {
+    type Err = HexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+impl From<U256> for Target {
+    fn from(value: U256) -> Self {
+        Target(value)
+    }
+}
+
+impl fmt::Debug for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Target({:#x})", self.0)
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+// Serialized as the 32 big-endian bytes: a 64-character hex string in
+// human-readable formats, raw bytes otherwise
+#[cfg(feature = "serde")]
+crate::serde_fixed::impl_fixed_serde!(Target, 32, to_be_bytes, from_be_bytes);
+
+// Same fixed-size layout, see `codec`
+#[cfg(any(feature = "borsh", feature = "bincode"))]
+crate::codec::impl_fixed_codec!(Target, 32, to_be_bytes, from_be_bytes);
+
+/// Expected number of hashes per solution, as a human-facing number
+///
+/// Difficulty 1 is [`Target::MAX`], which every hash meets, and difficulty
+/// `D` is the target `2^256 / D - 1`, so `2^d` is the same as
+/// [`Target::from_difficulty_bits(d)`](Target::from_difficulty_bits).
+/// Stored as an `f64`: conversions are exact for powers of two and
+/// otherwise accurate to about 16 significant digits.
+///
+/// Parses plain numbers (`"1500000"`, `"1.5e6"`), numbers with an SI
+/// suffix (`"1.5M"`; `k`, `M`, `G`, `T`, `P`, `E`) and `0x`-prefixed hex
+/// targets. Displays with an SI suffix and up to three decimals.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{Difficulty, Target};
+///
+/// let difficulty: Difficulty = "1.5M".parse().unwrap();
+/// assert_eq!(difficulty.value(), 1_500_000.0);
+/// assert_eq!(difficulty.to_string(), "1.5M");
+///
+/// let bits: Difficulty = "0x00000fffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(bits.to_target(), Target::from_difficulty_bits(20));
+/// assert_eq!(bits.to_string(), "1.049M");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Difficulty(f64);
+
+/// Display suffixes, largest first
+const DIFFICULTY_SUFFIXES: [(f64, &str); 6] = [
+    (1e18, "E"),
+    (1e15, "P"),
+    (1e12, "T"),
+    (1e9, "G"),
+    (1e6, "M"),
+    (1e3, "k"),
+];
+
+impl Difficulty {
+    /// Difficulty of [`Target::MAX`]
+    pub const ONE: Difficulty = Difficulty(1.0);
+
+    /// Difficulty `value`, or `None` unless it is finite and at least 1
+    pub fn new(value: f64) -> Option<Self> {
+        (value.is_finite() && value >= 1.0).then_some(Difficulty(value))
+    }
+
+    /// Expected number of hashes per solution
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+
+    /// Difficulty of `target`
+    pub fn from_target(target: &Target) -> Self {
+        Difficulty(work_from_target(target).to_f64())
+    }
+
+    /// Target of this difficulty
+    pub fn to_target(&self) -> Target {
+        // value = mantissa * 2^exponent, exactly; value >= 1 is normal
+        let bits = self.0.to_bits();
+        let mut mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let mut exponent = ((bits >> 52) & 0x7FF) as i32 - 1075;
+        let zeros = mantissa.trailing_zeros();
+        mantissa >>= zeros;
+        exponent += zeros as i32;
+
+        if mantissa == 1 {
+            // Power of two, exponent >= 0: matches `from_difficulty_bits`
+            return Target(U256::MAX >> exponent as u32);
+        }
+        // An odd mantissa does not divide 2^256, so (2^256 - 1) / mantissa
+        // is floor(2^256 / mantissa); value >= 1 keeps the shift in range
+        let quotient = U256::MAX.checked_div(U256::from_u64(mantissa)).unwrap();
+        let scaled = if exponent >= 0 {
+            quotient >> exponent as u32
+        } else {
+            quotient << (-exponent) as u32
+        };
+        // Saturates for values beyond 2^256, which no hash can meet anyway
+        Target(scaled.saturating_sub(U256::ONE))
+    }
+}
+
+impl From<&Target> for Difficulty {
+    fn from(target: &Target) -> Self {
+        Difficulty::from_target(target)
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (scale, suffix) = DIFFICULTY_SUFFIXES
+            .iter()
+            .copied()
+            .find(|&(scale, _)| self.0 >= scale)
+            .unwrap_or((1.0, ""));
+        let scaled = self.0 / scale;
+        if scaled >= 1e15 {
+            return write!(f, "{:e}", self.0);
+        }
+
+        // Three decimals with trailing zeros dropped; no float rounding in core
+        let thousandths = (scaled * 1000.0 + 0.5) as u64;
+        let (whole, mut fraction) = (thousandths / 1000, thousandths % 1000);
+        if fraction == 0 {
+            return write!(f, "{}{}", whole, suffix);
+        }
+        let mut width = 3;
+        while fraction % 10 == 0 {
+            fraction /= 10;
+            width -= 1;
+        }
+        write!(f, "{}.{:0width$}{}", whole, fraction, suffix, width = width)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = ParseDifficultyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with("0x") || s.starts_with("0X") {
+            let target = Target::from_hex(s).map_err(ParseDifficultyError::InvalidTarget)?;
+            return Ok(Difficulty::from_target(&target));
+        }
+
+        let scale = match s.bytes().last() {
+            Some(b'k' | b'K') => 1e3,
+            Some(b'M') => 1e6,
+            Some(b'G') => 1e9,
+            Some(b'T') => 1e12,
+            Some(b'P') => 1e15,
+            Some(b'E') => 1e18,
+            _ => 1.0,
+        };
+        let number = if scale == 1.0 { s } else { &s[..s.len() - 1] };
+        let value: f64 = number
+            .parse()
+            .map_err(|_| ParseDifficultyError::InvalidNumber)?;
+        Difficulty::new(value * scale).ok_or(ParseDifficultyError::OutOfRange)
+    }
+}
+
+/// Check whether `hash` meets `target` (hash ≤ target, big-endian)
+///
+/// Runs in constant time: every byte of both values is compared, so
+/// timing doesn't reveal where `hash` first differs from `target`.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{Target, meets_difficulty, meets_target};
+///
+/// let hash = [0x00, 0x0F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+///             0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+///             0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+///             0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+/// assert!(meets_target(&hash, &Target::from_difficulty_bits(12)));
+/// assert!(!meets_target(&hash, &Target::from_difficulty_bits(13)));
+/// assert!(meets_difficulty(&hash, 12));
+/// ```
+pub fn meets_target(hash: &[u8; 32], target: &Target) -> bool {
+    crate::ct::le(hash, &target.to_be_bytes())
+}
+
+/// Classification of a pool share submission
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShareResult {
+    /// Hash does not meet the share target
+    Invalid,
+    /// Hash meets the share target but not the block target
+    Share {
+        /// How many times harder the hash is than the share target requires
+        /// (`(share_target + 1) / (hash + 1)`, at least 1.0)
+        margin: f64,
+    },
+    /// Hash meets the block target
+    Block {
+        /// How many times harder the hash is than the share target requires
+        margin: f64,
+    },
+    /// Submission was already accepted, see `ShareDeduplicator`
+    Duplicate,
+}
+
+impl ShareResult {
+    /// Whether the submission counts as a valid share (blocks included)
+    pub fn is_valid(&self) -> bool {
+        matches!(self, ShareResult::Share { .. } | ShareResult::Block { .. })
+    }
+
+    /// Whether the submission solves a block
+    pub fn is_block(&self) -> bool {
+        matches!(self, ShareResult::Block { .. })
+    }
+}
+
+/// Classify `hash` against a pool's share target and the network block target
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{ShareResult, Target, check_share};
+///
+/// let share = Target::from_difficulty_bits(8);
+/// let block = Target::from_difficulty_bits(16);
+///
+/// let mut hash = [0xFFu8; 32];
+/// assert_eq!(check_share(&hash, &share, &block), ShareResult::Invalid);
+///
+/// hash[0] = 0x00; // 8 leading zero bits
+/// assert!(matches!(check_share(&hash, &share, &block), ShareResult::Share { .. }));
+///
+/// hash[1] = 0x00; // 16 leading zero bits
+/// assert!(check_share(&hash, &share, &block).is_block());
+/// ```
+pub fn check_share(hash: &[u8; 32], share_target: &Target, block_target: &Target) -> ShareResult {
+    let value = U256::from_be_bytes(hash);
+    if value > share_target.0 && value > block_target.0 {
+        return ShareResult::Invalid;
+    }
+    let margin = share_quality(&Hash256(*hash), share_target).max(1.0);
+    if value <= block_target.0 {
+        ShareResult::Block { margin }
+    } else {
+        ShareResult::Share { margin }
+    }
+}
+
+/// Number of leading zero bits of `hash`, its leading-zero-bit difficulty
+///
+/// `meets_difficulty(hash, d)` holds exactly for `d <= leading_zero_bits(hash)`.
+pub fn leading_zero_bits(hash: &Hash256) -> u32 {
+    hash.leading_zeros()
+}
+
+/// How close `hash` comes to meeting `target`: `(target + 1) / (hash + 1)`
+///
+/// At least 1.0 when the hash meets the target, with larger values for
+/// luckier hashes; below 1.0 for near misses, e.g. 0.5 for a hash twice
+/// the target. Comparable across targets when multiplied by the target's
+/// [`Difficulty`], which gives the hash's own difficulty for "best share"
+/// statistics.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{Hash256, Target, leading_zero_bits, share_quality};
+///
+/// let mut hash = Hash256([0xFF; 32]);
+/// hash.0[0] = 0x00;
+/// hash.0[1] = 0x17; // 11 leading zero bits
+/// assert_eq!(leading_zero_bits(&hash), 11);
+///
+/// let quality = share_quality(&hash, &Target::from_difficulty_bits(12));
+/// assert!(quality > 0.5 && quality < 1.0); // near miss
+/// ```
+pub fn share_quality(hash: &Hash256, target: &Target) -> f64 {
+    // +1.0 keeps an all-zero hash finite; precision loss is irrelevant for statistics
+    (target.0.to_f64() + 1.0) / (hash.to_u256().to_f64() + 1.0)
+}
+
+/// Expected work for a target: `2^256 / (target + 1)`
+///
+/// A zero target (no hash except all-zero meets it) saturates to
+/// [`U256::MAX`].
+pub fn work_from_target(target: &Target) -> U256 {
+    if target.0 == U256::MAX {
+        return U256::ONE;
+    }
+    // 2^256 / (t + 1) == (2^256 - 1 - t) / (t + 1) + 1, avoiding a 257-bit value
+    let divisor = target.0 + U256::ONE;
+    (!target.0)
+        .checked_div(divisor)
+        .unwrap()
+        .saturating_add(U256::ONE)
+}
+
+/// Expected time to find a hash meeting `target` at `hashrate` hashes per second
+///
+/// Returns [`Duration::MAX`] for a non-positive hashrate or when the
+/// expectation does not fit in a `Duration`.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use uhash_core::{Target, estimate_time_to_block, required_hashrate};
+///
+/// let target = Target::from_difficulty_bits(20); // ~1M hashes per block
+/// assert_eq!(estimate_time_to_block(1024.0, &target), Duration::from_secs(1024));
+/// assert_eq!(required_hashrate(&target, Duration::from_secs(1024)), 1024.0);
+/// ```
+pub fn estimate_time_to_block(hashrate: f64, target: &Target) -> Duration {
+    if hashrate.is_nan() || hashrate <= 0.0 {
+        return Duration::MAX;
+    }
+    Duration::try_from_secs_f64(work_from_target(target).to_f64() / hashrate)
+        .unwrap_or(Duration::MAX)
+}
+
+/// Hashrate needed to find a hash meeting `target` in `desired_time` on average
+///
+/// Returns `f64::INFINITY` for a zero `desired_time`.
+pub fn required_hashrate(target: &Target, desired_time: Duration) -> f64 {
+    let seconds = desired_time.as_secs_f64();
+    if seconds == 0.0 {
+        return f64::INFINITY;
+    }
+    work_from_target(target).to_f64() / seconds
+}
+
+/// Target at which `hashrate` hashes per second find a block every `block_time` on average
+///
+/// The inverse of [`estimate_time_to_block`], for deriving an initial
+/// difficulty from an expected network hashrate. Returns [`Target::MAX`]
+/// when less than one hash per block is expected (including a
+/// non-positive or NaN hashrate), and the zero target when the expected
+/// work exceeds 2^256.
+///
+/// # Example
+///
+/// ```rust
+/// use std::time::Duration;
+/// use uhash_core::{Target, estimate_time_to_block, target_for};
+///
+/// // A testnet of ten 1.6 kH/s phones aiming for 60 second blocks
+/// let target = target_for(16_000.0, Duration::from_secs(60));
+/// let expected = estimate_time_to_block(16_000.0, &target);
+/// assert_eq!(expected.as_secs(), 60);
+///
+/// assert_eq!(target_for(1024.0, Duration::from_secs(1024)), Target::from_difficulty_bits(20));
+/// ```
+pub fn target_for(hashrate: f64, block_time: Duration) -> Target {
+    let work = hashrate * block_time.as_secs_f64();
+    if work.is_nan() || work <= 1.0 {
+        return Target::MAX;
+    }
+    Difficulty::new(work).map_or(Target(U256::ZERO), |difficulty| difficulty.to_target())
+}
+
+/// Accumulated chainwork for fork choice
+///
+/// Saturates at [`U256::MAX`] rather than overflowing.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct ChainWork(pub U256);
+
+impl ChainWork {
+    /// No work
+    pub const ZERO: ChainWork = ChainWork(U256::ZERO);
+
+    /// Total accumulated work
+    pub fn total(&self) -> U256 {
+        self.0
+    }
+
+    /// Add the work of one block solved at `target`
+    pub fn add_target(&mut self, target: &Target) {
+        self.add_work(work_from_target(target));
+    }
+
+    /// Add an explicit amount of work
+    pub fn add_work(&mut self, work: U256) {
+        self.0 = self.0.saturating_add(work);
+    }
+}
+
+impl Add for ChainWork {
+    type Output = ChainWork;
+
+    fn add(self, rhs: ChainWork) -> ChainWork {
+        ChainWork(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl AddAssign<&Target> for ChainWork {
+    fn add_assign(&mut self, target: &Target) {
+        self.add_target(target);
+    }
+}
+
+impl fmt::Debug for ChainWork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ChainWork({})", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meets_difficulty;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_difficulty_bits_agree_with_meets_difficulty() {
+        let mut hash = [0xFFu8; 32];
+        hash[0] = 0x00;
+        hash[1] = 0x01; // 15 leading zero bits
+
+        for bits in 0..=32 {
+            assert_eq!(
+                meets_target(&hash, &Target::from_difficulty_bits(bits)),
+                meets_difficulty(&hash, bits),
+                "bits = {}",
+                bits
+            );
+        }
+        assert!(meets_target(&[0u8; 32], &Target::from_difficulty_bits(256)));
+        assert!(!meets_target(&hash, &Target::from_difficulty_bits(256)));
+    }
+
+    #[test]
+    fn test_work_from_target() {
+        assert_eq!(work_from_target(&Target::MAX), U256::ONE);
+        assert_eq!(
+            work_from_target(&Target::from_difficulty_bits(1)),
+            U256::from_u64(2)
+        );
+        assert_eq!(
+            work_from_target(&Target::from_difficulty_bits(32)),
+            U256::from_u64(1 << 32)
+        );
+        assert_eq!(work_from_target(&Target(U256::ZERO)), U256::MAX);
+    }
+
+    #[test]
+    fn test_check_share() {
+        let share = Target::from_difficulty_bits(4);
+        let block = Target::from_difficulty_bits(12);
+
+        assert_eq!(
+            check_share(&[0xFF; 32], &share, &block),
+            ShareResult::Invalid
+        );
+
+        let mut hash = [0xFFu8; 32];
+        hash[0] = 0x0F;
+        assert_eq!(
+            check_share(&hash, &share, &block),
+            ShareResult::Share { margin: 1.0 }
+        );
+
+        hash[0] = 0x00; // 8 bits: 16x harder than the share target
+        match check_share(&hash, &share, &block) {
+            ShareResult::Share { margin } => assert!((margin - 16.0).abs() < 1e-9),
+            other => panic!("expected share, got {:?}", other),
+        }
+
+        hash[1] = 0x0F;
+        let result = check_share(&hash, &share, &block);
+        assert!(result.is_block() && result.is_valid());
+        assert!(check_share(&[0u8; 32], &share, &block).is_block());
+    }
+
+    #[test]
+    fn test_leading_zero_bits_and_share_quality() {
+        let mut hash = Hash256([0xFF; 32]);
+        assert_eq!(leading_zero_bits(&hash), 0);
+        hash.0[0] = 0x00;
+        hash.0[1] = 0x7F;
+        assert_eq!(leading_zero_bits(&hash), 9);
+        assert_eq!(leading_zero_bits(&Hash256::ZERO), 256);
+        for bits in 0..=16 {
+            assert_eq!(meets_difficulty(&hash.0, bits), bits <= 9);
+        }
+
+        // The hash is exactly the 9-bit target
+        let target = Target::from_difficulty_bits(9);
+        assert_eq!(hash.to_u256(), target.0);
+        assert_eq!(share_quality(&hash, &target), 1.0);
+        assert_eq!(share_quality(&hash, &Target::from_difficulty_bits(10)), 0.5);
+        assert_eq!(share_quality(&hash, &Target::from_difficulty_bits(7)), 4.0);
+        assert!(share_quality(&Hash256::ZERO, &target).is_finite());
+
+        // Scaled by the target's difficulty, quality is independent of the target
+        let own = |bits| {
+            let target = Target::from_difficulty_bits(bits);
+            share_quality(&hash, &target) * Difficulty::from_target(&target).value()
+        };
+        assert_eq!(own(4), own(20));
+    }
+
+    #[test]
+    fn test_time_estimates() {
+        let target = Target::from_difficulty_bits(10);
+        assert_eq!(
+            estimate_time_to_block(256.0, &target),
+            Duration::from_secs(4)
+        );
+        assert_eq!(estimate_time_to_block(0.0, &target), Duration::MAX);
+        assert_eq!(estimate_time_to_block(f64::NAN, &target), Duration::MAX);
+        assert_eq!(
+            estimate_time_to_block(1e-300, &Target(U256::ZERO)),
+            Duration::MAX
+        );
+
+        assert_eq!(required_hashrate(&target, Duration::from_secs(4)), 256.0);
+        assert_eq!(required_hashrate(&target, Duration::ZERO), f64::INFINITY);
+        assert_eq!(
+            required_hashrate(&Target::MAX, Duration::from_millis(500)),
+            2.0
+        );
+    }
+
+    #[test]
+    fn test_target_for_inverts_time_estimate() {
+        for (hashrate, seconds) in [(1.0e3, 60), (2.5e6, 600), (7.0, 1)] {
+            let block_time = Duration::from_secs(seconds);
+            let target = target_for(hashrate, block_time);
+            let estimate = estimate_time_to_block(hashrate, &target).as_secs_f64();
+            assert!((estimate / seconds as f64 - 1.0).abs() < 1e-9);
+            assert!((required_hashrate(&target, block_time) / hashrate - 1.0).abs() < 1e-9);
+        }
+
+        let minute = Duration::from_secs(60);
+        assert_eq!(target_for(0.0, minute), Target::MAX);
+        assert_eq!(target_for(-5.0, minute), Target::MAX);
+        assert_eq!(target_for(f64::NAN, minute), Target::MAX);
+        assert_eq!(target_for(1e3, Duration::ZERO), Target::MAX);
+        assert_eq!(target_for(f64::INFINITY, minute), Target(U256::ZERO));
+    }
+
+    #[test]
+    fn test_chainwork_accumulates() {
+        let mut work = ChainWork::ZERO;
+        work += &Target::from_difficulty_bits(8);
+        work.add_target(&Target::from_difficulty_bits(10));
+        assert_eq!(work.total(), U256::from_u64(256 + 1024));
+
+        let heavier = work + ChainWork(U256::ONE);
+        assert!(heavier > work);
+
+        work.add_work(U256::MAX);
+        assert_eq!(work.total(), U256::MAX);
+    }
+
+    #[test]
+    fn test_difficulty_target_conversions() {
+        assert_eq!(Difficulty::ONE.to_target(), Target::MAX);
+        assert_eq!(Difficulty::from_target(&Target::MAX), Difficulty::ONE);
+        for bits in [1, 20, 63, 200, 255] {
+            let target = Target::from_difficulty_bits(bits);
+            let difficulty = Difficulty::from_target(&target);
+            assert_eq!(difficulty.value(), 2f64.powi(bits as i32));
+            assert_eq!(difficulty.to_target(), target);
+        }
+
+        // Integer difficulties survive the round trip through a target
+        for value in [3.0, 1.5e6, 123_456_789.0, 1e30] {
+            let difficulty = Difficulty::new(value).unwrap();
+            assert_eq!(Difficulty::from_target(&difficulty.to_target()), difficulty);
+        }
+        let fractional = Difficulty::new(1.75).unwrap().to_target();
+        let seventh = U256::MAX.checked_div(U256::from_u64(7)).unwrap();
+        assert_eq!(fractional, Target((seventh << 2) - U256::ONE));
+
+        assert_eq!(
+            Difficulty::new(3e77).unwrap().to_target(),
+            Target(U256::ZERO)
+        );
+        assert_eq!(
+            Difficulty::new(f64::MAX).unwrap().to_target(),
+            Target(U256::ZERO)
+        );
+        assert_eq!(Difficulty::new(0.5), None);
+        assert_eq!(Difficulty::new(f64::INFINITY), None);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_difficulty_parse_and_display() {
+        let parse = |s: &str| s.parse::<Difficulty>().map(|d| d.value());
+        assert_eq!(parse("1"), Ok(1.0));
+        assert_eq!(parse(" 1500000 "), Ok(1.5e6));
+        assert_eq!(parse("1.5e6"), Ok(1.5e6));
+        assert_eq!(parse("1.5M"), Ok(1.5e6));
+        assert_eq!(parse("2k"), Ok(2e3));
+        assert_eq!(parse("2K"), Ok(2e3));
+        assert_eq!(parse("3G"), Ok(3e9));
+        assert_eq!(parse("1E"), Ok(1e18));
+        assert_eq!(parse("0x0fff"), Ok(2f64.powi(244)));
+
+        assert_eq!(parse(""), Err(ParseDifficultyError::InvalidNumber));
+        assert_eq!(parse("1.5X"), Err(ParseDifficultyError::InvalidNumber));
+        assert_eq!(parse("M"), Err(ParseDifficultyError::InvalidNumber));
+        assert_eq!(parse("0.5"), Err(ParseDifficultyError::OutOfRange));
+        assert_eq!(parse("inf"), Err(ParseDifficultyError::OutOfRange));
+        assert_eq!(
+            parse("0xzz"),
+            Err(ParseDifficultyError::InvalidTarget(HexError::InvalidDigit))
+        );
+
+        let show = |value: f64| Difficulty::new(value).unwrap().to_string();
+        assert_eq!(show(1.0), "1");
+        assert_eq!(show(999.0), "999");
+        assert_eq!(show(1500.0), "1.5k");
+        assert_eq!(show(1.5e6), "1.5M");
+        assert_eq!(show(1_048_576.0), "1.049M");
+        assert_eq!(show(2e18), "2E");
+        assert_eq!(show(1e40), "1e40");
+
+        // Display output parses back
+        for value in [1.0, 1234.0, 1.5e6, 7.25e12, 3e20] {
+            let shown = show(value);
+            assert_eq!(parse(&shown), Ok(value), "{}", shown);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_target_hex_roundtrip() {
+        let target = Target::from_difficulty_bits(20);
+        assert_eq!(target.to_string().parse(), Ok(target));
+        assert_eq!(Target::from_hex("ff"), Ok(Target(U256::from_u64(0xFF))));
+        assert_eq!(Target::from_hex(&"f".repeat(64)), Ok(Target::MAX));
+        assert!(Target::from_hex("0x").is_err());
+        assert!(Target::from_hex(&"f".repeat(65)).is_err());
+    }
+}
@@ -3,7 +3,7 @@
 use crate::{UniversalHash, hash, meets_difficulty};
 
 #[cfg(not(feature = "std"))]
-use alloc::vec;
+use alloc::{boxed::Box, vec, vec::Vec};
 
 #[test]
 fn test_basic_hash() {
@@ -104,6 +104,144 @@ fn test_empty_input() {
     assert_eq!(result.len(), 32);
 }
 
+#[test]
+fn test_try_hash_rejects_short_input() {
+    use crate::{UhashError, try_hash};
+
+    for len in 0..8 {
+        assert_eq!(
+            try_hash(&vec![0u8; len]),
+            Err(UhashError::InputTooShort { len, min: 8 })
+        );
+    }
+
+    let input = [0x11u8; 8];
+    assert_eq!(try_hash(&input), Ok(hash(&input)));
+}
+
+#[test]
+fn test_hash_header_strict_length() {
+    use crate::{HEADER_SIZE, UhashError, hash_header};
+
+    for len in [0, 8, 67, 69, 100] {
+        assert_eq!(
+            hash_header(&vec![0u8; len]),
+            Err(UhashError::InvalidLength {
+                expected: HEADER_SIZE,
+                actual: len
+            })
+        );
+    }
+
+    let input = [0x22u8; HEADER_SIZE];
+    assert_eq!(hash_header(&input), Ok(hash(&input)));
+}
+
+#[test]
+fn test_hash_parts_matches_concatenation() {
+    let mut hasher = UniversalHash::new();
+    let data: Vec<u8> = (0u8..68).collect();
+
+    // Splits before, inside and after the nonce region
+    for split in [0, 30, 60, 63, 68] {
+        let (a, b) = data.split_at(split);
+        assert_eq!(
+            hasher.hash_parts(&[a, b]),
+            hash(&data),
+            "split at {}",
+            split
+        );
+    }
+    assert_eq!(
+        hasher.hash_parts(&[&data[..32], &data[32..52], &data[52..60], &data[60..]]),
+        hash(&data)
+    );
+
+    // Short inputs and empty parts
+    assert_eq!(hasher.hash_parts(&[b"ab", b"", b"c"]), hash(b"abc"));
+    assert_eq!(hasher.hash_parts(&[]), hash(b""));
+}
+
+#[test]
+fn test_hash_keyed_domain_separation() {
+    use crate::{Job, hash_keyed};
+
+    let input = [0x33u8; 68];
+    let key_a = [1u8; 32];
+    let key_b = [2u8; 32];
+
+    let keyed_a = hash_keyed(&key_a, &input);
+    assert_ne!(keyed_a, hash(&input));
+    assert_ne!(keyed_a, hash_keyed(&key_b, &input));
+    assert_eq!(keyed_a, hash_keyed(&key_a, &input));
+
+    // Keyed job midstate agrees with the keyed hash
+    let job = Job::from_header_keyed(&key_a, &input[..60]);
+    let nonce = u64::from_le_bytes(input[60..].try_into().unwrap());
+    assert_eq!(UniversalHash::new().hash_nonce(&job, nonce), keyed_a);
+}
+
+#[test]
+fn test_reused_hasher_resets_contexts() {
+    // A keyed hash leaves keyed state in the reused seed context; neither
+    // it nor the previous header may leak into the next hash
+    let mut hasher = UniversalHash::new_sequential();
+    let keyed = hasher.hash_keyed(&[9u8; 32], b"reuse");
+    assert_eq!(hasher.hash(b"reuse"), hash(b"reuse"));
+    assert_eq!(hasher.hash_keyed(&[9u8; 32], b"reuse"), keyed);
+
+    let mut xof = [0u8; 32];
+    hasher.hash_xof(b"other input", &mut xof);
+    assert_eq!(xof, hash(b"other input"));
+}
+
+#[test]
+fn test_hash_chain_links() {
+    use crate::{double_hash, hash_chain};
+
+    let first = hash(b"chain seed");
+    let second = hash(&first);
+    let third = hash(&second);
+    assert_eq!(double_hash(b"chain seed"), second);
+    assert_eq!(hash_chain(b"chain seed", 0), first);
+    assert_eq!(hash_chain(b"chain seed", 2), third);
+    assert_eq!(UniversalHash::new().hash_chain(b"chain seed", 2), third);
+}
+
+#[test]
+fn test_hash_xof_extends_hash() {
+    let mut hasher = UniversalHash::new();
+    let input = b"xof input";
+
+    let mut long = [0u8; 200];
+    hasher.hash_xof(input, &mut long);
+    assert_eq!(long[..32], hash(input));
+    assert_ne!(long[32..64], long[..32]);
+
+    // Shorter requests are prefixes of longer ones
+    let mut short = [0u8; 40];
+    hasher.hash_xof(input, &mut short);
+    assert_eq!(short, long[..40]);
+}
+
+#[test]
+fn test_hash_into_matches_hash() {
+    use crate::{SCRATCHPAD_SIZE, UniversalHashIn};
+
+    let mut hasher = UniversalHash::new();
+    let mut buffer = vec![0u8; SCRATCHPAD_SIZE];
+    let mut hasher_in = UniversalHashIn::new_in(&mut buffer).unwrap();
+    for input in [&b"into"[..], b"", &[0x5A; 68]] {
+        let mut out = [0xFFu8; 32];
+        hasher.hash_into(input, &mut out);
+        assert_eq!(out, hash(input));
+
+        let mut out_in = [0u8; 32];
+        hasher_in.hash_into(input, &mut out_in);
+        assert_eq!(out_in, out);
+    }
+}
+
 #[test]
 fn test_large_input() {
     let large_input = vec![0xABu8; 10000];
@@ -111,6 +249,19 @@ fn test_large_input() {
     assert_eq!(result.len(), 32);
 }
 
+#[cfg(feature = "std")]
+#[test]
+fn test_hash_reader_streams_large_input() {
+    // Larger than the read buffer, with a length that isn't a multiple of it
+    let payload: Vec<u8> = (0..3_000_001u32).map(|i| (i % 251) as u8).collect();
+    let mut hasher = UniversalHash::new();
+    assert_eq!(
+        hasher.hash_reader(payload.as_slice()).unwrap(),
+        hash(&payload)
+    );
+    assert_eq!(hasher.hash_reader(&b"short"[..]).unwrap(), hash(b"short"));
+}
+
 /// Spec compliance test vectors
 /// These vectors verify the implementation matches the UniversalHash v4 spec:
 /// - Seed generation: BLAKE3(header || (nonce ⊕ (c × golden_ratio)))
@@ -238,97 +389,297 @@ fn test_known_vector() {
     assert_eq!(result, result2);
 }
 
+#[cfg(feature = "std")]
 #[test]
 #[ignore] // Run with: cargo test timing_breakdown -- --ignored --nocapture
 fn timing_breakdown() {
-    use crate::params::*;
-    use crate::primitives::{aes_compress, aes_expand_block, blake3_compress, sha256_compress};
-    use std::time::Instant;
+    println!("\n=== TIMING BREAKDOWN ===");
+    println!("{}", crate::profile::breakdown());
+}
 
-    let input = b"timing test input";
-    let iterations = 10;
+#[test]
+fn test_job_midstate_matches_full_hash() {
+    use crate::Job;
 
-    // Warmup
-    for _ in 0..3 {
-        let _ = hash(input);
-    }
+    let prefix = [0x5Au8; 60];
+    let job = Job::from_header(&prefix);
+    let mut hasher = UniversalHash::new();
 
-    // Measure total hash time
-    let start = Instant::now();
-    for _ in 0..iterations {
-        let _ = hash(input);
+    for nonce in [0u64, 1, u64::MAX] {
+        let mut input = prefix.to_vec();
+        input.extend_from_slice(&nonce.to_le_bytes());
+        assert_eq!(hasher.hash_nonce(&job, nonce), hash(&input));
     }
-    let total = start.elapsed();
-    let per_hash = total / iterations;
 
-    // Measure individual primitives
-    let state = [0u8; 32];
-    let block = [1u8; 64];
-    let prim_iters = 10000;
+    // Empty prefix: input is just the nonce
+    let job = Job::from_header(&[]);
+    assert_eq!(hasher.hash_nonce(&job, 7), hash(&7u64.to_le_bytes()));
+}
+
+#[test]
+fn test_hash_in_caller_buffer() {
+    use crate::{Job, MiningHeader, SCRATCHPAD_SIZE, UhashError, UniversalHashIn};
+
+    let mut small = vec![0u8; SCRATCHPAD_SIZE - 1];
+    assert_eq!(
+        UniversalHashIn::new_in(&mut small).err(),
+        Some(UhashError::BufferTooSmall {
+            len: SCRATCHPAD_SIZE - 1,
+            min: SCRATCHPAD_SIZE
+        })
+    );
+
+    // Extra space beyond one scratchpad is ignored
+    let mut buffer = vec![0u8; SCRATCHPAD_SIZE + 64];
+    let mut hasher = UniversalHashIn::new_in(&mut buffer).unwrap();
+    let header = MiningHeader::default().with_nonce(42);
+    let bytes = header.to_bytes();
+
+    assert_eq!(hasher.hash(b"short"), hash(b"short"));
+    assert_eq!(hasher.hash_header(&bytes), Ok(hash(&bytes)));
+    assert_eq!(
+        hasher.hash_nonce(&Job::from_header(&bytes[..60]), 42),
+        hash(&bytes)
+    );
+    assert_eq!(
+        hasher.verify_pow(&header, 0).unwrap().0,
+        UniversalHash::new().verify_pow(&header, 0).unwrap().0
+    );
+}
 
-    let start_aes = Instant::now();
-    for _ in 0..prim_iters {
-        let _ = aes_compress(&state, &block);
+#[test]
+fn test_sequential_matches_parallel() {
+    let mut sequential = UniversalHash::new_sequential();
+    for input in [&b"sequential"[..], b"", &[0xA5; 68]] {
+        assert_eq!(sequential.hash(input), hash(input));
     }
-    let aes_time = start_aes.elapsed() / prim_iters;
+}
 
-    let start_sha = Instant::now();
-    for _ in 0..prim_iters {
-        let _ = sha256_compress(&state, &block);
+#[test]
+fn test_primitive_set_slots() {
+    use crate::{
+        AesCompress, BLOCK_SIZE, Blake3Compress, CompressionPrimitive, ConsensusPrimitives,
+        Primitives, Sha256Compress,
+    };
+
+    /// Stand-in for a research primitive
+    struct Identity;
+
+    impl CompressionPrimitive for Identity {
+        fn compress(state: &[u8; 32], _block: &[u8; BLOCK_SIZE]) -> [u8; 32] {
+            *state
+        }
     }
-    let sha_time = start_sha.elapsed() / prim_iters;
 
-    let start_blake = Instant::now();
-    for _ in 0..prim_iters {
-        let _ = blake3_compress(&state, &block);
+    let input = b"primitives";
+    let mut consensus = UniversalHash::<ConsensusPrimitives>::with_primitives();
+    assert_eq!(consensus.hash(input), hash(input));
+
+    // Every slot is actually used: replacing any one changes the result
+    let swapped = [
+        UniversalHash::<Primitives<Identity, Sha256Compress, Blake3Compress>>::with_primitives()
+            .hash(input),
+        UniversalHash::<Primitives<AesCompress, Identity, Blake3Compress>>::with_primitives()
+            .hash(input),
+        UniversalHash::<Primitives<AesCompress, Sha256Compress, Identity>>::with_primitives()
+            .hash(input),
+    ];
+    for research in swapped {
+        assert_ne!(research, hash(input));
     }
-    let blake_time = start_blake.elapsed() / prim_iters;
-
-    // Measure AES expand (used in scratchpad init)
-    let key16 = [0u8; 16];
-    let state16 = [1u8; 16];
-    let start_expand = Instant::now();
-    for _ in 0..prim_iters {
-        let _ = aes_expand_block(&state16, &key16);
+}
+
+#[test]
+fn test_hardened_rounds_match_direct_access() {
+    use crate::SCRATCHPAD_SIZE;
+    use crate::primitives::ConsensusPrimitives;
+    use crate::uhash::{fill_scratchpad_aes, round_step_spec_compliant};
+
+    let seed = [0x42u8; 32];
+    let mut direct: Box<[u8; SCRATCHPAD_SIZE]> = vec![0u8; SCRATCHPAD_SIZE].try_into().unwrap();
+    fill_scratchpad_aes(&mut direct[..], &seed);
+    let mut hardened = direct.clone();
+    let (mut direct_state, mut hardened_state) = (seed, seed);
+    let (mut direct_sum, mut hardened_sum) = (0u64, 0u64);
+
+    for round in 0..64 {
+        round_step_spec_compliant::<ConsensusPrimitives>(
+            &mut direct,
+            &mut direct_state,
+            1,
+            round,
+            false,
+            false,
+            Some(&mut direct_sum),
+        );
+        round_step_spec_compliant::<ConsensusPrimitives>(
+            &mut hardened,
+            &mut hardened_state,
+            1,
+            round,
+            false,
+            true,
+            Some(&mut hardened_sum),
+        );
     }
-    let expand_time = start_expand.elapsed() / prim_iters;
+    assert_eq!(hardened_state, direct_state);
+    assert_eq!(hardened_sum, direct_sum);
+    assert!(hardened == direct);
+}
 
-    // Estimate scratchpad init time
-    // Each scratchpad has BLOCKS_PER_SCRATCHPAD blocks, each needs 2 AES expansions
-    let scratchpad_init_est = expand_time * (BLOCKS_PER_SCRATCHPAD * 2 * CHAINS) as u32;
+#[test]
+#[ignore] // Orders of magnitude slower than a normal hash
+fn test_cache_hardened_hash() {
+    let mut hasher = UniversalHash::new().with_cache_hardening(true);
+    assert_eq!(hasher.hash(b"secret"), hash(b"secret"));
+}
 
-    // Round execution estimate
-    let ops_per_hash = ROUNDS * CHAINS;
-    let primitive_avg = (aes_time + sha_time + blake_time) / 3;
-    let rounds_est = primitive_avg * ops_per_hash as u32;
+#[test]
+fn test_address_words_little_endian() {
+    use crate::uhash::compute_address;
+
+    // Low bytes of each state word select the block on every host
+    let mut state = [0u8; 32];
+    state[0] = 1;
+    assert_eq!(compute_address(&state, 0), 64);
+    state[8] = 2;
+    assert_eq!(compute_address(&state, 0), 3 * 64);
+}
 
-    println!("\n=== TIMING BREAKDOWN ===");
-    println!("Total per hash: {:?}", per_hash);
-    println!("Hashrate: {:.1} H/s", 1.0 / per_hash.as_secs_f64());
-    println!("\nPrimitive timing:");
-    println!("  AES_Compress:    {:?}", aes_time);
-    println!("  SHA256_Compress: {:?}", sha_time);
-    println!("  BLAKE3_Compress: {:?}", blake_time);
-    println!("  AES_Expand:      {:?}", expand_time);
-    println!("  Primitive avg:   {:?}", primitive_avg);
-    println!("\nParameters:");
-    println!(
-        "  ROUNDS: {} × {} chains = {} ops",
-        ROUNDS, CHAINS, ops_per_hash
-    );
-    println!(
-        "  SCRATCHPAD: {} blocks × {} chains × 2 AES = {} AES ops",
-        BLOCKS_PER_SCRATCHPAD,
-        CHAINS,
-        BLOCKS_PER_SCRATCHPAD * 2 * CHAINS
+#[test]
+fn test_consensus_arithmetic_at_bounds() {
+    use crate::uhash::{compute_address, extract_nonce, initial_primitive, round_primitive};
+    use crate::{BLOCK_SIZE, ROUNDS, SCRATCHPAD_SIZE};
+
+    // (nonce + chain) wraps at 2^64; 2^64 - 1 is divisible by 3
+    assert_eq!(initial_primitive(u64::MAX, 0), 0);
+    assert_eq!(initial_primitive(u64::MAX, 1), 0);
+    assert_eq!(initial_primitive(u64::MAX, 3), 2);
+
+    // Exact for rounds far beyond ROUNDS, where a plain sum would overflow
+    for initial in 0..3 {
+        for round in [0, 1, ROUNDS - 1, ROUNDS, usize::MAX - 1, usize::MAX] {
+            let expected = ((initial as u128 + round as u128 + 1) % 3) as usize;
+            assert_eq!(round_primitive(initial, round), expected);
+        }
+    }
+
+    for round in [0, ROUNDS - 1, usize::MAX] {
+        let address = compute_address(&[0xFF; 32], round);
+        assert!(address <= SCRATCHPAD_SIZE - BLOCK_SIZE);
+        assert_eq!(address % BLOCK_SIZE, 0);
+    }
+
+    assert_eq!(extract_nonce(&[0xFF; 8]), u64::MAX);
+    assert_eq!(
+        extract_nonce(&[0xFF; 7]),
+        u64::from_le_bytes(blake3::hash(&[0xFF; 7]).as_bytes()[..8].try_into().unwrap())
     );
-    println!("\nTime breakdown estimate:");
-    println!("  Scratchpad init: {:?}", scratchpad_init_est);
-    println!("  Round execution: {:?}", rounds_est);
-    println!("  Total estimated: {:?}", scratchpad_init_est + rounds_est);
-    println!("  Actual total:    {:?}", per_hash);
-    println!(
-        "  Overhead:        {:?}",
-        per_hash.saturating_sub(scratchpad_init_est + rounds_est)
+}
+
+#[test]
+fn test_block_offset_mask_keeps_blocks_in_bounds() {
+    use crate::uhash::{BLOCK_OFFSET_MASK, compute_address};
+    use crate::{BLOCK_SIZE, ROUNDS, SCRATCHPAD_SIZE};
+
+    // Largest block-aligned offset whose block still fits
+    assert_eq!(BLOCK_OFFSET_MASK, SCRATCHPAD_SIZE - BLOCK_SIZE);
+
+    // Any offset at all masks into bounds, so `fast` never reads outside
+    let mut offset = 0x9E37_79B9_7F4A_7C15u64 as usize;
+    for extreme in [0, 1, BLOCK_SIZE - 1, SCRATCHPAD_SIZE, usize::MAX] {
+        let masked = extreme & BLOCK_OFFSET_MASK;
+        assert!(masked + BLOCK_SIZE <= SCRATCHPAD_SIZE);
+        assert_eq!(masked % BLOCK_SIZE, 0);
+    }
+    for _ in 0..10_000 {
+        offset = offset
+            .rotate_left(7)
+            .wrapping_mul(0x2545_F491_4F6C_DD1D_u64 as usize);
+        assert!((offset & BLOCK_OFFSET_MASK) + BLOCK_SIZE <= SCRATCHPAD_SIZE);
+    }
+
+    // ...and leaves every address the round loop computes unchanged
+    let mut state = [0u8; 32];
+    for round in (0..ROUNDS).chain([usize::MAX]) {
+        state = *blake3::hash(&state).as_bytes();
+        let address = compute_address(&state, round);
+        assert_eq!(address & BLOCK_OFFSET_MASK, address);
+    }
+}
+
+#[test]
+fn test_nonce_max_matches_reference() {
+    let mut input = vec![0xAB; 60];
+    input.extend_from_slice(&u64::MAX.to_le_bytes());
+    assert_eq!(hash(&input), crate::reference::hash(&input));
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing_spans_cover_phases() {
+    use std::sync::{Arc, Mutex};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, subscriber::Subscriber};
+
+    /// Records the name of every span created
+    struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+
+    impl Subscriber for SpanNames {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut names = self.0.lock().unwrap();
+            names.push(span.metadata().name());
+            Id::from_u64(names.len() as u64)
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, _: &Event<'_>) {}
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    let names = Arc::new(Mutex::new(Vec::new()));
+    tracing::subscriber::with_default(SpanNames(names.clone()), || {
+        UniversalHash::new().hash(b"traced");
+    });
+    assert_eq!(
+        *names.lock().unwrap(),
+        ["uhash", "init_scratchpads", "execute_rounds", "finalize"]
     );
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_hex_forms() {
+    use crate::{DaaParams, Hash256, MiningHeader, Target};
+
+    let hash = Hash256([0xAB; 32]);
+    let json = serde_json::to_string(&hash).unwrap();
+    assert_eq!(json, format!("\"{}\"", "ab".repeat(32)));
+    assert_eq!(serde_json::from_str::<Hash256>(&json).unwrap(), hash);
+
+    // Targets are written big-endian, zero-padded to 32 bytes
+    let target = Target::from_difficulty_bits(12);
+    let json = serde_json::to_string(&target).unwrap();
+    assert_eq!(json, format!("\"000{}\"", "f".repeat(61)));
+    assert_eq!(serde_json::from_str::<Target>(&json).unwrap(), target);
+
+    let header = MiningHeader::default().with_nonce(7);
+    let json = serde_json::to_string(&header).unwrap();
+    assert_eq!(json.len(), crate::HEADER_SIZE * 2 + 2);
+    assert_eq!(serde_json::from_str::<MiningHeader>(&json).unwrap(), header);
+
+    // Wrong lengths and non-hex input are rejected
+    assert!(serde_json::from_str::<Hash256>("\"abcd\"").is_err());
+    assert!(serde_json::from_str::<Target>(&format!("\"{}\"", "zz".repeat(32))).is_err());
+
+    // Config files may give only some parameters
+    let params: DaaParams = serde_json::from_str(r#"{"target_block_time": 30}"#).unwrap();
+    assert_eq!(params.target_block_time, 30);
+    assert_eq!(params.window, DaaParams::default().window);
+    let json = serde_json::to_string(&params).unwrap();
+    assert_eq!(serde_json::from_str::<DaaParams>(&json).unwrap(), params);
+}
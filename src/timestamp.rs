@@ -0,0 +1,164 @@
+//! Header timestamp rules
+//!
+//! Timestamps feed the difficulty adjustment, so a node that accepts a
+//! timestamp its peers reject, or the reverse, splits from the network.
+//! The rules follow Bitcoin: a timestamp must be strictly after the median
+//! of the previous [`MEDIAN_TIME_SPAN`] blocks, and not more than a
+//! configured drift ahead of the validating node's clock.
+
+use core::fmt;
+
+/// Number of preceding blocks whose median timestamp a header must exceed
+pub const MEDIAN_TIME_SPAN: usize = 11;
+
+/// Reason a header timestamp was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampError {
+    /// Timestamp is not after the median time past
+    NotAfterMedianTimePast {
+        /// Header timestamp
+        timestamp: u64,
+        /// Median of the preceding timestamps
+        median_time_past: u64,
+    },
+    /// Timestamp is further in the future than the allowed drift
+    TooFarInFuture {
+        /// Header timestamp
+        timestamp: u64,
+        /// Latest accepted timestamp (`now + max_future_drift`)
+        max: u64,
+    },
+}
+
+impl fmt::Display for TimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimestampError::NotAfterMedianTimePast {
+                timestamp,
+                median_time_past,
+            } => write!(
+                f,
+                "timestamp {} is not after median time past {}",
+                timestamp, median_time_past
+            ),
+            TimestampError::TooFarInFuture { timestamp, max } => {
+                write!(
+                    f,
+                    "timestamp {} is after the latest allowed {}",
+                    timestamp, max
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for TimestampError {}
+
+/// Check a header timestamp against the chain and the local clock
+///
+/// All values are seconds since the Unix epoch (drift in seconds). The
+/// median-time-past rule is checked first, since it does not depend on
+/// the local clock and so marks a header invalid for every node.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{TimestampError, median_time_past, validate_timestamp};
+///
+/// let previous = [100, 160, 220, 280, 340];
+/// let mtp = median_time_past(&previous);
+/// assert_eq!(mtp, 220);
+///
+/// let now = 400;
+/// assert!(validate_timestamp(401, now, 7200, mtp).is_ok());
+/// assert!(matches!(
+///     validate_timestamp(220, now, 7200, mtp),
+///     Err(TimestampError::NotAfterMedianTimePast { .. })
+/// ));
+/// ```
+pub fn validate_timestamp(
+    header_timestamp: u64,
+    now: u64,
+    max_future_drift: u64,
+    median_time_past: u64,
+) -> Result<(), TimestampError> {
+    if header_timestamp <= median_time_past {
+        return Err(TimestampError::NotAfterMedianTimePast {
+            timestamp: header_timestamp,
+            median_time_past,
+        });
+    }
+    let max = now.saturating_add(max_future_drift);
+    if header_timestamp > max {
+        return Err(TimestampError::TooFarInFuture {
+            timestamp: header_timestamp,
+            max,
+        });
+    }
+    Ok(())
+}
+
+/// Median of the last [`MEDIAN_TIME_SPAN`] timestamps in `timestamps`
+///
+/// `timestamps` lists blocks oldest first and need not be sorted. With an
+/// even count (fewer than `MEDIAN_TIME_SPAN` blocks) the lower middle
+/// value is used; an empty list gives 0, so any timestamp passes.
+pub fn median_time_past(timestamps: &[u64]) -> u64 {
+    let recent = &timestamps[timestamps.len().saturating_sub(MEDIAN_TIME_SPAN)..];
+    if recent.is_empty() {
+        return 0;
+    }
+    let mut sorted = [0u64; MEDIAN_TIME_SPAN];
+    let sorted = &mut sorted[..recent.len()];
+    sorted.copy_from_slice(recent);
+    sorted.sort_unstable();
+    sorted[(recent.len() - 1) / 2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::vec::Vec;
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_median_time_past() {
+        assert_eq!(median_time_past(&[]), 0);
+        assert_eq!(median_time_past(&[5]), 5);
+        assert_eq!(median_time_past(&[10, 30]), 10);
+        // Out-of-order timestamps are sorted
+        assert_eq!(median_time_past(&[50, 10, 40, 20, 30]), 30);
+        // Only the last eleven count
+        let history: Vec<u64> = (0..20).map(|i| i * 10).collect();
+        assert_eq!(median_time_past(&history), 140);
+    }
+
+    #[test]
+    fn test_validate_timestamp() {
+        let drift = 2 * 60 * 60;
+        assert_eq!(validate_timestamp(1001, 1000, drift, 1000), Ok(()));
+        assert_eq!(validate_timestamp(1000 + drift, 1000, drift, 0), Ok(()));
+        assert_eq!(
+            validate_timestamp(1000, 2000, drift, 1000),
+            Err(TimestampError::NotAfterMedianTimePast {
+                timestamp: 1000,
+                median_time_past: 1000
+            })
+        );
+        assert_eq!(
+            validate_timestamp(1001 + drift, 1000, drift, 0),
+            Err(TimestampError::TooFarInFuture {
+                timestamp: 1001 + drift,
+                max: 1000 + drift
+            })
+        );
+        // Both rules broken: the clock-independent one is reported
+        assert!(matches!(
+            validate_timestamp(5, 0, 0, 10),
+            Err(TimestampError::NotAfterMedianTimePast { .. })
+        ));
+        assert_eq!(validate_timestamp(u64::MAX, u64::MAX, drift, 0), Ok(()));
+    }
+}
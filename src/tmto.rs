@@ -0,0 +1,245 @@
+//! Time-memory tradeoff simulator
+//!
+//! Evaluates the hash the way a memory-constrained attacker would: instead
+//! of the 512KB scratchpad, keep only every `k`-th state of the AES
+//! expansion chain that fills it, plus the 32-byte halves the rounds write
+//! back, and recompute any other block on demand from the nearest stored
+//! state. The resulting [`TmtoReport`] puts numbers on the spec's claim
+//! that trading memory for time is expensive: written-back blocks cannot be
+//! recomputed at all, and every read of an untouched block costs up to `k`
+//! extra AES expansions on the critical path.
+//!
+//! ```rust
+//! use uhash_core::{hash, tmto};
+//!
+//! let report = tmto::simulate(b"input data", 16);
+//! assert_eq!(report.hash, hash(b"input data"));
+//! println!("{}", report);
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+use core::fmt;
+
+use crate::params::{BLOCK_SIZE, BLOCKS_PER_SCRATCHPAD, CHAINS, ROUNDS, SCRATCHPAD_SIZE};
+use crate::primitives::{ConsensusPrimitives, PrimitiveSet, aes_expand_block};
+use crate::uhash::{
+    chain_seed, compute_address, extract_nonce, finalize_states, header_midstate,
+    initial_primitive, round_primitive,
+};
+
+/// Bytes of one stored AES expansion state
+const STATE_SIZE: usize = 16;
+
+/// Bytes a round writes back into a block
+const WRITE_SIZE: usize = 32;
+
+/// Outcome of a reduced-memory evaluation
+///
+/// Work is counted in primitive calls: one AES expansion (4 AESENC rounds)
+/// or one round compression each. The full-memory evaluation needs
+/// `BLOCKS_PER_SCRATCHPAD + 1` expansions and `ROUNDS` compressions per
+/// chain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TmtoReport {
+    /// Every `checkpoint_interval`-th expansion state is kept
+    pub checkpoint_interval: usize,
+    /// Largest per-chain storage: expansion checkpoints plus written-back
+    /// halves, in bytes
+    pub peak_memory: usize,
+    /// Scratchpad reads over all chains
+    pub reads: u64,
+    /// Reads that needed untouched data rebuilt from a checkpoint
+    pub recomputed_reads: u64,
+    /// AES expansions spent rebuilding blocks
+    pub recomputed_expansions: u64,
+    /// Primitive calls of the full-memory evaluation
+    pub baseline_work: u64,
+    /// Resulting hash, identical to [`hash`](crate::hash)
+    pub hash: [u8; 32],
+}
+
+impl TmtoReport {
+    /// Peak storage as a fraction of a full scratchpad
+    pub fn memory_fraction(&self) -> f64 {
+        self.peak_memory as f64 / SCRATCHPAD_SIZE as f64
+    }
+
+    /// Primitive calls of the reduced-memory evaluation
+    pub fn total_work(&self) -> u64 {
+        self.baseline_work + self.recomputed_expansions
+    }
+
+    /// Work relative to the full-memory evaluation (1.0 = no penalty)
+    pub fn penalty(&self) -> f64 {
+        self.total_work() as f64 / self.baseline_work as f64
+    }
+}
+
+impl fmt::Display for TmtoReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "k={}: {} bytes/chain ({:.1}% of scratchpad), {}/{} reads recomputed, \
+             {} extra AES expansions, {:.2}x work",
+            self.checkpoint_interval,
+            self.peak_memory,
+            self.memory_fraction() * 100.0,
+            self.recomputed_reads,
+            self.reads,
+            self.recomputed_expansions,
+            self.penalty()
+        )
+    }
+}
+
+/// Hash `input` keeping every `checkpoint_interval`-th expansion state
+///
+/// An interval of 1 keeps the whole expansion chain (no recomputation, but
+/// still only 16 of every 64 bytes plus written halves). Panics if the
+/// interval is zero.
+pub fn simulate(input: &[u8], checkpoint_interval: usize) -> TmtoReport {
+    assert!(
+        checkpoint_interval > 0,
+        "checkpoint interval must be non-zero"
+    );
+
+    let midstate = header_midstate(input);
+    let nonce = extract_nonce(input);
+    let mut report = TmtoReport {
+        checkpoint_interval,
+        peak_memory: 0,
+        reads: 0,
+        recomputed_reads: 0,
+        recomputed_expansions: 0,
+        baseline_work: ((BLOCKS_PER_SCRATCHPAD + 1 + ROUNDS) * CHAINS) as u64,
+        hash: [0; 32],
+    };
+
+    let mut chain_states = [[0u8; 32]; CHAINS];
+    for (chain, state) in chain_states.iter_mut().enumerate() {
+        *state = chain_seed(&midstate, nonce, chain);
+        let mut scratchpad = LowMemoryScratchpad::new(state, checkpoint_interval);
+        let initial_primitive = initial_primitive(nonce, chain);
+
+        for round in 0..ROUNDS {
+            let addr = compute_address(state, round);
+            let block = scratchpad.read(addr / BLOCK_SIZE, &mut report);
+            let primitive = round_primitive(initial_primitive, round);
+            *state = ConsensusPrimitives::compress(primitive, state, &block);
+            scratchpad.written[addr / BLOCK_SIZE] = Some(*state);
+        }
+        report.peak_memory = report.peak_memory.max(scratchpad.memory());
+    }
+    report.hash = finalize_states(&chain_states);
+    report
+}
+
+/// What the attacker keeps of one chain's scratchpad
+///
+/// The expansion chain is `e[0] = seed[16..32]`, `e[n + 1] =
+/// AES_4Rounds(e[n], key)`, and block `i` initially holds
+/// `e[i + 1] || e[i + 2]` twice.
+struct LowMemoryScratchpad {
+    key: [u8; 16],
+    interval: usize,
+    /// `e[j * interval]` for every `j` up to the end of the chain
+    checkpoints: Vec<[u8; 16]>,
+    /// First half of each block a round has written back
+    written: Vec<Option<[u8; 32]>>,
+}
+
+impl LowMemoryScratchpad {
+    /// Walk the expansion chain once, keeping every `interval`-th state
+    fn new(seed: &[u8; 32], interval: usize) -> Self {
+        let key: [u8; 16] = seed[0..16].try_into().unwrap();
+        let mut state: [u8; 16] = seed[16..32].try_into().unwrap();
+        let mut checkpoints = Vec::new();
+        for n in 0..=BLOCKS_PER_SCRATCHPAD + 1 {
+            if n.is_multiple_of(interval) {
+                checkpoints.push(state);
+            }
+            state = aes_expand_block(&state, &key);
+        }
+        Self {
+            key,
+            interval,
+            checkpoints,
+            written: vec![None; BLOCKS_PER_SCRATCHPAD],
+        }
+    }
+
+    /// Bytes held: key, checkpoints and written-back halves
+    fn memory(&self) -> usize {
+        let written = self.written.iter().filter(|w| w.is_some()).count();
+        STATE_SIZE * (1 + self.checkpoints.len()) + WRITE_SIZE * written
+    }
+
+    /// Expansion state `e[n]`, rebuilt from the nearest checkpoint below it
+    fn expansion(&self, n: usize, report: &mut TmtoReport) -> [u8; 16] {
+        let mut state = self.checkpoints[n / self.interval];
+        for _ in 0..n % self.interval {
+            state = aes_expand_block(&state, &self.key);
+            report.recomputed_expansions += 1;
+        }
+        state
+    }
+
+    /// Contents of block `index` as the full scratchpad would hold them
+    fn read(&self, index: usize, report: &mut TmtoReport) -> [u8; BLOCK_SIZE] {
+        let expansions_before = report.recomputed_expansions;
+
+        // The second half is never written, so it always comes from the
+        // expansion chain; e[i + 2] is one step past e[i + 1]
+        let first = self.expansion(index + 1, report);
+        let second = if (index + 2).is_multiple_of(self.interval) {
+            self.checkpoints[(index + 2) / self.interval]
+        } else {
+            report.recomputed_expansions += 1;
+            aes_expand_block(&first, &self.key)
+        };
+        let mut initial = [0u8; 32];
+        initial[..16].copy_from_slice(&first);
+        initial[16..].copy_from_slice(&second);
+
+        report.reads += 1;
+        if report.recomputed_expansions > expansions_before {
+            report.recomputed_reads += 1;
+        }
+
+        let mut block = [0u8; BLOCK_SIZE];
+        block[..32].copy_from_slice(self.written[index].as_ref().unwrap_or(&initial));
+        block[32..].copy_from_slice(&initial);
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uhash::hash;
+
+    #[test]
+    fn test_simulation_matches_hash() {
+        for interval in [1, 7, 64] {
+            let report = simulate(b"tmto", interval);
+            assert_eq!(report.hash, hash(b"tmto"), "interval {}", interval);
+            assert_eq!(report.reads, (ROUNDS * CHAINS) as u64);
+        }
+    }
+
+    #[test]
+    fn test_less_memory_costs_more_work() {
+        let dense = simulate(b"tmto", 1);
+        let sparse = simulate(b"tmto", 32);
+
+        // With every expansion state kept, nothing is ever recomputed
+        assert_eq!(dense.recomputed_expansions, 0);
+        assert_eq!(dense.penalty(), 1.0);
+
+        assert!(sparse.peak_memory < dense.peak_memory);
+        assert!(sparse.penalty() > 1.0);
+        assert!(sparse.recomputed_reads > 0);
+    }
+}
@@ -0,0 +1,216 @@
+//! Round-by-round execution trace
+//!
+//! Records every intermediate value of a hash evaluation so that other
+//! implementations (GPU kernels, ports to other languages) can find the
+//! exact round where they diverge. Tracing runs the chains sequentially
+//! and allocates ~4MB per trace, so it is meant for debugging only.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::params::*;
+use crate::primitives::{NATIVE_PRIMITIVES, PrimitiveTable};
+use crate::uhash::{
+    chain_seed, compute_address, extract_nonce, fill_scratchpad_with, finalize_states,
+    header_midstate, initial_primitive, round_primitive,
+};
+
+/// Compression primitive selected for a round
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Primitive {
+    /// AES_Compress (primitive index 0)
+    Aes,
+    /// SHA256_Compress (primitive index 1)
+    Sha256,
+    /// BLAKE3_Compress (primitive index 2)
+    Blake3,
+}
+
+impl Primitive {
+    /// Map a spec primitive index (0, 1, 2) to a primitive
+    pub fn from_index(index: usize) -> Self {
+        match index % 3 {
+            0 => Primitive::Aes,
+            1 => Primitive::Sha256,
+            _ => Primitive::Blake3,
+        }
+    }
+
+    /// Spec primitive index (0 = AES, 1 = SHA-256, 2 = BLAKE3)
+    pub fn index(self) -> usize {
+        match self {
+            Primitive::Aes => 0,
+            Primitive::Sha256 => 1,
+            Primitive::Blake3 => 2,
+        }
+    }
+}
+
+/// One round of one chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundTrace {
+    /// Round number (0-based)
+    pub round: usize,
+    /// Scratchpad byte offset read from and written back to (same per spec)
+    pub address: usize,
+    /// Primitive applied this round
+    pub primitive: Primitive,
+    /// Block read from the scratchpad
+    pub block: [u8; BLOCK_SIZE],
+    /// Chain state after this round (also the 32 bytes written back)
+    pub state: [u8; 32],
+}
+
+/// Full trace of one chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainTrace {
+    /// Chain seed (also the initial chain state)
+    pub seed: [u8; 32],
+    /// Primitive index the chain starts from: (nonce + c) mod 3
+    pub initial_primitive: usize,
+    /// Every round in execution order
+    pub rounds: Vec<RoundTrace>,
+}
+
+/// Full trace of a hash evaluation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashTrace {
+    /// Effective nonce extracted from the input
+    pub nonce: u64,
+    /// One trace per chain
+    pub chains: Vec<ChainTrace>,
+    /// Final hash output (identical to `hash(input)`)
+    pub output: [u8; 32],
+}
+
+impl HashTrace {
+    /// First round where `other` differs from this trace, as `(chain, round)`
+    ///
+    /// Returns `None` if all recorded rounds match.
+    pub fn first_divergence(&self, other: &HashTrace) -> Option<(usize, usize)> {
+        for (chain, (a, b)) in self.chains.iter().zip(&other.chains).enumerate() {
+            if a.seed != b.seed {
+                return Some((chain, 0));
+            }
+            for (ra, rb) in a.rounds.iter().zip(&b.rounds) {
+                if ra != rb {
+                    return Some((chain, ra.round));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Compute the UniversalHash of `input`, recording every intermediate value
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{hash, hash_traced};
+///
+/// let trace = hash_traced(b"input data");
+/// assert_eq!(trace.output, hash(b"input data"));
+/// println!("chain 0, round 0 read {:#x}", trace.chains[0].rounds[0].address);
+/// ```
+pub fn hash_traced(input: &[u8]) -> HashTrace {
+    hash_traced_with(input, &NATIVE_PRIMITIVES)
+}
+
+/// [`hash_traced`] evaluated with a specific primitive implementation
+pub(crate) fn hash_traced_with(input: &[u8], primitives: &PrimitiveTable) -> HashTrace {
+    let nonce = extract_nonce(input);
+    let midstate = header_midstate(input);
+    let mut scratchpad = vec![0u8; SCRATCHPAD_SIZE];
+    let mut chains = Vec::with_capacity(CHAINS);
+    let mut chain_states = [[0u8; 32]; CHAINS];
+
+    for (chain, state) in chain_states.iter_mut().enumerate() {
+        let seed = chain_seed(&midstate, nonce, chain);
+        fill_scratchpad_with(&mut scratchpad, &seed, primitives.aes_expand);
+        *state = seed;
+
+        let initial = initial_primitive(nonce, chain);
+        let mut rounds = Vec::with_capacity(ROUNDS);
+
+        for round in 0..ROUNDS {
+            let address = compute_address(state, round);
+            let block: [u8; BLOCK_SIZE] = scratchpad[address..address + BLOCK_SIZE]
+                .try_into()
+                .unwrap();
+
+            let primitive = Primitive::from_index(round_primitive(initial, round));
+            *state = match primitive {
+                Primitive::Aes => (primitives.aes)(state, &block),
+                Primitive::Sha256 => (primitives.sha256)(state, &block),
+                Primitive::Blake3 => (primitives.blake3)(state, &block),
+            };
+            scratchpad[address..address + 32].copy_from_slice(state);
+
+            rounds.push(RoundTrace {
+                round,
+                address,
+                primitive,
+                block,
+                state: *state,
+            });
+        }
+
+        chains.push(ChainTrace {
+            seed,
+            initial_primitive: initial,
+            rounds,
+        });
+    }
+
+    HashTrace {
+        nonce,
+        chains,
+        output: finalize_states(&chain_states),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    #[test]
+    fn test_trace_matches_hash() {
+        let input = b"trace test input";
+        let trace = hash_traced(input);
+
+        assert_eq!(trace.output, hash(input));
+        assert_eq!(trace.chains.len(), CHAINS);
+        for chain in &trace.chains {
+            assert_eq!(chain.rounds.len(), ROUNDS);
+            assert!(chain.rounds.iter().all(|r| r.address % BLOCK_SIZE == 0));
+        }
+    }
+
+    #[test]
+    fn test_trace_primitive_rotation() {
+        let trace = hash_traced(b"rotation");
+        for (chain, ct) in trace.chains.iter().enumerate() {
+            assert_eq!(ct.initial_primitive, initial_primitive(trace.nonce, chain));
+            for r in &ct.rounds[..6] {
+                assert_eq!(
+                    r.primitive.index(),
+                    (ct.initial_primitive + r.round + 1) % 3
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        let a = hash_traced(b"divergence");
+        let mut b = a.clone();
+        assert_eq!(a.first_divergence(&b), None);
+
+        b.chains[2].rounds[100].state[0] ^= 1;
+        assert_eq!(a.first_divergence(&b), Some((2, 100)));
+    }
+}
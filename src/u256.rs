@@ -0,0 +1,486 @@
+//! 256-bit unsigned integer for target and work arithmetic
+//!
+//! Hashes are interpreted as big-endian integers, so a hash with more
+//! leading zero bits is numerically smaller. This matches the ordering
+//! used by [`meets_difficulty`](crate::meets_difficulty).
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Add, BitAnd, BitOr, Not, Shl, Shr, Sub};
+
+use crate::hash256::Hash256;
+
+/// 256-bit unsigned integer
+///
+/// Stored as four little-endian `u64` limbs (limb 0 is least significant).
+/// `+` and `-` panic on overflow like the primitive integer types; use the
+/// `checked_*`/`saturating_*` methods where overflow is expected.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct U256(pub [u64; 4]);
+
+impl U256 {
+    /// Zero
+    pub const ZERO: U256 = U256([0, 0, 0, 0]);
+    /// One
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+    /// 2^256 - 1
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    /// Construct from a `u64`
+    pub const fn from_u64(value: u64) -> Self {
+        U256([value, 0, 0, 0])
+    }
+
+    /// Construct from a `u128`
+    pub const fn from_u128(value: u128) -> Self {
+        U256([value as u64, (value >> 64) as u64, 0, 0])
+    }
+
+    /// Interpret 32 bytes as a big-endian integer
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let start = 32 - (i + 1) * 8;
+            *limb = u64::from_be_bytes(bytes[start..start + 8].try_into().unwrap());
+        }
+        U256(limbs)
+    }
+
+    /// Encode as 32 big-endian bytes
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            let start = 32 - (i + 1) * 8;
+            bytes[start..start + 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Whether the value is zero
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    /// Number of leading zero bits
+    pub fn leading_zeros(&self) -> u32 {
+        let mut zeros = 0;
+        for limb in self.0.iter().rev() {
+            if *limb == 0 {
+                zeros += 64;
+            } else {
+                return zeros + limb.leading_zeros();
+            }
+        }
+        zeros
+    }
+
+    /// Number of significant bits (0 for zero)
+    pub fn bits(&self) -> u32 {
+        256 - self.leading_zeros()
+    }
+
+    /// Value of bit `n` (0 = least significant)
+    fn bit(&self, n: u32) -> bool {
+        (self.0[(n / 64) as usize] >> (n % 64)) & 1 == 1
+    }
+
+    /// The low 64 bits
+    pub fn low_u64(&self) -> u64 {
+        self.0[0]
+    }
+
+    /// Addition returning the wrapped result and whether it overflowed
+    pub fn overflowing_add(self, rhs: U256) -> (U256, bool) {
+        let mut out = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in out.iter_mut().enumerate() {
+            let (sum, c1) = self.0[i].overflowing_add(rhs.0[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        (U256(out), carry)
+    }
+
+    /// Subtraction returning the wrapped result and whether it underflowed
+    pub fn overflowing_sub(self, rhs: U256) -> (U256, bool) {
+        let mut out = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in out.iter_mut().enumerate() {
+            let (diff, b1) = self.0[i].overflowing_sub(rhs.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        (U256(out), borrow)
+    }
+
+    /// Addition, `None` on overflow
+    pub fn checked_add(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_add(rhs) {
+            (sum, false) => Some(sum),
+            _ => None,
+        }
+    }
+
+    /// Subtraction, `None` on underflow
+    pub fn checked_sub(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_sub(rhs) {
+            (diff, false) => Some(diff),
+            _ => None,
+        }
+    }
+
+    /// Addition clamped to [`U256::MAX`]
+    pub fn saturating_add(self, rhs: U256) -> U256 {
+        self.checked_add(rhs).unwrap_or(U256::MAX)
+    }
+
+    /// Subtraction clamped to zero
+    pub fn saturating_sub(self, rhs: U256) -> U256 {
+        self.checked_sub(rhs).unwrap_or(U256::ZERO)
+    }
+
+    /// Multiplication, `None` on overflow
+    pub fn checked_mul(self, rhs: U256) -> Option<U256> {
+        let mut out = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let cur = out[i + j] as u128 + self.0[i] as u128 * rhs.0[j] as u128 + carry;
+                out[i + j] = cur as u64;
+                carry = cur >> 64;
+            }
+            out[i + 4] = carry as u64;
+        }
+        if out[4..] != [0; 4] {
+            return None;
+        }
+        Some(U256([out[0], out[1], out[2], out[3]]))
+    }
+
+    /// Multiplication by a `u64`, `None` on overflow
+    pub fn checked_mul_u64(self, rhs: u64) -> Option<U256> {
+        self.checked_mul(U256::from_u64(rhs))
+    }
+
+    /// Quotient and remainder, `None` if `divisor` is zero
+    pub fn checked_div_rem(self, divisor: U256) -> Option<(U256, U256)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        if self < divisor {
+            return Some((U256::ZERO, self));
+        }
+
+        // Binary long division, one bit per step
+        let mut quotient = U256::ZERO;
+        let mut remainder = U256::ZERO;
+        for n in (0..self.bits()).rev() {
+            remainder = remainder << 1;
+            if self.bit(n) {
+                remainder.0[0] |= 1;
+            }
+            if remainder >= divisor {
+                remainder = remainder - divisor;
+                quotient.0[(n / 64) as usize] |= 1 << (n % 64);
+            }
+        }
+        Some((quotient, remainder))
+    }
+
+    /// Division, `None` if `divisor` is zero
+    pub fn checked_div(self, divisor: U256) -> Option<U256> {
+        self.checked_div_rem(divisor).map(|(q, _)| q)
+    }
+
+    /// Lossy conversion to `f64`
+    pub fn to_f64(&self) -> f64 {
+        self.0.iter().rev().fold(0.0, |acc, limb| {
+            acc * 18_446_744_073_709_551_616.0 + *limb as f64
+        })
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for U256 {
+    type Output = U256;
+
+    fn add(self, rhs: U256) -> U256 {
+        self.checked_add(rhs).expect("U256 addition overflow")
+    }
+}
+
+impl Sub for U256 {
+    type Output = U256;
+
+    fn sub(self, rhs: U256) -> U256 {
+        self.checked_sub(rhs).expect("U256 subtraction underflow")
+    }
+}
+
+impl Shl<u32> for U256 {
+    type Output = U256;
+
+    fn shl(self, shift: u32) -> U256 {
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+        let limbs = (shift / 64) as usize;
+        let bits = shift % 64;
+        let mut out = [0u64; 4];
+        for (i, limb) in out.iter_mut().enumerate().skip(limbs) {
+            *limb = self.0[i - limbs] << bits;
+            if bits > 0 && i > limbs {
+                *limb |= self.0[i - limbs - 1] >> (64 - bits);
+            }
+        }
+        U256(out)
+    }
+}
+
+impl Shr<u32> for U256 {
+    type Output = U256;
+
+    fn shr(self, shift: u32) -> U256 {
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+        let limbs = (shift / 64) as usize;
+        let bits = shift % 64;
+        let mut out = [0u64; 4];
+        for (i, limb) in out.iter_mut().take(4 - limbs).enumerate() {
+            *limb = self.0[i + limbs] >> bits;
+            if bits > 0 && i + limbs + 1 < 4 {
+                *limb |= self.0[i + limbs + 1] << (64 - bits);
+            }
+        }
+        U256(out)
+    }
+}
+
+impl Not for U256 {
+    type Output = U256;
+
+    fn not(self) -> U256 {
+        U256(self.0.map(|limb| !limb))
+    }
+}
+
+impl BitAnd for U256 {
+    type Output = U256;
+
+    fn bitand(self, rhs: U256) -> U256 {
+        U256([
+            self.0[0] & rhs.0[0],
+            self.0[1] & rhs.0[1],
+            self.0[2] & rhs.0[2],
+            self.0[3] & rhs.0[3],
+        ])
+    }
+}
+
+impl BitOr for U256 {
+    type Output = U256;
+
+    fn bitor(self, rhs: U256) -> U256 {
+        U256([
+            self.0[0] | rhs.0[0],
+            self.0[1] | rhs.0[1],
+            self.0[2] | rhs.0[2],
+            self.0[3] | rhs.0[3],
+        ])
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(value: u64) -> Self {
+        U256::from_u64(value)
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(value: u128) -> Self {
+        U256::from_u128(value)
+    }
+}
+
+impl From<Hash256> for U256 {
+    fn from(hash: Hash256) -> Self {
+        U256::from_be_bytes(&hash.0)
+    }
+}
+
+impl From<U256> for Hash256 {
+    fn from(value: U256) -> Self {
+        Hash256(value.to_be_bytes())
+    }
+}
+
+impl Hash256 {
+    /// Interpret this hash as a big-endian 256-bit integer
+    pub fn to_u256(&self) -> U256 {
+        U256::from_be_bytes(&self.0)
+    }
+}
+
+impl fmt::LowerHex for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        let mut started = false;
+        for limb in self.0.iter().rev() {
+            if started {
+                write!(f, "{:016x}", limb)?;
+            } else if *limb != 0 {
+                write!(f, "{:x}", limb)?;
+                started = true;
+            }
+        }
+        if !started {
+            f.write_str("0")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.write_str("0");
+        }
+        // 78 decimal digits is enough for 2^256 - 1
+        let mut digits = [0u8; 78];
+        let mut len = 0;
+        let mut value = *self;
+        let ten = U256::from_u64(10);
+        while !value.is_zero() {
+            let (q, r) = value.checked_div_rem(ten).unwrap();
+            digits[len] = b'0' + r.low_u64() as u8;
+            len += 1;
+            value = q;
+        }
+        for digit in digits[..len].iter().rev() {
+            write!(f, "{}", *digit as char)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "U256({:#x})", self)
+    }
+}
+
+#[cfg(feature = "primitive-types")]
+impl From<U256> for primitive_types::U256 {
+    fn from(value: U256) -> Self {
+        primitive_types::U256(value.0)
+    }
+}
+
+#[cfg(feature = "primitive-types")]
+impl From<primitive_types::U256> for U256 {
+    fn from(value: primitive_types::U256) -> Self {
+        U256(value.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "alloc", not(feature = "std")))]
+    use alloc::format;
+
+    #[test]
+    fn test_be_bytes_roundtrip() {
+        let mut bytes = [0u8; 32];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        let value = U256::from_be_bytes(&bytes);
+        assert_eq!(value.to_be_bytes(), bytes);
+        assert_eq!(value.low_u64(), 0x18191a1b1c1d1e1f);
+    }
+
+    #[test]
+    fn test_ordering_matches_hash_bytes() {
+        let mut bytes = [0xFFu8; 32];
+        bytes[0] = 0x00;
+        bytes[1] = 0x01;
+        let small = Hash256(bytes);
+        let large = Hash256([0x01; 32]);
+        assert!(small < large);
+        assert!(small.to_u256() < large.to_u256());
+        assert_eq!(small.to_u256().leading_zeros(), small.leading_zeros());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = U256::from_u128(u128::MAX);
+        assert_eq!(a + U256::ONE, U256([0, 0, 1, 0]));
+        assert_eq!(U256([0, 0, 1, 0]) - U256::ONE, a);
+        assert_eq!(U256::MAX.checked_add(U256::ONE), None);
+        assert_eq!(U256::ZERO.checked_sub(U256::ONE), None);
+        assert_eq!(U256::MAX.saturating_add(U256::ONE), U256::MAX);
+
+        let b = U256::from_u64(u64::MAX);
+        assert_eq!(
+            b.checked_mul(b),
+            Some(U256::from_u128(u128::MAX - 2 * u64::MAX as u128))
+        );
+        assert_eq!(U256::MAX.checked_mul_u64(2), None);
+    }
+
+    #[test]
+    fn test_division() {
+        let (q, r) = U256::MAX.checked_div_rem(U256::from_u64(3)).unwrap();
+        assert_eq!(q.checked_mul_u64(3).unwrap() + r, U256::MAX);
+        assert_eq!(r, U256::ZERO);
+
+        let (q, r) = U256::from_u64(100)
+            .checked_div_rem(U256::from_u64(7))
+            .unwrap();
+        assert_eq!((q.low_u64(), r.low_u64()), (14, 2));
+        assert_eq!(U256::ONE.checked_div(U256::ZERO), None);
+    }
+
+    #[test]
+    fn test_shifts() {
+        assert_eq!(U256::ONE << 255, U256([0, 0, 0, 1 << 63]));
+        assert_eq!((U256::ONE << 255) >> 255, U256::ONE);
+        assert_eq!(U256::MAX >> 200, U256::from_u64((1 << 56) - 1));
+        assert_eq!(U256::MAX << 256, U256::ZERO);
+        assert_eq!((U256::ONE << 100).leading_zeros(), 155);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_formatting() {
+        assert_eq!(format!("{}", U256::ZERO), "0");
+        assert_eq!(format!("{}", U256::from_u64(1234567890)), "1234567890");
+        assert_eq!(
+            format!("{}", U256::MAX),
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+        );
+        assert_eq!(
+            format!("{:x}", U256::from_u128(1 << 64)),
+            "10000000000000000"
+        );
+        assert_eq!(format!("{:#x}", U256::ZERO), "0x0");
+    }
+}
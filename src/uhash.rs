@@ -10,52 +10,281 @@
 //! - Write-back: Same address as read (not computed from new state)
 //! - No cross-chain mixing (spec doesn't specify it)
 
-#[cfg(not(feature = "std"))]
-use alloc::vec;
-#[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+#![cfg_attr(feature = "safe", forbid(unsafe_code))]
+
+#[cfg(feature = "alloc")]
+use core::marker::PhantomData;
 
 use blake3::Hasher as Blake3;
 use sha2::{Digest, Sha256};
 
-#[cfg(feature = "parallel")]
+#[cfg(all(feature = "parallel", feature = "alloc"))]
 use rayon::prelude::*;
 
+use crate::absorb::Absorber;
+use crate::endian::load_u64_le;
+use crate::error::UhashError;
+use crate::header::HEADER_SIZE;
+use crate::job::Job;
 use crate::params::*;
-use crate::primitives::{aes_compress, blake3_compress, sha256_compress};
+use crate::primitives::{ConsensusPrimitives, ExpandKey, PrimitiveSet};
+#[cfg(feature = "alloc")]
+use crate::scratchpad::Scratchpads;
 
 /// Mask for address calculation (BLOCKS_PER_SCRATCHPAD - 1)
 /// Since BLOCKS_PER_SCRATCHPAD = 8192 = 2^13, this is 0x1FFF
 const ADDRESS_MASK: usize = BLOCKS_PER_SCRATCHPAD - 1;
 
-/// Golden ratio constant for seed generation (Fibonacci hashing constant)
-const GOLDEN_RATIO: u64 = 0x9E3779B97F4A7C15;
+/// Mask keeping a byte offset block-aligned and inside a scratchpad
+///
+/// Identity on every offset [`compute_address`] returns; the `fast` feature
+/// applies it again right before unchecked access, so the access is in
+/// bounds whatever the offset.
+#[cfg(any(
+    all(feature = "fast", not(feature = "safe")),
+    all(test, feature = "alloc")
+))]
+pub(crate) const BLOCK_OFFSET_MASK: usize = ADDRESS_MASK * BLOCK_SIZE;
+
+/// Whether new hashers prefetch scratchpad reads (see [`UniversalHash::with_prefetch`])
+const PREFETCH_DEFAULT: bool = false;
 
 /// UniversalHash v4 hasher
 ///
 /// This struct maintains the scratchpads and chain states needed for hashing.
 /// It can be reused for multiple hashes to avoid repeated allocations.
-pub struct UniversalHash {
-    /// 4 scratchpads, one per chain (512KB each)
-    scratchpads: Vec<Vec<u8>>,
+///
+/// Hashing never allocates after construction, with one exception: with the
+/// `parallel` feature, handing the chains to rayon's global pool from a
+/// thread outside it occasionally allocates a job queue block. A hasher from
+/// [`new_sequential`](Self::new_sequential) performs no heap allocation at
+/// all in `hash`, `hash_nonce` and `verify_pow`.
+///
+/// `P` selects the compression functions; anything other than the default
+/// [`ConsensusPrimitives`] is for research only (see
+/// [`with_primitives`](Self::with_primitives)).
+#[cfg(feature = "alloc")]
+pub struct UniversalHash<P = ConsensusPrimitives> {
+    /// 4 scratchpads, one per chain (512KB each), in one allocation
+    scratchpads: Scratchpads,
     /// Current state for each chain
     chain_states: [[u8; 32]; CHAINS],
     /// Effective nonce extracted from input (last 8 bytes)
     effective_nonce: u64,
+    /// Run chains on the rayon pool
+    #[cfg(feature = "parallel")]
+    pub(crate) parallel: bool,
+    /// Prefetch each round's block one round ahead
+    prefetch: bool,
+    /// Touch every block and run every primitive each round
+    hardened: bool,
+    /// Track `checksums` while hashing
+    integrity: bool,
+    /// Per chain, the XOR of every 64-bit word the last hash left in its
+    /// scratchpad, updated from the values written rather than re-read
+    checksums: [u64; CHAINS],
+    /// Seeding and finalization state, reset rather than rebuilt per hash
+    contexts: HashContexts,
+    primitives: PhantomData<fn() -> P>,
 }
 
+/// BLAKE3 and SHA-256 contexts a [`UniversalHash`] reuses across hashes
+///
+/// Each is reset before use instead of constructed afresh for every
+/// header, chain seed and final stage.
+#[cfg(feature = "alloc")]
+struct HashContexts {
+    /// Header of the current input, for inputs not already absorbed
+    header: Blake3,
+    /// Midstate extended with one chain's nonce
+    seed: Blake3,
+    /// SHA-256 of the combined chain states
+    sha256: Sha256,
+    /// Final BLAKE3 stage, kept for [`UniversalHash::hash_xof`]
+    output: Blake3,
+}
+
+#[cfg(feature = "alloc")]
+impl HashContexts {
+    fn new() -> Self {
+        Self {
+            header: Blake3::new(),
+            seed: Blake3::new(),
+            sha256: Sha256::new(),
+            output: Blake3::new(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
 impl UniversalHash {
     /// Create a new UniversalHash instance
     ///
     /// Allocates 2MB of memory for the scratchpads.
     pub fn new() -> Self {
+        Self::with_primitives()
+    }
+
+    /// Create a hasher that runs the chains on the calling thread only
+    ///
+    /// Slower per hash than [`new`](Self::new) with the `parallel` feature,
+    /// but never allocates or touches the rayon pool while hashing, for
+    /// latency-sensitive verifiers and callers that already parallelize
+    /// across hashes.
+    pub fn new_sequential() -> Self {
+        Self {
+            #[cfg(feature = "parallel")]
+            parallel: false,
+            ..Self::new()
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<P: PrimitiveSet> UniversalHash<P> {
+    /// Create a hasher running the round loop over primitive set `P`
+    ///
+    /// For research forks measuring the effect of a different compression
+    /// function; see [`Primitives`](crate::Primitives). With the default
+    /// [`ConsensusPrimitives`] this is the same as [`UniversalHash::new`].
+    pub fn with_primitives() -> Self {
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_allocation(CHAINS * SCRATCHPAD_SIZE);
         Self {
-            scratchpads: vec![vec![0u8; SCRATCHPAD_SIZE]; CHAINS],
+            scratchpads: Scratchpads::new(),
             chain_states: [[0u8; 32]; CHAINS],
             effective_nonce: 0,
+            #[cfg(feature = "parallel")]
+            parallel: true,
+            prefetch: PREFETCH_DEFAULT,
+            hardened: false,
+            integrity: false,
+            checksums: [0; CHAINS],
+            contexts: HashContexts::new(),
+            primitives: PhantomData,
         }
     }
 
+    /// Create a hasher with the same configuration and its own scratchpads
+    ///
+    /// Copies sequential mode, prefetch, cache hardening, integrity checking
+    /// and the primitive set, but not scratchpad contents (every hash rewrites them
+    /// anyway) nor NUMA placement. The new 2MB is a zeroed allocation the
+    /// OS maps lazily, so forking one configured hasher per worker thread
+    /// is cheap.
+    ///
+    /// ```rust
+    /// use uhash_core::{UniversalHash, hash};
+    ///
+    /// let template = UniversalHash::new_sequential().with_prefetch(true);
+    /// let workers: Vec<_> = (0..4)
+    ///     .map(|i: u64| {
+    ///         let mut hasher = template.fork();
+    ///         std::thread::spawn(move || hasher.hash(&i.to_le_bytes()))
+    ///     })
+    ///     .collect();
+    /// for (i, worker) in workers.into_iter().enumerate() {
+    ///     assert_eq!(worker.join().unwrap(), hash(&(i as u64).to_le_bytes()));
+    /// }
+    /// ```
+    pub fn fork(&self) -> Self {
+        Self {
+            #[cfg(feature = "parallel")]
+            parallel: self.parallel,
+            prefetch: self.prefetch,
+            hardened: self.hardened,
+            integrity: self.integrity,
+            ..Self::with_primitives()
+        }
+    }
+
+    /// Enable or disable software prefetch of scratchpad reads
+    ///
+    /// As soon as a round's new state is known, the block the next round
+    /// will read is requested from memory, overlapping the fetch with the
+    /// write-back. Off by default: it helps where the 2MB of scratchpads
+    /// spill out of L2, but on cores with a large L2 the extra instructions
+    /// can cost more than they save, so benchmark both settings. Output is
+    /// unaffected.
+    pub fn with_prefetch(mut self, enabled: bool) -> Self {
+        self.prefetch = enabled;
+        self
+    }
+
+    /// Enable or disable cache-timing hardening of the round loop
+    ///
+    /// Scratchpad addresses and the primitive order are derived from the
+    /// input, so another process sharing the cache can learn about the
+    /// input by timing which blocks a hash touched. That is harmless for
+    /// public mining headers, but not when the input is secret, such as an
+    /// anti-abuse puzzle over credentials. While enabled, each round reads
+    /// and rewrites every block of the scratchpad, keeping the addressed
+    /// one with a mask, and computes all three primitives, keeping the
+    /// scheduled one. Hashing becomes orders of magnitude slower, and
+    /// [`with_prefetch`](Self::with_prefetch) has no effect. Output is
+    /// unaffected.
+    ///
+    /// The guarantee holds only when [`hardware_aes`](crate::hardware_aes)
+    /// returns `true`. The software AES fallback (WASM, the `safe` feature,
+    /// CPUs without AES instructions) looks up an S-box table indexed by
+    /// state bytes, both in the AES rounds and in scratchpad
+    /// initialization, so its cache footprint still depends on the input.
+    pub fn with_cache_hardening(mut self, enabled: bool) -> Self {
+        self.hardened = enabled;
+        self
+    }
+
+    /// Enable or disable scratchpad fault detection
+    ///
+    /// While enabled, each hash keeps a rolling checksum of its scratchpad
+    /// writes at a cost of a few XORs per round, which
+    /// [`scratchpads_intact`](Self::scratchpads_intact) compares against
+    /// memory. Output is unaffected.
+    pub fn with_integrity_check(mut self, enabled: bool) -> Self {
+        self.integrity = enabled;
+        self
+    }
+
+    /// Whether the scratchpads still hold exactly what the last hash wrote
+    ///
+    /// A `false` means memory changed underneath the hash (a bit-flip from
+    /// faulty or overclocked RAM), so its result should not be trusted.
+    /// Reads all 2MB; intended for candidate solutions, not every hash.
+    /// Always `true` unless [`with_integrity_check`](Self::with_integrity_check)
+    /// is enabled.
+    pub fn scratchpads_intact(&self) -> bool {
+        !self.integrity
+            || self
+                .scratchpads
+                .chains()
+                .iter()
+                .zip(&self.checksums)
+                .all(|(scratchpad, &checksum)| fold_words(&scratchpad[..]) == checksum)
+    }
+
+    /// Fault in the scratchpads and prime caches before timed hashing
+    ///
+    /// A new hasher's scratchpads are zero pages the OS maps on first
+    /// write, so its first hashes also pay a page fault every 4KB, on top
+    /// of cold caches, untrained branch predictors and (with `parallel`)
+    /// sleeping rayon workers. Miners call this once per hasher so the
+    /// hashrate reported right after start-up or a job switch reflects
+    /// steady state. Costs about one hash; not counted in metrics and does
+    /// not affect later results.
+    pub fn warm_up(&mut self) {
+        self.seed_from_header(&[0u8; NONCE_SIZE], 0);
+        self.effective_nonce = 0;
+        self.checksums = [0; CHAINS];
+        self.init_scratchpads();
+        self.execute_rounds();
+    }
+
+    /// Scratchpad buffers, one per chain
+    #[cfg(any(feature = "numa", test))]
+    pub(crate) fn scratchpads_mut(&mut self) -> [&mut [u8; SCRATCHPAD_SIZE]; CHAINS] {
+        self.scratchpads.chains_mut()
+    }
+
     /// Compute the UniversalHash of input data
     ///
     /// The input should be formatted as:
@@ -64,159 +293,476 @@ impl UniversalHash {
     ///
     /// Returns a 32-byte hash.
     pub fn hash(&mut self, input: &[u8]) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        self.hash_into(input, &mut out);
+        out
+    }
+
+    /// Like [`hash`](Self::hash), but writes the result into `out`
+    ///
+    /// The final BLAKE3 output goes straight into caller storage, so FFI
+    /// and embedded callers with a fixed result buffer avoid the copy of a
+    /// returned array.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use uhash_core::{UniversalHash, hash};
+    ///
+    /// let mut hasher = UniversalHash::new();
+    /// let mut out = [0u8; 32];
+    /// hasher.hash_into(b"input data", &mut out);
+    /// assert_eq!(out, hash(b"input data"));
+    /// ```
+    pub fn hash_into(&mut self, input: &[u8], out: &mut [u8; 32]) {
         // Extract effective nonce from last 8 bytes of input (or hash if shorter)
-        self.effective_nonce = extract_nonce(input);
+        let nonce = extract_nonce(input);
+        self.seed_from_header(input, nonce);
+        self.compute_chains(nonce);
+        self.finalize_output();
+        out.copy_from_slice(self.contexts.output.finalize().as_bytes());
+    }
 
-        // Phase 1: Initialize scratchpads using input (spec-compliant seed generation)
-        self.init_scratchpads(input);
+    /// Hash the concatenation of `parts` without building it in memory
+    ///
+    /// Equivalent to `hash(&parts.concat())`, for callers that keep
+    /// epoch_seed, address, timestamp and nonce in separate buffers.
+    pub fn hash_parts(&mut self, parts: &[&[u8]]) -> [u8; 32] {
+        let mut absorber = Absorber::new();
+        for part in parts {
+            absorber.update(part);
+        }
+        self.hash_absorbed(&absorber)
+    }
 
-        // Phase 2: Execute main mixing rounds (spec-compliant, no cross-chain mixing)
-        self.execute_rounds();
+    /// Hash everything absorbed by `absorber`
+    ///
+    /// Equal to [`hash`](Self::hash) of the concatenated input, without
+    /// the input ever being held in memory; see [`Absorber`].
+    pub fn hash_absorbed(&mut self, absorber: &Absorber) -> [u8; 32] {
+        let (midstate, nonce) = absorber.split();
+        self.hash_midstate(midstate, nonce)
+    }
+
+    /// Hash all bytes read from `reader` until end of file
+    ///
+    /// Reads in 64KB blocks, so memory use does not grow with the input.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use uhash_core::{UniversalHash, hash};
+    ///
+    /// let payload = vec![3u8; 200_000];
+    /// let mut hasher = UniversalHash::new();
+    /// let result = hasher.hash_reader(payload.as_slice()).unwrap();
+    /// assert_eq!(result, hash(&payload));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn hash_reader(&mut self, reader: impl std::io::Read) -> std::io::Result<[u8; 32]> {
+        let mut absorber = Absorber::new();
+        std::io::copy(
+            &mut std::io::BufReader::with_capacity(1 << 16, reader),
+            &mut absorber,
+        )?;
+        Ok(self.hash_absorbed(&absorber))
+    }
+
+    /// Domain-separated hash for non-consensus uses
+    ///
+    /// Chain seeds are derived with keyed BLAKE3 instead of plain BLAKE3,
+    /// so outputs for any key are unrelated to mining hashes of the same
+    /// input (and to outputs under other keys). Intended for anti-spam
+    /// puzzles, commit-reveal schemes and similar.
+    pub fn hash_keyed(&mut self, key: &[u8; 32], input: &[u8]) -> [u8; 32] {
+        let nonce = extract_nonce(input);
+        let header_len = input.len().saturating_sub(NONCE_SIZE);
+        let mut midstate = Blake3::new_keyed(key);
+        midstate.update(&input[..header_len]);
+        self.hash_midstate(&midstate, nonce)
+    }
+
+    /// Fill `out` with extendable output from the final BLAKE3 stage
+    ///
+    /// The first 32 bytes equal `hash(input)`; any further bytes come from
+    /// the same BLAKE3 output stream, so several values (e.g. shuffle seeds)
+    /// can be derived from one memory-hard evaluation.
+    pub fn hash_xof(&mut self, input: &[u8], out: &mut [u8]) {
+        let nonce = extract_nonce(input);
+        self.seed_from_header(input, nonce);
+        self.compute_chains(nonce);
+        self.finalize_output();
+        self.contexts.output.finalize_xof().fill(out);
+    }
+
+    /// Hash of the hash: `hash(hash(input))`
+    ///
+    /// The intermediate value is re-fed as its raw 32 bytes, so its last
+    /// [`NONCE_SIZE`] bytes act as the nonce of the second evaluation.
+    pub fn double_hash(&mut self, input: &[u8]) -> [u8; 32] {
+        self.hash_chain(input, 1)
+    }
+
+    /// `hash(input)` re-hashed `n` more times, each link fed raw
+    ///
+    /// `hash_chain(input, 0)` is `hash(input)` and `hash_chain(input, 1)`
+    /// is [`double_hash`](Self::double_hash); revealing link `k` commits
+    /// to every later link. Costs `n + 1` full evaluations.
+    pub fn hash_chain(&mut self, input: &[u8], n: u32) -> [u8; 32] {
+        let mut link = self.hash(input);
+        for _ in 0..n {
+            link = self.hash(&link);
+        }
+        link
+    }
+
+    /// Like [`hash`](Self::hash), but rejects inputs without a full nonce
+    ///
+    /// `hash()` derives a nonce from inputs shorter than [`NONCE_SIZE`]
+    /// bytes; node software handling untrusted bytes should use this
+    /// variant so malformed input surfaces as an error instead.
+    pub fn try_hash(&mut self, input: &[u8]) -> Result<[u8; 32], UhashError> {
+        if input.len() < NONCE_SIZE {
+            return Err(UhashError::InputTooShort {
+                len: input.len(),
+                min: NONCE_SIZE,
+            });
+        }
+        Ok(self.hash(input))
+    }
+
+    /// Strict variant: hash `input` only if it is exactly one mining header
+    ///
+    /// Consensus code should use this so that inputs of any other length
+    /// (which `hash()` would silently accept) are rejected with
+    /// [`UhashError::InvalidLength`].
+    pub fn hash_header(&mut self, input: &[u8]) -> Result<[u8; 32], UhashError> {
+        if input.len() != HEADER_SIZE {
+            return Err(UhashError::InvalidLength {
+                expected: HEADER_SIZE,
+                actual: input.len(),
+            });
+        }
+        Ok(self.hash(input))
+    }
+
+    /// Compute the UniversalHash of `job`'s header prefix followed by `nonce`
+    ///
+    /// Equivalent to `hash(prefix || nonce.to_le_bytes())`, but reuses the
+    /// header work precomputed by [`Job::from_header`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use uhash_core::{Job, UniversalHash, hash};
+    ///
+    /// let prefix = [7u8; 60];
+    /// let job = Job::from_header(&prefix);
+    /// let mut hasher = UniversalHash::new();
+    ///
+    /// let mut input = prefix.to_vec();
+    /// input.extend_from_slice(&42u64.to_le_bytes());
+    /// assert_eq!(hasher.hash_nonce(&job, 42), hash(&input));
+    /// ```
+    pub fn hash_nonce(&mut self, job: &Job, nonce: u64) -> [u8; 32] {
+        self.hash_midstate(&job.midstate, nonce)
+    }
+
+    /// Hash from a BLAKE3 state that has already absorbed the header
+    fn hash_midstate(&mut self, midstate: &Blake3, nonce: u64) -> [u8; 32] {
+        seed_chains(
+            &mut self.contexts.seed,
+            midstate,
+            nonce,
+            &mut self.chain_states,
+        );
+        self.compute_chains(nonce);
 
         // Phase 3: Finalize and produce output
-        self.finalize()
+        self.finalize_output();
+        self.contexts.output.finalize().into()
     }
 
-    /// Initialize all scratchpads from input using expansion
-    /// Spec: seed[c] = BLAKE3_256(header || (nonce ⊕ (c × golden_ratio)))
-    #[cfg(feature = "parallel")]
-    fn init_scratchpads(&mut self, input: &[u8]) {
-        let nonce = self.effective_nonce;
+    /// Seed `chain_states` from the unkeyed BLAKE3 midstate of `input`'s
+    /// header, absorbed into the reused header context
+    fn seed_from_header(&mut self, input: &[u8], nonce: u64) {
+        let contexts = &mut self.contexts;
+        contexts.header.reset();
+        contexts.header.update(header_bytes(input));
+        seed_chains(
+            &mut contexts.seed,
+            &contexts.header,
+            nonce,
+            &mut self.chain_states,
+        );
+    }
 
-        // Pre-compute all chain seeds using BLAKE3 with XORed nonce per spec
-        let mut chain_seeds: [[u8; 32]; CHAINS] = [[0u8; 32]; CHAINS];
-        for (chain, (seed, state)) in chain_seeds
-            .iter_mut()
-            .zip(self.chain_states.iter_mut())
-            .enumerate()
+    /// Run phases 1 and 2 from the seeds in `chain_states`, leaving the
+    /// final chain states there
+    fn compute_chains(&mut self, nonce: u64) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("uhash", nonce).entered();
+        self.effective_nonce = nonce;
+        // Freshly initialized scratchpads fold to zero, see `fold_words`
+        self.checksums = [0; CHAINS];
+
+        // Phase 1: Initialize scratchpads using input (spec-compliant seed generation)
+        #[cfg(feature = "metrics")]
+        let init_started = std::time::Instant::now();
         {
-            // Spec: nonce ⊕ (c × golden_ratio)
-            let offset = (chain as u64).wrapping_mul(GOLDEN_RATIO);
-            let modified_nonce = nonce ^ offset;
-
-            // Spec: BLAKE3(header || modified_nonce)
-            // Header is input without last 8 bytes (nonce)
-            let header_len = input.len().saturating_sub(8);
-            let mut hasher = Blake3::new();
-            hasher.update(&input[..header_len]);
-            hasher.update(&modified_nonce.to_le_bytes());
-            let hash = hasher.finalize();
-            seed.copy_from_slice(hash.as_bytes());
-            state.copy_from_slice(hash.as_bytes());
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("init_scratchpads").entered();
+            self.init_scratchpads();
         }
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_init(init_started.elapsed());
+
+        // Phase 2: Execute main mixing rounds (spec-compliant, no cross-chain mixing)
+        {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("execute_rounds").entered();
+            self.execute_rounds();
+        }
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_hash(nonce);
+    }
 
-        // Fill scratchpads in parallel
+    /// Initialize all scratchpads by expanding the seeds in `chain_states`
+    fn init_scratchpads(&mut self) {
+        #[cfg(feature = "parallel")]
+        if self.parallel {
+            return self.init_scratchpads_parallel();
+        }
+        self.init_scratchpads_sequential()
+    }
+
+    /// Initialize all scratchpads in parallel
+    #[cfg(feature = "parallel")]
+    fn init_scratchpads_parallel(&mut self) {
         self.scratchpads
+            .chains_mut()
             .par_iter_mut()
-            .zip(chain_seeds.par_iter())
+            .zip(self.chain_states.par_iter())
             .for_each(|(scratchpad, seed)| {
-                fill_scratchpad_aes(scratchpad, seed);
+                fill_scratchpad_aes(&mut scratchpad[..], seed);
             });
     }
 
-    /// Initialize all scratchpads from input using expansion (sequential fallback)
-    /// Spec: seed[c] = BLAKE3_256(header || (nonce ⊕ (c × golden_ratio)))
-    #[cfg(not(feature = "parallel"))]
-    fn init_scratchpads(&mut self, input: &[u8]) {
-        let nonce = self.effective_nonce;
-        let header_len = input.len().saturating_sub(8);
-
-        for (chain, state) in self.chain_states.iter_mut().enumerate() {
-            // Spec: nonce ⊕ (c × golden_ratio)
-            let offset = (chain as u64).wrapping_mul(GOLDEN_RATIO);
-            let modified_nonce = nonce ^ offset;
-
-            // Spec: BLAKE3(header || modified_nonce)
-            let mut hasher = Blake3::new();
-            hasher.update(&input[..header_len]);
-            hasher.update(&modified_nonce.to_le_bytes());
-            let hash = hasher.finalize();
-
-            let hash_bytes = hash.as_bytes();
-            state.copy_from_slice(hash_bytes);
-
-            // Fill scratchpad using AES-based expansion
-            let mut seed_array = [0u8; 32];
-            seed_array.copy_from_slice(hash_bytes);
-            fill_scratchpad_aes(&mut self.scratchpads[chain], &seed_array);
-        }
+    /// Initialize all scratchpads one after another
+    fn init_scratchpads_sequential(&mut self) {
+        // Interleave the chains' AES expansions instead of filling one
+        // scratchpad at a time
+        fill_scratchpads_lockstep(self.scratchpads.chains_mut(), &self.chain_states);
     }
 
     /// Execute the main mixing rounds (spec-compliant: no cross-chain mixing)
-    #[cfg(feature = "parallel")]
     fn execute_rounds(&mut self) {
+        #[cfg(feature = "parallel")]
+        if self.parallel {
+            return self.execute_rounds_parallel();
+        }
+        self.execute_rounds_sequential()
+    }
+
+    /// Execute the main mixing rounds with one rayon task per chain
+    #[cfg(feature = "parallel")]
+    fn execute_rounds_parallel(&mut self) {
         let nonce = self.effective_nonce;
+        let prefetch = self.prefetch;
+        let hardened = self.hardened;
+        let integrity = self.integrity;
 
         // Process all chains in parallel - each chain runs all rounds independently
         // Spec does NOT specify cross-chain mixing, so we don't do it
         self.scratchpads
+            .chains_mut()
             .par_iter_mut()
             .zip(self.chain_states.par_iter_mut())
+            .zip(self.checksums.par_iter_mut())
             .enumerate()
-            .for_each(|(chain, (scratchpad, state))| {
-                // Spec: primitive = (nonce + c) mod 3
-                let initial_primitive = ((nonce as usize) + chain) % 3;
+            .for_each(|(chain, ((scratchpad, state), checksum))| {
+                let initial_primitive = initial_primitive(nonce, chain);
 
                 // Execute all rounds for this chain
                 for round in 0..ROUNDS {
-                    round_step_spec_compliant(scratchpad, state, initial_primitive, round);
+                    round_step_spec_compliant::<P>(
+                        scratchpad,
+                        state,
+                        initial_primitive,
+                        round,
+                        prefetch,
+                        hardened,
+                        integrity.then_some(&mut *checksum),
+                    );
                 }
             });
     }
 
-    /// Execute the main mixing rounds (sequential fallback, spec-compliant)
-    #[cfg(not(feature = "parallel"))]
-    fn execute_rounds(&mut self) {
+    /// Execute the main mixing rounds for all chains in lockstep
+    ///
+    /// Each round depends on the previous one of its own chain only, so
+    /// stepping every chain once per round gives the CPU four independent
+    /// scratchpad loads and compressions in flight instead of one serial
+    /// dependency. Same result as running the chains one after another,
+    /// with lower single-hash latency and without rayon.
+    fn execute_rounds_sequential(&mut self) {
         let nonce = self.effective_nonce;
+        let initial_primitives: [usize; CHAINS] =
+            core::array::from_fn(|chain| initial_primitive(nonce, chain));
+        let mut scratchpads = self.scratchpads.chains_mut();
 
-        // Process each chain independently (spec-compliant: no cross-chain mixing)
-        for chain in 0..CHAINS {
-            // Spec: primitive = (nonce + c) mod 3
-            let initial_primitive = ((nonce as usize) + chain) % 3;
-
-            // Execute all rounds for this chain
-            for round in 0..ROUNDS {
-                round_step_spec_compliant(
-                    &mut self.scratchpads[chain],
-                    &mut self.chain_states[chain],
-                    initial_primitive,
+        for round in 0..ROUNDS {
+            for (chain, ((scratchpad, state), checksum)) in scratchpads
+                .iter_mut()
+                .zip(self.chain_states.iter_mut())
+                .zip(self.checksums.iter_mut())
+                .enumerate()
+            {
+                round_step_spec_compliant::<P>(
+                    scratchpad,
+                    state,
+                    initial_primitives[chain],
                     round,
+                    self.prefetch,
+                    self.hardened,
+                    self.integrity.then_some(checksum),
                 );
             }
         }
     }
 
-    /// Finalize and produce the 32-byte output hash per spec
-    /// Spec: result = BLAKE3_256(SHA256_256(combined))
-    fn finalize(&self) -> [u8; 32] {
-        // XOR all chain states together
-        let mut combined = [0u8; 32];
-        for state in &self.chain_states {
-            for i in 0..32 {
-                combined[i] ^= state[i];
-            }
-        }
+    /// Run phase 3 into the reused output context, ready to be read
+    fn finalize_output(&mut self) {
+        let contexts = &mut self.contexts;
+        finalize_with(
+            &self.chain_states,
+            &mut contexts.sha256,
+            &mut contexts.output,
+        );
+    }
+}
+
+/// BLAKE3 state after absorbing the header
+/// Header is input without last 8 bytes (nonce)
+pub(crate) fn header_midstate(input: &[u8]) -> Blake3 {
+    let mut hasher = Blake3::new();
+    hasher.update(header_bytes(input));
+    hasher
+}
 
-        // Double hash: SHA256 then BLAKE3 (per spec)
-        let sha_hash = Sha256::digest(combined);
-        let mut hasher = Blake3::new();
-        hasher.update(&sha_hash);
-        hasher.finalize().into()
+/// Input without its last [`NONCE_SIZE`] bytes
+#[inline(always)]
+fn header_bytes(input: &[u8]) -> &[u8] {
+    &input[..input.len().saturating_sub(NONCE_SIZE)]
+}
+
+/// Compute the seed for one chain from the header midstate
+/// Spec: seed[c] = BLAKE3_256(header || (nonce ⊕ (c × golden_ratio)))
+#[inline(always)]
+#[deny(clippy::arithmetic_side_effects)]
+pub(crate) fn chain_seed(midstate: &Blake3, nonce: u64, chain: usize) -> [u8; 32] {
+    chain_seed_with(&mut midstate.clone(), midstate, nonce, chain)
+}
+
+/// [`chain_seed`] using `scratch` as the working BLAKE3 state
+#[inline(always)]
+#[deny(clippy::arithmetic_side_effects)]
+fn chain_seed_with(scratch: &mut Blake3, midstate: &Blake3, nonce: u64, chain: usize) -> [u8; 32] {
+    // Spec: nonce ⊕ (c × golden_ratio)
+    let offset = (chain as u64).wrapping_mul(GOLDEN_RATIO);
+    let modified_nonce = nonce ^ offset;
+
+    // Spec: BLAKE3(header || modified_nonce)
+    scratch.clone_from(midstate);
+    scratch.update(&modified_nonce.to_le_bytes());
+    scratch.finalize().into()
+}
+
+/// Write every chain's seed into `states`, reusing `scratch` for all of them
+#[cfg(feature = "alloc")]
+#[inline(always)]
+fn seed_chains(
+    scratch: &mut Blake3,
+    midstate: &Blake3,
+    nonce: u64,
+    states: &mut [[u8; 32]; CHAINS],
+) {
+    for (chain, state) in states.iter_mut().enumerate() {
+        *state = chain_seed_with(scratch, midstate, nonce, chain);
+    }
+}
+
+/// Primitive index a chain starts from
+/// Spec: primitive = (nonce + c) mod 3, with the sum wrapping at 2^64
+#[inline(always)]
+#[deny(clippy::arithmetic_side_effects)]
+pub(crate) fn initial_primitive(nonce: u64, chain: usize) -> usize {
+    (nonce.wrapping_add(chain as u64) % 3) as usize
+}
+
+/// Primitive index used at a given round
+/// Spec: primitive = (primitive + 1) mod 3 BEFORE applying
+///
+/// Both operands are reduced first, so the sum is at most 5 and the result
+/// is exact for every `round`, not only those below [`ROUNDS`].
+#[inline(always)]
+#[deny(clippy::arithmetic_side_effects)]
+pub(crate) fn round_primitive(initial_primitive: usize, round: usize) -> usize {
+    (initial_primitive % 3)
+        .wrapping_add(round % 3)
+        .wrapping_add(1)
+        % 3
+}
+
+/// Combine final chain states into the output hash
+/// Spec: result = BLAKE3_256(SHA256_256(combined))
+pub(crate) fn finalize_states(chain_states: &[[u8; 32]; CHAINS]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    finalize_into(chain_states, &mut out);
+    out
+}
+
+/// [`finalize_states`] writing the output hash into `out`
+fn finalize_into(chain_states: &[[u8; 32]; CHAINS], out: &mut [u8; 32]) {
+    let mut hasher = Blake3::new();
+    finalize_with(chain_states, &mut Sha256::new(), &mut hasher);
+    out.copy_from_slice(hasher.finalize().as_bytes());
+}
+
+/// Leave the final stage's BLAKE3 state in `output`, before output is read
+///
+/// `sha256` must be fresh or reset; it is left reset. `output` must be an
+/// unkeyed BLAKE3 state and is reset first.
+#[deny(clippy::arithmetic_side_effects)]
+fn finalize_with(chain_states: &[[u8; 32]; CHAINS], sha256: &mut Sha256, output: &mut Blake3) {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("finalize").entered();
+
+    // XOR all chain states together
+    let mut combined = [0u8; 32];
+    for state in chain_states {
+        for i in 0..32 {
+            combined[i] ^= state[i];
+        }
     }
+
+    // Double hash: SHA256 then BLAKE3 (per spec)
+    Digest::update(sha256, combined);
+    let sha_hash = sha256.finalize_reset();
+    output.reset();
+    output.update(&sha_hash);
 }
 
 /// Extract nonce from input (last 8 bytes, or hash if shorter)
 #[inline(always)]
-fn extract_nonce(input: &[u8]) -> u64 {
-    if input.len() >= 8 {
+#[deny(clippy::arithmetic_side_effects)]
+pub(crate) fn extract_nonce(input: &[u8]) -> u64 {
+    match input.len().checked_sub(NONCE_SIZE) {
         // Use last 8 bytes as nonce
-        let nonce_bytes: [u8; 8] = input[input.len() - 8..].try_into().unwrap();
-        u64::from_le_bytes(nonce_bytes)
-    } else {
+        Some(offset) => load_u64_le(input, offset),
         // For short inputs, hash to get a nonce
-        let hash = blake3::hash(input);
-        let bytes: [u8; 8] = hash.as_bytes()[..8].try_into().unwrap();
-        u64::from_le_bytes(bytes)
+        None => load_u64_le(blake3::hash(input).as_bytes(), 0),
     }
 }
 
@@ -228,115 +774,598 @@ fn extract_nonce(input: &[u8]) -> u64 {
 ///     state = AES_4Rounds(state, key)
 ///     scratchpad[i × 64 : (i+1) × 64] = state || AES_4Rounds(state, key)
 #[inline(always)]
-fn fill_scratchpad_aes(scratchpad: &mut [u8], seed: &[u8; 32]) {
-    use crate::primitives::aes_expand_block;
+pub(crate) fn fill_scratchpad_aes(scratchpad: &mut [u8], seed: &[u8; 32]) {
+    let key = ExpandKey::new(seed[0..16].try_into().unwrap());
+    fill_scratchpad_from(scratchpad, seed, |state| key.expand(state));
+}
 
+/// [`fill_scratchpad_aes`] with the 4-round AES expansion supplied by the caller
+#[cfg(any(feature = "differential", feature = "trace"))]
+#[inline(always)]
+pub(crate) fn fill_scratchpad_with(
+    scratchpad: &mut [u8],
+    seed: &[u8; 32],
+    aes_expand_block: impl Fn(&[u8; 16], &[u8; 16]) -> [u8; 16],
+) {
     let key: [u8; 16] = seed[0..16].try_into().unwrap();
-    let mut state: [u8; 16] = seed[16..32].try_into().unwrap();
+    fill_scratchpad_from(scratchpad, seed, |state| aes_expand_block(state, &key));
+}
 
-    for i in 0..BLOCKS_PER_SCRATCHPAD {
-        // Apply 4 AESENC rounds (per spec)
-        state = aes_expand_block(&state, &key);
-        let offset = i * BLOCK_SIZE;
+/// Fill a scratchpad by chaining `expand`, which applies the 4-round AES
+/// expansion under `seed`'s key
+#[inline(always)]
+fn fill_scratchpad_from(
+    scratchpad: &mut [u8],
+    seed: &[u8; 32],
+    expand: impl Fn(&[u8; 16]) -> [u8; 16],
+) {
+    let seed_state: [u8; 16] = seed[16..32].try_into().unwrap();
 
-        // First 16 bytes: state after first AES
-        scratchpad[offset..offset + 16].copy_from_slice(&state);
+    // The spec's second expansion of block i is the next block's state, so
+    // each expansion is computed once and shared by neighbouring blocks
+    let mut state = expand(&seed_state);
+    for i in 0..BLOCKS_PER_SCRATCHPAD {
+        let next = expand(&state);
+        write_init_block(scratchpad, i, &state, &next);
+        state = next;
+    }
+}
 
-        // Next 16 bytes: state after second AES (per spec)
-        let state2 = aes_expand_block(&state, &key);
-        scratchpad[offset + 16..offset + 32].copy_from_slice(&state2);
+/// Fill every chain's scratchpad in lockstep on the calling thread
+///
+/// Produces the same contents as [`fill_scratchpad_aes`] per chain, but the
+/// chains' expansions are independent, so interleaving them lets the CPU
+/// overlap AES latency across chains instead of waiting on one chain's
+/// serial dependency.
+#[cfg(feature = "alloc")]
+#[inline(always)]
+fn fill_scratchpads_lockstep(
+    mut scratchpads: [&mut [u8; SCRATCHPAD_SIZE]; CHAINS],
+    seeds: &[[u8; 32]; CHAINS],
+) {
+    let keys: [ExpandKey; CHAINS] =
+        core::array::from_fn(|c| ExpandKey::new(seeds[c][0..16].try_into().unwrap()));
+    let mut states: [[u8; 16]; CHAINS] =
+        core::array::from_fn(|c| keys[c].expand(seeds[c][16..32].try_into().unwrap()));
 
-        // Remaining 32 bytes: duplicate first 32 bytes
-        // (spec says 32 bytes per block but BLOCK_SIZE is 64)
-        scratchpad[offset + 32..offset + 48].copy_from_slice(&state);
-        scratchpad[offset + 48..offset + 64].copy_from_slice(&state2);
+    for i in 0..BLOCKS_PER_SCRATCHPAD {
+        let next: [[u8; 16]; CHAINS] = core::array::from_fn(|c| keys[c].expand(&states[c]));
+        for (c, scratchpad) in scratchpads.iter_mut().enumerate() {
+            write_init_block(&mut scratchpad[..], i, &states[c], &next[c]);
+        }
+        states = next;
     }
 }
 
+/// Write block `index` of an initialized scratchpad: `state || next`, twice
+/// (the spec defines 32 bytes per block but BLOCK_SIZE is 64)
+#[inline(always)]
+fn write_init_block(scratchpad: &mut [u8], index: usize, state: &[u8; 16], next: &[u8; 16]) {
+    let offset = index * BLOCK_SIZE;
+    scratchpad[offset..offset + 16].copy_from_slice(state);
+    scratchpad[offset + 16..offset + 32].copy_from_slice(next);
+    scratchpad[offset + 32..offset + 48].copy_from_slice(state);
+    scratchpad[offset + 48..offset + 64].copy_from_slice(next);
+}
+
 /// Single round step for one chain (spec-compliant version)
 ///
 /// Spec:
 /// - Address: computed from current state
 /// - Primitive: (initial_primitive + round + 1) mod 3  (increment BEFORE use)
 /// - Write-back: SAME address as read (not new address)
+///
+/// With `hardened`, memory access and primitive choice go through
+/// [`hardened_round_step`] instead.
 #[inline(always)]
-fn round_step_spec_compliant(
-    scratchpad: &mut [u8],
+pub(crate) fn round_step_spec_compliant<P: PrimitiveSet>(
+    scratchpad: &mut [u8; SCRATCHPAD_SIZE],
     state: &mut [u8; 32],
     initial_primitive: usize,
     round: usize,
+    prefetch: bool,
+    hardened: bool,
+    checksum: Option<&mut u64>,
 ) {
+    if hardened {
+        return hardened_round_step::<P>(scratchpad, state, initial_primitive, round, checksum);
+    }
+
     // Compute memory address from state per spec formula
     let addr = compute_address(state, round);
 
     // Read block from scratchpad
-    // SAFETY: addr is always within bounds due to ADDRESS_MASK
-    let block: [u8; BLOCK_SIZE] =
-        unsafe { core::ptr::read(scratchpad.as_ptr().add(addr) as *const [u8; BLOCK_SIZE]) };
+    let block = read_block(scratchpad, addr);
 
     // Spec: primitive = (primitive + 1) mod 3 BEFORE applying
     // Where primitive starts at (nonce + chain) mod 3
     // So at round r: primitive = (initial_primitive + r + 1) mod 3
-    let primitive = (initial_primitive + round + 1) % 3;
+    let primitive = round_primitive(initial_primitive, round);
 
     // Apply raw compression function based on primitive
-    let new_state = match primitive {
-        0 => aes_compress(state, &block),
-        1 => sha256_compress(state, &block),
-        _ => blake3_compress(state, &block),
-    };
+    let new_state = P::compress(primitive, state, &block);
 
-    // Spec: Write back to SAME address as read (not computed from new_state!)
-    // SAFETY: addr is always within bounds due to ADDRESS_MASK
-    unsafe {
-        core::ptr::copy_nonoverlapping(new_state.as_ptr(), scratchpad.as_mut_ptr().add(addr), 32);
+    // The next round reads from an address derived from new_state; start
+    // loading it now so the fetch overlaps the write-back below
+    if prefetch {
+        prefetch_block(
+            scratchpad,
+            compute_address(&new_state, round.wrapping_add(1)),
+        );
     }
 
+    // Replace the overwritten half of the block in the scratchpad's checksum
+    if let Some(checksum) = checksum {
+        *checksum ^= fold_words(&block[..32]) ^ fold_words(&new_state);
+    }
+
+    // Spec: Write back to SAME address as read (not computed from new_state!)
+    write_block_state(scratchpad, addr, &new_state);
+
     // Update chain state
     *state = new_state;
 }
 
+/// The block at byte offset `addr`
+///
+/// Bounds-checked by default. With the `fast` feature the offset is masked
+/// with [`BLOCK_OFFSET_MASK`] (a no-op for offsets from
+/// [`compute_address`]) and read without a check.
+#[inline(always)]
+fn read_block(scratchpad: &[u8; SCRATCHPAD_SIZE], addr: usize) -> [u8; BLOCK_SIZE] {
+    #[cfg(all(feature = "fast", not(feature = "safe")))]
+    {
+        let addr = addr & BLOCK_OFFSET_MASK;
+        // SAFETY: the mask leaves `addr <= SCRATCHPAD_SIZE - BLOCK_SIZE`, so
+        // the whole block lies within the scratchpad
+        unsafe {
+            scratchpad
+                .get_unchecked(addr..addr + BLOCK_SIZE)
+                .try_into()
+                .unwrap_unchecked()
+        }
+    }
+    #[cfg(not(all(feature = "fast", not(feature = "safe"))))]
+    {
+        scratchpad[addr..addr + BLOCK_SIZE].try_into().unwrap()
+    }
+}
+
+/// Overwrite the first half of the block at byte offset `addr` with `state`,
+/// checked or masked as in [`read_block`]
+#[inline(always)]
+fn write_block_state(scratchpad: &mut [u8; SCRATCHPAD_SIZE], addr: usize, state: &[u8; 32]) {
+    #[cfg(all(feature = "fast", not(feature = "safe")))]
+    {
+        let addr = addr & BLOCK_OFFSET_MASK;
+        // SAFETY: as in `read_block`; the 32 bytes lie within the block
+        unsafe { scratchpad.get_unchecked_mut(addr..addr + 32) }.copy_from_slice(state);
+    }
+    #[cfg(not(all(feature = "fast", not(feature = "safe"))))]
+    {
+        scratchpad[addr..addr + 32].copy_from_slice(state);
+    }
+}
+
+/// [`round_step_spec_compliant`] without input-dependent memory access or
+/// primitive choice
+///
+/// Every block is read and its first half rewritten, the addressed one
+/// selected by a mask; all three primitives run and the scheduled one is
+/// selected the same way. The primitives themselves are only constant-time
+/// with hardware AES, see [`UniversalHash::with_cache_hardening`].
+#[inline(never)]
+fn hardened_round_step<P: PrimitiveSet>(
+    scratchpad: &mut [u8; SCRATCHPAD_SIZE],
+    state: &mut [u8; 32],
+    initial_primitive: usize,
+    round: usize,
+    checksum: Option<&mut u64>,
+) {
+    let target = compute_address(state, round) / BLOCK_SIZE;
+
+    let mut block = [0u8; BLOCK_SIZE];
+    for (index, candidate) in scratchpad.chunks_exact(BLOCK_SIZE).enumerate() {
+        let mask = crate::ct::eq_mask(index, target) as u8;
+        for (byte, &value) in block.iter_mut().zip(candidate) {
+            *byte |= value & mask;
+        }
+    }
+
+    let primitive = round_primitive(initial_primitive, round);
+    let mut new_state = [0u8; 32];
+    for candidate in 0..3 {
+        let mask = crate::ct::eq_mask(candidate, primitive) as u8;
+        let output = P::compress(candidate, state, &block);
+        for (byte, value) in new_state.iter_mut().zip(output) {
+            *byte |= value & mask;
+        }
+    }
+
+    if let Some(checksum) = checksum {
+        *checksum ^= fold_words(&block[..32]) ^ fold_words(&new_state);
+    }
+
+    for (index, candidate) in scratchpad.chunks_exact_mut(BLOCK_SIZE).enumerate() {
+        let mask = crate::ct::eq_mask(index, target) as u8;
+        for (byte, &value) in candidate.iter_mut().zip(&new_state) {
+            *byte = (*byte & !mask) | (value & mask);
+        }
+    }
+
+    *state = new_state;
+}
+
+/// XOR of the little-endian 64-bit words of `bytes`
+///
+/// Each freshly initialized block holds its two 16-byte halves twice, so a
+/// whole scratchpad folds to zero before the rounds start.
+#[inline(always)]
+fn fold_words(bytes: &[u8]) -> u64 {
+    (0..bytes.len() / 8).fold(0, |acc, i| acc ^ load_u64_le(bytes, i * 8))
+}
+
+/// Hint the CPU to pull the block at `addr` into L1
+///
+/// Only a hint: never faults, and compiles to nothing on targets without a
+/// prefetch instruction or under the `safe` feature.
+#[inline(always)]
+fn prefetch_block(scratchpad: &[u8], addr: usize) {
+    #[cfg(all(
+        any(
+            target_arch = "x86_64",
+            all(target_arch = "x86", target_feature = "sse")
+        ),
+        not(feature = "safe")
+    ))]
+    // SAFETY: addr is within bounds due to ADDRESS_MASK; prefetch has no
+    // architectural effect
+    unsafe {
+        #[cfg(target_arch = "x86")]
+        use core::arch::x86::{_MM_HINT_T0, _mm_prefetch};
+        #[cfg(target_arch = "x86_64")]
+        use core::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
+        let block = scratchpad.as_ptr().add(addr) as *const i8;
+        // A block spans two cache lines unless the buffer is 64-byte aligned
+        _mm_prefetch::<_MM_HINT_T0>(block);
+        _mm_prefetch::<_MM_HINT_T0>(block.add(BLOCK_SIZE - 1));
+    }
+
+    #[cfg(all(target_arch = "aarch64", not(feature = "safe")))]
+    // SAFETY: addr is within bounds due to ADDRESS_MASK; prfm has no
+    // architectural effect
+    unsafe {
+        let block = scratchpad.as_ptr().add(addr);
+        core::arch::asm!(
+            "prfm pldl1keep, [{0}]",
+            "prfm pldl1keep, [{0}, #63]",
+            in(reg) block,
+            options(nostack, readonly, preserves_flags),
+        );
+    }
+
+    #[cfg(any(
+        feature = "safe",
+        not(any(
+            target_arch = "x86_64",
+            all(target_arch = "x86", target_feature = "sse"),
+            target_arch = "aarch64"
+        ))
+    ))]
+    let _ = (scratchpad, addr);
+}
+
 /// Compute scratchpad address from state per spec
 /// Spec: mixed = state[0:8] ⊕ state[8:16] ⊕ rotl64(round, 13) ⊕ (round × 0x517cc1b727220a95)
 ///       addr = (mixed mod NUM_BLOCKS) × BLOCK_SIZE
 #[inline(always)]
-fn compute_address(state: &[u8; 32], round: usize) -> usize {
-    const MIXING_CONSTANT: u64 = 0x517cc1b727220a95;
+#[deny(clippy::arithmetic_side_effects)]
+pub(crate) fn compute_address(state: &[u8; 32], round: usize) -> usize {
+    // Use bitwise AND instead of modulo (NUM_BLOCKS is power of 2). The
+    // product is below SCRATCHPAD_SIZE, so the multiply cannot wrap
+    let index = mixed_word(state, round) & ADDRESS_MASK;
+    index.wrapping_mul(BLOCK_SIZE)
+}
+
+const MIXING_CONSTANT: u64 = 0x517cc1b727220a95;
 
-    // Read u64s directly using pointer reads (faster than try_into)
-    // SAFETY: state is 32 bytes, reading at offsets 0 and 8 is safe
-    let state_lo = unsafe { core::ptr::read_unaligned(state.as_ptr() as *const u64) };
-    let state_hi = unsafe { core::ptr::read_unaligned(state.as_ptr().add(8) as *const u64) };
+/// Low bits of the spec's `mixed`, as many as fit in a `usize`
+#[cfg(not(target_pointer_width = "32"))]
+#[inline(always)]
+#[deny(clippy::arithmetic_side_effects)]
+fn mixed_word(state: &[u8; 32], round: usize) -> usize {
+    // Spec: state words are little-endian regardless of host byte order
+    let state_lo = load_u64_le(state, 0);
+    let state_hi = load_u64_le(state, 8);
     let round_u64 = round as u64;
 
     // Spec formula for unpredictable address
     let mixed =
         state_lo ^ state_hi ^ round_u64.rotate_left(13) ^ round_u64.wrapping_mul(MIXING_CONSTANT);
+    mixed as usize
+}
+
+/// Low bits of the spec's `mixed`, as many as fit in a `usize`
+///
+/// Only the low 13 bits select a block, and the low 32 bits of each term
+/// depend only on the low 32 bits of its operands: XOR is bitwise, the
+/// product is taken mod 2^64, and the bits `rotl64` wraps around come from
+/// bits 51..64 of `round`, which are zero in a 32-bit `usize`. Working in
+/// `u32` avoids emulating the 64-bit multiply every round.
+#[cfg(target_pointer_width = "32")]
+#[inline(always)]
+#[deny(clippy::arithmetic_side_effects)]
+fn mixed_word(state: &[u8; 32], round: usize) -> usize {
+    let state_lo = crate::endian::load_u32_le(state, 0);
+    let state_hi = crate::endian::load_u32_le(state, 8);
+    let round_u32 = round as u32;
 
-    // Use bitwise AND instead of modulo (NUM_BLOCKS is power of 2)
-    ((mixed as usize) & ADDRESS_MASK) * BLOCK_SIZE
+    let mixed = state_lo
+        ^ state_hi
+        ^ round_u32.wrapping_shl(13)
+        ^ round_u32.wrapping_mul(MIXING_CONSTANT as u32);
+    mixed as usize
 }
 
+#[cfg(feature = "alloc")]
 impl Default for UniversalHash {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// UniversalHash over a scratchpad buffer supplied by the caller
+///
+/// Runs the chains one after another in a single [`SCRATCHPAD_SIZE`]
+/// (512KB) region, so it needs neither an allocator nor the full 2MB of
+/// [`UniversalHash`]. Intended for embedded verifiers; the buffer can be a
+/// `static` array.
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{SCRATCHPAD_SIZE, UniversalHashIn};
+///
+/// let mut buffer = [0u8; SCRATCHPAD_SIZE];
+/// let mut hasher = UniversalHashIn::new_in(&mut buffer)?;
+/// let result = hasher.hash(b"input data");
+/// # #[cfg(feature = "alloc")]
+/// assert_eq!(result, uhash_core::hash(b"input data"));
+/// # Ok::<(), uhash_core::UhashError>(())
+/// ```
+pub struct UniversalHashIn<'a> {
+    scratchpad: &'a mut [u8; SCRATCHPAD_SIZE],
+    prefetch: bool,
+}
+
+impl<'a> UniversalHashIn<'a> {
+    /// Use the first [`SCRATCHPAD_SIZE`] bytes of `buffer` as the scratchpad
+    pub fn new_in(buffer: &'a mut [u8]) -> Result<Self, UhashError> {
+        let len = buffer.len();
+        let scratchpad = buffer
+            .get_mut(..SCRATCHPAD_SIZE)
+            .and_then(|region| region.try_into().ok())
+            .ok_or(UhashError::BufferTooSmall {
+                len,
+                min: SCRATCHPAD_SIZE,
+            })?;
+        Ok(Self {
+            scratchpad,
+            prefetch: PREFETCH_DEFAULT,
+        })
+    }
+
+    /// Enable or disable software prefetch, as [`UniversalHash::with_prefetch`]
+    pub fn with_prefetch(mut self, enabled: bool) -> Self {
+        self.prefetch = enabled;
+        self
+    }
+
+    /// Compute the UniversalHash of input data, as [`UniversalHash::hash`]
+    pub fn hash(&mut self, input: &[u8]) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        self.hash_into(input, &mut out);
+        out
+    }
+
+    /// Write the UniversalHash of input data into `out`, as [`UniversalHash::hash_into`]
+    pub fn hash_into(&mut self, input: &[u8], out: &mut [u8; 32]) {
+        let chain_states = self.compute_chains(&header_midstate(input), extract_nonce(input));
+        finalize_into(&chain_states, out);
+    }
+
+    /// Hash `input` only if it is exactly one mining header, as [`UniversalHash::hash_header`]
+    pub fn hash_header(&mut self, input: &[u8]) -> Result<[u8; 32], UhashError> {
+        if input.len() != HEADER_SIZE {
+            return Err(UhashError::InvalidLength {
+                expected: HEADER_SIZE,
+                actual: input.len(),
+            });
+        }
+        Ok(self.hash(input))
+    }
+
+    /// Hash everything absorbed by `absorber`, as [`UniversalHash::hash_absorbed`]
+    pub fn hash_absorbed(&mut self, absorber: &Absorber) -> [u8; 32] {
+        let (midstate, nonce) = absorber.split();
+        self.hash_midstate(midstate, nonce)
+    }
+
+    /// Hash `job`'s header prefix followed by `nonce`, as [`UniversalHash::hash_nonce`]
+    pub fn hash_nonce(&mut self, job: &Job, nonce: u64) -> [u8; 32] {
+        self.hash_midstate(&job.midstate, nonce)
+    }
+
+    /// Hash from a BLAKE3 state that has already absorbed the header
+    fn hash_midstate(&mut self, midstate: &Blake3, nonce: u64) -> [u8; 32] {
+        finalize_states(&self.compute_chains(midstate, nonce))
+    }
+
+    /// Run phases 1 and 2, returning the final chain states
+    fn compute_chains(&mut self, midstate: &Blake3, nonce: u64) -> [[u8; 32]; CHAINS] {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("uhash", nonce).entered();
+        let mut chain_states = [[0u8; 32]; CHAINS];
+        #[cfg(feature = "metrics")]
+        let mut init_time = std::time::Duration::ZERO;
+        for (chain, state) in chain_states.iter_mut().enumerate() {
+            *state = chain_seed(midstate, nonce, chain);
+            #[cfg(feature = "metrics")]
+            let init_started = std::time::Instant::now();
+            {
+                #[cfg(feature = "tracing")]
+                let _span = tracing::debug_span!("init_scratchpads", chain).entered();
+                fill_scratchpad_aes(&mut self.scratchpad[..], state);
+            }
+            #[cfg(feature = "metrics")]
+            {
+                init_time += init_started.elapsed();
+            }
+
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("execute_rounds", chain).entered();
+            let initial_primitive = initial_primitive(nonce, chain);
+            for round in 0..ROUNDS {
+                round_step_spec_compliant::<ConsensusPrimitives>(
+                    self.scratchpad,
+                    state,
+                    initial_primitive,
+                    round,
+                    self.prefetch,
+                    false,
+                    None,
+                );
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        {
+            crate::metrics::record_init(init_time);
+            crate::metrics::record_hash(nonce);
+        }
+        chain_states
+    }
+}
+
 /// Convenience function for single-shot hashing
 ///
 /// Creates a new hasher, computes the hash, and returns it.
 /// For multiple hashes, prefer creating a `UniversalHash` instance
 /// and reusing it to avoid repeated memory allocation.
+#[cfg(feature = "alloc")]
 pub fn hash(input: &[u8]) -> [u8; 32] {
     let mut hasher = UniversalHash::new();
     hasher.hash(input)
 }
 
+/// Single-shot [`UniversalHash::hash_parts`]
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{hash, hash_parts};
+///
+/// let header = [1u8; 60];
+/// let nonce = 7u64.to_le_bytes();
+/// assert_eq!(hash_parts(&[&header, &nonce]), hash(&[header.as_slice(), &nonce].concat()));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn hash_parts(parts: &[&[u8]]) -> [u8; 32] {
+    UniversalHash::new().hash_parts(parts)
+}
+
+/// Single-shot [`UniversalHash::hash_keyed`]
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{hash, hash_keyed};
+///
+/// let key = *b"my-app anti-spam puzzle key v1!!";
+/// assert_ne!(hash_keyed(&key, b"input data"), hash(b"input data"));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn hash_keyed(key: &[u8; 32], input: &[u8]) -> [u8; 32] {
+    UniversalHash::new().hash_keyed(key, input)
+}
+
+/// Single-shot [`UniversalHash::hash_xof`]
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{hash, hash_xof};
+///
+/// let mut out = [0u8; 96];
+/// hash_xof(b"input data", &mut out);
+/// assert_eq!(out[..32], hash(b"input data"));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn hash_xof(input: &[u8], out: &mut [u8]) {
+    UniversalHash::new().hash_xof(input, out)
+}
+
+/// Single-shot [`UniversalHash::double_hash`]
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{double_hash, hash};
+///
+/// assert_eq!(double_hash(b"commitment"), hash(&hash(b"commitment")));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn double_hash(input: &[u8]) -> [u8; 32] {
+    UniversalHash::new().double_hash(input)
+}
+
+/// Single-shot [`UniversalHash::hash_chain`]
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{double_hash, hash, hash_chain};
+///
+/// assert_eq!(hash_chain(b"seed", 0), hash(b"seed"));
+/// assert_eq!(hash_chain(b"seed", 1), double_hash(b"seed"));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn hash_chain(input: &[u8], n: u32) -> [u8; 32] {
+    UniversalHash::new().hash_chain(input, n)
+}
+
+/// Single-shot [`UniversalHash::try_hash`]
+///
+/// # Example
+///
+/// ```rust
+/// use uhash_core::{UhashError, try_hash};
+///
+/// assert!(try_hash(b"header||nonce123").is_ok());
+/// assert_eq!(
+///     try_hash(b"short"),
+///     Err(UhashError::InputTooShort { len: 5, min: 8 })
+/// );
+/// ```
+#[cfg(feature = "alloc")]
+pub fn try_hash(input: &[u8]) -> Result<[u8; 32], UhashError> {
+    UniversalHash::new().try_hash(input)
+}
+
+/// Single-shot [`UniversalHash::hash_header`]
+///
+/// Only accepts exactly [`HEADER_SIZE`] (68) bytes.
+#[cfg(feature = "alloc")]
+pub fn hash_header(input: &[u8]) -> Result<[u8; 32], UhashError> {
+    UniversalHash::new().hash_header(input)
+}
+
 /// Check if a hash meets the required difficulty
 ///
 /// Difficulty is measured as the number of leading zero bits required.
 /// For example, difficulty 16 requires the first 2 bytes to be zero.
+/// Runs in constant time with respect to `hash`, so puzzle services don't
+/// leak how close a submission came.
 ///
 /// # Example
 ///
@@ -356,16 +1385,86 @@ pub fn hash(input: &[u8]) -> [u8; 32] {
 /// ```
 #[inline(always)]
 pub fn meets_difficulty(hash: &[u8; 32], difficulty: u32) -> bool {
-    let mut zero_bits = 0u32;
-
-    for byte in hash.iter() {
-        if *byte == 0 {
-            zero_bits += 8;
-        } else {
-            zero_bits += byte.leading_zeros();
-            break;
-        }
+    crate::ct::has_leading_zeros(hash, difficulty)
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::hash;
+
+    #[cfg(not(feature = "std"))]
+    use alloc::vec;
+
+    #[test]
+    fn test_prefetch_does_not_change_output() {
+        let input = b"prefetch";
+        let expected = hash(input);
+        assert_eq!(
+            UniversalHash::new().with_prefetch(true).hash(input),
+            expected
+        );
+        assert_eq!(
+            UniversalHash::new_sequential()
+                .with_prefetch(true)
+                .hash(input),
+            expected
+        );
+
+        let mut buffer = vec![0u8; SCRATCHPAD_SIZE];
+        let mut hasher = UniversalHashIn::new_in(&mut buffer)
+            .unwrap()
+            .with_prefetch(true);
+        assert_eq!(hasher.hash(input), expected);
+    }
+
+    #[test]
+    fn test_integrity_check_detects_bit_flip() {
+        let input = b"integrity";
+        let mut hasher = UniversalHash::new().with_integrity_check(true);
+        assert_eq!(hasher.hash(input), hash(input));
+        assert!(hasher.scratchpads_intact());
+
+        hasher.scratchpads_mut()[2][12345] ^= 0x10;
+        assert!(!hasher.scratchpads_intact());
+
+        // The next hash rewrites every scratchpad and starts a fresh checksum
+        let mut sequential = UniversalHash::new_sequential().with_integrity_check(true);
+        assert_eq!(sequential.hash(input), hash(input));
+        assert!(sequential.scratchpads_intact());
+        assert_eq!(hasher.hash(input), hash(input));
+        assert!(hasher.scratchpads_intact());
     }
 
-    zero_bits >= difficulty
+    #[test]
+    fn test_fork_keeps_configuration() {
+        let mut template = UniversalHash::new_sequential().with_integrity_check(true);
+        template.hash(b"template");
+
+        let mut fork = template.fork();
+        assert_eq!(fork.hash(b"fork"), hash(b"fork"));
+        // Integrity checking carried over: a flip is caught in the fork
+        fork.scratchpads_mut()[0][0] ^= 1;
+        assert!(!fork.scratchpads_intact());
+        assert!(template.scratchpads_intact());
+    }
+
+    #[test]
+    fn test_warm_up_leaves_results_unchanged() {
+        let mut hasher = UniversalHash::new().with_integrity_check(true);
+        hasher.warm_up();
+        // Every page has been written
+        assert!(
+            hasher
+                .scratchpads_mut()
+                .iter()
+                .flat_map(|scratchpad| scratchpad.chunks(4096))
+                .all(|page| page.iter().any(|&b| b != 0))
+        );
+        assert!(hasher.scratchpads_intact());
+        assert_eq!(hasher.hash(b"warm"), hash(b"warm"));
+
+        hasher.warm_up();
+        assert_eq!(hasher.hash(b"warm"), hash(b"warm"));
+    }
 }
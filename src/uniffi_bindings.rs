@@ -0,0 +1,164 @@
+//! Swift and Kotlin bindings generated with UniFFI
+//!
+//! Exposes hashing, share verification and single-nonce mining to mobile
+//! apps with the same results as the rest of the crate. Generate the
+//! bindings from a release build of the library:
+//!
+//! ```text
+//! cargo build --release --features uniffi
+//! cargo run --features uniffi-bindgen --bin uniffi-bindgen -- generate \
+//!     --library target/release/libuhash_core.so --language kotlin --out-dir bindings/kotlin
+//! ```
+//!
+//! (`--language swift` for iOS, with the `.dylib` or `.a` built for the
+//! target.) Byte strings cross the boundary as `ByteArray` / `Data`.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::hash256::Hash256;
+use crate::header::{HEADER_PREFIX_SIZE, HEADER_SIZE};
+use crate::job::Job;
+use crate::uhash::{UniversalHash, meets_difficulty};
+
+/// Error returned to Swift and Kotlin callers
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Error)]
+pub enum UhashBindingError {
+    /// Input is not the required number of bytes
+    InvalidLength {
+        /// Required length
+        expected: u64,
+        /// Length that was supplied
+        actual: u64,
+    },
+    /// Hash has fewer leading zero bits than required
+    BelowDifficulty {
+        /// Required leading zero bits
+        difficulty: u32,
+        /// Leading zero bits the hash actually has
+        achieved: u32,
+    },
+}
+
+impl fmt::Display for UhashBindingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UhashBindingError::InvalidLength { expected, actual } => {
+                write!(f, "input is {} bytes, expected {}", actual, expected)
+            }
+            UhashBindingError::BelowDifficulty {
+                difficulty,
+                achieved,
+            } => write!(
+                f,
+                "hash has {} leading zero bits, difficulty requires {}",
+                achieved, difficulty
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UhashBindingError {}
+
+/// Outcome of hashing one candidate nonce
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct NonceResult {
+    /// Nonce that was tried
+    pub nonce: u64,
+    /// Hash of the header with that nonce
+    pub hash: Vec<u8>,
+    /// Whether the hash meets the requested difficulty
+    pub found: bool,
+}
+
+/// Reusable hasher, keeping its 2MB of scratchpads between calls
+///
+/// Calls on one instance are serialized; create one per thread to hash
+/// in parallel.
+#[derive(uniffi::Object)]
+pub struct UniversalHasher {
+    inner: Mutex<UniversalHash>,
+}
+
+#[uniffi::export]
+impl UniversalHasher {
+    /// Allocate a hasher
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Arc::new(UniversalHasher {
+            inner: Mutex::new(UniversalHash::new()),
+        })
+    }
+
+    /// UniversalHash of `input`
+    pub fn hash(&self, input: Vec<u8>) -> Vec<u8> {
+        self.hasher().hash(&input).to_vec()
+    }
+
+    /// Hash a 68-byte mining header and check it has `difficulty`
+    /// leading zero bits, returning the hash
+    pub fn verify_share(
+        &self,
+        header: Vec<u8>,
+        difficulty: u32,
+    ) -> Result<Vec<u8>, UhashBindingError> {
+        if header.len() != HEADER_SIZE {
+            return Err(UhashBindingError::InvalidLength {
+                expected: HEADER_SIZE as u64,
+                actual: header.len() as u64,
+            });
+        }
+        let hash = Hash256(self.hasher().hash(&header));
+        if !meets_difficulty(hash.as_bytes(), difficulty) {
+            return Err(UhashBindingError::BelowDifficulty {
+                difficulty,
+                achieved: hash.leading_zeros(),
+            });
+        }
+        Ok(hash.to_bytes().to_vec())
+    }
+
+    /// Hash the 60-byte header prefix with `nonce` appended
+    pub fn mine_nonce(
+        &self,
+        header_prefix: Vec<u8>,
+        nonce: u64,
+        difficulty: u32,
+    ) -> Result<NonceResult, UhashBindingError> {
+        if header_prefix.len() != HEADER_PREFIX_SIZE {
+            return Err(UhashBindingError::InvalidLength {
+                expected: HEADER_PREFIX_SIZE as u64,
+                actual: header_prefix.len() as u64,
+            });
+        }
+        let job = Job::from_header(&header_prefix);
+        let hash = self.hasher().hash_nonce(&job, nonce);
+        Ok(NonceResult {
+            nonce,
+            hash: hash.to_vec(),
+            found: meets_difficulty(&hash, difficulty),
+        })
+    }
+}
+
+impl UniversalHasher {
+    fn hasher(&self) -> std::sync::MutexGuard<'_, UniversalHash> {
+        // A panic mid-hash leaves nothing half-written that the next hash reads
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// One-shot UniversalHash of `input`
+///
+/// Allocates a fresh 2MB hasher; reuse a [`UniversalHasher`] for repeated
+/// calls.
+#[uniffi::export]
+pub fn uhash(input: Vec<u8>) -> Vec<u8> {
+    crate::hash(&input).to_vec()
+}
+
+/// Library version, for apps to report which implementation they embed
+#[uniffi::export]
+pub fn uhash_version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
@@ -0,0 +1,196 @@
+//! Known-answer vectors for each consensus algorithm version
+//!
+//! Other implementations, and nodes validating old chain segments, check
+//! themselves against [`vectors_for`]. The vectors of a released version
+//! never change; a new version gets a new [`AlgorithmVersion`] and its own
+//! list.
+
+use crate::header::HEADER_SIZE;
+use crate::params::VERSION;
+
+/// A consensus generation of the UniversalHash algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum AlgorithmVersion {
+    /// UniversalHash v4: 4 chains, 512KB scratchpads, 12,288 rounds
+    V4,
+}
+
+impl AlgorithmVersion {
+    /// Version implemented by this crate
+    pub const CURRENT: AlgorithmVersion = AlgorithmVersion::V4;
+
+    /// Every version with a vector registry, oldest first
+    pub const ALL: &'static [AlgorithmVersion] = &[AlgorithmVersion::V4];
+
+    /// Version number, as in [`VERSION`]
+    pub const fn number(self) -> u8 {
+        match self {
+            AlgorithmVersion::V4 => 4,
+        }
+    }
+
+    /// Version with number `number`, if known
+    pub fn from_number(number: u8) -> Option<Self> {
+        Self::ALL.iter().copied().find(|v| v.number() == number)
+    }
+}
+
+/// One input and the hash it must produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KnownAnswer {
+    /// Short description of what the vector exercises
+    pub name: &'static str,
+    /// Input as passed to [`hash`](crate::hash)
+    pub input: &'static [u8],
+    /// Expected output
+    pub expected: [u8; 32],
+}
+
+/// Known-answer vectors for `version`
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "alloc")] {
+/// use uhash_core::{AlgorithmVersion, hash, vectors_for};
+///
+/// for vector in vectors_for(AlgorithmVersion::CURRENT).iter().take(2) {
+///     assert_eq!(hash(vector.input), vector.expected, "{}", vector.name);
+/// }
+/// # }
+/// ```
+pub fn vectors_for(version: AlgorithmVersion) -> &'static [KnownAnswer] {
+    match version {
+        AlgorithmVersion::V4 => V4_VECTORS,
+    }
+}
+
+const _: () = assert!(AlgorithmVersion::CURRENT.number() == VERSION);
+
+/// `epoch_seed || miner_address || timestamp || nonce` with repeated bytes
+const fn header(seed: u8, address: u8, timestamp: u64, nonce: u64) -> [u8; HEADER_SIZE] {
+    let mut bytes = [0u8; HEADER_SIZE];
+    let timestamp = timestamp.to_le_bytes();
+    let nonce = nonce.to_le_bytes();
+    let mut i = 0;
+    while i < HEADER_SIZE {
+        bytes[i] = match i {
+            0..32 => seed,
+            32..52 => address,
+            52..60 => timestamp[i - 52],
+            _ => nonce[i - 60],
+        };
+        i += 1;
+    }
+    bytes
+}
+
+/// Decode 64 lowercase hex digits at compile time
+const fn hex32(hex: &str) -> [u8; 32] {
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            _ => panic!("invalid hex digit"),
+        }
+    }
+    let hex = hex.as_bytes();
+    assert!(hex.len() == 64);
+    let mut out = [0u8; 32];
+    let mut i = 0;
+    while i < 32 {
+        out[i] = (nibble(hex[2 * i]) << 4) | nibble(hex[2 * i + 1]);
+        i += 1;
+    }
+    out
+}
+
+const V4_VECTORS: &[KnownAnswer] = &[
+    KnownAnswer {
+        name: "zero seed, nonce 0",
+        input: &header(0x00, 0x01, 0, 0),
+        expected: hex32("4b4d8575471999dcf2c1acb429361718580f4c291c4628957a643c269500b469"),
+    },
+    KnownAnswer {
+        name: "zero seed, nonce 1",
+        input: &header(0x00, 0x01, 0, 1),
+        expected: hex32("99d9682f743ad694298e6d57f22e042d3ed92bba2abb020f957039fe4dc383b3"),
+    },
+    KnownAnswer {
+        name: "seed 0xAB, nonce 0",
+        input: &header(0xAB, 0x01, 0, 0),
+        expected: hex32("1d39fc8ba89cb3204f5da7e6175352775f1cfc07a00912ef9545a7e8bd7e7b35"),
+    },
+    KnownAnswer {
+        name: "timestamp set, nonce u64::MAX (primitive index wraps)",
+        input: &header(0x5C, 0xE7, 1_700_000_000, u64::MAX),
+        expected: hex32("b0326b3ff701794a3150cf64cc24b701615709e39d40cf7ea2519710316c4689"),
+    },
+    KnownAnswer {
+        name: "empty input (nonce derived from BLAKE3)",
+        input: b"",
+        expected: hex32("eac890c5ae85b39b59c51a9fc36eb13d5f11d4d584dc51102c4811633d3afb04"),
+    },
+    KnownAnswer {
+        name: "short input (nonce derived from BLAKE3)",
+        input: b"abc",
+        expected: hex32("0af26a5e812c6e8b1a981dd2bc1473ab60b4ad77a0ff8d9ff4b3e6fc6886e764"),
+    },
+    KnownAnswer {
+        name: "input longer than a header",
+        input: &[0x5A; 100],
+        expected: hex32("b1c37e12e5456e1bc8a872a3bc84d3143ebd7c5ed4e09c98bd75790ed0d9958c"),
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "alloc")]
+    use crate::reference;
+    #[cfg(feature = "alloc")]
+    use crate::uhash::hash;
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_vectors_match_implementation() {
+        for &version in AlgorithmVersion::ALL {
+            for vector in vectors_for(version) {
+                assert_eq!(hash(vector.input), vector.expected, "{}", vector.name);
+                assert_eq!(
+                    reference::hash(vector.input),
+                    vector.expected,
+                    "{}",
+                    vector.name
+                );
+            }
+        }
+    }
+
+    /// Earlier aarch64 builds with compile-time `aes` ran AESE with the
+    /// round key instead of adding it after MixColumns, and disagreed with
+    /// every other backend; pin that path to the published vectors
+    #[cfg(all(
+        target_arch = "aarch64",
+        target_feature = "aes",
+        not(feature = "safe"),
+        feature = "alloc"
+    ))]
+    #[test]
+    fn test_vectors_match_aarch64_hardware_aes() {
+        assert!(crate::hardware_aes());
+        for vector in vectors_for(AlgorithmVersion::V4) {
+            assert_eq!(hash(vector.input), vector.expected, "{}", vector.name);
+        }
+    }
+
+    #[test]
+    fn test_version_numbers() {
+        assert_eq!(
+            AlgorithmVersion::from_number(VERSION),
+            Some(AlgorithmVersion::CURRENT)
+        );
+        assert_eq!(AlgorithmVersion::from_number(3), None);
+    }
+}
@@ -0,0 +1,69 @@
+//! The sequential verify path makes no heap allocations
+//!
+//! Lives in its own test binary so the counting `#[global_allocator]` only
+//! wraps this test, not the crate's unit tests.
+
+// The `metrics` facade builds a labelled key, which allocates, per event
+#![cfg(all(feature = "std", not(feature = "metrics-rs")))]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use uhash_core::{
+    Job, MiningHeader, SCRATCHPAD_SIZE, UniversalHash, UniversalHashIn, meets_difficulty,
+};
+
+/// Global allocator counting allocations made by the current thread
+struct Counting;
+
+std::thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Allocations made on this thread while running `f`
+fn allocations_during(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn test_verify_path_does_not_allocate() {
+    let header = MiningHeader::default();
+    let job = header.job();
+    let mut hasher = UniversalHash::new_sequential();
+    let mut buffer = vec![0u8; SCRATCHPAD_SIZE];
+    let mut hasher_in = UniversalHashIn::new_in(&mut buffer).unwrap();
+
+    let allocations = allocations_during(|| {
+        for nonce in 0..3 {
+            let bytes = header.with_nonce(nonce).to_bytes();
+            assert!(meets_difficulty(&hasher.hash(&bytes), 0));
+            assert!(hasher.verify_pow(&header.with_nonce(nonce), 0).is_ok());
+            assert_eq!(hasher.hash_nonce(&job, nonce), hasher_in.hash(&bytes));
+            assert!(hasher_in.verify_pow(&header, 0).is_ok());
+        }
+        let _ = Job::from_header(&header.prefix());
+    });
+    assert_eq!(allocations, 0);
+
+    // The counter itself works
+    assert_eq!(
+        allocations_during(|| drop(std::hint::black_box(vec![0u8; 1]))),
+        1
+    );
+}